@@ -0,0 +1,410 @@
+//! Command-line interface of the `carbonpay` admin and operator tool.
+//!
+//! Commands are either an [`Action`], which signs and submits a transaction, or a [`Query`],
+//! which only reads accounts. Both go through an [`AccountFetcher`], so the transactions they
+//! build and the reports they render can be checked without a cluster.
+
+pub mod output;
+
+use std::path::PathBuf;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::Instruction;
+use carbonpay_client::accounts::{self, AccountFetcher, FetchError};
+use carbonpay_client::instructions::{self as ix, ProjectArgs};
+use carbonpay_client::state::FeeRounding;
+use carbonpay_client::{pda, reconcile};
+use clap::{Parser, Subcommand, ValueEnum};
+use serde_json::{json, Value};
+use solana_keypair::Keypair;
+use solana_signer::Signer;
+
+use output::{OutputFormat, Report};
+
+pub type CliResult<T> = Result<T, FetchError>;
+
+#[derive(Parser)]
+#[command(name = "carbonpay", version, about = "Admin and operator tool for the carbon_pay program")]
+pub struct Cli {
+    /// Cluster moniker (localnet, devnet, testnet, mainnet-beta) or RPC URL
+    #[arg(short = 'u', long, global = true, env = "CARBONPAY_CLUSTER", default_value = "devnet")]
+    pub url: String,
+
+    /// Fee payer and signer keypair [default: ~/.config/solana/id.json]
+    #[arg(short = 'k', long, global = true, env = "CARBONPAY_KEYPAIR")]
+    pub keypair: Option<PathBuf>,
+
+    #[arg(short = 'o', long, global = true, value_enum, default_value = "table")]
+    pub output: OutputFormat,
+
+    /// Print the signed transaction instead of sending it
+    #[arg(long, global = true)]
+    pub dry_run: bool,
+
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    #[command(flatten)]
+    Action(Action),
+    #[command(flatten)]
+    Query(Query),
+}
+
+/// Commands that sign a transaction with the keypair
+#[derive(Debug, Subcommand)]
+pub enum Action {
+    /// Initialize the carbon_credits and platform config accounts
+    Init {
+        /// Offset approver wallet, repeat for several
+        #[arg(long = "approver", required = true)]
+        approvers: Vec<Pubkey>,
+        /// Approver votes needed to approve an offset request
+        #[arg(long, default_value_t = 1)]
+        threshold: u8,
+    },
+    /// Create a project with its NFT, token mint and vault
+    CreateProject {
+        /// Tokens to mint, in base units of the token decimals
+        #[arg(long)]
+        amount: u64,
+        /// Price per whole token in lamports
+        #[arg(long)]
+        price_per_token: u64,
+        /// Platform fee in basis points
+        #[arg(long)]
+        fee_bps: u64,
+        /// Token decimals, e.g. 3 to trade in kilograms
+        #[arg(long, default_value_t = 0)]
+        decimals: u8,
+        #[arg(long)]
+        name: String,
+        #[arg(long)]
+        symbol: String,
+        #[arg(long)]
+        uri: String,
+    },
+    /// Buy credits from a project
+    Purchase {
+        /// Project account address
+        project: Pubkey,
+        #[arg(long)]
+        amount: u64,
+        /// Allowlist entry, when the project or platform requires one
+        #[arg(long)]
+        allowlist_entry: Option<Pubkey>,
+    },
+    /// Request an offset against a purchase
+    RequestOffset {
+        /// Purchase account address
+        purchase: Pubkey,
+        #[arg(long)]
+        amount: u64,
+        #[arg(long)]
+        request_id: String,
+    },
+    /// Vote to approve a pending offset request
+    ApproveOffset {
+        /// Offset request account address
+        offset_request: Pubkey,
+    },
+    /// Replace the offset approver set
+    SetApprovers {
+        #[arg(long = "approver", required = true)]
+        approvers: Vec<Pubkey>,
+        #[arg(long)]
+        threshold: u8,
+    },
+    /// Set how platform fees are rounded and the minimum fee
+    SetFeePolicy {
+        #[arg(long, value_enum, default_value = "floor")]
+        rounding: Rounding,
+        /// Minimum platform fee in lamports on sales charging a fee
+        #[arg(long, default_value_t = 0)]
+        min_fee: u64,
+    },
+}
+
+/// Read-only commands, which never load the keypair
+#[derive(Debug, Subcommand)]
+pub enum Query {
+    /// List every project of the program
+    ListProjects,
+    /// Show a purchase record
+    ShowPurchase {
+        /// Purchase account address
+        purchase: Pubkey,
+    },
+    /// Compare project and purchase bookkeeping against mint supplies and token balances
+    Reconcile,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Rounding {
+    Floor,
+    Ceil,
+    /// Round half to even (banker's rounding)
+    HalfEven,
+}
+
+impl From<Rounding> for FeeRounding {
+    fn from(rounding: Rounding) -> Self {
+        match rounding {
+            Rounding::Floor => FeeRounding::Floor,
+            Rounding::Ceil => FeeRounding::Ceil,
+            Rounding::HalfEven => FeeRounding::HalfEven,
+        }
+    }
+}
+
+/// Transaction an [`Action`] submits: its instructions, the keypairs that sign besides the
+/// payer, and the addresses worth reporting
+pub struct Plan {
+    pub instructions: Vec<Instruction>,
+    pub signers: Vec<Keypair>,
+    pub details: Value,
+}
+
+impl Action {
+    /// Build the transaction with `payer` as fee payer and signer, reading the accounts the
+    /// instructions depend on from `fetcher`
+    pub fn plan(&self, fetcher: &impl AccountFetcher, payer: &Pubkey) -> CliResult<Plan> {
+        match self {
+            Action::Init {
+                approvers,
+                threshold,
+            } => Ok(Plan {
+                instructions: vec![
+                    ix::initialize_carbon_credits(payer),
+                    ix::initialize_platform_config(payer, approvers.clone(), *threshold),
+                ],
+                signers: vec![],
+                details: json!({
+                    "carbon_credits": pda::carbon_credits().0.to_string(),
+                    "platform_config": pda::platform_config().0.to_string(),
+                }),
+            }),
+
+            Action::CreateProject {
+                amount,
+                price_per_token,
+                fee_bps,
+                decimals,
+                name,
+                symbol,
+                uri,
+            } => {
+                let nft_mint = Keypair::new();
+                let token_mint = Keypair::new();
+                let project = pda::project(payer, &nft_mint.pubkey()).0;
+                Ok(Plan {
+                    instructions: ix::initialize_project(
+                        payer,
+                        &nft_mint.pubkey(),
+                        &token_mint.pubkey(),
+                        ProjectArgs {
+                            amount: *amount,
+                            price_per_token: *price_per_token,
+                            carbon_pay_fee: *fee_bps,
+                            decimals: *decimals,
+                            uri: uri.clone(),
+                            name: name.clone(),
+                            symbol: symbol.clone(),
+                        },
+                    ),
+                    details: json!({
+                        "project": project.to_string(),
+                        "nft_mint": nft_mint.pubkey().to_string(),
+                        "token_mint": token_mint.pubkey().to_string(),
+                    }),
+                    signers: vec![nft_mint, token_mint],
+                })
+            }
+
+            Action::Purchase {
+                project,
+                amount,
+                allowlist_entry,
+            } => {
+                let project_account = accounts::project(fetcher, project)?
+                    .ok_or_else(|| format!("project {} not found", project))?;
+                let nft_mint = Keypair::new();
+                let purchase = pda::purchase(payer, project, &nft_mint.pubkey()).0;
+                Ok(Plan {
+                    instructions: ix::purchase_carbon_credits(
+                        payer,
+                        project,
+                        &project_account,
+                        &nft_mint.pubkey(),
+                        *amount,
+                        *allowlist_entry,
+                    ),
+                    details: json!({
+                        "purchase": purchase.to_string(),
+                        "nft_mint": nft_mint.pubkey().to_string(),
+                    }),
+                    signers: vec![nft_mint],
+                })
+            }
+
+            Action::RequestOffset {
+                purchase,
+                amount,
+                request_id,
+            } => {
+                let purchase_account = accounts::purchase(fetcher, purchase)?
+                    .ok_or_else(|| format!("purchase {} not found", purchase))?;
+                let project_account = accounts::project(fetcher, &purchase_account.project)?
+                    .ok_or_else(|| format!("project {} not found", purchase_account.project))?;
+                let new_nft_mint = Keypair::new();
+                let offset_request =
+                    pda::offset_request(&purchase_account.buyer, purchase, request_id).0;
+                Ok(Plan {
+                    instructions: ix::request_offset(
+                        payer,
+                        purchase,
+                        &purchase_account,
+                        &project_account,
+                        &new_nft_mint.pubkey(),
+                        *amount,
+                        request_id.clone(),
+                    ),
+                    signers: vec![new_nft_mint],
+                    details: json!({ "offset_request": offset_request.to_string() }),
+                })
+            }
+
+            Action::ApproveOffset { offset_request } => Ok(Plan {
+                instructions: vec![ix::approve_offset_vote(payer, offset_request)],
+                signers: vec![],
+                details: json!({
+                    "offset_request": offset_request.to_string(),
+                    "offset_approval": pda::offset_approval(offset_request).0.to_string(),
+                }),
+            }),
+
+            Action::SetApprovers {
+                approvers,
+                threshold,
+            } => Ok(Plan {
+                instructions: vec![ix::update_offset_approvers(payer, approvers.clone(), *threshold)],
+                signers: vec![],
+                details: json!({ "platform_config": pda::platform_config().0.to_string() }),
+            }),
+
+            Action::SetFeePolicy { rounding, min_fee } => Ok(Plan {
+                instructions: vec![ix::set_fee_policy(payer, (*rounding).into(), *min_fee)],
+                signers: vec![],
+                details: json!({ "platform_config": pda::platform_config().0.to_string() }),
+            }),
+        }
+    }
+}
+
+impl Query {
+    pub fn run(&self, fetcher: &impl AccountFetcher) -> CliResult<Report> {
+        match self {
+            Query::ListProjects => {
+                let projects = accounts::projects(fetcher)?;
+                Ok(Report {
+                    json: json!(projects
+                        .iter()
+                        .map(|(address, p)| json!({
+                            "address": address.to_string(),
+                            "owner": p.owner.to_string(),
+                            "token_mint": p.token_mint.to_string(),
+                            "decimals": p.decimals,
+                            "is_active": p.is_active,
+                            "amount": p.amount,
+                            "remaining_amount": p.remaining_amount,
+                            "offset_amount": p.offset_amount,
+                            "price_per_token": p.price_per_token,
+                        }))
+                        .collect::<Vec<_>>()),
+                    headers: vec!["ADDRESS", "OWNER", "ACTIVE", "AMOUNT", "REMAINING", "OFFSET", "PRICE"],
+                    rows: projects
+                        .iter()
+                        .map(|(address, p)| {
+                            vec![
+                                address.to_string(),
+                                p.owner.to_string(),
+                                p.is_active.to_string(),
+                                p.amount.to_string(),
+                                p.remaining_amount.to_string(),
+                                p.offset_amount.to_string(),
+                                p.price_per_token.to_string(),
+                            ]
+                        })
+                        .collect(),
+                })
+            }
+
+            Query::ShowPurchase { purchase } => {
+                let p = accounts::purchase(fetcher, purchase)?
+                    .ok_or_else(|| format!("purchase {} not found", purchase))?;
+                Ok(Report::record(
+                    json!({
+                        "address": purchase.to_string(),
+                        "buyer": p.buyer.to_string(),
+                        "project": p.project.to_string(),
+                        "nft_mint": p.nft_mint.to_string(),
+                        "certificate_mint": p.certificate_mint.to_string(),
+                        "amount": p.amount,
+                        "remaining_amount": p.remaining_amount,
+                        "uncertified_offset": p.uncertified_offset,
+                        "purchase_date": p.purchase_date,
+                    }),
+                    vec![
+                        ("address", purchase.to_string()),
+                        ("buyer", p.buyer.to_string()),
+                        ("project", p.project.to_string()),
+                        ("nft_mint", p.nft_mint.to_string()),
+                        ("certificate_mint", p.certificate_mint.to_string()),
+                        ("amount", p.amount.to_string()),
+                        ("remaining_amount", p.remaining_amount.to_string()),
+                        ("uncertified_offset", p.uncertified_offset.to_string()),
+                        ("purchase_date", p.purchase_date.to_string()),
+                    ],
+                ))
+            }
+
+            Query::Reconcile => {
+                let report = reconcile::reconcile(fetcher)?;
+                Ok(Report {
+                    json: json!({
+                        "projects_checked": report.projects_checked,
+                        "purchases_checked": report.purchases_checked,
+                        "discrepancies": report
+                            .discrepancies
+                            .iter()
+                            .map(|d| json!({
+                                "check": d.check.to_string(),
+                                "project": d.project.to_string(),
+                                "account": d.account.to_string(),
+                                "purchases": d.purchases.iter().map(Pubkey::to_string).collect::<Vec<_>>(),
+                                "expected": d.expected,
+                                "actual": d.actual,
+                            }))
+                            .collect::<Vec<_>>(),
+                    }),
+                    headers: vec!["CHECK", "PROJECT", "ACCOUNT", "EXPECTED", "ACTUAL"],
+                    rows: report
+                        .discrepancies
+                        .iter()
+                        .map(|d| {
+                            vec![
+                                d.check.to_string(),
+                                d.project.to_string(),
+                                d.account.to_string(),
+                                d.expected.to_string(),
+                                d.actual.to_string(),
+                            ]
+                        })
+                        .collect(),
+                })
+            }
+        }
+    }
+}
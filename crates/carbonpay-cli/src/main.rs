@@ -0,0 +1,98 @@
+//! `carbonpay` — admin and operator tool for the carbon_pay program.
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use anchor_lang::solana_program::instruction::Instruction;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use carbonpay_cli::output::Report;
+use carbonpay_cli::{Cli, CliResult, Command, Plan};
+use carbonpay_client::rpc::RpcClient;
+use clap::Parser;
+use serde_json::json;
+use solana_hash::Hash;
+use solana_keypair::{read_keypair_file, Keypair};
+use solana_signer::Signer;
+use solana_transaction::Transaction;
+
+/// Sign `plan` with `payer` and its own signers, then send it or, in dry-run mode, describe
+/// the transaction
+fn submit(rpc: &RpcClient, payer: &Keypair, plan: Plan, dry_run: bool) -> CliResult<Report> {
+    let mut signers: Vec<&dyn Signer> = vec![payer];
+    signers.extend(plan.signers.iter().map(|k| k as &dyn Signer));
+
+    let blockhash = match dry_run {
+        true => Hash::default(),
+        false => rpc.latest_blockhash()?,
+    };
+    let transaction = Transaction::new_signed_with_payer(
+        &plan.instructions,
+        Some(&payer.pubkey()),
+        &signers,
+        blockhash,
+    );
+
+    if dry_run {
+        let described: Vec<_> = plan.instructions.iter().map(describe_instruction).collect();
+        let encoded = STANDARD.encode(bincode::serialize(&transaction)?);
+        return Ok(Report::record(
+            json!({ "details": plan.details, "instructions": described, "transaction": encoded }),
+            vec![
+                ("instructions", plan.instructions.len().to_string()),
+                ("transaction", encoded),
+            ],
+        ));
+    }
+
+    let signature = rpc.send_transaction(&bincode::serialize(&transaction)?)?;
+    Ok(Report::record(
+        json!({ "details": plan.details, "signature": signature }),
+        vec![("signature", signature)],
+    ))
+}
+
+fn describe_instruction(instruction: &Instruction) -> serde_json::Value {
+    json!({
+        "program_id": instruction.program_id.to_string(),
+        "accounts": instruction.accounts.iter().map(|meta| json!({
+            "pubkey": meta.pubkey.to_string(),
+            "is_signer": meta.is_signer,
+            "is_writable": meta.is_writable,
+        })).collect::<Vec<_>>(),
+        "data": STANDARD.encode(&instruction.data),
+    })
+}
+
+fn default_keypair_path() -> PathBuf {
+    let home = std::env::var_os("HOME").unwrap_or_default();
+    PathBuf::from(home).join(".config/solana/id.json")
+}
+
+fn run(cli: Cli) -> CliResult<Report> {
+    let rpc = RpcClient::new(&cli.url);
+    let action = match cli.command {
+        Command::Query(query) => return query.run(&rpc),
+        Command::Action(action) => action,
+    };
+
+    let keypair_path = cli.keypair.unwrap_or_else(default_keypair_path);
+    let payer = read_keypair_file(&keypair_path)
+        .map_err(|err| format!("failed to read keypair {}: {}", keypair_path.display(), err))?;
+    let plan = action.plan(&rpc, &payer.pubkey())?;
+    submit(&rpc, &payer, plan, cli.dry_run)
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let format = cli.output;
+    match run(cli) {
+        Ok(report) => {
+            report.print(format);
+            ExitCode::SUCCESS
+        }
+        Err(err) => {
+            eprintln!("error: {}", err);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,63 @@
+//! JSON and table rendering of command results.
+
+use clap::ValueEnum;
+use serde_json::Value;
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum OutputFormat {
+    Table,
+    Json,
+}
+
+/// A command result, rendered as `json` or as a table of `rows` under `headers`
+pub struct Report {
+    pub json: Value,
+    pub headers: Vec<&'static str>,
+    pub rows: Vec<Vec<String>>,
+}
+
+impl Report {
+    /// A single record shown as a two-column field/value table
+    pub fn record(json: Value, fields: Vec<(&'static str, String)>) -> Self {
+        Self {
+            json,
+            headers: vec!["FIELD", "VALUE"],
+            rows: fields
+                .into_iter()
+                .map(|(field, value)| vec![field.to_string(), value])
+                .collect(),
+        }
+    }
+
+    pub fn print(&self, format: OutputFormat) {
+        match format {
+            OutputFormat::Json => {
+                println!("{}", serde_json::to_string_pretty(&self.json).unwrap_or_default())
+            }
+            OutputFormat::Table => print!("{}", render_table(&self.headers, &self.rows)),
+        }
+    }
+}
+
+fn render_table(headers: &[&str], rows: &[Vec<String>]) -> String {
+    let mut widths: Vec<usize> = headers.iter().map(|h| h.len()).collect();
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.len());
+        }
+    }
+    let line = |cells: Vec<&str>| {
+        let padded: Vec<String> = cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+            .collect();
+        format!("{}\n", padded.join("  ").trim_end())
+    };
+
+    let mut out = line(headers.to_vec());
+    for row in rows {
+        out.push_str(&line(row.iter().map(String::as_str).collect()));
+    }
+    out
+}
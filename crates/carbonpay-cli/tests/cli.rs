@@ -0,0 +1,282 @@
+use std::collections::HashMap;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::{AccountSerialize, Discriminator, InstructionData};
+use carbonpay_cli::{Action, Cli, Command, Query, Rounding};
+use carbonpay_client::accounts::{AccountFetcher, FetchError};
+use carbonpay_client::state::{FeeRounding, Project, ProjectCategory, Purchase};
+use carbonpay_client::{pda, PROGRAM_ID};
+use clap::Parser;
+use solana_signer::Signer;
+
+#[derive(Default)]
+struct Snapshot {
+    accounts: HashMap<Pubkey, Vec<u8>>,
+}
+
+impl Snapshot {
+    fn program_account<T: AccountSerialize>(&mut self, address: Pubkey, account: &T) {
+        let mut data = Vec::new();
+        account.try_serialize(&mut data).unwrap();
+        self.accounts.insert(address, data);
+    }
+}
+
+impl AccountFetcher for Snapshot {
+    fn account_data(&self, address: &Pubkey) -> Result<Option<Vec<u8>>, FetchError> {
+        Ok(self.accounts.get(address).cloned())
+    }
+
+    fn program_accounts(
+        &self,
+        program_id: &Pubkey,
+        prefix: &[u8],
+    ) -> Result<Vec<(Pubkey, Vec<u8>)>, FetchError> {
+        assert_eq!(*program_id, PROGRAM_ID);
+        Ok(self
+            .accounts
+            .iter()
+            .filter(|(_, data)| data.starts_with(prefix))
+            .map(|(address, data)| (*address, data.clone()))
+            .collect())
+    }
+}
+
+fn project(owner: Pubkey, token_mint: Pubkey) -> Project {
+    Project {
+        owner,
+        mint: Pubkey::new_unique(),
+        token_mint,
+        token_bump: 0,
+        is_active: true,
+        amount: 1000,
+        remaining_amount: 800,
+        offset_amount: 0,
+        price_per_token: 1_000,
+        carbon_pay_fee: 500,
+        carbon_pay_authority: Pubkey::new_unique(),
+        project_bump: 255,
+        allowlist_required: false,
+        min_purchase_amount: 0,
+        max_purchase_amount: 0,
+        max_per_buyer: 0,
+        sale_start: 0,
+        sale_end: 0,
+        early_access_start: 0,
+        price_tiers: vec![],
+        auction: None,
+        category: ProjectCategory::Unspecified,
+        vault: Pubkey::new_unique(),
+        decimals: 0,
+        layout_version: Project::LAYOUT_VERSION,
+    }
+}
+
+/// One project and a purchase of 200 of its tokens by `buyer`
+fn snapshot(buyer: Pubkey) -> (Snapshot, Pubkey, Pubkey) {
+    let mut snapshot = Snapshot::default();
+    let (project_address, purchase_address) = (Pubkey::new_unique(), Pubkey::new_unique());
+    snapshot.program_account(project_address, &project(Pubkey::new_unique(), Pubkey::new_unique()));
+    let nft_mint = Pubkey::new_unique();
+    snapshot.program_account(
+        purchase_address,
+        &Purchase {
+            buyer,
+            project: project_address,
+            amount: 200,
+            remaining_amount: 200,
+            purchase_date: 1_700_000_000,
+            purchase_bump: 255,
+            nft_mint,
+            certificate_mint: nft_mint,
+            uncertified_offset: 0,
+        },
+    );
+    (snapshot, project_address, purchase_address)
+}
+
+fn parse(args: &[&str]) -> Cli {
+    Cli::try_parse_from([&["carbonpay"], args].concat()).unwrap()
+}
+
+fn action(args: &[&str]) -> Action {
+    match parse(args).command {
+        Command::Action(action) => action,
+        Command::Query(query) => panic!("{:?} is a query", query),
+    }
+}
+
+#[test]
+fn read_only_commands_are_queries() {
+    let purchase = Pubkey::new_unique().to_string();
+    for args in [vec!["list-projects"], vec!["show-purchase", &purchase], vec!["reconcile"]] {
+        assert!(matches!(parse(&args).command, Command::Query(_)), "{:?}", args);
+    }
+    for args in [
+        vec!["init", "--approver", &purchase],
+        vec!["approve-offset", &purchase],
+        vec!["set-fee-policy"],
+    ] {
+        assert!(matches!(parse(&args).command, Command::Action(_)), "{:?}", args);
+    }
+}
+
+#[test]
+fn parses_global_options_after_the_subcommand() {
+    let cli = parse(&[
+        "set-fee-policy",
+        "--rounding",
+        "half-even",
+        "--min-fee",
+        "5",
+        "-u",
+        "localnet",
+        "-k",
+        "ops.json",
+        "--dry-run",
+        "-o",
+        "json",
+    ]);
+    assert_eq!(cli.url, "localnet");
+    assert_eq!(cli.keypair.as_deref(), Some("ops.json".as_ref()));
+    assert!(cli.dry_run);
+    assert!(matches!(
+        cli.command,
+        Command::Action(Action::SetFeePolicy { rounding: Rounding::HalfEven, min_fee: 5 })
+    ));
+}
+
+#[test]
+fn rejects_missing_or_malformed_arguments() {
+    for args in [
+        vec!["carbonpay", "init"],
+        vec!["carbonpay", "purchase", "not-a-pubkey", "--amount", "1"],
+        vec!["carbonpay", "purchase", &Pubkey::new_unique().to_string()],
+        vec!["carbonpay", "set-fee-policy", "--rounding", "up"],
+        vec!["carbonpay", "request-offset", &Pubkey::new_unique().to_string(), "--amount", "-1", "--request-id", "R1"],
+    ] {
+        assert!(Cli::try_parse_from(&args).is_err(), "{:?}", args);
+    }
+}
+
+#[test]
+fn init_builds_both_platform_instructions() {
+    let payer = Pubkey::new_unique();
+    let approver = Pubkey::new_unique();
+    let plan = action(&["init", "--approver", &approver.to_string(), "--threshold", "1"])
+        .plan(&Snapshot::default(), &payer)
+        .unwrap();
+
+    assert!(plan.signers.is_empty());
+    assert_eq!(plan.instructions.len(), 2);
+    assert!(plan.instructions.iter().all(|ix| ix.program_id == PROGRAM_ID));
+    assert!(plan.instructions[0].accounts.iter().any(|meta| meta.pubkey == payer && meta.is_signer));
+    assert_eq!(
+        plan.instructions[0].data,
+        carbonpay::instruction::InitializeCarbonCredits {}.data()
+    );
+    assert_eq!(
+        plan.instructions[1].data,
+        carbonpay::instruction::InitializePlatformConfig { approvers: vec![approver], approval_threshold: 1 }.data()
+    );
+    assert_eq!(plan.details["carbon_credits"], pda::carbon_credits().0.to_string());
+}
+
+#[test]
+fn create_project_signs_with_its_new_mints() {
+    let payer = Pubkey::new_unique();
+    let plan = action(&[
+        "create-project",
+        "--amount",
+        "1000",
+        "--price-per-token",
+        "5000",
+        "--fee-bps",
+        "250",
+        "--decimals",
+        "3",
+        "--name",
+        "Forest",
+        "--symbol",
+        "FRST",
+        "--uri",
+        "https://example.com/forest.json",
+    ])
+    .plan(&Snapshot::default(), &payer)
+    .unwrap();
+
+    let [nft_mint, token_mint] = [&plan.signers[0], &plan.signers[1]].map(|k| k.pubkey());
+    assert_eq!(plan.details["project"], pda::project(&payer, &nft_mint).0.to_string());
+    assert_eq!(plan.details["token_mint"], token_mint.to_string());
+    let initialize = plan.instructions.last().unwrap();
+    assert!(initialize.data.starts_with(carbonpay::instruction::InitializeProject::DISCRIMINATOR));
+}
+
+#[test]
+fn purchase_reads_the_project_it_buys_from() {
+    let payer = Pubkey::new_unique();
+    let (snapshot, project, _) = snapshot(Pubkey::new_unique());
+    let plan = action(&["purchase", &project.to_string(), "--amount", "10"])
+        .plan(&snapshot, &payer)
+        .unwrap();
+
+    let nft_mint = plan.signers[0].pubkey();
+    let purchase = pda::purchase(&payer, &project, &nft_mint).0;
+    assert_eq!(plan.details["purchase"], purchase.to_string());
+    let instruction = plan.instructions.last().unwrap();
+    assert!(instruction.data.starts_with(carbonpay::instruction::PurchaseCarbonCredits::DISCRIMINATOR));
+    assert!(instruction.accounts.iter().any(|meta| meta.pubkey == purchase));
+
+    let missing = Pubkey::new_unique();
+    let err = action(&["purchase", &missing.to_string(), "--amount", "10"])
+        .plan(&snapshot, &payer)
+        .err()
+        .unwrap();
+    assert_eq!(err.to_string(), format!("project {} not found", missing));
+}
+
+#[test]
+fn request_offset_derives_the_buyers_request() {
+    let buyer = Pubkey::new_unique();
+    let (snapshot, _, purchase) = snapshot(buyer);
+    let plan = action(&["request-offset", &purchase.to_string(), "--amount", "50", "--request-id", "R1"])
+        .plan(&snapshot, &buyer)
+        .unwrap();
+
+    let offset_request = pda::offset_request(&buyer, &purchase, "R1").0;
+    assert_eq!(plan.details["offset_request"], offset_request.to_string());
+    let instruction = plan.instructions.last().unwrap();
+    assert!(instruction.data.starts_with(carbonpay::instruction::RequestOffset::DISCRIMINATOR));
+    assert!(instruction.accounts.iter().any(|meta| meta.pubkey == offset_request));
+}
+
+#[test]
+fn set_fee_policy_encodes_the_rounding() {
+    let payer = Pubkey::new_unique();
+    let plan = action(&["set-fee-policy", "--rounding", "ceil", "--min-fee", "10"])
+        .plan(&Snapshot::default(), &payer)
+        .unwrap();
+    assert_eq!(
+        plan.instructions[0].data,
+        carbonpay::instruction::SetFeePolicy { fee_rounding: FeeRounding::Ceil, min_fee: 10 }.data()
+    );
+}
+
+#[test]
+fn queries_render_fetched_accounts() {
+    let buyer = Pubkey::new_unique();
+    let (snapshot, project, purchase) = snapshot(buyer);
+
+    let report = Query::ShowPurchase { purchase }.run(&snapshot).unwrap();
+    assert_eq!(report.json["buyer"], buyer.to_string());
+    assert_eq!(report.json["remaining_amount"], 200);
+    assert_eq!(report.rows.len(), 9);
+
+    let report = Query::ListProjects.run(&snapshot).unwrap();
+    assert_eq!(report.rows.len(), 1);
+    assert_eq!(report.json[0]["address"], project.to_string());
+
+    let missing = Pubkey::new_unique();
+    let err = Query::ShowPurchase { purchase: missing }.run(&snapshot).err().unwrap();
+    assert_eq!(err.to_string(), format!("purchase {} not found", missing));
+}
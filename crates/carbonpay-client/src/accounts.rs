@@ -0,0 +1,100 @@
+//! Typed account fetch and decode.
+//!
+//! Fetching goes through [`AccountFetcher`] so the client stays independent of any RPC stack.
+
+use std::fmt;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::{AccountDeserialize, Discriminator};
+
+use crate::{pda, PROGRAM_ID};
+use carbonpay::state::{CarbonCredits, OffsetRequest, PlatformConfig, Project, Purchase};
+
+pub type FetchError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Source of raw account data, e.g. a JSON-RPC connection or an in-memory snapshot
+pub trait AccountFetcher {
+    /// Data of the account at `address`, or None if it does not exist
+    fn account_data(&self, address: &Pubkey) -> Result<Option<Vec<u8>>, FetchError>;
+
+    /// Address and data of every account owned by `program_id` whose data starts with `prefix`
+    fn program_accounts(
+        &self,
+        program_id: &Pubkey,
+        prefix: &[u8],
+    ) -> Result<Vec<(Pubkey, Vec<u8>)>, FetchError>;
+}
+
+#[derive(Debug)]
+pub enum ClientError {
+    Fetch(FetchError),
+    Decode(anchor_lang::error::Error),
+}
+
+impl fmt::Display for ClientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClientError::Fetch(err) => write!(f, "failed to fetch account: {}", err),
+            ClientError::Decode(err) => write!(f, "failed to decode account: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for ClientError {}
+
+/// Decode an account of type `T`, checking its discriminator
+pub fn decode<T: AccountDeserialize>(data: &[u8]) -> Result<T, ClientError> {
+    let mut data = data;
+    T::try_deserialize(&mut data).map_err(ClientError::Decode)
+}
+
+/// Fetch and decode the `T` at `address`, None if the account does not exist
+pub fn fetch<T: AccountDeserialize>(
+    fetcher: &impl AccountFetcher,
+    address: &Pubkey,
+) -> Result<Option<T>, ClientError> {
+    fetcher
+        .account_data(address)
+        .map_err(ClientError::Fetch)?
+        .map(|data| decode(&data))
+        .transpose()
+}
+
+/// Fetch and decode every program account of type `T`
+pub fn fetch_all<T: AccountDeserialize + Discriminator>(
+    fetcher: &impl AccountFetcher,
+) -> Result<Vec<(Pubkey, T)>, ClientError> {
+    fetcher
+        .program_accounts(&PROGRAM_ID, T::DISCRIMINATOR)
+        .map_err(ClientError::Fetch)?
+        .into_iter()
+        .map(|(address, data)| Ok((address, decode(&data)?)))
+        .collect()
+}
+
+pub fn carbon_credits(fetcher: &impl AccountFetcher) -> Result<Option<CarbonCredits>, ClientError> {
+    fetch(fetcher, &pda::carbon_credits().0)
+}
+
+pub fn platform_config(fetcher: &impl AccountFetcher) -> Result<Option<PlatformConfig>, ClientError> {
+    fetch(fetcher, &pda::platform_config().0)
+}
+
+pub fn project(fetcher: &impl AccountFetcher, address: &Pubkey) -> Result<Option<Project>, ClientError> {
+    fetch(fetcher, address)
+}
+
+pub fn purchase(fetcher: &impl AccountFetcher, address: &Pubkey) -> Result<Option<Purchase>, ClientError> {
+    fetch(fetcher, address)
+}
+
+pub fn offset_request(
+    fetcher: &impl AccountFetcher,
+    address: &Pubkey,
+) -> Result<Option<OffsetRequest>, ClientError> {
+    fetch(fetcher, address)
+}
+
+pub fn projects(fetcher: &impl AccountFetcher) -> Result<Vec<(Pubkey, Project)>, ClientError> {
+    fetch_all(fetcher)
+}
@@ -0,0 +1,1246 @@
+//! Instruction builders.
+//!
+//! Builders that need fresh mints take the mint address and emit the instructions creating it;
+//! the caller signs with the mint keypair alongside the payer. ATAs are created idempotently.
+
+use anchor_lang::prelude::{Pubkey, Rent};
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program_pack::Pack;
+use anchor_lang::{system_program, InstructionData, ToAccountMetas};
+use anchor_spl::associated_token::{
+    get_associated_token_address, spl_associated_token_account::instruction as ata_instruction,
+};
+use anchor_spl::metadata::mpl_token_metadata;
+use anchor_spl::token::spl_token;
+use carbonpay::state::{
+    BasketReserve, FeeRounding, Listing, OffsetDelegate, OtcOffer, PriceTier, Project,
+    ProjectCategory, Purchase, Subscription,
+};
+use solana_address_lookup_table_interface::instruction as lookup_table;
+use solana_system_interface::instruction as system_instruction;
+
+use crate::{pda, PROGRAM_ID};
+
+/// Build a carbon_pay instruction from its generated accounts and data structs
+pub fn program_instruction(accounts: impl ToAccountMetas, data: impl InstructionData) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+/// Create and initialize an SPL mint at `mint`, with `authority` as mint and freeze authority
+pub fn create_mint(payer: &Pubkey, mint: &Pubkey, authority: &Pubkey, decimals: u8) -> Vec<Instruction> {
+    let space = spl_token::state::Mint::LEN;
+    vec![
+        system_instruction::create_account(
+            payer,
+            mint,
+            Rent::default().minimum_balance(space),
+            space as u64,
+            &spl_token::ID,
+        ),
+        spl_token::instruction::initialize_mint2(
+            &spl_token::ID,
+            mint,
+            authority,
+            Some(authority),
+            decimals,
+        )
+        .expect("valid initialize_mint2 accounts"),
+    ]
+}
+
+/// Create `wallet`'s ATA for `mint` if it does not exist yet
+pub fn create_ata(payer: &Pubkey, wallet: &Pubkey, mint: &Pubkey) -> Instruction {
+    ata_instruction::create_associated_token_account_idempotent(payer, wallet, mint, &spl_token::ID)
+}
+
+/// `initialize_carbon_credits`, signed by `admin`
+pub fn initialize_carbon_credits(admin: &Pubkey) -> Instruction {
+    program_instruction(
+        carbonpay::accounts::InitializeCarbonCreditsAccountConstraints {
+            admin: *admin,
+            carbon_credits: pda::carbon_credits().0,
+            system_program: system_program::ID,
+            rent: anchor_lang::solana_program::sysvar::rent::ID,
+        },
+        carbonpay::instruction::InitializeCarbonCredits {},
+    )
+}
+
+/// `initialize_platform_config`, signed by the carbon_credits authority
+pub fn initialize_platform_config(
+    authority: &Pubkey,
+    approvers: Vec<Pubkey>,
+    approval_threshold: u8,
+) -> Instruction {
+    program_instruction(
+        carbonpay::accounts::InitializePlatformConfig {
+            authority: *authority,
+            carbon_credits: pda::carbon_credits().0,
+            platform_config: pda::platform_config().0,
+            system_program: system_program::ID,
+        },
+        carbonpay::instruction::InitializePlatformConfig {
+            approvers,
+            approval_threshold,
+        },
+    )
+}
+
+/// `update_offset_approvers`, signed by the platform config authority
+pub fn update_offset_approvers(
+    authority: &Pubkey,
+    approvers: Vec<Pubkey>,
+    approval_threshold: u8,
+) -> Instruction {
+    program_instruction(
+        carbonpay::accounts::UpdateOffsetApprovers {
+            authority: *authority,
+            platform_config: pda::platform_config().0,
+        },
+        carbonpay::instruction::UpdateOffsetApprovers {
+            approvers,
+            approval_threshold,
+        },
+    )
+}
+
+/// `set_fee_policy`, signed by the platform config authority
+pub fn set_fee_policy(authority: &Pubkey, fee_rounding: FeeRounding, min_fee: u64) -> Instruction {
+    program_instruction(
+        carbonpay::accounts::SetFeePolicy {
+            authority: *authority,
+            platform_config: pda::platform_config().0,
+        },
+        carbonpay::instruction::SetFeePolicy {
+            fee_rounding,
+            min_fee,
+        },
+    )
+}
+
+/// `migrate_carbon_credits`, signed by the carbon_credits authority
+pub fn migrate_carbon_credits(authority: &Pubkey) -> Instruction {
+    program_instruction(
+        carbonpay::accounts::MigrateCarbonCredits {
+            authority: *authority,
+            carbon_credits: pda::carbon_credits().0,
+            system_program: system_program::ID,
+        },
+        carbonpay::instruction::MigrateCarbonCredits {},
+    )
+}
+
+/// `migrate_project` for a project still in the legacy layout, signed by the carbon_credits
+/// authority
+pub fn migrate_project(
+    authority: &Pubkey,
+    project_address: &Pubkey,
+    token_mint: &Pubkey,
+) -> Instruction {
+    let carbon_credits = pda::carbon_credits().0;
+    program_instruction(
+        carbonpay::accounts::MigrateProject {
+            authority: *authority,
+            carbon_credits,
+            project: *project_address,
+            token_mint: *token_mint,
+            vault: get_associated_token_address(&carbon_credits, token_mint),
+            system_program: system_program::ID,
+        },
+        carbonpay::instruction::MigrateProject {},
+    )
+}
+
+/// `migrate_purchase` for a purchase of `project` by `owner` still in the legacy layout,
+/// signed by whoever pays the extra rent
+pub fn migrate_purchase(payer: &Pubkey, purchase_address: &Pubkey, owner: &Pubkey, project: &Pubkey) -> Instruction {
+    program_instruction(
+        carbonpay::accounts::MigratePurchase {
+            payer: *payer,
+            purchase: *purchase_address,
+            owner: *owner,
+            project: *project,
+            holdings: pda::holdings(owner, project).0,
+            system_program: system_program::ID,
+        },
+        carbonpay::instruction::MigratePurchase {},
+    )
+}
+
+/// Arguments of `initialize_project`
+#[derive(Clone, Debug)]
+pub struct ProjectArgs {
+    /// Whole tokens to mint, in base units of `decimals`
+    pub amount: u64,
+    /// Lamports per whole token
+    pub price_per_token: u64,
+    pub carbon_pay_fee: u64,
+    /// Decimals of the project token mint, e.g. 3 for kilograms
+    pub decimals: u8,
+    pub uri: String,
+    pub name: String,
+    pub symbol: String,
+}
+
+/// Create the project NFT and token mints and the owner's NFT ATA, then `initialize_project`,
+/// which creates the vault. Signed by `owner`, `nft_mint` and `token_mint`.
+pub fn initialize_project(
+    owner: &Pubkey,
+    nft_mint: &Pubkey,
+    token_mint: &Pubkey,
+    args: ProjectArgs,
+) -> Vec<Instruction> {
+    let carbon_credits = pda::carbon_credits().0;
+    let mut ixs = create_mint(owner, nft_mint, owner, 0);
+    ixs.extend(create_mint(owner, token_mint, owner, args.decimals));
+    ixs.push(create_ata(owner, owner, nft_mint));
+    ixs.push(program_instruction(
+        carbonpay::accounts::InitializeProject {
+            project_owner: *owner,
+            project: pda::project(owner, nft_mint).0,
+            nft_mint: *nft_mint,
+            token_mint: *token_mint,
+            project_owner_nft_account: get_associated_token_address(owner, nft_mint),
+            vault: get_associated_token_address(&carbon_credits, token_mint),
+            carbon_credits,
+            metadata: pda::metadata(nft_mint),
+            master_edition: pda::master_edition(nft_mint),
+            token_program: spl_token::ID,
+            token_metadata_program: mpl_token_metadata::ID,
+            system_program: system_program::ID,
+            rent: anchor_lang::solana_program::sysvar::rent::ID,
+            associated_token_program: anchor_spl::associated_token::ID,
+        },
+        carbonpay::instruction::InitializeProject {
+            amount: args.amount,
+            price_per_token: args.price_per_token,
+            carbon_pay_fee: args.carbon_pay_fee,
+            uri: args.uri,
+            name: args.name,
+            symbol: args.symbol,
+        },
+    ));
+    ixs
+}
+
+/// Create the purchase NFT mint and the buyer's ATAs, then `purchase_carbon_credits`.
+/// Signed by `buyer` and `purchase_nft_mint`.
+pub fn purchase_carbon_credits(
+    buyer: &Pubkey,
+    project_address: &Pubkey,
+    project: &Project,
+    purchase_nft_mint: &Pubkey,
+    amount: u64,
+    allowlist_entry: Option<Pubkey>,
+) -> Vec<Instruction> {
+    let carbon_credits = pda::carbon_credits().0;
+    let mut ixs = create_mint(buyer, purchase_nft_mint, buyer, 0);
+    ixs.push(create_ata(buyer, buyer, purchase_nft_mint));
+    ixs.push(create_ata(buyer, buyer, &project.token_mint));
+    ixs.push(program_instruction(
+        carbonpay::accounts::PurchaseCarbonCredits {
+            project: *project_address,
+            project_owner: project.owner,
+            project_mint: project.token_mint,
+            carbon_credits,
+            project_token_account: get_associated_token_address(&carbon_credits, &project.token_mint),
+            purchase_nft_mint: *purchase_nft_mint,
+            buyer_nft_account: get_associated_token_address(buyer, purchase_nft_mint),
+            buyer_token_account: get_associated_token_address(buyer, &project.token_mint),
+            purchase: pda::purchase(buyer, project_address, purchase_nft_mint).0,
+            purchase_metadata: pda::metadata(purchase_nft_mint),
+            buyer_stats: pda::buyer_stats(buyer, project_address).0,
+            holdings: pda::holdings(buyer, project_address).0,
+            buyer: *buyer,
+            platform_config: pda::platform_config().0,
+            allowlist_entry,
+            token_program: spl_token::ID,
+            token_metadata_program: mpl_token_metadata::ID,
+            system_program: system_program::ID,
+            rent: anchor_lang::solana_program::sysvar::rent::ID,
+        },
+        carbonpay::instruction::PurchaseCarbonCredits { amount },
+    ));
+    ixs
+}
+
+/// Create the residual NFT mint and the owner's ATA for it, then `request_offset`.
+/// `requester` is the purchase owner or its approved offset delegate.
+/// Signed by `requester` and `new_nft_mint`.
+pub fn request_offset(
+    requester: &Pubkey,
+    purchase_address: &Pubkey,
+    purchase: &Purchase,
+    project: &Project,
+    new_nft_mint: &Pubkey,
+    amount: u64,
+    request_id: String,
+) -> Vec<Instruction> {
+    let offset_delegate =
+        (*requester != purchase.buyer).then(|| pda::offset_delegate(purchase_address).0);
+    // a delegate works from the certificate and tokens escrowed by the delegate PDA
+    let holder = offset_delegate.unwrap_or(purchase.buyer);
+    let mut ixs = create_mint(requester, new_nft_mint, requester, 0);
+    ixs.push(create_ata(requester, &holder, new_nft_mint));
+    ixs.push(program_instruction(
+        carbonpay::accounts::RequestOffset {
+            offset_requester: *requester,
+            purchase: *purchase_address,
+            offset_delegate,
+            purchase_owner: offset_delegate.map(|_| purchase.buyer),
+            project: purchase.project,
+            holdings: pda::holdings(&purchase.buyer, &purchase.project).0,
+            original_nft_mint: purchase.certificate_mint,
+            original_nft_account: get_associated_token_address(&holder, &purchase.certificate_mint),
+            new_nft_mint: *new_nft_mint,
+            new_nft_account: get_associated_token_address(&holder, new_nft_mint),
+            new_nft_metadata: pda::metadata(new_nft_mint),
+            token_mint: project.token_mint,
+            buyer_token_account: get_associated_token_address(&holder, &project.token_mint),
+            carbon_credits: pda::carbon_credits().0,
+            offset_request: pda::offset_request(&purchase.buyer, purchase_address, &request_id).0,
+            token_program: spl_token::ID,
+            token_metadata_program: mpl_token_metadata::ID,
+            system_program: system_program::ID,
+            rent: anchor_lang::solana_program::sysvar::rent::ID,
+        },
+        carbonpay::instruction::RequestOffset { amount, request_id },
+    ));
+    ixs
+}
+
+/// `approve_offset_vote` on `offset_request`, signed by `approver`
+pub fn approve_offset_vote(approver: &Pubkey, offset_request: &Pubkey) -> Instruction {
+    program_instruction(
+        carbonpay::accounts::ApproveOffsetVote {
+            approver: *approver,
+            platform_config: pda::platform_config().0,
+            offset_request: *offset_request,
+            offset_approval: pda::offset_approval(offset_request).0,
+            system_program: system_program::ID,
+        },
+        carbonpay::instruction::ApproveOffsetVote {},
+    )
+}
+
+/// Scope of an allowlist entry: the project, or the platform config for a platform-wide entry
+fn allowlist_scope(project: Option<Pubkey>) -> Pubkey {
+    project.unwrap_or_else(|| pda::platform_config().0)
+}
+
+/// `add_allowlist_entry` for `wallet`, scoped to `project` or platform-wide when `None`.
+/// Signed by the platform config authority.
+pub fn add_allowlist_entry(
+    authority: &Pubkey,
+    project: Option<Pubkey>,
+    wallet: &Pubkey,
+    expires_at: i64,
+) -> Instruction {
+    let scope = allowlist_scope(project);
+    program_instruction(
+        carbonpay::accounts::AddAllowlistEntry {
+            authority: *authority,
+            platform_config: pda::platform_config().0,
+            project,
+            allowlist_entry: pda::allowlist_entry(&scope, wallet).0,
+            system_program: system_program::ID,
+        },
+        carbonpay::instruction::AddAllowlistEntry {
+            scope,
+            wallet: *wallet,
+            expires_at,
+        },
+    )
+}
+
+/// `set_allowlist_expiry` on an existing entry, signed by the platform config authority
+pub fn set_allowlist_expiry(authority: &Pubkey, allowlist_entry: &Pubkey, expires_at: i64) -> Instruction {
+    program_instruction(
+        carbonpay::accounts::SetAllowlistExpiry {
+            authority: *authority,
+            platform_config: pda::platform_config().0,
+            allowlist_entry: *allowlist_entry,
+        },
+        carbonpay::instruction::SetAllowlistExpiry { expires_at },
+    )
+}
+
+/// `remove_allowlist_entry`, signed by the platform config authority who receives the rent
+pub fn remove_allowlist_entry(authority: &Pubkey, allowlist_entry: &Pubkey) -> Instruction {
+    program_instruction(
+        carbonpay::accounts::RemoveAllowlistEntry {
+            authority: *authority,
+            platform_config: pda::platform_config().0,
+            allowlist_entry: *allowlist_entry,
+        },
+        carbonpay::instruction::RemoveAllowlistEntry {},
+    )
+}
+
+/// `set_allowlist_required` on `project`, or platform-wide when `None`.
+/// Signed by the platform config authority.
+pub fn set_allowlist_required(authority: &Pubkey, project: Option<Pubkey>, required: bool) -> Instruction {
+    program_instruction(
+        carbonpay::accounts::SetAllowlistRequired {
+            authority: *authority,
+            platform_config: pda::platform_config().0,
+            project,
+        },
+        carbonpay::instruction::SetAllowlistRequired { required },
+    )
+}
+
+/// `set_project_category`, signed by the platform config authority
+pub fn set_project_category(authority: &Pubkey, project: &Pubkey, category: ProjectCategory) -> Instruction {
+    program_instruction(
+        carbonpay::accounts::SetProjectCategory {
+            authority: *authority,
+            platform_config: pda::platform_config().0,
+            project: *project,
+        },
+        carbonpay::instruction::SetProjectCategory { category },
+    )
+}
+
+/// `set_purchase_limits`, signed by the project owner
+pub fn set_purchase_limits(
+    owner: &Pubkey,
+    project: &Pubkey,
+    min_purchase_amount: u64,
+    max_purchase_amount: u64,
+    max_per_buyer: u64,
+) -> Instruction {
+    program_instruction(
+        carbonpay::accounts::SetPurchaseLimits {
+            project_owner: *owner,
+            project: *project,
+        },
+        carbonpay::instruction::SetPurchaseLimits {
+            min_purchase_amount,
+            max_purchase_amount,
+            max_per_buyer,
+        },
+    )
+}
+
+/// `set_sale_window`, signed by the project owner
+pub fn set_sale_window(
+    owner: &Pubkey,
+    project: &Pubkey,
+    sale_start: i64,
+    sale_end: i64,
+    early_access_start: i64,
+) -> Instruction {
+    program_instruction(
+        carbonpay::accounts::SetSaleWindow {
+            project_owner: *owner,
+            project: *project,
+        },
+        carbonpay::instruction::SetSaleWindow {
+            sale_start,
+            sale_end,
+            early_access_start,
+        },
+    )
+}
+
+/// `set_price_tiers`, signed by the project owner
+pub fn set_price_tiers(owner: &Pubkey, project: &Pubkey, price_tiers: Vec<PriceTier>) -> Instruction {
+    program_instruction(
+        carbonpay::accounts::SetPriceTiers {
+            project_owner: *owner,
+            project: *project,
+        },
+        carbonpay::instruction::SetPriceTiers { price_tiers },
+    )
+}
+
+/// `list_credits`, moving `amount` tokens of the purchase into the listing escrow.
+/// Signed by the purchase owner.
+pub fn list_credits(
+    seller: &Pubkey,
+    purchase_address: &Pubkey,
+    purchase: &Purchase,
+    project: &Project,
+    amount: u64,
+    price_per_token: u64,
+) -> Instruction {
+    let listing = pda::listing(purchase_address).0;
+    program_instruction(
+        carbonpay::accounts::ListCredits {
+            seller: *seller,
+            purchase: *purchase_address,
+            project: purchase.project,
+            token_mint: project.token_mint,
+            seller_token_account: get_associated_token_address(seller, &project.token_mint),
+            holdings: pda::holdings(seller, &purchase.project).0,
+            subscription: pda::subscription(seller, &purchase.project).0,
+            offset_delegate: pda::offset_delegate(purchase_address).0,
+            seller_nft_account: get_associated_token_address(seller, &purchase.certificate_mint),
+            listing,
+            escrow: get_associated_token_address(&listing, &project.token_mint),
+            token_program: spl_token::ID,
+            associated_token_program: anchor_spl::associated_token::ID,
+            system_program: system_program::ID,
+        },
+        carbonpay::instruction::ListCredits {
+            amount,
+            price_per_token,
+        },
+    )
+}
+
+/// Create the purchase NFT mint and the buyer's ATAs, then `buy_listing`.
+/// `allowlist_entry` is required when the project or platform enforces an allowlist.
+/// Signed by `buyer` and `purchase_nft_mint`.
+pub fn buy_listing(
+    buyer: &Pubkey,
+    listing_address: &Pubkey,
+    listing: &Listing,
+    project: &Project,
+    purchase_nft_mint: &Pubkey,
+    allowlist_entry: Option<Pubkey>,
+) -> Vec<Instruction> {
+    let mut ixs = create_mint(buyer, purchase_nft_mint, buyer, 0);
+    ixs.push(create_ata(buyer, buyer, purchase_nft_mint));
+    ixs.push(create_ata(buyer, buyer, &project.token_mint));
+    ixs.push(program_instruction(
+        carbonpay::accounts::BuyListing {
+            buyer: *buyer,
+            seller: listing.seller,
+            listing: *listing_address,
+            project: listing.project,
+            token_mint: project.token_mint,
+            carbon_credits: pda::carbon_credits().0,
+            platform_config: pda::platform_config().0,
+            allowlist_entry,
+            escrow: get_associated_token_address(listing_address, &project.token_mint),
+            buyer_token_account: get_associated_token_address(buyer, &project.token_mint),
+            purchase_nft_mint: *purchase_nft_mint,
+            buyer_nft_account: get_associated_token_address(buyer, purchase_nft_mint),
+            purchase: pda::purchase(buyer, &listing.project, purchase_nft_mint).0,
+            holdings: pda::holdings(buyer, &listing.project).0,
+            purchase_metadata: pda::metadata(purchase_nft_mint),
+            token_program: spl_token::ID,
+            token_metadata_program: mpl_token_metadata::ID,
+            system_program: system_program::ID,
+            rent: anchor_lang::solana_program::sysvar::rent::ID,
+        },
+        carbonpay::instruction::BuyListing {},
+    ));
+    ixs
+}
+
+/// `cancel_listing`, returning the escrowed tokens. Signed by the seller.
+pub fn cancel_listing(seller: &Pubkey, listing_address: &Pubkey, listing: &Listing, project: &Project) -> Instruction {
+    program_instruction(
+        carbonpay::accounts::CancelListing {
+            seller: *seller,
+            listing: *listing_address,
+            purchase: listing.purchase,
+            holdings: pda::holdings(seller, &listing.project).0,
+            token_mint: project.token_mint,
+            escrow: get_associated_token_address(listing_address, &project.token_mint),
+            seller_token_account: get_associated_token_address(seller, &project.token_mint),
+            token_program: spl_token::ID,
+        },
+        carbonpay::instruction::CancelListing {},
+    )
+}
+
+/// `start_dutch_auction`, signed by the project owner
+pub fn start_dutch_auction(
+    owner: &Pubkey,
+    project: &Pubkey,
+    start_price: u64,
+    floor_price: u64,
+    start_time: i64,
+    duration: i64,
+    decay_interval: i64,
+) -> Instruction {
+    program_instruction(
+        carbonpay::accounts::StartDutchAuction {
+            project_owner: *owner,
+            project: *project,
+        },
+        carbonpay::instruction::StartDutchAuction {
+            start_price,
+            floor_price,
+            start_time,
+            duration,
+            decay_interval,
+        },
+    )
+}
+
+/// `settle_dutch_auction`, permissionless once the auction ended or sold out
+pub fn settle_dutch_auction(project_address: &Pubkey, project: &Project) -> Instruction {
+    program_instruction(
+        carbonpay::accounts::SettleDutchAuction {
+            project: *project_address,
+            project_owner: project.owner,
+            carbon_credits: pda::carbon_credits().0,
+            platform_config: pda::platform_config().0,
+        },
+        carbonpay::instruction::SettleDutchAuction {},
+    )
+}
+
+/// `claim_auction_refund` of what `buyer` paid above the clearing price
+pub fn claim_auction_refund(buyer: &Pubkey, project: &Pubkey) -> Instruction {
+    program_instruction(
+        carbonpay::accounts::ClaimAuctionRefund {
+            buyer: *buyer,
+            project: *project,
+            buyer_stats: pda::buyer_stats(buyer, project).0,
+        },
+        carbonpay::instruction::ClaimAuctionRefund {},
+    )
+}
+
+/// `create_otc_offer` reserving `amount` tokens for `buyer`, signed by the project owner
+pub fn create_otc_offer(
+    owner: &Pubkey,
+    project: &Pubkey,
+    buyer: &Pubkey,
+    offer_id: u64,
+    amount: u64,
+    price_per_token: u64,
+    expires_at: i64,
+) -> Instruction {
+    program_instruction(
+        carbonpay::accounts::CreateOtcOffer {
+            project_owner: *owner,
+            project: *project,
+            otc_offer: pda::otc_offer(project, buyer, offer_id).0,
+            system_program: system_program::ID,
+        },
+        carbonpay::instruction::CreateOtcOffer {
+            buyer: *buyer,
+            offer_id,
+            amount,
+            price_per_token,
+            expires_at,
+        },
+    )
+}
+
+/// Create the purchase NFT mint and the buyer's ATAs, then `accept_otc_offer`.
+/// `allowlist_entry` is required when the project or platform enforces an allowlist.
+/// Signed by the offer's buyer and `purchase_nft_mint`.
+pub fn accept_otc_offer(
+    offer: &OtcOffer,
+    project: &Project,
+    purchase_nft_mint: &Pubkey,
+    allowlist_entry: Option<Pubkey>,
+) -> Vec<Instruction> {
+    let buyer = &offer.buyer;
+    let carbon_credits = pda::carbon_credits().0;
+    let mut ixs = create_mint(buyer, purchase_nft_mint, buyer, 0);
+    ixs.push(create_ata(buyer, buyer, purchase_nft_mint));
+    ixs.push(create_ata(buyer, buyer, &project.token_mint));
+    ixs.push(program_instruction(
+        carbonpay::accounts::AcceptOtcOffer {
+            buyer: *buyer,
+            otc_offer: pda::otc_offer(&offer.project, buyer, offer.offer_id).0,
+            project: offer.project,
+            project_owner: project.owner,
+            project_mint: project.token_mint,
+            carbon_credits,
+            platform_config: pda::platform_config().0,
+            allowlist_entry,
+            buyer_stats: pda::buyer_stats(buyer, &offer.project).0,
+            holdings: pda::holdings(buyer, &offer.project).0,
+            project_token_account: project.vault,
+            purchase_nft_mint: *purchase_nft_mint,
+            buyer_nft_account: get_associated_token_address(buyer, purchase_nft_mint),
+            buyer_token_account: get_associated_token_address(buyer, &project.token_mint),
+            purchase: pda::purchase(buyer, &offer.project, purchase_nft_mint).0,
+            purchase_metadata: pda::metadata(purchase_nft_mint),
+            token_program: spl_token::ID,
+            token_metadata_program: mpl_token_metadata::ID,
+            system_program: system_program::ID,
+            rent: anchor_lang::solana_program::sysvar::rent::ID,
+        },
+        carbonpay::instruction::AcceptOtcOffer {},
+    ));
+    ixs
+}
+
+/// `cancel_otc_offer`, releasing the reserved tokens. Signed by the project owner, or by
+/// anyone once the offer has expired.
+pub fn cancel_otc_offer(canceller: &Pubkey, offer: &OtcOffer, project: &Project) -> Instruction {
+    program_instruction(
+        carbonpay::accounts::CancelOtcOffer {
+            canceller: *canceller,
+            project_owner: project.owner,
+            project: offer.project,
+            otc_offer: pda::otc_offer(&offer.project, &offer.buyer, offer.offer_id).0,
+        },
+        carbonpay::instruction::CancelOtcOffer {},
+    )
+}
+
+/// Create the NFT mint of the purchase the subscription fills and the buyer's ATAs, then
+/// `create_subscription`. Signed by `buyer` and `purchase_nft_mint`.
+pub fn create_subscription(
+    buyer: &Pubkey,
+    project_address: &Pubkey,
+    project: &Project,
+    purchase_nft_mint: &Pubkey,
+    amount_per_period: u64,
+    max_price_per_token: u64,
+    deposit: u64,
+) -> Vec<Instruction> {
+    let mut ixs = create_mint(buyer, purchase_nft_mint, buyer, 0);
+    ixs.push(create_ata(buyer, buyer, purchase_nft_mint));
+    ixs.push(create_ata(buyer, buyer, &project.token_mint));
+    ixs.push(program_instruction(
+        carbonpay::accounts::CreateSubscription {
+            buyer: *buyer,
+            project: *project_address,
+            subscription: pda::subscription(buyer, project_address).0,
+            purchase: pda::purchase(buyer, project_address, purchase_nft_mint).0,
+            buyer_stats: pda::buyer_stats(buyer, project_address).0,
+            holdings: pda::holdings(buyer, project_address).0,
+            purchase_nft_mint: *purchase_nft_mint,
+            buyer_nft_account: get_associated_token_address(buyer, purchase_nft_mint),
+            purchase_metadata: pda::metadata(purchase_nft_mint),
+            token_program: spl_token::ID,
+            token_metadata_program: mpl_token_metadata::ID,
+            system_program: system_program::ID,
+            rent: anchor_lang::solana_program::sysvar::rent::ID,
+        },
+        carbonpay::instruction::CreateSubscription {
+            amount_per_period,
+            max_price_per_token,
+            deposit,
+        },
+    ));
+    ixs
+}
+
+/// `fund_subscription` with `lamports`, signed by the subscriber
+pub fn fund_subscription(buyer: &Pubkey, project: &Pubkey, lamports: u64) -> Instruction {
+    program_instruction(
+        carbonpay::accounts::FundSubscription {
+            buyer: *buyer,
+            subscription: pda::subscription(buyer, project).0,
+            system_program: system_program::ID,
+        },
+        carbonpay::instruction::FundSubscription { lamports },
+    )
+}
+
+/// `execute_subscription`, the permissionless crank buying one period of credits.
+/// `allowlist_entry` is the subscriber's, required when the project or platform enforces an allowlist.
+pub fn execute_subscription(
+    subscription: &Subscription,
+    project: &Project,
+    allowlist_entry: Option<Pubkey>,
+) -> Instruction {
+    program_instruction(
+        carbonpay::accounts::ExecuteSubscription {
+            subscription: pda::subscription(&subscription.buyer, &subscription.project).0,
+            purchase: subscription.purchase,
+            project: subscription.project,
+            project_owner: project.owner,
+            project_mint: project.token_mint,
+            carbon_credits: pda::carbon_credits().0,
+            platform_config: pda::platform_config().0,
+            allowlist_entry,
+            buyer_stats: pda::buyer_stats(&subscription.buyer, &subscription.project).0,
+            holdings: pda::holdings(&subscription.buyer, &subscription.project).0,
+            project_token_account: project.vault,
+            buyer_token_account: get_associated_token_address(&subscription.buyer, &project.token_mint),
+            token_program: spl_token::ID,
+        },
+        carbonpay::instruction::ExecuteSubscription {},
+    )
+}
+
+/// `cancel_subscription`, refunding the unspent deposit. Signed by the subscriber.
+pub fn cancel_subscription(buyer: &Pubkey, project: &Pubkey) -> Instruction {
+    program_instruction(
+        carbonpay::accounts::CancelSubscription {
+            buyer: *buyer,
+            subscription: pda::subscription(buyer, project).0,
+        },
+        carbonpay::instruction::CancelSubscription {},
+    )
+}
+
+/// `create_basket` and its mint, signed by the platform config authority
+pub fn create_basket(authority: &Pubkey, basket_id: u64, allowed_categories: u8) -> Instruction {
+    let basket = pda::basket(basket_id).0;
+    program_instruction(
+        carbonpay::accounts::CreateBasket {
+            authority: *authority,
+            platform_config: pda::platform_config().0,
+            basket,
+            basket_mint: pda::basket_mint(&basket).0,
+            token_program: spl_token::ID,
+            system_program: system_program::ID,
+            rent: anchor_lang::solana_program::sysvar::rent::ID,
+        },
+        carbonpay::instruction::CreateBasket {
+            basket_id,
+            allowed_categories,
+        },
+    )
+}
+
+/// `update_basket_eligibility`, signed by the basket authority
+pub fn update_basket_eligibility(authority: &Pubkey, basket_id: u64, allowed_categories: u8) -> Instruction {
+    program_instruction(
+        carbonpay::accounts::UpdateBasketEligibility {
+            authority: *authority,
+            basket: pda::basket(basket_id).0,
+        },
+        carbonpay::instruction::UpdateBasketEligibility { allowed_categories },
+    )
+}
+
+/// Create the NFT mint and ATA for the replacement certificate and the depositor's basket
+/// token ATA, then `deposit_to_basket` from the purchase. Signed by the purchase owner and
+/// `replacement_nft_mint`.
+pub fn deposit_to_basket(
+    depositor: &Pubkey,
+    basket_id: u64,
+    purchase_address: &Pubkey,
+    purchase: &Purchase,
+    project: &Project,
+    replacement_nft_mint: &Pubkey,
+    amount: u64,
+) -> Vec<Instruction> {
+    let basket = pda::basket(basket_id).0;
+    let basket_mint = pda::basket_mint(&basket).0;
+    let mut ixs = create_mint(depositor, replacement_nft_mint, depositor, 0);
+    ixs.push(create_ata(depositor, depositor, replacement_nft_mint));
+    ixs.push(create_ata(depositor, depositor, &basket_mint));
+    ixs.push(program_instruction(
+        carbonpay::accounts::DepositToBasket {
+            depositor: *depositor,
+            basket,
+            basket_mint,
+            project: purchase.project,
+            token_mint: project.token_mint,
+            purchase: *purchase_address,
+            holdings: pda::holdings(depositor, &purchase.project).0,
+            certificate_mint: purchase.certificate_mint,
+            nft_account: get_associated_token_address(depositor, &purchase.certificate_mint),
+            replacement_nft_mint: *replacement_nft_mint,
+            replacement_nft_account: get_associated_token_address(depositor, replacement_nft_mint),
+            replacement_nft_metadata: pda::metadata(replacement_nft_mint),
+            depositor_token_account: get_associated_token_address(depositor, &project.token_mint),
+            depositor_basket_account: get_associated_token_address(depositor, &basket_mint),
+            basket_reserve: pda::basket_reserve(&basket, &purchase.project).0,
+            basket_vault: get_associated_token_address(&basket, &project.token_mint),
+            token_program: spl_token::ID,
+            token_metadata_program: mpl_token_metadata::ID,
+            associated_token_program: anchor_spl::associated_token::ID,
+            system_program: system_program::ID,
+            rent: anchor_lang::solana_program::sysvar::rent::ID,
+        },
+        carbonpay::instruction::DepositToBasket { amount },
+    ));
+    ixs
+}
+
+/// Create the redeemer's ATA for every project in the basket, then `redeem_from_basket`.
+/// `reserves` must list every reserve of the basket. Signed by the redeemer.
+pub fn redeem_from_basket(
+    redeemer: &Pubkey,
+    basket_id: u64,
+    reserves: &[BasketReserve],
+    amount: u64,
+) -> Vec<Instruction> {
+    let basket = pda::basket(basket_id).0;
+    let basket_mint = pda::basket_mint(&basket).0;
+    let mut ixs: Vec<Instruction> = reserves
+        .iter()
+        .map(|reserve| create_ata(redeemer, redeemer, &reserve.token_mint))
+        .collect();
+    let mut ix = program_instruction(
+        carbonpay::accounts::RedeemFromBasket {
+            redeemer: *redeemer,
+            basket,
+            basket_mint,
+            redeemer_basket_account: get_associated_token_address(redeemer, &basket_mint),
+            token_program: spl_token::ID,
+        },
+        carbonpay::instruction::RedeemFromBasket { amount },
+    );
+    for reserve in reserves {
+        ix.accounts.extend([
+            AccountMeta::new(pda::basket_reserve(&basket, &reserve.project).0, false),
+            AccountMeta::new(reserve.vault, false),
+            AccountMeta::new(get_associated_token_address(redeemer, &reserve.token_mint), false),
+        ]);
+    }
+    ixs.push(ix);
+    ixs
+}
+
+/// `retire_from_basket`; `reserves` must list every reserve of the basket. Signed by the retiree.
+pub fn retire_from_basket(retiree: &Pubkey, basket_id: u64, reserves: &[BasketReserve], amount: u64) -> Instruction {
+    let basket = pda::basket(basket_id).0;
+    let basket_mint = pda::basket_mint(&basket).0;
+    let mut ix = program_instruction(
+        carbonpay::accounts::RetireFromBasket {
+            retiree: *retiree,
+            basket,
+            basket_mint,
+            retiree_basket_account: get_associated_token_address(retiree, &basket_mint),
+            carbon_credits: pda::carbon_credits().0,
+            token_program: spl_token::ID,
+        },
+        carbonpay::instruction::RetireFromBasket { amount },
+    );
+    for reserve in reserves {
+        ix.accounts.extend([
+            AccountMeta::new(pda::basket_reserve(&basket, &reserve.project).0, false),
+            AccountMeta::new(reserve.vault, false),
+            AccountMeta::new(reserve.token_mint, false),
+            AccountMeta::new(reserve.project, false),
+        ]);
+    }
+    ix
+}
+
+/// `retire_held_credits`, burning `amount` of the holder's project tokens that belong to no
+/// purchase, e.g. basket redemptions. Signed by `holder`.
+pub fn retire_held_credits(
+    holder: &Pubkey,
+    project_address: &Pubkey,
+    project: &Project,
+    amount: u64,
+    request_id: String,
+) -> Instruction {
+    program_instruction(
+        carbonpay::accounts::RetireHeldCredits {
+            holder: *holder,
+            project: *project_address,
+            token_mint: project.token_mint,
+            holder_token_account: get_associated_token_address(holder, &project.token_mint),
+            holdings: pda::holdings(holder, project_address).0,
+            carbon_credits: pda::carbon_credits().0,
+            offset_request: pda::retirement(holder, project_address, &request_id).0,
+            token_program: spl_token::ID,
+            system_program: system_program::ID,
+        },
+        carbonpay::instruction::RetireHeldCredits { amount, request_id },
+    )
+}
+
+/// `purchase_and_retire`, buying `amount` tokens and burning them at once. Signed by `buyer`.
+pub fn purchase_and_retire(
+    buyer: &Pubkey,
+    project_address: &Pubkey,
+    project: &Project,
+    amount: u64,
+    request_id: String,
+    allowlist_entry: Option<Pubkey>,
+) -> Instruction {
+    program_instruction(
+        carbonpay::accounts::PurchaseAndRetire {
+            project: *project_address,
+            project_owner: project.owner,
+            project_mint: project.token_mint,
+            carbon_credits: pda::carbon_credits().0,
+            project_token_account: project.vault,
+            offset_request: pda::retirement(buyer, project_address, &request_id).0,
+            buyer_stats: pda::buyer_stats(buyer, project_address).0,
+            buyer: *buyer,
+            platform_config: pda::platform_config().0,
+            allowlist_entry,
+            token_program: spl_token::ID,
+            system_program: system_program::ID,
+        },
+        carbonpay::instruction::PurchaseAndRetire { amount, request_id },
+    )
+}
+
+/// `approve_offset_delegate` letting `delegate` offset up to `max_amount` tokens of the
+/// purchase. Escrows the certificate and the allowance with the delegate PDA. Signed by the
+/// purchase owner.
+pub fn approve_offset_delegate(
+    owner: &Pubkey,
+    purchase_address: &Pubkey,
+    purchase: &Purchase,
+    project: &Project,
+    delegate: &Pubkey,
+    max_amount: u64,
+    expires_at: i64,
+) -> Instruction {
+    let offset_delegate = pda::offset_delegate(purchase_address).0;
+    program_instruction(
+        carbonpay::accounts::ApproveOffsetDelegate {
+            owner: *owner,
+            purchase: *purchase_address,
+            project: purchase.project,
+            holdings: pda::holdings(owner, &purchase.project).0,
+            offset_delegate,
+            certificate_mint: purchase.certificate_mint,
+            nft_account: get_associated_token_address(owner, &purchase.certificate_mint),
+            delegate_nft_account: get_associated_token_address(&offset_delegate, &purchase.certificate_mint),
+            token_mint: project.token_mint,
+            owner_token_account: get_associated_token_address(owner, &project.token_mint),
+            delegate_token_account: get_associated_token_address(&offset_delegate, &project.token_mint),
+            token_program: spl_token::ID,
+            associated_token_program: anchor_spl::associated_token::ID,
+            system_program: system_program::ID,
+        },
+        carbonpay::instruction::ApproveOffsetDelegate {
+            delegate: *delegate,
+            max_amount,
+            expires_at,
+        },
+    )
+}
+
+/// `revoke_offset_delegate`, returning the escrowed certificate and unused allowance. The
+/// allowance escrow is left out once `delegate` used it up, as `request_offset` closed it.
+/// Signed by the purchase owner.
+pub fn revoke_offset_delegate(
+    owner: &Pubkey,
+    purchase_address: &Pubkey,
+    purchase: &Purchase,
+    project: &Project,
+    delegate: &OffsetDelegate,
+) -> Instruction {
+    let offset_delegate = pda::offset_delegate(purchase_address).0;
+    program_instruction(
+        carbonpay::accounts::RevokeOffsetDelegate {
+            owner: *owner,
+            purchase: *purchase_address,
+            project: purchase.project,
+            holdings: pda::holdings(owner, &purchase.project).0,
+            offset_delegate,
+            certificate_mint: purchase.certificate_mint,
+            delegate_nft_account: get_associated_token_address(&offset_delegate, &purchase.certificate_mint),
+            nft_account: get_associated_token_address(owner, &purchase.certificate_mint),
+            token_mint: project.token_mint,
+            delegate_token_account: (delegate.remaining_amount > 0)
+                .then(|| get_associated_token_address(&offset_delegate, &project.token_mint)),
+            owner_token_account: get_associated_token_address(owner, &project.token_mint),
+            token_program: spl_token::ID,
+            associated_token_program: anchor_spl::associated_token::ID,
+            system_program: system_program::ID,
+        },
+        carbonpay::instruction::RevokeOffsetDelegate {},
+    )
+}
+
+/// `request_batch_offset` over `(purchase address, record, amount)` entries of one project.
+/// Each purchase's certificate must be in the requester's ATA. A legacy transaction only fits a
+/// few purchases; for larger batches put [`lookup_table_addresses`] of the instruction in a table
+/// from [`create_lookup_table`] and send it in a v0 transaction. Signed by the purchases' owner.
+pub fn request_batch_offset(
+    requester: &Pubkey,
+    project_address: &Pubkey,
+    project: &Project,
+    purchases: &[(Pubkey, Purchase, u64)],
+    request_id: String,
+) -> Instruction {
+    let offset_request = pda::offset_request(requester, project_address, &request_id).0;
+    let mut ix = program_instruction(
+        carbonpay::accounts::RequestBatchOffset {
+            offset_requester: *requester,
+            project: *project_address,
+            token_mint: project.token_mint,
+            buyer_token_account: get_associated_token_address(requester, &project.token_mint),
+            holdings: pda::holdings(requester, project_address).0,
+            carbon_credits: pda::carbon_credits().0,
+            offset_request,
+            offset_batch: pda::offset_batch(&offset_request).0,
+            token_program: spl_token::ID,
+            system_program: system_program::ID,
+        },
+        carbonpay::instruction::RequestBatchOffset {
+            amounts: purchases.iter().map(|(_, _, amount)| *amount).collect(),
+            request_id,
+        },
+    );
+    for (address, purchase, _) in purchases {
+        ix.accounts.extend([
+            AccountMeta::new(*address, false),
+            AccountMeta::new_readonly(get_associated_token_address(requester, &purchase.certificate_mint), false),
+        ]);
+    }
+    ix
+}
+
+/// The accounts of `instruction` an address lookup table can stand in for: all but the signers
+pub fn lookup_table_addresses(instruction: &Instruction) -> Vec<Pubkey> {
+    let mut addresses: Vec<Pubkey> = Vec::new();
+    for meta in instruction.accounts.iter().filter(|meta| !meta.is_signer) {
+        if !addresses.contains(&meta.pubkey) {
+            addresses.push(meta.pubkey);
+        }
+    }
+    addresses
+}
+
+/// Addresses added per `extend_lookup_table`, so that each fits a legacy transaction
+const LOOKUP_TABLE_EXTEND_CHUNK: usize = 20;
+
+/// Create an address lookup table owned and paid for by `authority` holding `addresses`.
+/// Returns the table and its instructions, each to be sent in its own transaction, in order;
+/// `recent_slot` must be a recent finalized slot. The table can be used from the slot after the
+/// last one lands.
+pub fn create_lookup_table(authority: &Pubkey, recent_slot: u64, addresses: &[Pubkey]) -> (Pubkey, Vec<Instruction>) {
+    let (create, table) = lookup_table::create_lookup_table(*authority, *authority, recent_slot);
+    let mut ixs = vec![create];
+    ixs.extend(
+        addresses
+            .chunks(LOOKUP_TABLE_EXTEND_CHUNK)
+            .map(|chunk| lookup_table::extend_lookup_table(table, *authority, Some(*authority), chunk.to_vec())),
+    );
+    (table, ixs)
+}
+
+/// Create the NFT mint and ATA for the merged certificate, then `merge_purchases` folding
+/// `sources` (address and record) into `purchase`, whose record is `target`. Signed by the
+/// buyer and `replacement_nft_mint`.
+pub fn merge_purchases(
+    buyer: &Pubkey,
+    purchase: &Pubkey,
+    target: &Purchase,
+    replacement_nft_mint: &Pubkey,
+    sources: &[(Pubkey, Purchase)],
+) -> Vec<Instruction> {
+    let mut ixs = create_mint(buyer, replacement_nft_mint, buyer, 0);
+    ixs.push(create_ata(buyer, buyer, replacement_nft_mint));
+    let mut ix = program_instruction(
+        carbonpay::accounts::MergePurchases {
+            buyer: *buyer,
+            purchase: *purchase,
+            subscription: pda::subscription(buyer, &target.project).0,
+            certificate_mint: target.certificate_mint,
+            nft_account: get_associated_token_address(buyer, &target.certificate_mint),
+            replacement_nft_mint: *replacement_nft_mint,
+            replacement_nft_account: get_associated_token_address(buyer, replacement_nft_mint),
+            replacement_nft_metadata: pda::metadata(replacement_nft_mint),
+            token_program: spl_token::ID,
+            token_metadata_program: mpl_token_metadata::ID,
+            system_program: system_program::ID,
+            rent: anchor_lang::solana_program::sysvar::rent::ID,
+        },
+        carbonpay::instruction::MergePurchases {},
+    );
+    for (address, source) in sources {
+        ix.accounts.extend([
+            AccountMeta::new(*address, false),
+            AccountMeta::new(source.certificate_mint, false),
+            AccountMeta::new(get_associated_token_address(buyer, &source.certificate_mint), false),
+            AccountMeta::new_readonly(pda::listing(address).0, false),
+            AccountMeta::new_readonly(pda::offset_delegate(address).0, false),
+        ]);
+    }
+    ixs.push(ix);
+    ixs
+}
+
+/// Create the NFT mints and ATAs for the new position and for what the purchase keeps, then
+/// `split_purchase` carving `amount` tokens out of the purchase. Signed by the buyer,
+/// `new_nft_mint` and `replacement_nft_mint`.
+pub fn split_purchase(
+    buyer: &Pubkey,
+    purchase_address: &Pubkey,
+    purchase: &Purchase,
+    new_nft_mint: &Pubkey,
+    replacement_nft_mint: &Pubkey,
+    amount: u64,
+) -> Vec<Instruction> {
+    let mut ixs = create_mint(buyer, new_nft_mint, buyer, 0);
+    ixs.push(create_ata(buyer, buyer, new_nft_mint));
+    ixs.extend(create_mint(buyer, replacement_nft_mint, buyer, 0));
+    ixs.push(create_ata(buyer, buyer, replacement_nft_mint));
+    ixs.push(program_instruction(
+        carbonpay::accounts::SplitPurchase {
+            buyer: *buyer,
+            project: purchase.project,
+            purchase: *purchase_address,
+            certificate_mint: purchase.certificate_mint,
+            nft_account: get_associated_token_address(buyer, &purchase.certificate_mint),
+            replacement_nft_mint: *replacement_nft_mint,
+            replacement_nft_account: get_associated_token_address(buyer, replacement_nft_mint),
+            replacement_nft_metadata: pda::metadata(replacement_nft_mint),
+            new_nft_mint: *new_nft_mint,
+            new_nft_account: get_associated_token_address(buyer, new_nft_mint),
+            new_nft_metadata: pda::metadata(new_nft_mint),
+            subscription: pda::subscription(buyer, &purchase.project).0,
+            new_purchase: pda::purchase(buyer, &purchase.project, new_nft_mint).0,
+            token_program: spl_token::ID,
+            token_metadata_program: mpl_token_metadata::ID,
+            system_program: system_program::ID,
+            rent: anchor_lang::solana_program::sysvar::rent::ID,
+        },
+        carbonpay::instruction::SplitPurchase { amount },
+    ));
+    ixs
+}
+
+/// `transfer_purchase` moving the position, its certificate and tokens to `recipient`.
+/// Signed by the purchase owner.
+pub fn transfer_purchase(
+    owner: &Pubkey,
+    recipient: &Pubkey,
+    purchase_address: &Pubkey,
+    purchase: &Purchase,
+    project: &Project,
+) -> Instruction {
+    program_instruction(
+        carbonpay::accounts::TransferPurchase {
+            owner: *owner,
+            recipient: *recipient,
+            project: purchase.project,
+            purchase: *purchase_address,
+            listing: pda::listing(purchase_address).0,
+            subscription: pda::subscription(owner, &purchase.project).0,
+            offset_delegate: pda::offset_delegate(purchase_address).0,
+            new_purchase: pda::purchase(recipient, &purchase.project, &purchase.nft_mint).0,
+            holdings: pda::holdings(owner, &purchase.project).0,
+            recipient_holdings: pda::holdings(recipient, &purchase.project).0,
+            nft_mint: purchase.certificate_mint,
+            owner_nft_account: get_associated_token_address(owner, &purchase.certificate_mint),
+            recipient_nft_account: get_associated_token_address(recipient, &purchase.certificate_mint),
+            token_mint: project.token_mint,
+            owner_token_account: get_associated_token_address(owner, &project.token_mint),
+            recipient_token_account: get_associated_token_address(recipient, &project.token_mint),
+            token_program: spl_token::ID,
+            associated_token_program: anchor_spl::associated_token::ID,
+            system_program: system_program::ID,
+        },
+        carbonpay::instruction::TransferPurchase {},
+    )
+}
+
+/// `claim_purchase` re-keying the position under `holder`, who already holds its certificate
+/// and tokens. Signed by `holder`.
+pub fn claim_purchase(holder: &Pubkey, purchase_address: &Pubkey, purchase: &Purchase, project: &Project) -> Instruction {
+    program_instruction(
+        carbonpay::accounts::ClaimPurchase {
+            holder: *holder,
+            previous_owner: purchase.buyer,
+            project: purchase.project,
+            purchase: *purchase_address,
+            listing: pda::listing(purchase_address).0,
+            subscription: pda::subscription(&purchase.buyer, &purchase.project).0,
+            new_purchase: pda::purchase(holder, &purchase.project, &purchase.nft_mint).0,
+            previous_holdings: pda::holdings(&purchase.buyer, &purchase.project).0,
+            holdings: pda::holdings(holder, &purchase.project).0,
+            certificate_mint: purchase.certificate_mint,
+            holder_nft_account: get_associated_token_address(holder, &purchase.certificate_mint),
+            token_mint: project.token_mint,
+            holder_token_account: get_associated_token_address(holder, &project.token_mint),
+            system_program: system_program::ID,
+        },
+        carbonpay::instruction::ClaimPurchase {},
+    )
+}
@@ -0,0 +1,18 @@
+//! Rust client for the `carbon_pay` program.
+//!
+//! - [`pda`] derives the program's account addresses
+//! - [`instructions`] builds ready-to-sign instructions, including the mints, ATAs and
+//!   Metaplex accounts each program instruction expects to exist
+//! - [`accounts`] fetches and decodes the program's accounts
+//! - [`reconcile`] checks the program's bookkeeping against mint supplies and token balances
+//! - `rpc` (feature `rpc`) talks JSON-RPC to a cluster and fetches accounts through it
+
+pub mod accounts;
+pub mod instructions;
+pub mod pda;
+pub mod reconcile;
+#[cfg(feature = "rpc")]
+pub mod rpc;
+
+pub use carbonpay::state;
+pub use carbonpay::ID as PROGRAM_ID;
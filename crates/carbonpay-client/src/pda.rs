@@ -0,0 +1,151 @@
+use anchor_lang::prelude::Pubkey;
+use anchor_spl::metadata::mpl_token_metadata;
+
+use crate::PROGRAM_ID;
+
+/// `[b"carbon_credits"]`
+pub fn carbon_credits() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"carbon_credits"], &PROGRAM_ID)
+}
+
+/// `[b"platform_config"]`
+pub fn platform_config() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"platform_config"], &PROGRAM_ID)
+}
+
+/// `[b"project", owner, nft_mint]`
+pub fn project(owner: &Pubkey, nft_mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"project", owner.as_ref(), nft_mint.as_ref()],
+        &PROGRAM_ID,
+    )
+}
+
+/// `[b"purchase", buyer, project, nft_mint]`
+pub fn purchase(buyer: &Pubkey, project: &Pubkey, nft_mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"purchase", buyer.as_ref(), project.as_ref(), nft_mint.as_ref()],
+        &PROGRAM_ID,
+    )
+}
+
+/// `[b"offset_request", requester, purchase, request_id]`.
+/// Batch offsets span several purchases and use the project in its place.
+pub fn offset_request(requester: &Pubkey, purchase: &Pubkey, request_id: &str) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            b"offset_request",
+            requester.as_ref(),
+            purchase.as_ref(),
+            request_id.as_bytes(),
+        ],
+        &PROGRAM_ID,
+    )
+}
+
+/// `[b"retirement", buyer, project, request_id]`, the OffsetRequest of `purchase_and_retire`
+/// and `retire_held_credits`
+pub fn retirement(buyer: &Pubkey, project: &Pubkey, request_id: &str) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            b"retirement",
+            buyer.as_ref(),
+            project.as_ref(),
+            request_id.as_bytes(),
+        ],
+        &PROGRAM_ID,
+    )
+}
+
+/// `[b"offset_approval", offset_request]`
+pub fn offset_approval(offset_request: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"offset_approval", offset_request.as_ref()], &PROGRAM_ID)
+}
+
+/// `[b"buyer_stats", buyer, project]`
+pub fn buyer_stats(buyer: &Pubkey, project: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"buyer_stats", buyer.as_ref(), project.as_ref()],
+        &PROGRAM_ID,
+    )
+}
+
+/// `[b"holdings", owner, project]`
+pub fn holdings(owner: &Pubkey, project: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"holdings", owner.as_ref(), project.as_ref()],
+        &PROGRAM_ID,
+    )
+}
+
+/// `[b"allowlist", scope, wallet]`, scoped to a project or the platform config
+pub fn allowlist_entry(scope: &Pubkey, wallet: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"allowlist", scope.as_ref(), wallet.as_ref()],
+        &PROGRAM_ID,
+    )
+}
+
+/// Metaplex metadata account of `mint`
+pub fn metadata(mint: &Pubkey) -> Pubkey {
+    mpl_token_metadata::accounts::Metadata::find_pda(mint).0
+}
+
+/// Metaplex master edition account of `mint`
+pub fn master_edition(mint: &Pubkey) -> Pubkey {
+    mpl_token_metadata::accounts::MasterEdition::find_pda(mint).0
+}
+
+/// `[b"offset_delegate", purchase]`
+pub fn offset_delegate(purchase: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"offset_delegate", purchase.as_ref()], &PROGRAM_ID)
+}
+
+/// `[b"listing", purchase]`
+pub fn listing(purchase: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"listing", purchase.as_ref()], &PROGRAM_ID)
+}
+
+/// `[b"otc_offer", project, buyer, offer_id]`
+pub fn otc_offer(project: &Pubkey, buyer: &Pubkey, offer_id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            b"otc_offer",
+            project.as_ref(),
+            buyer.as_ref(),
+            offer_id.to_le_bytes().as_ref(),
+        ],
+        &PROGRAM_ID,
+    )
+}
+
+/// `[b"subscription", buyer, project]`
+pub fn subscription(buyer: &Pubkey, project: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"subscription", buyer.as_ref(), project.as_ref()],
+        &PROGRAM_ID,
+    )
+}
+
+/// `[b"basket", basket_id]`
+pub fn basket(basket_id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"basket", basket_id.to_le_bytes().as_ref()], &PROGRAM_ID)
+}
+
+/// `[b"basket_mint", basket]`
+pub fn basket_mint(basket: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"basket_mint", basket.as_ref()], &PROGRAM_ID)
+}
+
+/// `[b"basket_reserve", basket, project]`
+pub fn basket_reserve(basket: &Pubkey, project: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"basket_reserve", basket.as_ref(), project.as_ref()],
+        &PROGRAM_ID,
+    )
+}
+
+/// `[b"offset_batch", offset_request]`
+pub fn offset_batch(offset_request: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"offset_batch", offset_request.as_ref()], &PROGRAM_ID)
+}
@@ -0,0 +1,201 @@
+//! Reconciliation of program bookkeeping against SPL token state.
+//!
+//! For every project the report checks:
+//! - the mint supply against `amount - offset_amount`, since offsets burn tokens
+//! - the vault (the carbon_credits PDA's ATA) against `remaining_amount` plus tokens reserved
+//!   by open OTC offers
+//! - each holder's ATA against the `remaining_amount` of their purchases of the project.
+//!   Tokens escrowed for an `OffsetDelegate` still belong to the owner's position and count
+//!   toward the holder. A holder may own more, e.g. from basket redemptions, but never less.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_spl::associated_token::get_associated_token_address;
+use anchor_spl::token::{Mint, TokenAccount};
+
+use crate::accounts::{fetch, fetch_all, AccountFetcher, ClientError};
+use crate::pda;
+use carbonpay::state::{OffsetDelegate, OtcOffer, Project, Purchase};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Check {
+    /// `amount` or `remaining_amount` inconsistent with `offset_amount`
+    ProjectAmounts,
+    MintSupply,
+    VaultBalance,
+    HolderBalance,
+}
+
+impl fmt::Display for Check {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Check::ProjectAmounts => "project_amounts",
+            Check::MintSupply => "mint_supply",
+            Check::VaultBalance => "vault_balance",
+            Check::HolderBalance => "holder_balance",
+        };
+        f.write_str(name)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Discrepancy {
+    pub check: Check,
+    pub project: Pubkey,
+    /// The account the actual value was read from: project, mint or token account
+    pub account: Pubkey,
+    /// Purchases whose bookkeeping the holder balance was compared against
+    pub purchases: Vec<Pubkey>,
+    pub expected: u64,
+    pub actual: u64,
+}
+
+#[derive(Debug, Default)]
+pub struct Report {
+    pub projects_checked: usize,
+    pub purchases_checked: usize,
+    pub discrepancies: Vec<Discrepancy>,
+}
+
+impl Report {
+    pub fn is_clean(&self) -> bool {
+        self.discrepancies.is_empty()
+    }
+}
+
+fn token_balance(fetcher: &impl AccountFetcher, address: &Pubkey) -> Result<u64, ClientError> {
+    Ok(fetch::<TokenAccount>(fetcher, address)?.map_or(0, |account| account.amount))
+}
+
+fn check_project(
+    fetcher: &impl AccountFetcher,
+    address: Pubkey,
+    project: &Project,
+    reserved: u64,
+    report: &mut Report,
+) -> Result<(), ClientError> {
+    let mut flag = |check, account, expected, actual| {
+        if expected != actual {
+            report.discrepancies.push(Discrepancy {
+                check,
+                project: address,
+                account,
+                purchases: Vec::new(),
+                expected,
+                actual,
+            });
+        }
+    };
+
+    let circulating = project.amount.checked_sub(project.offset_amount);
+    match circulating {
+        Some(circulating) if project.remaining_amount <= circulating => {}
+        _ => flag(
+            Check::ProjectAmounts,
+            address,
+            project.amount,
+            project
+                .remaining_amount
+                .saturating_add(project.offset_amount),
+        ),
+    }
+
+    let supply = fetch::<Mint>(fetcher, &project.token_mint)?.map_or(0, |mint| mint.supply);
+    flag(
+        Check::MintSupply,
+        project.token_mint,
+        circulating.unwrap_or(0),
+        supply,
+    );
+
+    let vault = get_associated_token_address(&pda::carbon_credits().0, &project.token_mint);
+    flag(
+        Check::VaultBalance,
+        vault,
+        project.remaining_amount.saturating_add(reserved),
+        token_balance(fetcher, &vault)?,
+    );
+    Ok(())
+}
+
+/// Compare every project and purchase against the token accounts backing them
+pub fn reconcile(fetcher: &impl AccountFetcher) -> Result<Report, ClientError> {
+    let projects: Vec<(Pubkey, Project)> = fetch_all(fetcher)?;
+    let purchases: Vec<(Pubkey, Purchase)> = fetch_all(fetcher)?;
+    let offers: Vec<(Pubkey, OtcOffer)> = fetch_all(fetcher)?;
+    let delegates: Vec<(Pubkey, OffsetDelegate)> = fetch_all(fetcher)?;
+
+    let mut reserved: BTreeMap<Pubkey, u64> = BTreeMap::new();
+    for (_, offer) in &offers {
+        let total = reserved.entry(offer.project).or_default();
+        *total = total.saturating_add(offer.amount);
+    }
+
+    let mut report = Report {
+        projects_checked: projects.len(),
+        purchases_checked: purchases.len(),
+        ..Report::default()
+    };
+    for (address, project) in &projects {
+        let reserved = reserved.get(address).copied().unwrap_or(0);
+        check_project(fetcher, *address, project, reserved, &mut report)?;
+    }
+
+    // Purchases of the same project by the same buyer share one token account
+    let mut holdings: BTreeMap<(Pubkey, Pubkey), (u64, Vec<Pubkey>)> = BTreeMap::new();
+    for (address, purchase) in &purchases {
+        let (total, addresses) = holdings
+            .entry((purchase.project, purchase.buyer))
+            .or_default();
+        *total = total.saturating_add(purchase.remaining_amount);
+        addresses.push(*address);
+    }
+    // A delegated purchase's allowance sits in the delegate PDA's escrow, not the owner's ATA
+    let mut escrowed: BTreeMap<(Pubkey, Pubkey), u64> = BTreeMap::new();
+    for (address, delegate) in &delegates {
+        let Some((_, purchase)) = purchases
+            .iter()
+            .find(|(purchase, _)| *purchase == delegate.purchase)
+        else {
+            continue;
+        };
+        let Some((_, project)) = projects
+            .iter()
+            .find(|(project, _)| *project == purchase.project)
+        else {
+            continue;
+        };
+        let escrow = get_associated_token_address(address, &project.token_mint);
+        let total = escrowed.entry((purchase.project, delegate.owner)).or_default();
+        *total = total.saturating_add(token_balance(fetcher, &escrow)?);
+    }
+
+    for ((project_address, buyer), (expected, addresses)) in holdings {
+        let Some((_, project)) = projects
+            .iter()
+            .find(|(address, _)| *address == project_address)
+        else {
+            continue;
+        };
+        let account = get_associated_token_address(&buyer, &project.token_mint);
+        let actual = token_balance(fetcher, &account)?.saturating_add(
+            escrowed
+                .get(&(project_address, buyer))
+                .copied()
+                .unwrap_or(0),
+        );
+        if actual < expected {
+            report.discrepancies.push(Discrepancy {
+                check: Check::HolderBalance,
+                project: project_address,
+                account,
+                purchases: addresses,
+                expected,
+                actual,
+            });
+        }
+    }
+    Ok(report)
+}
@@ -0,0 +1,132 @@
+//! Minimal JSON-RPC connection to a Solana cluster, behind the `rpc` feature.
+
+use std::str::FromStr;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::hash::Hash;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde_json::{json, Value};
+
+use crate::accounts::{AccountFetcher, FetchError};
+
+/// RPC URL of a cluster moniker (`localnet`, `devnet`, `testnet`, `mainnet-beta`), any other
+/// value is taken as a URL
+pub fn cluster_url(cluster: &str) -> &str {
+    match cluster {
+        "localnet" | "localhost" => "http://127.0.0.1:8899",
+        "devnet" => "https://api.devnet.solana.com",
+        "testnet" => "https://api.testnet.solana.com",
+        "mainnet-beta" | "mainnet" => "https://api.mainnet-beta.solana.com",
+        url => url,
+    }
+}
+
+pub struct RpcClient {
+    url: String,
+    commitment: String,
+}
+
+impl RpcClient {
+    /// `cluster` is a moniker or an RPC URL, see [`cluster_url`]; reads are `confirmed`
+    pub fn new(cluster: &str) -> Self {
+        Self::with_commitment(cluster, "confirmed")
+    }
+
+    pub fn with_commitment(cluster: &str, commitment: &str) -> Self {
+        Self {
+            url: cluster_url(cluster).to_string(),
+            commitment: commitment.to_string(),
+        }
+    }
+
+    pub fn commitment(&self) -> &str {
+        &self.commitment
+    }
+
+    /// Call `method` and return its `result`, a JSON-RPC `error` becomes an `Err`
+    pub fn call(&self, method: &str, params: Value) -> Result<Value, FetchError> {
+        let response: Value = ureq::post(&self.url)
+            .send_json(json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": method,
+                "params": params,
+            }))?
+            .into_json()?;
+        if let Some(error) = response.get("error") {
+            return Err(format!("{} failed: {}", method, error).into());
+        }
+        Ok(response["result"].clone())
+    }
+
+    pub fn latest_blockhash(&self) -> Result<Hash, FetchError> {
+        let result = self.call("getLatestBlockhash", json!([{ "commitment": self.commitment }]))?;
+        let blockhash = result["value"]["blockhash"]
+            .as_str()
+            .ok_or("getLatestBlockhash returned no blockhash")?;
+        Ok(Hash::from_str(blockhash)?)
+    }
+
+    /// Send a signed transaction in its wire format, returning its signature
+    pub fn send_transaction(&self, transaction: &[u8]) -> Result<String, FetchError> {
+        let result = self.call(
+            "sendTransaction",
+            json!([STANDARD.encode(transaction), { "encoding": "base64", "preflightCommitment": self.commitment }]),
+        )?;
+        transaction_signature(&result)
+    }
+}
+
+/// Signature returned by `sendTransaction`, anything but a string is an error
+pub fn transaction_signature(result: &Value) -> Result<String, FetchError> {
+    let signature = result
+        .as_str()
+        .ok_or_else(|| format!("sendTransaction returned no signature: {}", result))?;
+    Ok(signature.to_string())
+}
+
+/// Data of an account returned with `"encoding": "base64"`
+pub fn decode_data(account: &Value) -> Result<Vec<u8>, FetchError> {
+    let data = account["data"][0].as_str().ok_or("account data is not base64")?;
+    Ok(STANDARD.decode(data)?)
+}
+
+impl AccountFetcher for RpcClient {
+    fn account_data(&self, address: &Pubkey) -> Result<Option<Vec<u8>>, FetchError> {
+        let result = self.call(
+            "getAccountInfo",
+            json!([address.to_string(), { "encoding": "base64", "commitment": self.commitment }]),
+        )?;
+        match &result["value"] {
+            Value::Null => Ok(None),
+            account => Ok(Some(decode_data(account)?)),
+        }
+    }
+
+    fn program_accounts(
+        &self,
+        program_id: &Pubkey,
+        prefix: &[u8],
+    ) -> Result<Vec<(Pubkey, Vec<u8>)>, FetchError> {
+        let result = self.call(
+            "getProgramAccounts",
+            json!([
+                program_id.to_string(),
+                {
+                    "encoding": "base64",
+                    "commitment": self.commitment,
+                    "filters": [{ "memcmp": { "offset": 0, "bytes": STANDARD.encode(prefix), "encoding": "base64" } }],
+                }
+            ]),
+        )?;
+        result
+            .as_array()
+            .ok_or("getProgramAccounts returned no accounts")?
+            .iter()
+            .map(|entry| {
+                let address = Pubkey::from_str(entry["pubkey"].as_str().unwrap_or_default())?;
+                Ok((address, decode_data(&entry["account"])?))
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,232 @@
+use std::collections::HashMap;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::program_option::COption;
+use anchor_lang::solana_program::program_pack::Pack;
+use anchor_lang::{AccountSerialize, Discriminator};
+use anchor_spl::associated_token::get_associated_token_address;
+use anchor_spl::token::spl_token;
+use carbonpay_client::accounts::{AccountFetcher, FetchError};
+use carbonpay_client::reconcile::{reconcile, Check};
+use carbonpay_client::state::{
+    OffsetDelegate, OtcOffer, Project, ProjectCategory, Purchase,
+};
+use carbonpay_client::{pda, PROGRAM_ID};
+
+#[derive(Default)]
+struct Snapshot {
+    accounts: HashMap<Pubkey, (Pubkey, Vec<u8>)>,
+}
+
+impl Snapshot {
+    fn program_account<T: AccountSerialize>(&mut self, address: Pubkey, account: &T) {
+        let mut data = Vec::new();
+        account.try_serialize(&mut data).unwrap();
+        self.accounts.insert(address, (PROGRAM_ID, data));
+    }
+
+    fn mint(&mut self, address: Pubkey, supply: u64) {
+        let mut data = vec![0; spl_token::state::Mint::LEN];
+        let mint = spl_token::state::Mint {
+            mint_authority: COption::Some(pda::carbon_credits().0),
+            supply,
+            decimals: 0,
+            is_initialized: true,
+            freeze_authority: COption::None,
+        };
+        mint.pack_into_slice(&mut data);
+        self.accounts.insert(address, (spl_token::ID, data));
+    }
+
+    fn token_account(&mut self, owner: Pubkey, mint: Pubkey, amount: u64) -> Pubkey {
+        let address = get_associated_token_address(&owner, &mint);
+        let mut data = vec![0; spl_token::state::Account::LEN];
+        let account = spl_token::state::Account {
+            mint,
+            owner,
+            amount,
+            state: spl_token::state::AccountState::Initialized,
+            ..Default::default()
+        };
+        account.pack_into_slice(&mut data);
+        self.accounts.insert(address, (spl_token::ID, data));
+        address
+    }
+}
+
+impl AccountFetcher for Snapshot {
+    fn account_data(&self, address: &Pubkey) -> Result<Option<Vec<u8>>, FetchError> {
+        Ok(self.accounts.get(address).map(|(_, data)| data.clone()))
+    }
+
+    fn program_accounts(
+        &self,
+        program_id: &Pubkey,
+        prefix: &[u8],
+    ) -> Result<Vec<(Pubkey, Vec<u8>)>, FetchError> {
+        Ok(self
+            .accounts
+            .iter()
+            .filter(|(_, (owner, data))| owner == program_id && data.starts_with(prefix))
+            .map(|(address, (_, data))| (*address, data.clone()))
+            .collect())
+    }
+}
+
+fn project(token_mint: Pubkey, amount: u64, remaining_amount: u64, offset_amount: u64) -> Project {
+    Project {
+        owner: Pubkey::new_unique(),
+        mint: Pubkey::new_unique(),
+        token_mint,
+        token_bump: 0,
+        is_active: true,
+        amount,
+        remaining_amount,
+        offset_amount,
+        price_per_token: 1_000,
+        carbon_pay_fee: 500,
+        carbon_pay_authority: Pubkey::new_unique(),
+        project_bump: 255,
+        allowlist_required: false,
+        min_purchase_amount: 0,
+        max_purchase_amount: 0,
+        max_per_buyer: 0,
+        sale_start: 0,
+        sale_end: 0,
+        early_access_start: 0,
+        price_tiers: vec![],
+        auction: None,
+        category: ProjectCategory::Unspecified,
+        vault: get_associated_token_address(&pda::carbon_credits().0, &token_mint),
+        decimals: 0,
+        layout_version: Project::LAYOUT_VERSION,
+    }
+}
+
+fn purchase(buyer: Pubkey, project: Pubkey, amount: u64, remaining_amount: u64) -> Purchase {
+    let nft_mint = Pubkey::new_unique();
+    Purchase {
+        buyer,
+        project,
+        amount,
+        remaining_amount,
+        purchase_date: 0,
+        purchase_bump: 255,
+        nft_mint,
+        certificate_mint: nft_mint,
+        uncertified_offset: 0,
+    }
+}
+
+/// 1000 tokens: 300 sold to one buyer over two purchases, 50 of them offset, 100 reserved by
+/// an OTC offer
+fn consistent_snapshot() -> (Snapshot, Pubkey, Pubkey, Pubkey) {
+    let mut snapshot = Snapshot::default();
+    let (address, token_mint, buyer) = (
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+    );
+    snapshot.program_account(address, &project(token_mint, 1000, 600, 50));
+    snapshot.mint(token_mint, 950);
+    snapshot.token_account(pda::carbon_credits().0, token_mint, 700);
+    snapshot.program_account(Pubkey::new_unique(), &purchase(buyer, address, 200, 150));
+    snapshot.program_account(Pubkey::new_unique(), &purchase(buyer, address, 100, 100));
+    snapshot.token_account(buyer, token_mint, 250);
+    snapshot.program_account(
+        Pubkey::new_unique(),
+        &OtcOffer {
+            project: address,
+            buyer: Pubkey::new_unique(),
+            offer_id: 1,
+            amount: 100,
+            price_per_token: 900,
+            expires_at: 0,
+            bump: 255,
+        },
+    );
+    (snapshot, address, token_mint, buyer)
+}
+
+#[test]
+fn consistent_state_reports_nothing() {
+    let (snapshot, ..) = consistent_snapshot();
+    let report = reconcile(&snapshot).unwrap();
+    assert_eq!(report.projects_checked, 1);
+    assert_eq!(report.purchases_checked, 2);
+    assert!(report.is_clean(), "{:?}", report.discrepancies);
+}
+
+#[test]
+fn reports_vault_supply_and_holder_mismatches() {
+    let (mut snapshot, address, token_mint, buyer) = consistent_snapshot();
+    // Supply minted outside the vault, and the buyer moved tokens out of their ATA
+    snapshot.mint(token_mint, 1950);
+    snapshot.token_account(pda::carbon_credits().0, token_mint, 0);
+    let holder = snapshot.token_account(buyer, token_mint, 200);
+
+    let report = reconcile(&snapshot).unwrap();
+    let found: Vec<_> = report
+        .discrepancies
+        .iter()
+        .map(|d| (d.check, d.expected, d.actual))
+        .collect();
+    assert_eq!(
+        found,
+        [
+            (Check::MintSupply, 950, 1950),
+            (Check::VaultBalance, 700, 0),
+            (Check::HolderBalance, 250, 200),
+        ]
+    );
+    let holder_discrepancy = &report.discrepancies[2];
+    assert_eq!(holder_discrepancy.project, address);
+    assert_eq!(holder_discrepancy.account, holder);
+    assert_eq!(holder_discrepancy.purchases.len(), 2);
+}
+
+#[test]
+fn holder_may_own_more_than_purchased() {
+    let (mut snapshot, _, token_mint, buyer) = consistent_snapshot();
+    snapshot.token_account(buyer, token_mint, 400);
+    assert!(reconcile(&snapshot).unwrap().is_clean());
+}
+
+#[test]
+fn delegated_allowance_counts_toward_the_holder() {
+    let (mut snapshot, address, token_mint, buyer) = consistent_snapshot();
+    // 100 of the buyer's 250 tokens are escrowed for a delegate of the first purchase
+    let delegated = snapshot
+        .accounts
+        .iter()
+        .find(|(_, (owner, data))| {
+            *owner == PROGRAM_ID && data.starts_with(Purchase::DISCRIMINATOR)
+        })
+        .map(|(address, _)| *address)
+        .unwrap();
+    let delegate = pda::offset_delegate(&delegated).0;
+    snapshot.program_account(
+        delegate,
+        &OffsetDelegate {
+            owner: buyer,
+            purchase: delegated,
+            delegate: Pubkey::new_unique(),
+            remaining_amount: 100,
+            expires_at: 0,
+            bump: 255,
+        },
+    );
+    snapshot.token_account(delegate, token_mint, 100);
+    let holder = snapshot.token_account(buyer, token_mint, 150);
+    assert!(reconcile(&snapshot).unwrap().is_clean());
+
+    // Escrowed tokens still have to cover the rest of the position
+    snapshot.token_account(buyer, token_mint, 100);
+    let report = reconcile(&snapshot).unwrap();
+    let found: Vec<_> = report
+        .discrepancies
+        .iter()
+        .map(|d| (d.check, d.project, d.account, d.expected, d.actual))
+        .collect();
+    assert_eq!(found, [(Check::HolderBalance, address, holder, 250, 200)]);
+}
@@ -0,0 +1,28 @@
+#![cfg(feature = "rpc")]
+
+use carbonpay_client::rpc::{cluster_url, decode_data, transaction_signature};
+use serde_json::json;
+
+#[test]
+fn maps_cluster_monikers_and_keeps_urls() {
+    assert_eq!(cluster_url("localnet"), "http://127.0.0.1:8899");
+    assert_eq!(cluster_url("localhost"), "http://127.0.0.1:8899");
+    assert_eq!(cluster_url("devnet"), "https://api.devnet.solana.com");
+    assert_eq!(cluster_url("testnet"), "https://api.testnet.solana.com");
+    assert_eq!(cluster_url("mainnet"), "https://api.mainnet-beta.solana.com");
+    assert_eq!(cluster_url("mainnet-beta"), "https://api.mainnet-beta.solana.com");
+    assert_eq!(cluster_url("http://10.0.0.2:8899"), "http://10.0.0.2:8899");
+}
+
+#[test]
+fn decodes_base64_account_data() {
+    assert_eq!(decode_data(&json!({ "data": ["AQID", "base64"] })).unwrap(), vec![1, 2, 3]);
+    assert!(decode_data(&json!({ "data": "AQID" })).is_err());
+}
+
+#[test]
+fn rejects_a_send_result_without_signature() {
+    assert_eq!(transaction_signature(&json!("5VERv8NMvzbJMEkV")).unwrap(), "5VERv8NMvzbJMEkV");
+    assert!(transaction_signature(&json!(null)).is_err());
+    assert!(transaction_signature(&json!({ "signature": "5VERv8NMvzbJMEkV" })).is_err());
+}
@@ -0,0 +1,147 @@
+//! Decoding of carbon_pay instructions, events and accounts.
+
+use anchor_lang::{AnchorDeserialize, Discriminator};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use carbonpay::events::{AuctionSettled, BasketRetired, CarbonCreditsPurchased, ListingSold};
+use carbonpay::state::{OffsetRequest, Project, Purchase};
+use carbonpay_client::accounts::decode;
+use carbonpay_client::PROGRAM_ID;
+
+use crate::IndexError;
+
+macro_rules! instruction_names {
+    ($($name:ident),* $(,)?) => {
+        /// Name of the carbon_pay instruction `data` encodes, None if the discriminator is unknown
+        pub fn instruction_name(data: &[u8]) -> Option<&'static str> {
+            $(
+                if data.starts_with(carbonpay::instruction::$name::DISCRIMINATOR) {
+                    return Some(stringify!($name));
+                }
+            )*
+            None
+        }
+    };
+}
+
+instruction_names!(
+    InitializeCarbonCredits,
+    InitializeProject,
+    RequestOffset,
+    PurchaseCarbonCredits,
+    InitializePlatformConfig,
+    UpdateOffsetApprovers,
+    ApproveOffsetVote,
+    AddAllowlistEntry,
+    SetAllowlistExpiry,
+    RemoveAllowlistEntry,
+    SetAllowlistRequired,
+    SetPurchaseLimits,
+    SetSaleWindow,
+    SetPriceTiers,
+    ListCredits,
+    BuyListing,
+    CancelListing,
+    StartDutchAuction,
+    SettleDutchAuction,
+    ClaimAuctionRefund,
+    CreateOtcOffer,
+    AcceptOtcOffer,
+    CancelOtcOffer,
+    CreateSubscription,
+    FundSubscription,
+    ExecuteSubscription,
+    CancelSubscription,
+    SetProjectCategory,
+    CreateBasket,
+    UpdateBasketEligibility,
+    DepositToBasket,
+    RedeemFromBasket,
+    RetireFromBasket,
+    RetireHeldCredits,
+    PurchaseAndRetire,
+    ApproveOffsetDelegate,
+    RevokeOffsetDelegate,
+    RequestBatchOffset,
+    MergePurchases,
+    SplitPurchase,
+    TransferPurchase,
+    ClaimPurchase,
+    MigrateCarbonCredits,
+    MigrateProject,
+    MigratePurchase,
+);
+
+pub enum ProgramEvent {
+    CarbonCreditsPurchased(CarbonCreditsPurchased),
+    ListingSold(ListingSold),
+    AuctionSettled(AuctionSettled),
+    BasketRetired(BasketRetired),
+}
+
+fn decode_event(data: &[u8]) -> Result<Option<ProgramEvent>, IndexError> {
+    let event = if let Some(body) = data.strip_prefix(CarbonCreditsPurchased::DISCRIMINATOR) {
+        ProgramEvent::CarbonCreditsPurchased(CarbonCreditsPurchased::try_from_slice(body)?)
+    } else if let Some(body) = data.strip_prefix(ListingSold::DISCRIMINATOR) {
+        ProgramEvent::ListingSold(ListingSold::try_from_slice(body)?)
+    } else if let Some(body) = data.strip_prefix(AuctionSettled::DISCRIMINATOR) {
+        ProgramEvent::AuctionSettled(AuctionSettled::try_from_slice(body)?)
+    } else if let Some(body) = data.strip_prefix(BasketRetired::DISCRIMINATOR) {
+        ProgramEvent::BasketRetired(BasketRetired::try_from_slice(body)?)
+    } else {
+        return Ok(None);
+    };
+    Ok(Some(event))
+}
+
+/// Events emitted by carbon_pay, in log order.
+///
+/// Tracks the invocation stack so `Program data:` lines logged by other programs, including
+/// ones carbon_pay calls into, are ignored.
+pub fn program_events(log_messages: &[String]) -> Result<Vec<ProgramEvent>, IndexError> {
+    let program_id = PROGRAM_ID.to_string();
+    let mut stack: Vec<&str> = Vec::new();
+    let mut events = Vec::new();
+    for line in log_messages {
+        if let Some(data) = line.strip_prefix("Program data: ") {
+            if stack.last() != Some(&program_id.as_str()) {
+                continue;
+            }
+            for part in data.split_whitespace() {
+                if let Some(event) = decode_event(&STANDARD.decode(part)?)? {
+                    events.push(event);
+                }
+            }
+        } else if let Some(rest) = line.strip_prefix("Program ") {
+            let mut words = rest.split_whitespace();
+            match (words.next(), words.next()) {
+                (Some(program), Some("invoke")) => stack.push(program),
+                (Some(_), Some("success" | "failed:")) => {
+                    stack.pop();
+                }
+                _ => {}
+            }
+        }
+    }
+    Ok(events)
+}
+
+/// carbon_pay accounts the indexer materializes
+pub enum ProgramAccount {
+    Project(Box<Project>),
+    Purchase(Purchase),
+    OffsetRequest(OffsetRequest),
+}
+
+/// Decode a carbon_pay account by its discriminator, None for account types that are not indexed
+pub fn program_account(data: &[u8]) -> Result<Option<ProgramAccount>, IndexError> {
+    let account = if data.starts_with(Project::DISCRIMINATOR) {
+        ProgramAccount::Project(Box::new(decode(data)?))
+    } else if data.starts_with(Purchase::DISCRIMINATOR) {
+        ProgramAccount::Purchase(decode(data)?)
+    } else if data.starts_with(OffsetRequest::DISCRIMINATOR) {
+        ProgramAccount::OffsetRequest(decode(data)?)
+    } else {
+        return Ok(None);
+    };
+    Ok(Some(account))
+}
@@ -0,0 +1,107 @@
+//! Off-chain indexer for the carbon_pay program.
+//!
+//! Replays program transactions in slot order, records decoded instructions and events, and
+//! materializes projects, purchases, offset requests and fee flows into SQLite. Syncing is
+//! idempotent, so an interrupted run simply resumes, and recently indexed transactions are
+//! re-checked against the chain so a re-org rolls the database back before replay continues.
+
+pub mod decode;
+pub mod store;
+pub mod transaction;
+
+use std::collections::BTreeSet;
+
+use anchor_lang::prelude::Pubkey;
+use carbonpay_client::PROGRAM_ID;
+
+pub use store::Indexer;
+pub use transaction::TransactionRecord;
+
+pub type IndexError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Where the indexer reads the chain from, e.g. a JSON-RPC node or recorded fixtures
+pub trait ChainSource {
+    /// Signatures of carbon_pay transactions newer than `after`, oldest first
+    fn signatures(&self, after: Option<&str>) -> Result<Vec<String>, IndexError>;
+
+    fn transaction(&self, signature: &str) -> Result<Option<TransactionRecord>, IndexError>;
+
+    /// Whether each signature is still part of the chain
+    fn confirmed(&self, signatures: &[String]) -> Result<Vec<bool>, IndexError>;
+
+    /// Slot the account was read at and its data, None if it does not exist or is not
+    /// owned by carbon_pay
+    fn account(&self, address: &Pubkey) -> Result<(u64, Option<Vec<u8>>), IndexError>;
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct SyncSummary {
+    pub rolled_back_to: Option<u64>,
+    pub transactions: usize,
+    pub accounts: usize,
+}
+
+/// Accounts passed to the transaction's carbon_pay instructions
+pub fn touched_accounts(record: &TransactionRecord) -> BTreeSet<Pubkey> {
+    if record.failed {
+        return BTreeSet::new();
+    }
+    record
+        .instructions
+        .iter()
+        .filter(|instruction| instruction.program_id == PROGRAM_ID)
+        .flat_map(|instruction| instruction.accounts.iter().copied())
+        .collect()
+}
+
+/// Bring the database up to date with `source`.
+///
+/// The last `reorg_depth` indexed transactions are checked first; if any of them has been
+/// dropped, everything from the earliest dropped slot onwards is rolled back and replayed.
+pub fn sync(
+    indexer: &mut Indexer,
+    source: &impl ChainSource,
+    reorg_depth: usize,
+) -> Result<SyncSummary, IndexError> {
+    let mut summary = SyncSummary::default();
+    let mut stale = BTreeSet::new();
+
+    let recent = indexer.recent_signatures(reorg_depth)?;
+    let signatures: Vec<String> = recent
+        .iter()
+        .map(|(signature, _)| signature.clone())
+        .collect();
+    let dropped_slot = source
+        .confirmed(&signatures)?
+        .into_iter()
+        .zip(&recent)
+        .filter(|(confirmed, _)| !confirmed)
+        .map(|(_, (_, slot))| *slot)
+        .min();
+    if let Some(slot) = dropped_slot {
+        let slot = slot.saturating_sub(1);
+        stale.extend(indexer.rollback(slot)?);
+        summary.rolled_back_to = Some(slot);
+    }
+
+    let after = indexer
+        .recent_signatures(1)?
+        .pop()
+        .map(|(signature, _)| signature);
+    for signature in source.signatures(after.as_deref())? {
+        let Some(record) = source.transaction(&signature)? else {
+            continue;
+        };
+        if indexer.index_transaction(&record)? {
+            summary.transactions += 1;
+            stale.extend(touched_accounts(&record));
+        }
+    }
+
+    for address in &stale {
+        let (slot, data) = source.account(address)?;
+        indexer.index_account(address, slot, data.as_deref())?;
+        summary.accounts += 1;
+    }
+    Ok(summary)
+}
@@ -0,0 +1,79 @@
+//! `carbonpay-indexer` — keeps a SQLite database in sync with the carbon_pay program.
+
+mod rpc;
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::thread;
+use std::time::Duration;
+
+use carbonpay_indexer::{sync, IndexError, Indexer};
+use clap::Parser;
+
+use rpc::RpcSource;
+
+#[derive(Parser)]
+#[command(
+    name = "carbonpay-indexer",
+    version,
+    about = "Index carbon_pay program state into SQLite"
+)]
+struct Cli {
+    /// Cluster moniker (localnet, devnet, testnet, mainnet-beta) or RPC URL
+    #[arg(short = 'u', long, env = "CARBONPAY_CLUSTER", default_value = "devnet")]
+    url: String,
+
+    /// SQLite database file, created if missing
+    #[arg(
+        short = 'd',
+        long,
+        env = "CARBONPAY_INDEXER_DB",
+        default_value = "carbonpay.db"
+    )]
+    database: PathBuf,
+
+    /// Commitment level transactions and accounts are read at
+    #[arg(long, default_value = "confirmed")]
+    commitment: String,
+
+    /// Number of recently indexed transactions re-checked for re-orgs on every sync
+    #[arg(long, default_value_t = 64)]
+    reorg_depth: usize,
+
+    /// Seconds between syncs; sync once and exit if omitted
+    #[arg(long)]
+    poll_interval: Option<u64>,
+}
+
+fn run(cli: Cli) -> Result<(), IndexError> {
+    let mut indexer = Indexer::open(&cli.database)?;
+    let source = RpcSource::new(&cli.url, &cli.commitment);
+    loop {
+        let summary = sync(&mut indexer, &source, cli.reorg_depth)?;
+        if let Some(slot) = summary.rolled_back_to {
+            eprintln!("re-org detected, rolled back to slot {}", slot);
+        }
+        eprintln!(
+            "indexed {} transactions, refreshed {} accounts, last slot {}",
+            summary.transactions,
+            summary.accounts,
+            indexer
+                .last_slot()?
+                .map_or("-".to_string(), |slot| slot.to_string()),
+        );
+        match cli.poll_interval {
+            Some(seconds) => thread::sleep(Duration::from_secs(seconds)),
+            None => return Ok(()),
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    match run(Cli::parse()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: {}", err);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,115 @@
+//! [`ChainSource`] backed by a Solana JSON-RPC node.
+
+use std::str::FromStr;
+
+use anchor_lang::prelude::Pubkey;
+use carbonpay_client::rpc::{decode_data, RpcClient};
+use carbonpay_client::PROGRAM_ID;
+use carbonpay_indexer::{ChainSource, IndexError, TransactionRecord};
+use serde_json::json;
+
+/// Most signatures `getSignaturesForAddress` returns per page
+const SIGNATURE_PAGE: usize = 1000;
+
+pub struct RpcSource {
+    client: RpcClient,
+}
+
+impl RpcSource {
+    /// `cluster` is a moniker (`localnet`, `devnet`, `testnet`, `mainnet-beta`) or an RPC URL
+    pub fn new(cluster: &str, commitment: &str) -> Self {
+        Self {
+            client: RpcClient::with_commitment(cluster, commitment),
+        }
+    }
+}
+
+impl ChainSource for RpcSource {
+    fn signatures(&self, after: Option<&str>) -> Result<Vec<String>, IndexError> {
+        // Pages come back newest first, walk backwards until `after` or the program's first one
+        let mut signatures = Vec::new();
+        let mut before: Option<String> = None;
+        loop {
+            let page = self.client.call(
+                "getSignaturesForAddress",
+                json!([
+                    PROGRAM_ID.to_string(),
+                    {
+                        "limit": SIGNATURE_PAGE,
+                        "before": before,
+                        "until": after,
+                        "commitment": self.client.commitment(),
+                    }
+                ]),
+            )?;
+            let page = page
+                .as_array()
+                .ok_or("getSignaturesForAddress returned no list")?;
+            for entry in page {
+                let signature = entry["signature"]
+                    .as_str()
+                    .ok_or("entry has no signature")?;
+                signatures.push(signature.to_string());
+            }
+            if page.len() < SIGNATURE_PAGE {
+                break;
+            }
+            before = signatures.last().cloned();
+        }
+        signatures.reverse();
+        Ok(signatures)
+    }
+
+    fn transaction(&self, signature: &str) -> Result<Option<TransactionRecord>, IndexError> {
+        let result = self.client.call(
+            "getTransaction",
+            json!([
+                signature,
+                {
+                    "encoding": "json",
+                    "commitment": self.client.commitment(),
+                    "maxSupportedTransactionVersion": 0,
+                }
+            ]),
+        )?;
+        if result.is_null() {
+            return Ok(None);
+        }
+        Ok(Some(TransactionRecord::from_rpc_json(&result)?))
+    }
+
+    fn confirmed(&self, signatures: &[String]) -> Result<Vec<bool>, IndexError> {
+        if signatures.is_empty() {
+            return Ok(Vec::new());
+        }
+        let result = self.client.call(
+            "getSignatureStatuses",
+            json!([signatures, { "searchTransactionHistory": true }]),
+        )?;
+        Ok(result["value"]
+            .as_array()
+            .ok_or("getSignatureStatuses returned no statuses")?
+            .iter()
+            .map(|status| !status.is_null())
+            .collect())
+    }
+
+    fn account(&self, address: &Pubkey) -> Result<(u64, Option<Vec<u8>>), IndexError> {
+        let result = self.client.call(
+            "getAccountInfo",
+            json!([address.to_string(), { "encoding": "base64", "commitment": self.client.commitment() }]),
+        )?;
+        let slot = result["context"]["slot"]
+            .as_u64()
+            .ok_or("response has no slot")?;
+        let account = &result["value"];
+        let owner = account["owner"]
+            .as_str()
+            .map(Pubkey::from_str)
+            .transpose()?;
+        if owner != Some(PROGRAM_ID) {
+            return Ok((slot, None));
+        }
+        Ok((slot, Some(decode_data(account)?)))
+    }
+}
@@ -0,0 +1,461 @@
+//! SQLite schema and the idempotent writes the indexer performs.
+//!
+//! Every row carries the slot it was observed at. Account rows only move forward in slot
+//! order, so replaying a transaction or re-fetching an account after a restart is a no-op,
+//! and [`Indexer::rollback`] can drop everything above a slot that was abandoned by a re-org.
+
+use std::path::Path;
+
+use anchor_lang::prelude::Pubkey;
+use carbonpay::state::RequestStatus;
+use carbonpay_client::PROGRAM_ID;
+use rusqlite::{params, Connection, OptionalExtension};
+
+use crate::decode::{
+    instruction_name, program_account, program_events, ProgramAccount, ProgramEvent,
+};
+use crate::transaction::TransactionRecord;
+use crate::IndexError;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS transactions (
+    signature TEXT PRIMARY KEY,
+    slot INTEGER NOT NULL,
+    block_time INTEGER,
+    failed INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS instructions (
+    signature TEXT NOT NULL,
+    instruction_index INTEGER NOT NULL,
+    slot INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    accounts TEXT NOT NULL,
+    PRIMARY KEY (signature, instruction_index)
+);
+CREATE TABLE IF NOT EXISTS projects (
+    address TEXT PRIMARY KEY,
+    slot INTEGER NOT NULL,
+    owner TEXT NOT NULL,
+    nft_mint TEXT NOT NULL,
+    token_mint TEXT NOT NULL,
+    decimals INTEGER NOT NULL,
+    is_active INTEGER NOT NULL,
+    amount INTEGER NOT NULL,
+    remaining_amount INTEGER NOT NULL,
+    offset_amount INTEGER NOT NULL,
+    price_per_token INTEGER NOT NULL,
+    carbon_pay_fee INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS purchases (
+    address TEXT PRIMARY KEY,
+    slot INTEGER NOT NULL,
+    buyer TEXT NOT NULL,
+    project TEXT NOT NULL,
+    nft_mint TEXT NOT NULL,
+    amount INTEGER NOT NULL,
+    remaining_amount INTEGER NOT NULL,
+    purchase_date INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS offset_requests (
+    address TEXT PRIMARY KEY,
+    slot INTEGER NOT NULL,
+    requester TEXT NOT NULL,
+    purchase TEXT NOT NULL,
+    project TEXT NOT NULL,
+    amount INTEGER NOT NULL,
+    request_id TEXT NOT NULL,
+    status TEXT NOT NULL,
+    request_date INTEGER NOT NULL,
+    processed_date INTEGER NOT NULL,
+    processor TEXT
+);
+CREATE TABLE IF NOT EXISTS fee_flows (
+    signature TEXT NOT NULL,
+    event_index INTEGER NOT NULL,
+    slot INTEGER NOT NULL,
+    kind TEXT NOT NULL,
+    buyer TEXT,
+    project TEXT NOT NULL,
+    purchase TEXT,
+    offset_request TEXT,
+    amount INTEGER NOT NULL,
+    decimals INTEGER NOT NULL,
+    total_price INTEGER NOT NULL,
+    fee INTEGER NOT NULL,
+    fee_remainder INTEGER NOT NULL,
+    owner_proceeds INTEGER NOT NULL,
+    timestamp INTEGER NOT NULL,
+    PRIMARY KEY (signature, event_index)
+);
+CREATE TABLE IF NOT EXISTS basket_retirements (
+    signature TEXT NOT NULL,
+    event_index INTEGER NOT NULL,
+    slot INTEGER NOT NULL,
+    basket TEXT NOT NULL,
+    retiree TEXT NOT NULL,
+    amount INTEGER NOT NULL,
+    timestamp INTEGER NOT NULL,
+    PRIMARY KEY (signature, event_index)
+);
+";
+
+/// Tables holding decoded accounts, re-fetched after a rollback
+const ACCOUNT_TABLES: [&str; 3] = ["projects", "purchases", "offset_requests"];
+
+/// Tables holding rows derived from transactions, replayed after a rollback
+const TRANSACTION_TABLES: [&str; 4] = [
+    "transactions",
+    "instructions",
+    "fee_flows",
+    "basket_retirements",
+];
+
+/// A row of `fee_flows`: lamports paid for credits and the platform's cut of them.
+///
+/// `owner_proceeds` go to the project owner, or to the seller of a listing. Auction purchases
+/// are recorded at the price paid into escrow with no fee; the fee and the proceeds at the
+/// clearing price are recorded when the auction settles, on a row without a buyer or purchase.
+struct FeeFlow<'a> {
+    kind: &'static str,
+    buyer: Option<&'a Pubkey>,
+    project: &'a Pubkey,
+    purchase: Option<&'a Pubkey>,
+    offset_request: Option<&'a Pubkey>,
+    amount: u64,
+    decimals: u8,
+    total_price: u64,
+    fee: u64,
+    fee_remainder: u64,
+    timestamp: i64,
+}
+
+impl FeeFlow<'_> {
+    fn insert(
+        &self,
+        tx: &Connection,
+        signature: &str,
+        event_index: usize,
+        slot: i64,
+    ) -> Result<(), IndexError> {
+        tx.execute(
+            "INSERT INTO fee_flows (signature, event_index, slot, kind, buyer, project, purchase,
+                 offset_request, amount, decimals, total_price, fee, fee_remainder,
+                 owner_proceeds, timestamp)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)",
+            params![
+                signature,
+                event_index as i64,
+                slot,
+                self.kind,
+                self.buyer.map(Pubkey::to_string),
+                self.project.to_string(),
+                self.purchase.map(Pubkey::to_string),
+                self.offset_request.map(Pubkey::to_string),
+                self.amount as i64,
+                self.decimals,
+                self.total_price as i64,
+                self.fee as i64,
+                self.fee_remainder as i64,
+                self.total_price.saturating_sub(self.fee) as i64,
+                self.timestamp,
+            ],
+        )?;
+        Ok(())
+    }
+}
+
+fn status_name(status: &RequestStatus) -> &'static str {
+    match status {
+        RequestStatus::Pending => "pending",
+        RequestStatus::Approved => "approved",
+        RequestStatus::Rejected => "rejected",
+    }
+}
+
+pub struct Indexer {
+    conn: Connection,
+}
+
+impl Indexer {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, IndexError> {
+        Self::with_connection(Connection::open(path)?)
+    }
+
+    pub fn open_in_memory() -> Result<Self, IndexError> {
+        Self::with_connection(Connection::open_in_memory()?)
+    }
+
+    fn with_connection(conn: Connection) -> Result<Self, IndexError> {
+        conn.execute_batch(SCHEMA)?;
+        Ok(Self { conn })
+    }
+
+    pub fn connection(&self) -> &Connection {
+        &self.conn
+    }
+
+    /// Highest slot of an indexed transaction, where replay resumes after a restart
+    pub fn last_slot(&self) -> Result<Option<u64>, IndexError> {
+        let slot: Option<i64> =
+            self.conn
+                .query_row("SELECT MAX(slot) FROM transactions", [], |row| row.get(0))?;
+        Ok(slot.map(|slot| slot as u64))
+    }
+
+    /// The `limit` most recently indexed signatures with their slots, newest first
+    pub fn recent_signatures(&self, limit: usize) -> Result<Vec<(String, u64)>, IndexError> {
+        let mut statement = self
+            .conn
+            .prepare("SELECT signature, slot FROM transactions ORDER BY slot DESC LIMIT ?1")?;
+        let rows = statement.query_map([limit as i64], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)? as u64))
+        })?;
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+
+    pub fn is_indexed(&self, signature: &str) -> Result<bool, IndexError> {
+        Ok(self
+            .conn
+            .query_row(
+                "SELECT 1 FROM transactions WHERE signature = ?1",
+                [signature],
+                |_| Ok(()),
+            )
+            .optional()?
+            .is_some())
+    }
+
+    /// Record a transaction's carbon_pay instructions and events.
+    ///
+    /// Returns false if the transaction was already indexed. Failed transactions are recorded
+    /// so they are not fetched again, but their instructions and events are skipped.
+    pub fn index_transaction(&mut self, record: &TransactionRecord) -> Result<bool, IndexError> {
+        if self.is_indexed(&record.signature)? {
+            return Ok(false);
+        }
+        let events = if record.failed {
+            Vec::new()
+        } else {
+            program_events(&record.log_messages)?
+        };
+
+        let tx = self.conn.transaction()?;
+        let slot = record.slot as i64;
+        tx.execute(
+            "INSERT INTO transactions (signature, slot, block_time, failed) VALUES (?1, ?2, ?3, ?4)",
+            params![record.signature, slot, record.block_time, record.failed],
+        )?;
+        if !record.failed {
+            for (index, instruction) in record.instructions.iter().enumerate() {
+                if instruction.program_id != PROGRAM_ID {
+                    continue;
+                }
+                let name = instruction_name(&instruction.data).unwrap_or("Unknown");
+                let accounts = instruction
+                    .accounts
+                    .iter()
+                    .map(Pubkey::to_string)
+                    .collect::<Vec<_>>()
+                    .join(",");
+                tx.execute(
+                    "INSERT INTO instructions (signature, instruction_index, slot, name, accounts)
+                     VALUES (?1, ?2, ?3, ?4, ?5)",
+                    params![record.signature, index as i64, slot, name, accounts],
+                )?;
+            }
+        }
+        for (index, event) in events.iter().enumerate() {
+            match event {
+                ProgramEvent::CarbonCreditsPurchased(event) => FeeFlow {
+                    kind: "purchase",
+                    buyer: Some(&event.buyer),
+                    project: &event.project,
+                    purchase: Some(&event.purchase),
+                    offset_request: event.offset_request.as_ref(),
+                    amount: event.amount,
+                    decimals: event.decimals,
+                    total_price: event.total_price,
+                    fee: event.fee,
+                    fee_remainder: event.fee_remainder,
+                    timestamp: event.timestamp,
+                }
+                .insert(&tx, &record.signature, index, slot)?,
+                ProgramEvent::ListingSold(event) => FeeFlow {
+                    kind: "listing_sale",
+                    buyer: Some(&event.buyer),
+                    project: &event.project,
+                    purchase: Some(&event.purchase),
+                    offset_request: None,
+                    amount: event.amount,
+                    decimals: event.decimals,
+                    total_price: event.total_price,
+                    fee: event.fee,
+                    fee_remainder: event.fee_remainder,
+                    timestamp: event.timestamp,
+                }
+                .insert(&tx, &record.signature, index, slot)?,
+                ProgramEvent::AuctionSettled(event) => FeeFlow {
+                    kind: "auction_settlement",
+                    buyer: None,
+                    project: &event.project,
+                    purchase: None,
+                    offset_request: None,
+                    amount: event.amount,
+                    decimals: event.decimals,
+                    total_price: event.total_price,
+                    fee: event.fee,
+                    fee_remainder: event.fee_remainder,
+                    timestamp: event.timestamp,
+                }
+                .insert(&tx, &record.signature, index, slot)?,
+                ProgramEvent::BasketRetired(event) => {
+                    tx.execute(
+                        "INSERT INTO basket_retirements (signature, event_index, slot, basket, retiree,
+                             amount, timestamp)
+                         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                        params![
+                            record.signature,
+                            index as i64,
+                            slot,
+                            event.basket.to_string(),
+                            event.retiree.to_string(),
+                            event.amount as i64,
+                            event.timestamp,
+                        ],
+                    )?;
+                }
+            }
+        }
+        tx.commit()?;
+        Ok(true)
+    }
+
+    /// Store the state of a carbon_pay account observed at `slot`, `None` if it was closed.
+    ///
+    /// Observations older than the stored row are ignored.
+    pub fn index_account(
+        &mut self,
+        address: &Pubkey,
+        slot: u64,
+        data: Option<&[u8]>,
+    ) -> Result<(), IndexError> {
+        let address = address.to_string();
+        let slot = slot as i64;
+        let Some(data) = data else {
+            for table in ACCOUNT_TABLES {
+                self.conn.execute(
+                    &format!("DELETE FROM {} WHERE address = ?1 AND slot <= ?2", table),
+                    params![address, slot],
+                )?;
+            }
+            return Ok(());
+        };
+        match program_account(data)? {
+            Some(ProgramAccount::Project(project)) => {
+                self.conn.execute(
+                    "INSERT INTO projects (address, slot, owner, nft_mint, token_mint, decimals, is_active,
+                         amount, remaining_amount, offset_amount, price_per_token, carbon_pay_fee)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)
+                     ON CONFLICT (address) DO UPDATE SET
+                         slot = excluded.slot,
+                         is_active = excluded.is_active,
+                         amount = excluded.amount,
+                         remaining_amount = excluded.remaining_amount,
+                         offset_amount = excluded.offset_amount,
+                         price_per_token = excluded.price_per_token,
+                         carbon_pay_fee = excluded.carbon_pay_fee
+                     WHERE excluded.slot >= projects.slot",
+                    params![
+                        address,
+                        slot,
+                        project.owner.to_string(),
+                        project.mint.to_string(),
+                        project.token_mint.to_string(),
+                        project.decimals,
+                        project.is_active,
+                        project.amount as i64,
+                        project.remaining_amount as i64,
+                        project.offset_amount as i64,
+                        project.price_per_token as i64,
+                        project.carbon_pay_fee as i64,
+                    ],
+                )?;
+            }
+            Some(ProgramAccount::Purchase(purchase)) => {
+                self.conn.execute(
+                    "INSERT INTO purchases (address, slot, buyer, project, nft_mint, amount,
+                         remaining_amount, purchase_date)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+                     ON CONFLICT (address) DO UPDATE SET
+                         slot = excluded.slot,
+                         buyer = excluded.buyer,
+                         nft_mint = excluded.nft_mint,
+                         amount = excluded.amount,
+                         remaining_amount = excluded.remaining_amount
+                     WHERE excluded.slot >= purchases.slot",
+                    params![
+                        address,
+                        slot,
+                        purchase.buyer.to_string(),
+                        purchase.project.to_string(),
+                        purchase.nft_mint.to_string(),
+                        purchase.amount as i64,
+                        purchase.remaining_amount as i64,
+                        purchase.purchase_date,
+                    ],
+                )?;
+            }
+            Some(ProgramAccount::OffsetRequest(request)) => {
+                self.conn.execute(
+                    "INSERT INTO offset_requests (address, slot, requester, purchase, project, amount,
+                         request_id, status, request_date, processed_date, processor)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)
+                     ON CONFLICT (address) DO UPDATE SET
+                         slot = excluded.slot,
+                         status = excluded.status,
+                         processed_date = excluded.processed_date,
+                         processor = excluded.processor
+                     WHERE excluded.slot >= offset_requests.slot",
+                    params![
+                        address,
+                        slot,
+                        request.offset_requester.to_string(),
+                        request.purchase.to_string(),
+                        request.project.to_string(),
+                        request.amount as i64,
+                        request.request_id,
+                        status_name(&request.status),
+                        request.request_date,
+                        request.processed_date,
+                        request.processor.map(|processor| processor.to_string()),
+                    ],
+                )?;
+            }
+            None => {}
+        }
+        Ok(())
+    }
+
+    /// Drop everything observed above `slot`, after the fork it was on was abandoned.
+    ///
+    /// Returns the addresses of the account rows that were dropped; they must be fetched again
+    /// from the surviving fork. Dropped transactions are replayed like any new ones.
+    pub fn rollback(&mut self, slot: u64) -> Result<Vec<Pubkey>, IndexError> {
+        let slot = slot as i64;
+        let tx = self.conn.transaction()?;
+        let mut stale = Vec::new();
+        for table in ACCOUNT_TABLES {
+            let mut statement =
+                tx.prepare(&format!("SELECT address FROM {} WHERE slot > ?1", table))?;
+            let addresses = statement.query_map([slot], |row| row.get::<_, String>(0))?;
+            for address in addresses {
+                stale.push(address?.parse()?);
+            }
+        }
+        for table in ACCOUNT_TABLES.iter().chain(TRANSACTION_TABLES.iter()) {
+            tx.execute(&format!("DELETE FROM {} WHERE slot > ?1", table), [slot])?;
+        }
+        tx.commit()?;
+        Ok(stale)
+    }
+}
@@ -0,0 +1,95 @@
+//! Confirmed transactions as returned by `getTransaction` with `"encoding": "json"`.
+
+use std::str::FromStr;
+
+use anchor_lang::prelude::Pubkey;
+use serde_json::Value;
+
+use crate::IndexError;
+
+/// An instruction with its program and accounts resolved against the transaction's keys
+pub struct InstructionRecord {
+    pub program_id: Pubkey,
+    pub accounts: Vec<Pubkey>,
+    pub data: Vec<u8>,
+}
+
+/// The parts of a confirmed transaction the indexer replays
+pub struct TransactionRecord {
+    pub signature: String,
+    pub slot: u64,
+    pub block_time: Option<i64>,
+    pub failed: bool,
+    pub instructions: Vec<InstructionRecord>,
+    pub log_messages: Vec<String>,
+}
+
+fn pubkeys(value: &Value) -> Result<Vec<Pubkey>, IndexError> {
+    value
+        .as_array()
+        .map(Vec::as_slice)
+        .unwrap_or_default()
+        .iter()
+        .map(|key| {
+            Ok(Pubkey::from_str(
+                key.as_str().ok_or("account key is not a string")?,
+            )?)
+        })
+        .collect()
+}
+
+impl TransactionRecord {
+    /// Parse a `getTransaction` result, including recorded fixtures of one
+    pub fn from_rpc_json(value: &Value) -> Result<Self, IndexError> {
+        let transaction = &value["transaction"];
+        let message = &transaction["message"];
+        let meta = &value["meta"];
+
+        // v0 transactions append addresses loaded from lookup tables after the static keys
+        let mut keys = pubkeys(&message["accountKeys"])?;
+        keys.extend(pubkeys(&meta["loadedAddresses"]["writable"])?);
+        keys.extend(pubkeys(&meta["loadedAddresses"]["readonly"])?);
+        let key = |index: &Value| -> Result<Pubkey, IndexError> {
+            let index = index.as_u64().ok_or("account index is not a number")? as usize;
+            Ok(*keys.get(index).ok_or("account index out of range")?)
+        };
+
+        let instructions = message["instructions"]
+            .as_array()
+            .ok_or("transaction has no instructions")?
+            .iter()
+            .map(|instruction| {
+                Ok(InstructionRecord {
+                    program_id: key(&instruction["programIdIndex"])?,
+                    accounts: instruction["accounts"]
+                        .as_array()
+                        .map(Vec::as_slice)
+                        .unwrap_or_default()
+                        .iter()
+                        .map(&key)
+                        .collect::<Result<_, IndexError>>()?,
+                    data: bs58::decode(instruction["data"].as_str().unwrap_or_default())
+                        .into_vec()?,
+                })
+            })
+            .collect::<Result<_, IndexError>>()?;
+
+        Ok(Self {
+            signature: transaction["signatures"][0]
+                .as_str()
+                .ok_or("transaction has no signature")?
+                .to_string(),
+            slot: value["slot"].as_u64().ok_or("transaction has no slot")?,
+            block_time: value["blockTime"].as_i64(),
+            failed: !meta["err"].is_null(),
+            instructions,
+            log_messages: meta["logMessages"]
+                .as_array()
+                .map(Vec::as_slice)
+                .unwrap_or_default()
+                .iter()
+                .filter_map(|line| line.as_str().map(str::to_string))
+                .collect(),
+        })
+    }
+}
@@ -0,0 +1,431 @@
+//! Replays recorded transaction fixtures through the indexer.
+//!
+//! `fixtures/chain.json` holds `getTransaction` results for a project launch, a purchase, an
+//! offset request and a failed purchase, plus the history of every carbon_pay account they
+//! touched. `fixtures/fork.json` holds a purchase on a fork branching after slot 110 that was
+//! later abandoned.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::Event;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use carbonpay::events::{AuctionSettled, ListingSold};
+use carbonpay_client::PROGRAM_ID;
+use carbonpay_indexer::{sync, ChainSource, IndexError, Indexer, SyncSummary, TransactionRecord};
+use rusqlite::Connection;
+use serde_json::Value;
+
+type AccountHistory = Vec<(u64, Option<Vec<u8>>)>;
+
+struct Fixture {
+    transactions: Vec<Value>,
+    accounts: HashMap<Pubkey, AccountHistory>,
+}
+
+fn load(name: &str) -> Fixture {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name);
+    let fixture: Value = serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap();
+    let accounts = fixture["accounts"]
+        .as_object()
+        .unwrap()
+        .iter()
+        .map(|(address, history)| {
+            let history = history
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|entry| {
+                    let data = entry["data"]
+                        .as_str()
+                        .map(|data| STANDARD.decode(data).unwrap());
+                    (entry["slot"].as_u64().unwrap(), data)
+                })
+                .collect();
+            (address.parse().unwrap(), history)
+        })
+        .collect();
+    Fixture {
+        transactions: fixture["transactions"].as_array().unwrap().clone(),
+        accounts,
+    }
+}
+
+/// The chain as seen by a node: the transactions on its fork, oldest first
+struct FixtureSource {
+    transactions: Vec<Value>,
+    accounts: HashMap<Pubkey, AccountHistory>,
+}
+
+impl FixtureSource {
+    fn new(fixtures: &[&Fixture], max_slot: u64) -> Self {
+        let mut transactions: Vec<Value> = fixtures
+            .iter()
+            .flat_map(|fixture| fixture.transactions.iter().cloned())
+            .filter(|tx| tx["slot"].as_u64().unwrap() <= max_slot)
+            .collect();
+        transactions.sort_by_key(|tx| tx["slot"].as_u64().unwrap());
+        let mut accounts: HashMap<Pubkey, AccountHistory> = HashMap::new();
+        for fixture in fixtures {
+            for (address, history) in &fixture.accounts {
+                accounts.entry(*address).or_default().extend(
+                    history
+                        .iter()
+                        .filter(|(slot, _)| *slot <= max_slot)
+                        .cloned(),
+                );
+            }
+        }
+        Self {
+            transactions,
+            accounts,
+        }
+    }
+
+    fn signature(tx: &Value) -> &str {
+        tx["transaction"]["signatures"][0].as_str().unwrap()
+    }
+
+    fn head(&self) -> u64 {
+        self.transactions
+            .last()
+            .map_or(0, |tx| tx["slot"].as_u64().unwrap())
+    }
+}
+
+impl ChainSource for FixtureSource {
+    fn signatures(&self, after: Option<&str>) -> Result<Vec<String>, IndexError> {
+        let start = after
+            .and_then(|after| {
+                self.transactions
+                    .iter()
+                    .position(|tx| Self::signature(tx) == after)
+            })
+            .map_or(0, |position| position + 1);
+        Ok(self.transactions[start..]
+            .iter()
+            .map(|tx| Self::signature(tx).to_string())
+            .collect())
+    }
+
+    fn transaction(&self, signature: &str) -> Result<Option<TransactionRecord>, IndexError> {
+        self.transactions
+            .iter()
+            .find(|tx| Self::signature(tx) == signature)
+            .map(TransactionRecord::from_rpc_json)
+            .transpose()
+    }
+
+    fn confirmed(&self, signatures: &[String]) -> Result<Vec<bool>, IndexError> {
+        Ok(signatures
+            .iter()
+            .map(|signature| {
+                self.transactions
+                    .iter()
+                    .any(|tx| Self::signature(tx) == signature)
+            })
+            .collect())
+    }
+
+    fn account(&self, address: &Pubkey) -> Result<(u64, Option<Vec<u8>>), IndexError> {
+        let latest = self
+            .accounts
+            .get(address)
+            .and_then(|history| history.iter().max_by_key(|(slot, _)| *slot));
+        Ok((self.head(), latest.and_then(|(_, data)| data.clone())))
+    }
+}
+
+/// Every table's rows, for comparing two databases
+fn dump(conn: &Connection) -> Vec<String> {
+    let mut rows = Vec::new();
+    for table in [
+        "transactions",
+        "instructions",
+        "projects",
+        "purchases",
+        "offset_requests",
+        "fee_flows",
+        "basket_retirements",
+    ] {
+        let mut statement = conn
+            .prepare(&format!("SELECT * FROM {} ORDER BY 1, 2", table))
+            .unwrap();
+        let columns = statement.column_count();
+        let table_rows = statement
+            .query_map([], |row| {
+                let values = (0..columns)
+                    .map(|i| Ok(format!("{:?}", row.get_ref(i)?)))
+                    .collect::<Result<Vec<_>, rusqlite::Error>>()?;
+                Ok(format!("{}: {}", table, values.join(" | ")))
+            })
+            .unwrap();
+        rows.extend(table_rows.map(Result::unwrap));
+    }
+    rows
+}
+
+fn count(conn: &Connection, sql: &str) -> i64 {
+    conn.query_row(sql, [], |row| row.get(0)).unwrap()
+}
+
+#[test]
+fn materializes_projects_purchases_offsets_and_fees() {
+    let chain = load("chain.json");
+    let source = FixtureSource::new(&[&chain], u64::MAX);
+    let mut indexer = Indexer::open_in_memory().unwrap();
+
+    let summary = sync(&mut indexer, &source, 16).unwrap();
+    assert_eq!(summary.transactions, 4);
+    assert_eq!(summary.rolled_back_to, None);
+    assert_eq!(indexer.last_slot().unwrap(), Some(125));
+
+    let conn = indexer.connection();
+    let names: Vec<String> = conn
+        .prepare("SELECT name FROM instructions ORDER BY slot")
+        .unwrap()
+        .query_map([], |row| row.get(0))
+        .unwrap()
+        .map(Result::unwrap)
+        .collect();
+    assert_eq!(
+        names,
+        [
+            "InitializeProject",
+            "PurchaseCarbonCredits",
+            "RequestOffset"
+        ]
+    );
+
+    let project: (i64, i64, i64) = conn
+        .query_row(
+            "SELECT amount, remaining_amount, offset_amount FROM projects",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .unwrap();
+    assert_eq!(project, (1000, 900, 40));
+
+    let purchase: (i64, i64) = conn
+        .query_row(
+            "SELECT amount, remaining_amount FROM purchases",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .unwrap();
+    assert_eq!(purchase, (100, 60));
+
+    let offset: (String, i64, String) = conn
+        .query_row(
+            "SELECT request_id, amount, status FROM offset_requests",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .unwrap();
+    assert_eq!(offset, ("offset-1".to_string(), 40, "pending".to_string()));
+
+    // Only the successful purchase moved lamports; data logged by the token program is ignored
+    let fees: (i64, i64, i64) = conn
+        .query_row(
+            "SELECT COUNT(*), SUM(fee), SUM(owner_proceeds) FROM fee_flows",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .unwrap();
+    assert_eq!(fees, (1, 5_000_000, 95_000_000));
+    assert_eq!(
+        count(conn, "SELECT COUNT(*) FROM transactions WHERE failed"),
+        1
+    );
+}
+
+#[test]
+fn resyncing_after_restart_is_idempotent() {
+    let chain = load("chain.json");
+    let path = std::env::temp_dir().join(format!("carbonpay-indexer-{}.db", std::process::id()));
+    let _ = fs::remove_file(&path);
+
+    // Index the first two transactions, then "crash" and reopen the database
+    let mut indexer = Indexer::open(&path).unwrap();
+    sync(&mut indexer, &FixtureSource::new(&[&chain], 110), 16).unwrap();
+    drop(indexer);
+
+    let source = FixtureSource::new(&[&chain], u64::MAX);
+    let mut indexer = Indexer::open(&path).unwrap();
+    let summary = sync(&mut indexer, &source, 16).unwrap();
+    assert_eq!(summary.transactions, 2);
+    let resumed = dump(indexer.connection());
+
+    // Nothing new: a second sync and replaying already indexed transactions change nothing
+    assert_eq!(
+        sync(&mut indexer, &source, 16).unwrap(),
+        SyncSummary::default()
+    );
+    for tx in &chain.transactions {
+        let record = TransactionRecord::from_rpc_json(tx).unwrap();
+        assert!(!indexer.index_transaction(&record).unwrap());
+    }
+    assert_eq!(dump(indexer.connection()), resumed);
+
+    let mut fresh = Indexer::open_in_memory().unwrap();
+    sync(&mut fresh, &source, 16).unwrap();
+    assert_eq!(dump(fresh.connection()), resumed);
+
+    drop(indexer);
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn stale_account_observations_are_ignored() {
+    let chain = load("chain.json");
+    let mut indexer = Indexer::open_in_memory().unwrap();
+    let (address, history) = chain
+        .accounts
+        .iter()
+        .find(|(_, history)| history.len() == 3)
+        .unwrap();
+
+    let (latest_slot, latest) = &history[2];
+    indexer
+        .index_account(address, *latest_slot, latest.as_deref())
+        .unwrap();
+    let (old_slot, old) = &history[0];
+    indexer
+        .index_account(address, *old_slot, old.as_deref())
+        .unwrap();
+    indexer.index_account(address, *old_slot, None).unwrap();
+
+    let remaining: i64 = indexer
+        .connection()
+        .query_row("SELECT remaining_amount FROM projects", [], |row| {
+            row.get(0)
+        })
+        .unwrap();
+    assert_eq!(remaining, 900);
+
+    indexer
+        .index_account(address, *latest_slot + 1, None)
+        .unwrap();
+    assert_eq!(
+        count(indexer.connection(), "SELECT COUNT(*) FROM projects"),
+        0
+    );
+}
+
+#[test]
+fn reorg_rolls_back_abandoned_fork() {
+    let chain = load("chain.json");
+    let fork = load("fork.json");
+    let mut indexer = Indexer::open_in_memory().unwrap();
+
+    // The node first follows the fork that branched after slot 110
+    let summary = sync(&mut indexer, &FixtureSource::new(&[&chain, &fork], 115), 16).unwrap();
+    assert_eq!(summary.transactions, 3);
+    assert_eq!(
+        count(indexer.connection(), "SELECT COUNT(*) FROM purchases"),
+        2
+    );
+    assert_eq!(
+        count(indexer.connection(), "SELECT SUM(amount) FROM fee_flows"),
+        400
+    );
+
+    // The fork is abandoned in favour of the canonical chain
+    let canonical = FixtureSource::new(&[&chain], u64::MAX);
+    let summary = sync(&mut indexer, &canonical, 16).unwrap();
+    assert_eq!(summary.rolled_back_to, Some(114));
+    assert_eq!(summary.transactions, 2);
+
+    let mut fresh = Indexer::open_in_memory().unwrap();
+    sync(&mut fresh, &canonical, 16).unwrap();
+    assert_eq!(dump(indexer.connection()), dump(fresh.connection()));
+}
+
+#[test]
+fn indexes_listing_sale_and_auction_settlement_fees() {
+    let (buyer, project, purchase) = (
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+    );
+    let sale = ListingSold {
+        buyer,
+        seller: Pubkey::new_unique(),
+        project,
+        purchase,
+        amount: 19,
+        decimals: 0,
+        total_price: 19,
+        fee: 1,
+        fee_remainder: 9_500,
+        price_per_token: 1,
+        timestamp: 1_000,
+    };
+    let settlement = AuctionSettled {
+        project,
+        amount: 10,
+        decimals: 0,
+        total_price: 15_000_000,
+        fee: 750_000,
+        fee_remainder: 0,
+        clearing_price: 1_500_000,
+        timestamp: 1_100,
+    };
+    let program_data = |data: Vec<u8>| {
+        vec![
+            format!("Program {} invoke [1]", PROGRAM_ID),
+            format!("Program data: {}", STANDARD.encode(data)),
+            format!("Program {} success", PROGRAM_ID),
+        ]
+    };
+    let mut indexer = Indexer::open_in_memory().unwrap();
+    for (slot, log_messages) in [
+        (200, program_data(sale.data())),
+        (210, program_data(settlement.data())),
+    ] {
+        let record = TransactionRecord {
+            signature: format!("signature-{}", slot),
+            slot,
+            block_time: None,
+            failed: false,
+            instructions: Vec::new(),
+            log_messages,
+        };
+        assert!(indexer.index_transaction(&record).unwrap());
+    }
+
+    let conn = indexer.connection();
+    let sale_row: (String, String, i64, i64, i64) = conn
+        .query_row(
+            "SELECT buyer, purchase, fee, fee_remainder, owner_proceeds FROM fee_flows
+             WHERE kind = 'listing_sale'",
+            [],
+            |row| {
+                Ok((
+                    row.get(0)?,
+                    row.get(1)?,
+                    row.get(2)?,
+                    row.get(3)?,
+                    row.get(4)?,
+                ))
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        sale_row,
+        (buyer.to_string(), purchase.to_string(), 1, 9_500, 18)
+    );
+    let settlement_row: (Option<String>, i64, i64) = conn
+        .query_row(
+            "SELECT buyer, fee, owner_proceeds FROM fee_flows WHERE kind = 'auction_settlement'",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .unwrap();
+    assert_eq!(settlement_row, (None, 750_000, 14_250_000));
+    assert_eq!(count(conn, "SELECT SUM(fee) FROM fee_flows"), 750_001);
+}
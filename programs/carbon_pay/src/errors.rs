@@ -51,24 +51,178 @@ pub enum ContractError {
     #[msg("Only the purchase owner can request an offset")]
     NotPurchaseOwner,
     
+    // Not raised by any instruction, kept so the codes after it stay stable
     #[msg("Invalid request status")]
     InvalidRequestStatus,
     
     #[msg("Offset request already processed")]
     RequestAlreadyProcessed,
     
+    // Not raised by any instruction, kept so the codes after it stay stable
     #[msg("Invalid offset request")]
     InvalidOffsetRequest,
     
     #[msg("Invalid project for this purchase")]
     InvalidProject,
     
+    // Not raised by any instruction, kept so the codes after it stay stable
     #[msg("Offset request already exists")]
     OffsetRequestExists,
     
+    // Not raised by any instruction, kept so the codes after it stay stable
     #[msg("Math operation overflow")]
     MathOverflow,
     
     #[msg("Insufficient fungible tokens in account")]
     InsufficientFungibleTokens,
+    
+    #[msg("Too many approvers")]
+    TooManyApprovers,
+    
+    #[msg("Approval threshold must be between 1 and the number of approvers")]
+    InvalidApprovalThreshold,
+    
+    #[msg("Approver listed more than once")]
+    DuplicateApprover,
+    
+    #[msg("Signer is not an offset approver")]
+    NotAnApprover,
+    
+    #[msg("Approver has already voted on this request")]
+    DuplicateVote,
+    
+    #[msg("Buyer is not on the allowlist")]
+    BuyerNotAllowlisted,
+    
+    #[msg("Allowlist entry has expired")]
+    AllowlistEntryExpired,
+    
+    #[msg("Allowlist entry does not apply to this project")]
+    InvalidAllowlistScope,
+    
+    #[msg("Purchase amount is below the project minimum")]
+    PurchaseBelowMinimum,
+    
+    #[msg("Purchase amount is above the project maximum")]
+    PurchaseAboveMaximum,
+    
+    #[msg("Purchase would exceed the per-buyer cap")]
+    BuyerCapExceeded,
+    
+    #[msg("Invalid purchase limits")]
+    InvalidPurchaseLimits,
+    
+    #[msg("Sale has not started yet")]
+    SaleNotStarted,
+    
+    #[msg("Sale has ended")]
+    SaleEnded,
+    
+    #[msg("Invalid sale window")]
+    InvalidSaleWindow,
+    
+    #[msg("Invalid price tiers")]
+    InvalidPriceTiers,
+    
+    #[msg("Listing does not match this purchase")]
+    InvalidListing,
+    
+    #[msg("Invalid auction parameters")]
+    InvalidAuction,
+    
+    #[msg("Auction has ended")]
+    AuctionEnded,
+    
+    #[msg("Auction has not ended yet")]
+    AuctionNotEnded,
+    
+    #[msg("Project has no auction to settle")]
+    NoActiveAuction,
+    
+    #[msg("Auction has not been settled yet")]
+    AuctionNotSettled,
+    
+    #[msg("Nothing to refund")]
+    NothingToRefund,
+    
+    #[msg("OTC offer has expired")]
+    OtcOfferExpired,
+    
+    #[msg("Subscription purchase is not due yet")]
+    SubscriptionNotDue,
+    
+    #[msg("Current price is above the subscription's maximum")]
+    SubscriptionPriceTooHigh,
+    
+    #[msg("Subscription escrow has insufficient funds")]
+    InsufficientSubscriptionFunds,
+    
+    #[msg("Project is in an active auction")]
+    AuctionActive,
+    
+    #[msg("Project category is not eligible for this basket")]
+    ProjectNotEligible,
+    
+    #[msg("Every basket reserve must be provided")]
+    InvalidBasketReserves,
+    
+    #[msg("Offset delegation has expired")]
+    OffsetDelegateExpired,
+    
+    #[msg("Offset exceeds the delegated allowance")]
+    OffsetDelegateAllowanceExceeded,
+    
+    #[msg("Too many purchases in one batch offset")]
+    TooManyBatchPurchases,
+    
+    #[msg("Batch purchases must be distinct and match the amounts")]
+    InvalidBatchPurchases,
+    
+    #[msg("Merge sources must be distinct purchases other than the target")]
+    InvalidMergePurchases,
+    
+    #[msg("Purchase is listed for sale")]
+    PurchaseListed,
+    
+    #[msg("Recipient must differ from the current owner")]
+    InvalidRecipient,
+    
+    #[msg("Project token mint has more decimals than supported")]
+    InvalidTokenDecimals,
+    
+    #[msg("Baskets only accept projects with whole-tonne tokens")]
+    UnsupportedTokenDecimals,
+    
+    #[msg("Token account is not the project's vault")]
+    InvalidProjectVault,
+    
+    #[msg("Project token mint already has a supply")]
+    TokenMintHasSupply,
+    
+    #[msg("Only the project owner can cancel an OTC offer before it expires")]
+    OtcOfferNotExpired,
+    
+    #[msg("Basket already holds the maximum number of projects")]
+    BasketFull,
+    
+    #[msg("Purchase is filled by a subscription")]
+    PurchaseSubscribed,
+    
+    #[msg("Purchase has an offset delegate")]
+    PurchaseDelegated,
+    
+    #[msg("CarbonCredits is already migrated")]
+    CarbonCreditsMigrated,
+    
+    #[msg("Project is already migrated")]
+    ProjectMigrated,
+    
+    #[msg("Purchase is not the one this subscription fills")]
+    InvalidSubscriptionPurchase,
+    
+    #[msg("Purchase is already migrated")]
+    PurchaseMigrated,
+    
+    #[msg("Tokens back one of the holder's purchases, offset them through the purchase")]
+    TokensBackPurchases,
 }
@@ -0,0 +1,51 @@
+use anchor_lang::prelude::*;
+
+#[event]
+pub struct CarbonCreditsPurchased {
+    pub buyer: Pubkey,
+    pub project: Pubkey,
+    pub purchase: Pubkey,               // Default key when no Purchase was created
+    pub offset_request: Option<Pubkey>, // Retirement record when the credits were retired on purchase
+    pub amount: u64,                    // Base units of the project token
+    pub decimals: u8,                   // Decimals of the project token
+    pub total_price: u64,               // Lamports paid for the tokens, fee included
+    pub fee: u64,                       // Lamports sent to the platform
+    pub fee_remainder: u64,             // total_price * fee bps % 10_000, before rounding and the minimum fee
+    pub blended_price_per_token: u64,   // Lamports per whole token, rounded down
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ListingSold {
+    pub buyer: Pubkey,
+    pub seller: Pubkey,
+    pub project: Pubkey,
+    pub purchase: Pubkey,     // The buyer's new Purchase
+    pub amount: u64,          // Base units of the project token
+    pub decimals: u8,         // Decimals of the project token
+    pub total_price: u64,     // Lamports paid for the tokens, fee included
+    pub fee: u64,             // Lamports sent to the platform
+    pub fee_remainder: u64,   // total_price * fee bps % 10_000, before rounding and the minimum fee
+    pub price_per_token: u64, // Listing price in lamports per whole token
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AuctionSettled {
+    pub project: Pubkey,
+    pub amount: u64,         // Base units sold in the auction
+    pub decimals: u8,        // Decimals of the project token
+    pub total_price: u64,    // Proceeds at the clearing price, fee included
+    pub fee: u64,            // Lamports sent to the platform
+    pub fee_remainder: u64,  // total_price * fee bps % 10_000, before rounding and the minimum fee
+    pub clearing_price: u64, // Lamports per whole token every buyer is settled at
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BasketRetired {
+    pub basket: Pubkey,
+    pub retiree: Pubkey,
+    pub amount: u64, // Basket tokens burned, retired pro-rata across the basket's projects
+    pub timestamp: i64,
+}
@@ -0,0 +1,245 @@
+use crate::errors::ContractError;
+use crate::events::CarbonCreditsPurchased;
+use crate::state::{
+    AllowlistEntry, BuyerStats, CarbonCredits, Holdings, OtcOffer, PlatformConfig, PlatformFee, Project,
+    Purchase,
+};
+use crate::utils::{
+    mint_purchase_nft, pay_owner_and_platform, price_for, transfer_from_vault,
+    PurchaseNft,
+};
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    metadata::Metadata,
+    token::{Mint, Token, TokenAccount},
+};
+
+#[derive(Accounts)]
+pub struct AcceptOtcOffer<'info> {
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    /// the offer is closed on acceptance, so it can only be used once
+    #[account(
+        mut,
+        close = project_owner,
+        constraint = otc_offer.buyer == buyer.key() @ ContractError::Unauthorized,
+        seeds = [b"otc_offer", project.key().as_ref(), buyer.key().as_ref(), otc_offer.offer_id.to_le_bytes().as_ref()],
+        bump = otc_offer.bump,
+    )]
+    pub otc_offer: Box<Account<'info, OtcOffer>>,
+
+    #[account(
+        seeds = [b"project", project.owner.as_ref(), project.mint.as_ref()],
+        bump = project.project_bump,
+    )]
+    pub project: Box<Account<'info, Project>>,
+
+    /// CHECK: project owner receives the payment and the offer rent
+    #[account(
+        mut,
+        constraint = project_owner.key() == project.owner @ ContractError::InvalidProjectOwner
+    )]
+    pub project_owner: UncheckedAccount<'info>,
+
+    #[account(
+        constraint = project_mint.key() == project.token_mint @ ContractError::InvalidProjectMint
+    )]
+    pub project_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        seeds = [b"carbon_credits"],
+        bump = carbon_credits.bump,
+        constraint = carbon_credits.key() == project.carbon_pay_authority @ ContractError::InvalidCarbonPayAuthority
+    )]
+    pub carbon_credits: Box<Account<'info, CarbonCredits>>,
+
+    /// platform-wide settings (allowlist requirement, fee rounding and minimum fee)
+    #[account(seeds = [b"platform_config"], bump = platform_config.bump)]
+    pub platform_config: Box<Account<'info, PlatformConfig>>,
+
+    /// buyer's allowlist entry, required when the project or platform enforces an allowlist
+    #[account(
+        seeds = [b"allowlist", allowlist_entry.scope.as_ref(), buyer.key().as_ref()],
+        bump = allowlist_entry.bump,
+    )]
+    pub allowlist_entry: Option<Box<Account<'info, AllowlistEntry>>>,
+
+    /// buyer's running totals for this project, used for the per-buyer cap
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        space = BuyerStats::DISCRIMINATOR_SIZE + BuyerStats::INIT_SPACE,
+        seeds = [b"buyer_stats", buyer.key().as_ref(), project.key().as_ref()],
+        bump
+    )]
+    pub buyer_stats: Box<Account<'info, BuyerStats>>,
+
+    /// tokens in the buyer's wallet backing their purchases of this project
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        space = Holdings::DISCRIMINATOR_SIZE + Holdings::INIT_SPACE,
+        seeds = [b"holdings", buyer.key().as_ref(), project.key().as_ref()],
+        bump
+    )]
+    pub holdings: Box<Account<'info, Holdings>>,
+
+    /// project's vault ATA
+    #[account(
+        mut,
+        address = project.vault @ ContractError::InvalidProjectVault,
+        token::mint = project_mint,
+        token::authority = carbon_credits,
+    )]
+    pub project_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// purchase NFT mint (create off-chain)
+    #[account(
+        mut,
+        constraint = purchase_nft_mint.mint_authority == Some(buyer.key()).into() @ ContractError::Unauthorized
+    )]
+    pub purchase_nft_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        token::mint = purchase_nft_mint,
+        token::authority = buyer,
+    )]
+    pub buyer_nft_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = project_mint,
+        token::authority = buyer,
+    )]
+    pub buyer_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        init,
+        payer = buyer,
+        space = Purchase::DISCRIMINATOR_SIZE + Purchase::INIT_SPACE,
+        seeds = [b"purchase", buyer.key().as_ref(), project.key().as_ref(), purchase_nft_mint.key().as_ref()],
+        bump
+    )]
+    pub purchase: Box<Account<'info, Purchase>>,
+
+    /// CHECK: This account will be initialized by the Token Metadata program via CPI.
+    #[account(
+        mut,
+        seeds = [b"metadata", token_metadata_program.key().as_ref(), purchase_nft_mint.key().as_ref()],
+        bump,
+        seeds::program = token_metadata_program.key(),
+    )]
+    pub purchase_metadata: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub token_metadata_program: Program<'info, Metadata>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+impl<'info> AcceptOtcOffer<'info> {
+    pub fn handler(&mut self, bumps: &AcceptOtcOfferBumps) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        require!(now < self.otc_offer.expires_at, ContractError::OtcOfferExpired);
+        let amount = self.otc_offer.amount;
+
+        // 0) buyer gates: the negotiated deal replaces the sale's price and per-transaction
+        // limits, not the KYC gate or the per-buyer cap
+        if self.project.allowlist_required || self.platform_config.allowlist_required {
+            let entry = self
+                .allowlist_entry
+                .as_ref()
+                .ok_or(ContractError::BuyerNotAllowlisted)?;
+            entry.check_buyer(
+                &self.buyer.key(),
+                &self.project.key(),
+                &self.platform_config.key(),
+                now,
+            )?;
+        }
+        self.project
+            .check_buyer_cap(amount, self.buyer_stats.total_purchased)?;
+
+        // 1) payments at the negotiated price
+        let total = price_for(amount, self.otc_offer.price_per_token, self.project.decimals)?;
+        let PlatformFee { fee, remainder } = self
+            .platform_config
+            .platform_fee(total, self.project.carbon_pay_fee)?;
+        let to_owner = total.checked_sub(fee).ok_or(ContractError::ArithmeticOverflow)?;
+        pay_owner_and_platform(
+            self.system_program.to_account_info(),
+            self.buyer.to_account_info(),
+            self.project_owner.to_account_info(),
+            self.carbon_credits.to_account_info(),
+            to_owner,
+            fee,
+        )?;
+
+        // 2) purchase NFT
+        mint_purchase_nft(
+            PurchaseNft {
+                nft_mint: self.purchase_nft_mint.to_account_info(),
+                nft_account: self.buyer_nft_account.to_account_info(),
+                nft_metadata: self.purchase_metadata.to_account_info(),
+                owner: self.buyer.to_account_info(),
+                token_program: self.token_program.to_account_info(),
+                token_metadata_program: self.token_metadata_program.to_account_info(),
+                system_program: self.system_program.to_account_info(),
+                rent: self.rent.to_account_info(),
+            },
+            format!("Carbon Credits Purchase - {}", amount),
+            format!("https://carbonpay.com/purchases/{}", self.purchase_nft_mint.key()),
+        )?;
+
+        // 3) tokens from the vault, already reserved out of remaining_amount
+        transfer_from_vault(
+            self.token_program.to_account_info(),
+            self.project_token_account.to_account_info(),
+            self.buyer_token_account.to_account_info(),
+            self.carbon_credits.to_account_info(),
+            self.carbon_credits.bump,
+            amount,
+        )?;
+
+        // 4) purchase record
+        self.purchase.set_inner(Purchase {
+            buyer: self.buyer.key(),
+            project: self.project.key(),
+            amount,
+            remaining_amount: amount,
+            purchase_date: now,
+            purchase_bump: bumps.purchase,
+            nft_mint: self.purchase_nft_mint.key(),
+            certificate_mint: self.purchase_nft_mint.key(),
+            uncertified_offset: 0,
+        });
+        if self.buyer_stats.buyer == Pubkey::default() {
+            self.buyer_stats.buyer = self.buyer.key();
+            self.buyer_stats.project = self.project.key();
+            self.buyer_stats.bump = bumps.buyer_stats;
+        }
+        self.buyer_stats.record_purchase(amount)?;
+        self.holdings.open(self.buyer.key(), self.project.key(), bumps.holdings);
+        self.holdings.add(amount)?;
+        self.carbon_credits.add_fees(fee)?;
+
+        emit!(CarbonCreditsPurchased {
+            buyer: self.buyer.key(),
+            project: self.project.key(),
+            purchase: self.purchase.key(),
+            offset_request: None,
+            amount,
+            decimals: self.project.decimals,
+            total_price: total,
+            fee,
+            fee_remainder: remainder,
+            blended_price_per_token: self.otc_offer.price_per_token,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+}
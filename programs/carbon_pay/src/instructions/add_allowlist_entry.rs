@@ -0,0 +1,60 @@
+use crate::errors::ContractError;
+use crate::state::{AllowlistEntry, PlatformConfig, Project};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+#[instruction(scope: Pubkey, wallet: Pubkey)]
+pub struct AddAllowlistEntry<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [b"platform_config"],
+        bump = platform_config.bump,
+        constraint = platform_config.authority == authority.key() @ ContractError::UnauthorizedAdmin,
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+
+    /// project the entry is scoped to, omit for a platform-wide entry
+    #[account(
+        seeds = [b"project", project.owner.as_ref(), project.mint.as_ref()],
+        bump = project.project_bump,
+    )]
+    pub project: Option<Box<Account<'info, Project>>>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = AllowlistEntry::DISCRIMINATOR_SIZE + AllowlistEntry::INIT_SPACE,
+        seeds = [b"allowlist", scope.as_ref(), wallet.as_ref()],
+        bump
+    )]
+    pub allowlist_entry: Account<'info, AllowlistEntry>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> AddAllowlistEntry<'info> {
+    pub fn handler(
+        &mut self,
+        scope: Pubkey,
+        wallet: Pubkey,
+        expires_at: i64,
+        bumps: &AddAllowlistEntryBumps,
+    ) -> Result<()> {
+        let expected_scope = match &self.project {
+            Some(project) => project.key(),
+            None => self.platform_config.key(),
+        };
+        require_keys_eq!(scope, expected_scope, ContractError::InvalidAllowlistScope);
+
+        self.allowlist_entry.set_inner(AllowlistEntry {
+            scope,
+            wallet,
+            expires_at,
+            bump: bumps.allowlist_entry,
+        });
+
+        Ok(())
+    }
+}
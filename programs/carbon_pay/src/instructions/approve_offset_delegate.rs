@@ -0,0 +1,140 @@
+use crate::errors::ContractError;
+use crate::state::{Holdings, OffsetDelegate, Project, Purchase};
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{self, Mint, Token, TokenAccount, Transfer},
+};
+
+#[derive(Accounts)]
+pub struct ApproveOffsetDelegate<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// the purchase being delegated, must belong to the owner
+    #[account(
+        constraint = purchase.buyer == owner.key() @ ContractError::NotPurchaseOwner,
+        seeds = [b"purchase", owner.key().as_ref(), purchase.project.as_ref(), purchase.nft_mint.as_ref()],
+        bump = purchase.purchase_bump,
+    )]
+    pub purchase: Box<Account<'info, Purchase>>,
+
+    #[account(
+        constraint = project.key() == purchase.project @ ContractError::InvalidProject,
+        seeds = [b"project", project.owner.as_ref(), project.mint.as_ref()],
+        bump = project.project_bump,
+    )]
+    pub project: Box<Account<'info, Project>>,
+
+    /// tokens in the owner's wallet backing their purchases of this project, the escrowed allowance no longer counts
+    #[account(
+        mut,
+        seeds = [b"holdings", owner.key().as_ref(), project.key().as_ref()],
+        bump = holdings.bump,
+    )]
+    pub holdings: Box<Account<'info, Holdings>>,
+
+    /// one approval per purchase, revoke it before approving again
+    #[account(
+        init,
+        payer = owner,
+        space = OffsetDelegate::DISCRIMINATOR_SIZE + OffsetDelegate::INIT_SPACE,
+        seeds = [b"offset_delegate", purchase.key().as_ref()],
+        bump
+    )]
+    pub offset_delegate: Box<Account<'info, OffsetDelegate>>,
+
+    /// the certificate currently held for the purchase
+    #[account(constraint = certificate_mint.key() == purchase.certificate_mint @ ContractError::InvalidNFTMint)]
+    pub certificate_mint: Box<Account<'info, Mint>>,
+
+    /// owner's certificate account, emptied into the delegate's escrow
+    #[account(
+        mut,
+        token::mint = certificate_mint,
+        token::authority = owner,
+        constraint = nft_account.amount > 0 @ ContractError::InvalidNFTAccount,
+    )]
+    pub nft_account: Box<Account<'info, TokenAccount>>,
+
+    /// certificate escrow held by the delegate PDA
+    #[account(
+        init_if_needed,
+        payer = owner,
+        associated_token::mint = certificate_mint,
+        associated_token::authority = offset_delegate,
+    )]
+    pub delegate_nft_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(constraint = token_mint.key() == project.token_mint @ ContractError::InvalidProjectMint)]
+    pub token_mint: Box<Account<'info, Mint>>,
+
+    /// owner's project token account, the allowance is moved out of it
+    #[account(
+        mut,
+        token::mint = token_mint,
+        token::authority = owner,
+    )]
+    pub owner_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// project token escrow held by the delegate PDA, only this purchase's allowance sits here
+    #[account(
+        init_if_needed,
+        payer = owner,
+        associated_token::mint = token_mint,
+        associated_token::authority = offset_delegate,
+    )]
+    pub delegate_token_account: Box<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> ApproveOffsetDelegate<'info> {
+    pub fn handler(
+        &mut self,
+        delegate: Pubkey,
+        max_amount: u64,
+        expires_at: i64,
+        bumps: &ApproveOffsetDelegateBumps,
+    ) -> Result<()> {
+        require!(max_amount > 0, ContractError::InvalidAmount);
+        require!(
+            max_amount <= self.purchase.remaining_amount,
+            ContractError::InsufficientRemainingTokens
+        );
+
+        // 1) escrow the certificate and the allowance, the delegate PDA signs the burns in request_offset
+        for (from, to, amount) in [
+            (&self.nft_account, &self.delegate_nft_account, 1),
+            (&self.owner_token_account, &self.delegate_token_account, max_amount),
+        ] {
+            token::transfer(
+                CpiContext::new(
+                    self.token_program.to_account_info(),
+                    Transfer {
+                        from: from.to_account_info(),
+                        to: to.to_account_info(),
+                        authority: self.owner.to_account_info(),
+                    },
+                ),
+                amount,
+            )?;
+        }
+
+        self.holdings.remove(max_amount)?;
+
+        // 2) record the approval
+        self.offset_delegate.set_inner(OffsetDelegate {
+            owner: self.owner.key(),
+            purchase: self.purchase.key(),
+            delegate,
+            remaining_amount: max_amount,
+            expires_at,
+            bump: bumps.offset_delegate,
+        });
+
+        Ok(())
+    }
+}
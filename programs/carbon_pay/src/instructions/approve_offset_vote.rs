@@ -0,0 +1,75 @@
+use crate::errors::ContractError;
+use crate::state::{OffsetApproval, OffsetRequest, PlatformConfig, RequestStatus};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct ApproveOffsetVote<'info> {
+    /// approver casting the vote, pays for the approval account on the first vote
+    #[account(mut)]
+    pub approver: Signer<'info>,
+
+    #[account(
+        seeds = [b"platform_config"],
+        bump = platform_config.bump,
+        constraint = platform_config.is_approver(&approver.key()) @ ContractError::NotAnApprover,
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+
+    /// the request being voted on, must still be pending
+    #[account(
+        mut,
+        constraint = offset_request.status == RequestStatus::Pending @ ContractError::RequestAlreadyProcessed,
+    )]
+    pub offset_request: Box<Account<'info, OffsetRequest>>,
+
+    /// votes collected so far for this request
+    #[account(
+        init_if_needed,
+        payer = approver,
+        space = OffsetApproval::DISCRIMINATOR_SIZE + OffsetApproval::INIT_SPACE,
+        seeds = [b"offset_approval", offset_request.key().as_ref()],
+        bump
+    )]
+    pub offset_approval: Box<Account<'info, OffsetApproval>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> ApproveOffsetVote<'info> {
+    pub fn handler(&mut self, bumps: &ApproveOffsetVoteBumps) -> Result<()> {
+        let approver = self.approver.key();
+
+        // 1) first vote initializes the approval record
+        if self.offset_approval.offset_request == Pubkey::default() {
+            self.offset_approval.offset_request = self.offset_request.key();
+            self.offset_approval.bump = bumps.offset_approval;
+        }
+
+        // 2) record the vote, votes of removed approvers no longer count nor take up space
+        self.offset_approval.prune_stale_votes(&self.platform_config);
+        require!(
+            !self.offset_approval.has_voted(&approver),
+            ContractError::DuplicateVote
+        );
+        require!(
+            self.offset_approval.votes.len() < PlatformConfig::MAX_APPROVERS,
+            ContractError::TooManyApprovers
+        );
+        self.offset_approval.votes.push(approver);
+
+        // 3) approve once the threshold is met by current approvers
+        let votes = self.offset_approval.valid_votes(&self.platform_config);
+        if votes >= self.platform_config.approval_threshold as usize {
+            self.offset_request.status = RequestStatus::Approved;
+            self.offset_request.processed_date = Clock::get()?.unix_timestamp;
+            self.offset_request.processor = Some(approver);
+        }
+
+        msg!(
+            "Offset approval vote {}/{}",
+            votes,
+            self.platform_config.approval_threshold
+        );
+        Ok(())
+    }
+}
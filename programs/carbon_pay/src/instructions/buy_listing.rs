@@ -0,0 +1,248 @@
+use crate::errors::ContractError;
+use crate::events::ListingSold;
+use crate::state::{
+    AllowlistEntry, CarbonCredits, Holdings, Listing, PlatformConfig, PlatformFee, Project, Purchase,
+};
+use crate::utils::{mint_purchase_nft, price_for, PurchaseNft};
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    metadata::Metadata,
+    token::{self, CloseAccount, Mint, Token, TokenAccount},
+};
+
+#[derive(Accounts)]
+pub struct BuyListing<'info> {
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    /// CHECK: seller receives the payment and the listing rent
+    #[account(
+        mut,
+        constraint = seller.key() == listing.seller @ ContractError::Unauthorized
+    )]
+    pub seller: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        close = seller,
+        seeds = [b"listing", listing.purchase.as_ref()],
+        bump = listing.bump,
+    )]
+    pub listing: Box<Account<'info, Listing>>,
+
+    #[account(
+        constraint = project.key() == listing.project @ ContractError::InvalidProject,
+        seeds = [b"project", project.owner.as_ref(), project.mint.as_ref()],
+        bump = project.project_bump,
+    )]
+    pub project: Box<Account<'info, Project>>,
+
+    #[account(constraint = token_mint.key() == project.token_mint @ ContractError::InvalidProjectMint)]
+    pub token_mint: Box<Account<'info, Mint>>,
+
+    /// CarbonCredits PDA, receives the platform fee
+    #[account(
+        mut,
+        seeds = [b"carbon_credits"],
+        bump = carbon_credits.bump,
+        constraint = carbon_credits.key() == project.carbon_pay_authority @ ContractError::InvalidCarbonPayAuthority
+    )]
+    pub carbon_credits: Box<Account<'info, CarbonCredits>>,
+
+    /// platform-wide settings (allowlist requirement, fee rounding and minimum fee)
+    #[account(seeds = [b"platform_config"], bump = platform_config.bump)]
+    pub platform_config: Box<Account<'info, PlatformConfig>>,
+
+    /// buyer's allowlist entry, required when the project or platform enforces an allowlist
+    #[account(
+        seeds = [b"allowlist", allowlist_entry.scope.as_ref(), buyer.key().as_ref()],
+        bump = allowlist_entry.bump,
+    )]
+    pub allowlist_entry: Option<Box<Account<'info, AllowlistEntry>>>,
+
+    #[account(
+        mut,
+        associated_token::mint = token_mint,
+        associated_token::authority = listing,
+    )]
+    pub escrow: Box<Account<'info, TokenAccount>>,
+
+    /// buyer's ATA for the fungible tokens (create off-chain)
+    #[account(
+        mut,
+        token::mint = token_mint,
+        token::authority = buyer,
+    )]
+    pub buyer_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// purchase NFT mint (create off-chain)
+    #[account(
+        mut,
+        constraint = purchase_nft_mint.mint_authority == Some(buyer.key()).into() @ ContractError::Unauthorized
+    )]
+    pub purchase_nft_mint: Box<Account<'info, Mint>>,
+
+    /// buyer's ATA for the purchase NFT (create off-chain)
+    #[account(
+        mut,
+        token::mint = purchase_nft_mint,
+        token::authority = buyer,
+    )]
+    pub buyer_nft_account: Box<Account<'info, TokenAccount>>,
+
+    /// the buyer's new purchase record
+    #[account(
+        init,
+        payer = buyer,
+        space = Purchase::DISCRIMINATOR_SIZE + Purchase::INIT_SPACE,
+        seeds = [b"purchase", buyer.key().as_ref(), project.key().as_ref(), purchase_nft_mint.key().as_ref()],
+        bump
+    )]
+    pub purchase: Box<Account<'info, Purchase>>,
+
+    /// tokens in the buyer's wallet backing their purchases of this project
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        space = Holdings::DISCRIMINATOR_SIZE + Holdings::INIT_SPACE,
+        seeds = [b"holdings", buyer.key().as_ref(), project.key().as_ref()],
+        bump
+    )]
+    pub holdings: Box<Account<'info, Holdings>>,
+
+    /// CHECK: This account will be initialized by the Token Metadata program via CPI.
+    #[account(
+        mut,
+        seeds = [b"metadata", token_metadata_program.key().as_ref(), purchase_nft_mint.key().as_ref()],
+        bump,
+        seeds::program = token_metadata_program.key(),
+    )]
+    pub purchase_metadata: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub token_metadata_program: Program<'info, Metadata>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+impl<'info> BuyListing<'info> {
+    pub fn handler(&mut self, bumps: &BuyListingBumps) -> Result<()> {
+        let amount = self.listing.amount;
+        let now = Clock::get()?.unix_timestamp;
+
+        // 0) secondary buyers pass the same KYC gate as primary ones
+        if self.project.allowlist_required || self.platform_config.allowlist_required {
+            let entry = self
+                .allowlist_entry
+                .as_ref()
+                .ok_or(ContractError::BuyerNotAllowlisted)?;
+            entry.check_buyer(
+                &self.buyer.key(),
+                &self.project.key(),
+                &self.platform_config.key(),
+                now,
+            )?;
+        }
+
+        // 1) payments: seller gets the price minus the platform fee
+        let total = price_for(amount, self.listing.price_per_token, self.project.decimals)?;
+        let PlatformFee { fee, remainder } = self
+            .platform_config
+            .platform_fee(total, self.project.carbon_pay_fee)?;
+        let to_seller = total.checked_sub(fee).ok_or(ContractError::ArithmeticOverflow)?;
+
+        anchor_lang::system_program::transfer(
+            CpiContext::new(
+                self.system_program.to_account_info(),
+                anchor_lang::system_program::Transfer {
+                    from: self.buyer.to_account_info(),
+                    to: self.seller.to_account_info(),
+                },
+            ),
+            to_seller,
+        )?;
+        anchor_lang::system_program::transfer(
+            CpiContext::new(
+                self.system_program.to_account_info(),
+                anchor_lang::system_program::Transfer {
+                    from: self.buyer.to_account_info(),
+                    to: self.carbon_credits.to_account_info(),
+                },
+            ),
+            fee,
+        )?;
+        self.carbon_credits.add_fees(fee)?;
+
+        // 2) release the escrow to the buyer and close it
+        let listing_purchase = self.listing.purchase;
+        let seeds: &[&[u8]] = &[b"listing", listing_purchase.as_ref(), &[self.listing.bump]];
+        token::transfer(
+            CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                token::Transfer {
+                    from: self.escrow.to_account_info(),
+                    to: self.buyer_token_account.to_account_info(),
+                    authority: self.listing.to_account_info(),
+                },
+                &[seeds],
+            ),
+            amount,
+        )?;
+        token::close_account(CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            CloseAccount {
+                account: self.escrow.to_account_info(),
+                destination: self.seller.to_account_info(),
+                authority: self.listing.to_account_info(),
+            },
+            &[seeds],
+        ))?;
+
+        // 3) purchase NFT for the buyer
+        mint_purchase_nft(
+            PurchaseNft {
+                nft_mint: self.purchase_nft_mint.to_account_info(),
+                nft_account: self.buyer_nft_account.to_account_info(),
+                nft_metadata: self.purchase_metadata.to_account_info(),
+                owner: self.buyer.to_account_info(),
+                token_program: self.token_program.to_account_info(),
+                token_metadata_program: self.token_metadata_program.to_account_info(),
+                system_program: self.system_program.to_account_info(),
+                rent: self.rent.to_account_info(),
+            },
+            format!("Carbon Credits Purchase - {}", amount),
+            format!("https://carbonpay.com/purchases/{}", self.purchase_nft_mint.key()),
+        )?;
+
+        // 4) buyer's purchase record
+        self.purchase.set_inner(Purchase {
+            buyer: self.buyer.key(),
+            project: self.project.key(),
+            amount,
+            remaining_amount: amount,
+            purchase_date: now,
+            purchase_bump: bumps.purchase,
+            nft_mint: self.purchase_nft_mint.key(),
+            certificate_mint: self.purchase_nft_mint.key(),
+            uncertified_offset: 0,
+        });
+        self.holdings.open(self.buyer.key(), self.project.key(), bumps.holdings);
+        self.holdings.add(amount)?;
+
+        emit!(ListingSold {
+            buyer: self.buyer.key(),
+            seller: self.seller.key(),
+            project: self.project.key(),
+            purchase: self.purchase.key(),
+            amount,
+            decimals: self.project.decimals,
+            total_price: total,
+            fee,
+            fee_remainder: remainder,
+            price_per_token: self.listing.price_per_token,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+}
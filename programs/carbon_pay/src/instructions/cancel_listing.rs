@@ -0,0 +1,94 @@
+use crate::errors::ContractError;
+use crate::state::{Holdings, Listing, Purchase};
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, CloseAccount, Mint, Token, TokenAccount};
+
+#[derive(Accounts)]
+pub struct CancelListing<'info> {
+    #[account(mut)]
+    pub seller: Signer<'info>,
+
+    #[account(
+        mut,
+        close = seller,
+        constraint = listing.seller == seller.key() @ ContractError::Unauthorized,
+        seeds = [b"listing", purchase.key().as_ref()],
+        bump = listing.bump,
+    )]
+    pub listing: Box<Account<'info, Listing>>,
+
+    /// the purchase the tokens return to
+    #[account(
+        mut,
+        constraint = purchase.key() == listing.purchase @ ContractError::InvalidListing,
+    )]
+    pub purchase: Box<Account<'info, Purchase>>,
+
+    /// tokens in the seller's wallet backing their purchases of this project
+    #[account(
+        mut,
+        seeds = [b"holdings", seller.key().as_ref(), listing.project.as_ref()],
+        bump = holdings.bump,
+    )]
+    pub holdings: Box<Account<'info, Holdings>>,
+
+    pub token_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        associated_token::mint = token_mint,
+        associated_token::authority = listing,
+    )]
+    pub escrow: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = token_mint,
+        token::authority = seller,
+    )]
+    pub seller_token_account: Box<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> CancelListing<'info> {
+    pub fn handler(&mut self) -> Result<()> {
+        let purchase_key = self.purchase.key();
+        let seeds: &[&[u8]] = &[b"listing", purchase_key.as_ref(), &[self.listing.bump]];
+
+        // 1) return escrowed tokens to the seller
+        token::transfer(
+            CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                token::Transfer {
+                    from: self.escrow.to_account_info(),
+                    to: self.seller_token_account.to_account_info(),
+                    authority: self.listing.to_account_info(),
+                },
+                &[seeds],
+            ),
+            self.listing.amount,
+        )?;
+
+        // 2) close the escrow, rent goes back to the seller
+        token::close_account(CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            CloseAccount {
+                account: self.escrow.to_account_info(),
+                destination: self.seller.to_account_info(),
+                authority: self.listing.to_account_info(),
+            },
+            &[seeds],
+        ))?;
+
+        // 3) restore the seller's position
+        self.purchase.remaining_amount = self
+            .purchase
+            .remaining_amount
+            .checked_add(self.listing.amount)
+            .ok_or(ContractError::ArithmeticOverflow)?;
+        self.holdings.add(self.listing.amount)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,50 @@
+use crate::errors::ContractError;
+use crate::state::{OtcOffer, Project};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct CancelOtcOffer<'info> {
+    /// the project owner, or anyone once the offer has expired
+    pub canceller: Signer<'info>,
+
+    /// CHECK: project owner receives the offer rent
+    #[account(
+        mut,
+        constraint = project_owner.key() == project.owner @ ContractError::InvalidProjectOwner
+    )]
+    pub project_owner: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"project", project.owner.as_ref(), project.mint.as_ref()],
+        bump = project.project_bump,
+    )]
+    pub project: Box<Account<'info, Project>>,
+
+    #[account(
+        mut,
+        close = project_owner,
+        seeds = [b"otc_offer", project.key().as_ref(), otc_offer.buyer.as_ref(), otc_offer.offer_id.to_le_bytes().as_ref()],
+        bump = otc_offer.bump,
+    )]
+    pub otc_offer: Box<Account<'info, OtcOffer>>,
+}
+
+impl<'info> CancelOtcOffer<'info> {
+    pub fn handler(&mut self) -> Result<()> {
+        // an expired offer can no longer be accepted, so anyone may release its reservation
+        require!(
+            self.canceller.key() == self.project.owner
+                || Clock::get()?.unix_timestamp >= self.otc_offer.expires_at,
+            ContractError::OtcOfferNotExpired
+        );
+
+        // release the reserved tokens back to the sale
+        self.project.remaining_amount = self
+            .project
+            .remaining_amount
+            .checked_add(self.otc_offer.amount)
+            .ok_or(ContractError::ArithmeticOverflow)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,19 @@
+use crate::errors::ContractError;
+use crate::state::Subscription;
+use anchor_lang::prelude::*;
+
+/// Closing the subscription returns the unused escrow together with the rent to the buyer
+#[derive(Accounts)]
+pub struct CancelSubscription<'info> {
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    #[account(
+        mut,
+        close = buyer,
+        constraint = subscription.buyer == buyer.key() @ ContractError::Unauthorized,
+        seeds = [b"subscription", buyer.key().as_ref(), subscription.project.as_ref()],
+        bump = subscription.bump,
+    )]
+    pub subscription: Box<Account<'info, Subscription>>,
+}
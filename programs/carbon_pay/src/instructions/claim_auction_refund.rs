@@ -0,0 +1,54 @@
+use crate::errors::ContractError;
+use crate::state::{BuyerStats, Project};
+use crate::utils::{price_for, transfer_lamports_from_program_account};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct ClaimAuctionRefund<'info> {
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"project", project.owner.as_ref(), project.mint.as_ref()],
+        bump = project.project_bump,
+    )]
+    pub project: Box<Account<'info, Project>>,
+
+    #[account(
+        mut,
+        seeds = [b"buyer_stats", buyer.key().as_ref(), project.key().as_ref()],
+        bump = buyer_stats.bump,
+    )]
+    pub buyer_stats: Box<Account<'info, BuyerStats>>,
+}
+
+impl<'info> ClaimAuctionRefund<'info> {
+    pub fn handler(&mut self) -> Result<()> {
+        let auction = self.project.auction.ok_or(ContractError::NoActiveAuction)?;
+        require!(auction.settled, ContractError::AuctionNotSettled);
+
+        // refund what was paid above the clearing price
+        let owed = price_for(
+            self.buyer_stats.auction_amount,
+            auction.last_price,
+            self.project.decimals,
+        )?;
+        let refund = self
+            .buyer_stats
+            .auction_paid
+            .checked_sub(owed)
+            .ok_or(ContractError::ArithmeticOverflow)?;
+        require!(refund > 0, ContractError::NothingToRefund);
+
+        transfer_lamports_from_program_account(
+            &self.project.to_account_info(),
+            &self.buyer.to_account_info(),
+            refund,
+        )?;
+        self.buyer_stats.auction_amount = 0;
+        self.buyer_stats.auction_paid = 0;
+
+        Ok(())
+    }
+}
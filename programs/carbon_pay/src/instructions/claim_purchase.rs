@@ -0,0 +1,130 @@
+use crate::errors::ContractError;
+use crate::state::{Holdings, Project, Purchase, Subscription};
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, TokenAccount};
+
+/// Re-keys a Purchase under whoever holds its certificate, for NFTs that changed hands
+/// outside `transfer_purchase`. The holder must also hold the unoffset tokens, beyond those
+/// already backing their own purchases of the project.
+#[derive(Accounts)]
+pub struct ClaimPurchase<'info> {
+    #[account(mut)]
+    pub holder: Signer<'info>,
+
+    /// CHECK: the recorded owner, refunded the closed record's rent
+    #[account(mut, address = purchase.buyer @ ContractError::NotPurchaseOwner)]
+    pub previous_owner: UncheckedAccount<'info>,
+
+    #[account(
+        constraint = project.key() == purchase.project @ ContractError::InvalidProject,
+        seeds = [b"project", project.owner.as_ref(), project.mint.as_ref()],
+        bump = project.project_bump,
+    )]
+    pub project: Box<Account<'info, Project>>,
+
+    /// the stale record, closed in favour of `new_purchase`
+    #[account(
+        mut,
+        close = previous_owner,
+        seeds = [b"purchase", purchase.buyer.as_ref(), project.key().as_ref(), purchase.nft_mint.as_ref()],
+        bump = purchase.purchase_bump,
+    )]
+    pub purchase: Box<Account<'info, Purchase>>,
+
+    /// CHECK: a listed position is settled by its listing, not claimed
+    #[account(
+        seeds = [b"listing", purchase.key().as_ref()],
+        bump,
+        constraint = listing.data_is_empty() @ ContractError::PurchaseListed,
+    )]
+    pub listing: UncheckedAccount<'info>,
+
+    /// CHECK: a position a subscription fills stays with the subscriber
+    #[account(
+        seeds = [b"subscription", previous_owner.key().as_ref(), project.key().as_ref()],
+        bump,
+    )]
+    pub subscription: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        payer = holder,
+        space = Purchase::DISCRIMINATOR_SIZE + Purchase::INIT_SPACE,
+        seeds = [b"purchase", holder.key().as_ref(), project.key().as_ref(), purchase.nft_mint.as_ref()],
+        bump
+    )]
+    pub new_purchase: Box<Account<'info, Purchase>>,
+
+    /// tokens in the recorded owner's wallet backing their purchases of this project
+    #[account(
+        mut,
+        seeds = [b"holdings", previous_owner.key().as_ref(), project.key().as_ref()],
+        bump = previous_holdings.bump,
+    )]
+    pub previous_holdings: Box<Account<'info, Holdings>>,
+
+    /// tokens in the holder's wallet backing their purchases of this project
+    #[account(
+        init_if_needed,
+        payer = holder,
+        space = Holdings::DISCRIMINATOR_SIZE + Holdings::INIT_SPACE,
+        seeds = [b"holdings", holder.key().as_ref(), project.key().as_ref()],
+        bump
+    )]
+    pub holdings: Box<Account<'info, Holdings>>,
+
+    #[account(constraint = certificate_mint.key() == purchase.certificate_mint @ ContractError::InvalidNFTMint)]
+    pub certificate_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        token::mint = certificate_mint,
+        token::authority = holder,
+        constraint = holder_nft_account.amount > 0 @ ContractError::InvalidNFTAccount,
+    )]
+    pub holder_nft_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(constraint = token_mint.key() == project.token_mint @ ContractError::InvalidProjectMint)]
+    pub token_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        token::mint = token_mint,
+        token::authority = holder,
+    )]
+    pub holder_token_account: Box<Account<'info, TokenAccount>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> ClaimPurchase<'info> {
+    pub fn handler(&mut self, bumps: &ClaimPurchaseBumps) -> Result<()> {
+        require_keys_neq!(self.holder.key(), self.purchase.buyer, ContractError::InvalidRecipient);
+        require!(
+            !Subscription::fills(&self.subscription.to_account_info(), &self.purchase.key())?,
+            ContractError::PurchaseSubscribed
+        );
+        // tokens already backing one of the holder's purchases can't back this one too
+        require!(
+            self.holder_token_account.amount.saturating_sub(self.holdings.backed_amount)
+                >= self.purchase.remaining_amount,
+            ContractError::InsufficientFungibleTokens
+        );
+
+        self.previous_holdings.remove(self.purchase.remaining_amount)?;
+        self.holdings.open(self.holder.key(), self.project.key(), bumps.holdings);
+        self.holdings.add(self.purchase.remaining_amount)?;
+
+        self.new_purchase.set_inner(Purchase {
+            buyer: self.holder.key(),
+            project: self.project.key(),
+            amount: self.purchase.amount,
+            remaining_amount: self.purchase.remaining_amount,
+            purchase_date: self.purchase.purchase_date,
+            purchase_bump: bumps.new_purchase,
+            nft_mint: self.purchase.nft_mint,
+            certificate_mint: self.purchase.certificate_mint,
+            uncertified_offset: self.purchase.uncertified_offset,
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,63 @@
+use crate::errors::ContractError;
+use crate::state::{Basket, PlatformConfig};
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token};
+
+#[derive(Accounts)]
+#[instruction(basket_id: u64)]
+pub struct CreateBasket<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [b"platform_config"],
+        bump = platform_config.bump,
+        constraint = platform_config.authority == authority.key() @ ContractError::UnauthorizedAdmin,
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = Basket::DISCRIMINATOR_SIZE + Basket::INIT_SPACE,
+        seeds = [b"basket", basket_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub basket: Box<Account<'info, Basket>>,
+
+    /// basket token, minted 1:1 for deposited project tokens
+    #[account(
+        init,
+        payer = authority,
+        mint::decimals = 0,
+        mint::authority = basket,
+        seeds = [b"basket_mint", basket.key().as_ref()],
+        bump
+    )]
+    pub basket_mint: Box<Account<'info, Mint>>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+impl<'info> CreateBasket<'info> {
+    pub fn handler(
+        &mut self,
+        basket_id: u64,
+        allowed_categories: u8,
+        bumps: &CreateBasketBumps,
+    ) -> Result<()> {
+        self.basket.set_inner(Basket {
+            authority: self.authority.key(),
+            basket_id,
+            basket_mint: self.basket_mint.key(),
+            allowed_categories,
+            project_count: 0,
+            total_deposited: 0,
+            bump: bumps.basket,
+            mint_bump: bumps.basket_mint,
+        });
+        Ok(())
+    }
+}
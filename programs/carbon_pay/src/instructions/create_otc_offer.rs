@@ -0,0 +1,68 @@
+use crate::errors::ContractError;
+use crate::state::{OtcOffer, Project};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+#[instruction(buyer: Pubkey, offer_id: u64, amount: u64)]
+pub struct CreateOtcOffer<'info> {
+    #[account(mut)]
+    pub project_owner: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = project.owner == project_owner.key() @ ContractError::InvalidProjectOwner,
+        constraint = project.is_active @ ContractError::ProjectInactive,
+        constraint = project.remaining_amount >= amount @ ContractError::InsufficientTokens,
+        seeds = [b"project", project.owner.as_ref(), project.mint.as_ref()],
+        bump = project.project_bump,
+    )]
+    pub project: Box<Account<'info, Project>>,
+
+    #[account(
+        init,
+        payer = project_owner,
+        space = OtcOffer::DISCRIMINATOR_SIZE + OtcOffer::INIT_SPACE,
+        seeds = [b"otc_offer", project.key().as_ref(), buyer.as_ref(), offer_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub otc_offer: Box<Account<'info, OtcOffer>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> CreateOtcOffer<'info> {
+    pub fn handler(
+        &mut self,
+        buyer: Pubkey,
+        offer_id: u64,
+        amount: u64,
+        price_per_token: u64,
+        expires_at: i64,
+        bumps: &CreateOtcOfferBumps,
+    ) -> Result<()> {
+        require!(amount > 0, ContractError::InvalidAmount);
+        require!(
+            self.project.active_auction().is_none(),
+            ContractError::AuctionActive
+        );
+        require!(
+            expires_at > Clock::get()?.unix_timestamp,
+            ContractError::OtcOfferExpired
+        );
+
+        // reserve the tokens so the offer can always be settled
+        self.project.record_purchase(amount)?;
+
+        self.otc_offer.set_inner(OtcOffer {
+            project: self.project.key(),
+            buyer,
+            offer_id,
+            amount,
+            price_per_token,
+            expires_at,
+            bump: bumps.otc_offer,
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,163 @@
+use crate::errors::ContractError;
+use crate::state::{BuyerStats, Holdings, Project, Purchase, Subscription};
+use crate::utils::{mint_purchase_nft, PurchaseNft};
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    metadata::Metadata,
+    token::{Mint, Token, TokenAccount},
+};
+
+#[derive(Accounts)]
+pub struct CreateSubscription<'info> {
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    #[account(
+        constraint = project.is_active @ ContractError::ProjectInactive,
+        seeds = [b"project", project.owner.as_ref(), project.mint.as_ref()],
+        bump = project.project_bump,
+    )]
+    pub project: Box<Account<'info, Project>>,
+
+    #[account(
+        init,
+        payer = buyer,
+        space = Subscription::DISCRIMINATOR_SIZE + Subscription::INIT_SPACE,
+        seeds = [b"subscription", buyer.key().as_ref(), project.key().as_ref()],
+        bump
+    )]
+    pub subscription: Box<Account<'info, Subscription>>,
+
+    /// the position every executed purchase is added to
+    #[account(
+        init,
+        payer = buyer,
+        space = Purchase::DISCRIMINATOR_SIZE + Purchase::INIT_SPACE,
+        seeds = [b"purchase", buyer.key().as_ref(), project.key().as_ref(), purchase_nft_mint.key().as_ref()],
+        bump
+    )]
+    pub purchase: Box<Account<'info, Purchase>>,
+
+    /// buyer's running totals for this project, checked and updated by every execution
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        space = BuyerStats::DISCRIMINATOR_SIZE + BuyerStats::INIT_SPACE,
+        seeds = [b"buyer_stats", buyer.key().as_ref(), project.key().as_ref()],
+        bump
+    )]
+    pub buyer_stats: Box<Account<'info, BuyerStats>>,
+
+    /// tokens in the buyer's wallet backing their purchases of this project
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        space = Holdings::DISCRIMINATOR_SIZE + Holdings::INIT_SPACE,
+        seeds = [b"holdings", buyer.key().as_ref(), project.key().as_ref()],
+        bump
+    )]
+    pub holdings: Box<Account<'info, Holdings>>,
+
+    /// purchase NFT mint (create off-chain)
+    #[account(
+        mut,
+        constraint = purchase_nft_mint.mint_authority == Some(buyer.key()).into() @ ContractError::Unauthorized
+    )]
+    pub purchase_nft_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        token::mint = purchase_nft_mint,
+        token::authority = buyer,
+    )]
+    pub buyer_nft_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: This account will be initialized by the Token Metadata program via CPI.
+    #[account(
+        mut,
+        seeds = [b"metadata", token_metadata_program.key().as_ref(), purchase_nft_mint.key().as_ref()],
+        bump,
+        seeds::program = token_metadata_program.key(),
+    )]
+    pub purchase_metadata: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub token_metadata_program: Program<'info, Metadata>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+impl<'info> CreateSubscription<'info> {
+    pub fn handler(
+        &mut self,
+        amount_per_period: u64,
+        max_price_per_token: u64,
+        deposit: u64,
+        bumps: &CreateSubscriptionBumps,
+    ) -> Result<()> {
+        require!(amount_per_period > 0, ContractError::InvalidAmount);
+        let now = Clock::get()?.unix_timestamp;
+
+        // 1) certificate for the accumulated position
+        mint_purchase_nft(
+            PurchaseNft {
+                nft_mint: self.purchase_nft_mint.to_account_info(),
+                nft_account: self.buyer_nft_account.to_account_info(),
+                nft_metadata: self.purchase_metadata.to_account_info(),
+                owner: self.buyer.to_account_info(),
+                token_program: self.token_program.to_account_info(),
+                token_metadata_program: self.token_metadata_program.to_account_info(),
+                system_program: self.system_program.to_account_info(),
+                rent: self.rent.to_account_info(),
+            },
+            format!("Carbon Credits Subscription - {}/month", amount_per_period),
+            format!("https://carbonpay.com/purchases/{}", self.purchase_nft_mint.key()),
+        )?;
+
+        self.purchase.set_inner(Purchase {
+            buyer: self.buyer.key(),
+            project: self.project.key(),
+            amount: 0,
+            remaining_amount: 0,
+            purchase_date: now,
+            purchase_bump: bumps.purchase,
+            nft_mint: self.purchase_nft_mint.key(),
+            certificate_mint: self.purchase_nft_mint.key(),
+            uncertified_offset: 0,
+        });
+
+        if self.buyer_stats.buyer == Pubkey::default() {
+            self.buyer_stats.buyer = self.buyer.key();
+            self.buyer_stats.project = self.project.key();
+            self.buyer_stats.bump = bumps.buyer_stats;
+        }
+        self.holdings.open(self.buyer.key(), self.project.key(), bumps.holdings);
+
+        self.subscription.set_inner(Subscription {
+            buyer: self.buyer.key(),
+            project: self.project.key(),
+            purchase: self.purchase.key(),
+            amount_per_period,
+            max_price_per_token,
+            next_execution: now,
+            executions: 0,
+            bump: bumps.subscription,
+        });
+
+        // 2) prefund the escrow held by the subscription PDA
+        if deposit > 0 {
+            anchor_lang::system_program::transfer(
+                CpiContext::new(
+                    self.system_program.to_account_info(),
+                    anchor_lang::system_program::Transfer {
+                        from: self.buyer.to_account_info(),
+                        to: self.subscription.to_account_info(),
+                    },
+                ),
+                deposit,
+            )?;
+        }
+
+        Ok(())
+    }
+}
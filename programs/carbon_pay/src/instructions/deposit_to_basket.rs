@@ -0,0 +1,241 @@
+use crate::errors::ContractError;
+use crate::state::{Basket, BasketReserve, Holdings, Project, Purchase};
+use crate::utils::{mint_purchase_nft, PurchaseNft};
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    metadata::Metadata,
+    token::{self, Burn, Mint, MintTo, Token, TokenAccount},
+};
+
+#[derive(Accounts)]
+#[instruction(amount: u64)]
+pub struct DepositToBasket<'info> {
+    #[account(mut)]
+    pub depositor: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"basket", basket.basket_id.to_le_bytes().as_ref()],
+        bump = basket.bump,
+    )]
+    pub basket: Box<Account<'info, Basket>>,
+
+    #[account(
+        mut,
+        seeds = [b"basket_mint", basket.key().as_ref()],
+        bump = basket.mint_bump,
+    )]
+    pub basket_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        constraint = project.is_active @ ContractError::ProjectInactive,
+        constraint = basket.accepts(project.category) @ ContractError::ProjectNotEligible,
+        constraint = project.decimals == 0 @ ContractError::UnsupportedTokenDecimals,
+        seeds = [b"project", project.owner.as_ref(), project.mint.as_ref()],
+        bump = project.project_bump,
+    )]
+    pub project: Box<Account<'info, Project>>,
+
+    #[account(constraint = token_mint.key() == project.token_mint @ ContractError::InvalidProjectMint)]
+    pub token_mint: Box<Account<'info, Mint>>,
+
+    /// the depositor's position the tokens are taken from
+    #[account(
+        mut,
+        constraint = purchase.buyer == depositor.key() @ ContractError::NotPurchaseOwner,
+        constraint = purchase.project == project.key() @ ContractError::InvalidProject,
+        constraint = purchase.remaining_amount >= amount @ ContractError::InsufficientRemainingTokens,
+        seeds = [b"purchase", depositor.key().as_ref(), project.key().as_ref(), purchase.nft_mint.as_ref()],
+        bump = purchase.purchase_bump,
+    )]
+    pub purchase: Box<Account<'info, Purchase>>,
+
+    /// tokens in the depositor's wallet backing their purchases of this project
+    #[account(
+        mut,
+        seeds = [b"holdings", depositor.key().as_ref(), project.key().as_ref()],
+        bump = holdings.bump,
+    )]
+    pub holdings: Box<Account<'info, Holdings>>,
+
+    /// the position's current certificate - will be burned
+    #[account(mut, constraint = certificate_mint.key() == purchase.certificate_mint @ ContractError::InvalidNFTMint)]
+    pub certificate_mint: Box<Account<'info, Mint>>,
+    #[account(
+        mut,
+        token::mint = certificate_mint,
+        token::authority = depositor,
+        constraint = nft_account.amount > 0 @ ContractError::InvalidNFTAccount,
+    )]
+    pub nft_account: Box<Account<'info, TokenAccount>>,
+
+    /// certificate mint for what stays in the position (create off-chain)
+    #[account(
+        mut,
+        constraint = replacement_nft_mint.mint_authority == Some(depositor.key()).into() @ ContractError::InvalidNFTMint
+    )]
+    pub replacement_nft_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        token::mint = replacement_nft_mint,
+        token::authority = depositor,
+    )]
+    pub replacement_nft_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: initialized by the Token Metadata program via CPI
+    #[account(
+        mut,
+        seeds = [b"metadata", token_metadata_program.key().as_ref(), replacement_nft_mint.key().as_ref()],
+        bump,
+        seeds::program = token_metadata_program.key(),
+    )]
+    pub replacement_nft_metadata: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        token::mint = token_mint,
+        token::authority = depositor,
+    )]
+    pub depositor_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// depositor's ATA for the basket token (create off-chain)
+    #[account(
+        mut,
+        token::mint = basket_mint,
+        token::authority = depositor,
+    )]
+    pub depositor_basket_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = depositor,
+        space = BasketReserve::DISCRIMINATOR_SIZE + BasketReserve::INIT_SPACE,
+        seeds = [b"basket_reserve", basket.key().as_ref(), project.key().as_ref()],
+        bump
+    )]
+    pub basket_reserve: Box<Account<'info, BasketReserve>>,
+
+    /// basket PDA's ATA for the project tokens
+    #[account(
+        init_if_needed,
+        payer = depositor,
+        associated_token::mint = token_mint,
+        associated_token::authority = basket,
+    )]
+    pub basket_vault: Box<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+    pub token_metadata_program: Program<'info, Metadata>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+impl<'info> DepositToBasket<'info> {
+    pub fn handler(&mut self, amount: u64, bumps: &DepositToBasketBumps) -> Result<()> {
+        require!(amount > 0, ContractError::InvalidAmount);
+
+        // 1) first deposit of this project creates its reserve
+        if self.basket_reserve.basket == Pubkey::default() {
+            require!(
+                self.basket.project_count < Basket::MAX_PROJECTS,
+                ContractError::BasketFull
+            );
+            self.basket_reserve.set_inner(BasketReserve {
+                basket: self.basket.key(),
+                project: self.project.key(),
+                token_mint: self.token_mint.key(),
+                vault: self.basket_vault.key(),
+                amount: 0,
+                bump: bumps.basket_reserve,
+            });
+            self.basket.project_count = self
+                .basket
+                .project_count
+                .checked_add(1)
+                .ok_or(ContractError::ArithmeticOverflow)?;
+        }
+
+        // 2) project tokens into the basket vault
+        token::transfer(
+            CpiContext::new(
+                self.token_program.to_account_info(),
+                token::Transfer {
+                    from: self.depositor_token_account.to_account_info(),
+                    to: self.basket_vault.to_account_info(),
+                    authority: self.depositor.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+
+        // 3) basket tokens 1:1 to the depositor
+        let basket_id = self.basket.basket_id.to_le_bytes();
+        let seeds: &[&[u8]] = &[b"basket", basket_id.as_ref(), &[self.basket.bump]];
+        token::mint_to(
+            CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                MintTo {
+                    mint: self.basket_mint.to_account_info(),
+                    to: self.depositor_basket_account.to_account_info(),
+                    authority: self.basket.to_account_info(),
+                },
+                &[seeds],
+            ),
+            amount,
+        )?;
+
+        // 4) deposited tokens leave the depositor's position
+        self.purchase.remaining_amount = self
+            .purchase
+            .remaining_amount
+            .checked_sub(amount)
+            .ok_or(ContractError::ArithmeticOverflow)?;
+        self.holdings.remove(amount)?;
+        self.basket_reserve.amount = self
+            .basket_reserve
+            .amount
+            .checked_add(amount)
+            .ok_or(ContractError::ArithmeticOverflow)?;
+        self.basket.total_deposited = self
+            .basket
+            .total_deposited
+            .checked_add(amount)
+            .ok_or(ContractError::ArithmeticOverflow)?;
+
+        // 5) replace the certificate, it no longer states the position's amount
+        token::burn(
+            CpiContext::new(
+                self.token_program.to_account_info(),
+                Burn {
+                    mint: self.certificate_mint.to_account_info(),
+                    from: self.nft_account.to_account_info(),
+                    authority: self.depositor.to_account_info(),
+                },
+            ),
+            1,
+        )?;
+        if self.purchase.remaining_amount > 0 {
+            mint_purchase_nft(
+                PurchaseNft {
+                    nft_mint: self.replacement_nft_mint.to_account_info(),
+                    nft_account: self.replacement_nft_account.to_account_info(),
+                    nft_metadata: self.replacement_nft_metadata.to_account_info(),
+                    owner: self.depositor.to_account_info(),
+                    token_program: self.token_program.to_account_info(),
+                    token_metadata_program: self.token_metadata_program.to_account_info(),
+                    system_program: self.system_program.to_account_info(),
+                    rent: self.rent.to_account_info(),
+                },
+                format!("Carbon Credits - Remaining: {}", self.purchase.remaining_amount),
+                format!("https://carbonpay.com/purchases/{}/remaining", self.replacement_nft_mint.key()),
+            )?;
+            self.purchase.certificate_mint = self.replacement_nft_mint.key();
+            self.purchase.uncertified_offset = 0;
+        }
+
+        Ok(())
+    }
+}
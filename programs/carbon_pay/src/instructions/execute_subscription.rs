@@ -0,0 +1,222 @@
+use crate::errors::ContractError;
+use crate::events::CarbonCreditsPurchased;
+use crate::state::{
+    AllowlistEntry, BuyerStats, CarbonCredits, Holdings, PlatformConfig, PlatformFee, Project,
+    Purchase, Subscription,
+};
+use crate::utils::{
+    blended_price, price_for, transfer_from_vault,
+    transfer_lamports_from_program_account,
+};
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+
+/// Permissionless crank: executes the subscription's purchase for the current period.
+/// The subscriber passes the same gates as a direct purchase.
+#[derive(Accounts)]
+pub struct ExecuteSubscription<'info> {
+    #[account(
+        mut,
+        seeds = [b"subscription", subscription.buyer.as_ref(), project.key().as_ref()],
+        bump = subscription.bump,
+    )]
+    pub subscription: Box<Account<'info, Subscription>>,
+
+    #[account(
+        mut,
+        constraint = purchase.key() == subscription.purchase @ ContractError::InvalidSubscriptionPurchase,
+    )]
+    pub purchase: Box<Account<'info, Purchase>>,
+
+    #[account(
+        mut,
+        constraint = project.is_active @ ContractError::ProjectInactive,
+        seeds = [b"project", project.owner.as_ref(), project.mint.as_ref()],
+        bump = project.project_bump,
+    )]
+    pub project: Box<Account<'info, Project>>,
+
+    /// CHECK: project owner receives the payment
+    #[account(
+        mut,
+        constraint = project_owner.key() == project.owner @ ContractError::InvalidProjectOwner
+    )]
+    pub project_owner: UncheckedAccount<'info>,
+
+    #[account(
+        constraint = project_mint.key() == project.token_mint @ ContractError::InvalidProjectMint
+    )]
+    pub project_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        seeds = [b"carbon_credits"],
+        bump = carbon_credits.bump,
+        constraint = carbon_credits.key() == project.carbon_pay_authority @ ContractError::InvalidCarbonPayAuthority
+    )]
+    pub carbon_credits: Box<Account<'info, CarbonCredits>>,
+
+    /// platform-wide settings (allowlist requirement, fee rounding and minimum fee)
+    #[account(seeds = [b"platform_config"], bump = platform_config.bump)]
+    pub platform_config: Box<Account<'info, PlatformConfig>>,
+
+    /// subscriber's allowlist entry, required when the project or platform enforces an allowlist
+    #[account(
+        seeds = [b"allowlist", allowlist_entry.scope.as_ref(), subscription.buyer.as_ref()],
+        bump = allowlist_entry.bump,
+    )]
+    pub allowlist_entry: Option<Box<Account<'info, AllowlistEntry>>>,
+
+    /// subscriber's running totals for this project, created with the subscription
+    #[account(
+        mut,
+        seeds = [b"buyer_stats", subscription.buyer.as_ref(), project.key().as_ref()],
+        bump = buyer_stats.bump,
+    )]
+    pub buyer_stats: Box<Account<'info, BuyerStats>>,
+
+    /// tokens in the subscriber's wallet backing their purchases of this project
+    #[account(
+        mut,
+        seeds = [b"holdings", subscription.buyer.as_ref(), project.key().as_ref()],
+        bump = holdings.bump,
+    )]
+    pub holdings: Box<Account<'info, Holdings>>,
+
+    /// project's vault ATA
+    #[account(
+        mut,
+        address = project.vault @ ContractError::InvalidProjectVault,
+        token::mint = project_mint,
+        token::authority = carbon_credits,
+    )]
+    pub project_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// subscriber's token account receiving the credits
+    #[account(
+        mut,
+        token::mint = project_mint,
+        token::authority = subscription.buyer,
+    )]
+    pub buyer_token_account: Box<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> ExecuteSubscription<'info> {
+    pub fn handler(&mut self) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let amount = self.subscription.amount_per_period;
+        require!(
+            now >= self.subscription.next_execution,
+            ContractError::SubscriptionNotDue
+        );
+        require!(
+            self.project.active_auction().is_none(),
+            ContractError::AuctionActive
+        );
+        require!(
+            self.project.remaining_amount >= amount,
+            ContractError::InsufficientTokens
+        );
+
+        // 0) buyer gates: sale window, allowlist and purchase limits, as for a direct purchase
+        let early_access = self.project.check_sale_window(now)?;
+        if early_access || self.project.allowlist_required || self.platform_config.allowlist_required {
+            let entry = self
+                .allowlist_entry
+                .as_ref()
+                .ok_or(ContractError::BuyerNotAllowlisted)?;
+            entry.check_buyer(
+                &self.subscription.buyer,
+                &self.project.key(),
+                &self.platform_config.key(),
+                now,
+            )?;
+        }
+        self.project
+            .check_purchase_limits(amount, self.buyer_stats.total_purchased)?;
+
+        // 1) price must stay within the subscriber's limit
+        let total = self.project.total_price(amount)?;
+        let max_total = price_for(
+            amount,
+            self.subscription.max_price_per_token,
+            self.project.decimals,
+        )?;
+        require!(total <= max_total, ContractError::SubscriptionPriceTooHigh);
+
+        // 2) pay from the escrow, keeping the subscription rent-exempt
+        let subscription_info = self.subscription.to_account_info();
+        let rent_exempt = Rent::get()?.minimum_balance(subscription_info.data_len());
+        let available = subscription_info.lamports().saturating_sub(rent_exempt);
+        require!(
+            available >= total,
+            ContractError::InsufficientSubscriptionFunds
+        );
+        let PlatformFee { fee, remainder } = self
+            .platform_config
+            .platform_fee(total, self.project.carbon_pay_fee)?;
+        let to_owner = total.checked_sub(fee).ok_or(ContractError::ArithmeticOverflow)?;
+        transfer_lamports_from_program_account(&subscription_info, &self.project_owner.to_account_info(), to_owner)?;
+        transfer_lamports_from_program_account(&subscription_info, &self.carbon_credits.to_account_info(), fee)?;
+
+        // 3) tokens from the vault
+        transfer_from_vault(
+            self.token_program.to_account_info(),
+            self.project_token_account.to_account_info(),
+            self.buyer_token_account.to_account_info(),
+            self.carbon_credits.to_account_info(),
+            self.carbon_credits.bump,
+            amount,
+        )?;
+
+        // 4) grow the subscription's position
+        self.project.record_purchase(amount)?;
+        self.purchase.amount = self
+            .purchase
+            .amount
+            .checked_add(amount)
+            .ok_or(ContractError::ArithmeticOverflow)?;
+        self.purchase.remaining_amount = self
+            .purchase
+            .remaining_amount
+            .checked_add(amount)
+            .ok_or(ContractError::ArithmeticOverflow)?;
+        self.buyer_stats.record_purchase(amount)?;
+        self.holdings.add(amount)?;
+        // a crank several periods late executes once, the next one is due a full period
+        // later rather than immediately, so missed periods are not caught up in a burst
+        let scheduled = self
+            .subscription
+            .next_execution
+            .checked_add(Subscription::PERIOD)
+            .ok_or(ContractError::ArithmeticOverflow)?;
+        let from_now = now
+            .checked_add(Subscription::PERIOD)
+            .ok_or(ContractError::ArithmeticOverflow)?;
+        self.subscription.next_execution = scheduled.max(from_now);
+        self.subscription.executions = self
+            .subscription
+            .executions
+            .checked_add(1)
+            .ok_or(ContractError::ArithmeticOverflow)?;
+        self.carbon_credits.add_fees(fee)?;
+
+        emit!(CarbonCreditsPurchased {
+            buyer: self.subscription.buyer,
+            project: self.project.key(),
+            purchase: self.purchase.key(),
+            offset_request: None,
+            amount,
+            decimals: self.project.decimals,
+            total_price: total,
+            fee,
+            fee_remainder: remainder,
+            blended_price_per_token: blended_price(total, amount, self.project.decimals)?,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+}
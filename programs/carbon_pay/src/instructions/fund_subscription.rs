@@ -0,0 +1,35 @@
+use crate::errors::ContractError;
+use crate::state::Subscription;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct FundSubscription<'info> {
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = subscription.buyer == buyer.key() @ ContractError::Unauthorized,
+        seeds = [b"subscription", buyer.key().as_ref(), subscription.project.as_ref()],
+        bump = subscription.bump,
+    )]
+    pub subscription: Box<Account<'info, Subscription>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> FundSubscription<'info> {
+    pub fn handler(&mut self, lamports: u64) -> Result<()> {
+        require!(lamports > 0, ContractError::InvalidAmount);
+        anchor_lang::system_program::transfer(
+            CpiContext::new(
+                self.system_program.to_account_info(),
+                anchor_lang::system_program::Transfer {
+                    from: self.buyer.to_account_info(),
+                    to: self.subscription.to_account_info(),
+                },
+            ),
+            lamports,
+        )
+    }
+}
@@ -0,0 +1,47 @@
+use crate::errors::ContractError;
+use crate::state::{CarbonCredits, FeeRounding, PlatformConfig};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct InitializePlatformConfig<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// CarbonCredits PDA, its authority is the platform admin
+    #[account(
+        seeds = [b"carbon_credits"],
+        bump = carbon_credits.bump,
+        constraint = carbon_credits.authority == authority.key() @ ContractError::UnauthorizedAdmin,
+    )]
+    pub carbon_credits: Account<'info, CarbonCredits>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = PlatformConfig::DISCRIMINATOR_SIZE + PlatformConfig::INIT_SPACE,
+        seeds = [b"platform_config"],
+        bump
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> InitializePlatformConfig<'info> {
+    pub fn handler(
+        &mut self,
+        approvers: Vec<Pubkey>,
+        approval_threshold: u8,
+        bumps: &InitializePlatformConfigBumps,
+    ) -> Result<()> {
+        let config = &mut self.platform_config;
+        config.authority = self.authority.key();
+        config.bump = bumps.platform_config;
+        config.allowlist_required = false;
+        config.fee_rounding = FeeRounding::Floor;
+        config.min_fee = 0;
+        config.set_approvers(approvers, approval_threshold)?;
+
+        Ok(())
+    }
+}
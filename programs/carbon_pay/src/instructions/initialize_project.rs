@@ -1,4 +1,5 @@
-use crate::state::{CarbonCredits, Project};
+use crate::errors::ContractError;
+use crate::state::{CarbonCredits, Project, ProjectCategory};
 use anchor_lang::prelude::*;
 use anchor_spl::{
     associated_token::AssociatedToken,
@@ -10,7 +11,7 @@ use anchor_spl::{
     token::{mint_to, set_authority, Mint, MintTo, SetAuthority, Token, TokenAccount},
 };
 
-/// ATAs for `project_owner_nft_account` and `vault` must exist before the call
+/// The ATA for `project_owner_nft_account` must exist before the call
 #[derive(Accounts)]
 #[instruction(
     amount: u64,
@@ -44,12 +45,14 @@ pub struct InitializeProject<'info> {
     )]
     pub nft_mint: Box<Account<'info, Mint>>,
 
-    /// The token mint - will be used for fungible tokens
+    /// The token mint - will be used for fungible tokens, one whole token is one tonne.
+    /// It must be fresh so the vault ends up holding the entire supply
     #[account(
         mut,
-        mint::decimals = 0,
         mint::authority = project_owner,
         mint::freeze_authority = project_owner,
+        constraint = token_mint.decimals <= Project::MAX_TOKEN_DECIMALS @ ContractError::InvalidTokenDecimals,
+        constraint = token_mint.supply == 0 @ ContractError::TokenMintHasSupply,
     )]
     pub token_mint: Box<Account<'info, Mint>>,
 
@@ -61,10 +64,13 @@ pub struct InitializeProject<'info> {
     )]
     pub project_owner_nft_account: Box<Account<'info, TokenAccount>>,
 
-    /// ATA of the `carbon_credits` PDA for fungible tokens (create off-chain)
+    /// ATA of the `carbon_credits` PDA for fungible tokens, created if missing.
+    /// Any other token account would let the owner keep the supply while it shows as for sale
     #[account(
-        mut,
-        token::mint = token_mint,
+        init_if_needed,
+        payer = project_owner,
+        associated_token::mint = token_mint,
+        associated_token::authority = carbon_credits,
     )]
     pub vault: Box<Account<'info, TokenAccount>>,
 
@@ -78,12 +84,22 @@ pub struct InitializeProject<'info> {
 
     /// Metadata account managed by the Token Metadata Program
     /// CHECK: This account is created via CPI to the token metadata program
-    #[account(mut)] 
+    #[account(
+        mut,
+        seeds = [b"metadata", token_metadata_program.key().as_ref(), nft_mint.key().as_ref()],
+        bump,
+        seeds::program = token_metadata_program.key(),
+    )]
     pub metadata: UncheckedAccount<'info>,
     
     /// Master Edition account managed by the Token Metadata Program
     /// CHECK: This account is created via CPI to the token metadata program
-    #[account(mut)] 
+    #[account(
+        mut,
+        seeds = [b"metadata", token_metadata_program.key().as_ref(), nft_mint.key().as_ref(), b"edition"],
+        bump,
+        seeds::program = token_metadata_program.key(),
+    )]
     pub master_edition: UncheckedAccount<'info>,
 
     pub token_program: Program<'info, Token>,
@@ -94,6 +110,7 @@ pub struct InitializeProject<'info> {
 }
 
 impl<'info> InitializeProject<'info> {
+    #[allow(clippy::too_many_arguments)]
     pub fn handler(
         &mut self,
         amount: u64,
@@ -118,8 +135,22 @@ impl<'info> InitializeProject<'info> {
             carbon_pay_authority: self.carbon_credits.key(),
             project_bump: bumps.project,
             is_active: true,
+            allowlist_required: false,
+            min_purchase_amount: 0,
+            max_purchase_amount: 0,
+            max_per_buyer: 0,
+            sale_start: 0,
+            sale_end: 0,
+            early_access_start: 0,
+            price_tiers: Vec::new(),
+            auction: None,
+            category: ProjectCategory::Unspecified,
+            vault: self.vault.key(),
+            decimals: self.token_mint.decimals,
+            layout_version: Project::LAYOUT_VERSION,
         });
-        self.carbon_credits.add_project_credits(amount)?;
+        let credits = self.project.platform_units(amount)?;
+        self.carbon_credits.add_project_credits(credits)?;
 
         // 2. Mint the NFT (1 token) for project owner
         let cpi_mint_nft = CpiContext::new(
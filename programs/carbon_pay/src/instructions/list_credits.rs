@@ -0,0 +1,139 @@
+use crate::errors::ContractError;
+use crate::state::{Holdings, Listing, Project, Purchase, Subscription};
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{self, Mint, Token, TokenAccount},
+};
+
+#[derive(Accounts)]
+#[instruction(amount: u64)]
+pub struct ListCredits<'info> {
+    #[account(mut)]
+    pub seller: Signer<'info>,
+
+    /// the purchase being resold, must belong to the seller
+    #[account(
+        mut,
+        constraint = purchase.buyer == seller.key() @ ContractError::NotPurchaseOwner,
+        constraint = purchase.remaining_amount >= amount @ ContractError::InsufficientRemainingTokens,
+        seeds = [b"purchase", seller.key().as_ref(), purchase.project.as_ref(), purchase.nft_mint.as_ref()],
+        bump = purchase.purchase_bump,
+    )]
+    pub purchase: Box<Account<'info, Purchase>>,
+
+    #[account(
+        constraint = project.key() == purchase.project @ ContractError::InvalidProject,
+        seeds = [b"project", project.owner.as_ref(), project.mint.as_ref()],
+        bump = project.project_bump,
+    )]
+    pub project: Box<Account<'info, Project>>,
+
+    #[account(constraint = token_mint.key() == project.token_mint @ ContractError::InvalidProjectMint)]
+    pub token_mint: Box<Account<'info, Mint>>,
+
+    /// seller's token account the listed tokens are taken from
+    #[account(
+        mut,
+        token::mint = token_mint,
+        token::authority = seller,
+        constraint = seller_token_account.amount >= amount @ ContractError::InsufficientFungibleTokens,
+    )]
+    pub seller_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// tokens in the seller's wallet backing their purchases of this project
+    #[account(
+        mut,
+        seeds = [b"holdings", seller.key().as_ref(), project.key().as_ref()],
+        bump = holdings.bump,
+    )]
+    pub holdings: Box<Account<'info, Holdings>>,
+
+    /// CHECK: a position a subscription fills stays whole with the subscriber
+    #[account(
+        seeds = [b"subscription", seller.key().as_ref(), project.key().as_ref()],
+        bump,
+    )]
+    pub subscription: UncheckedAccount<'info>,
+
+    /// CHECK: a delegate could still offset the tokens being sold
+    #[account(
+        seeds = [b"offset_delegate", purchase.key().as_ref()],
+        bump,
+        constraint = offset_delegate.data_is_empty() @ ContractError::PurchaseDelegated,
+    )]
+    pub offset_delegate: UncheckedAccount<'info>,
+
+    /// the seller's account holding the purchase certificate
+    #[account(
+        constraint = seller_nft_account.mint == purchase.certificate_mint @ ContractError::InvalidNFTAccount,
+        constraint = seller_nft_account.owner == seller.key() @ ContractError::InvalidNFTAccount,
+        constraint = seller_nft_account.amount > 0 @ ContractError::InvalidNFTAccount,
+    )]
+    pub seller_nft_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        init,
+        payer = seller,
+        space = Listing::DISCRIMINATOR_SIZE + Listing::INIT_SPACE,
+        seeds = [b"listing", purchase.key().as_ref()],
+        bump
+    )]
+    pub listing: Box<Account<'info, Listing>>,
+
+    /// escrow ATA owned by the listing PDA
+    #[account(
+        init,
+        payer = seller,
+        associated_token::mint = token_mint,
+        associated_token::authority = listing,
+    )]
+    pub escrow: Box<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> ListCredits<'info> {
+    pub fn handler(&mut self, amount: u64, price_per_token: u64, bumps: &ListCreditsBumps) -> Result<()> {
+        require!(amount > 0, ContractError::InvalidAmount);
+        require!(
+            !Subscription::fills(&self.subscription.to_account_info(), &self.purchase.key())?,
+            ContractError::PurchaseSubscribed
+        );
+
+        // 1) move the listed tokens into escrow
+        token::transfer(
+            CpiContext::new(
+                self.token_program.to_account_info(),
+                token::Transfer {
+                    from: self.seller_token_account.to_account_info(),
+                    to: self.escrow.to_account_info(),
+                    authority: self.seller.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+
+        // 2) escrowed tokens no longer count towards the seller's position
+        self.purchase.remaining_amount = self
+            .purchase
+            .remaining_amount
+            .checked_sub(amount)
+            .ok_or(ContractError::ArithmeticOverflow)?;
+        self.holdings.remove(amount)?;
+
+        self.listing.set_inner(Listing {
+            seller: self.seller.key(),
+            purchase: self.purchase.key(),
+            project: self.project.key(),
+            amount,
+            price_per_token,
+            created_at: Clock::get()?.unix_timestamp,
+            bump: bumps.listing,
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,172 @@
+use crate::errors::ContractError;
+use crate::state::{Purchase, Subscription};
+use crate::utils::{mint_purchase_nft, PurchaseNft};
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    metadata::Metadata,
+    token::{self, Burn, Mint, Token, TokenAccount},
+};
+
+/// Folds several of the buyer's purchases of one project into `purchase`.
+/// `remaining_accounts` must hold, for every source purchase:
+/// `[purchase (mut), nft_mint (mut), nft_account (mut), listing, offset_delegate]`
+/// where `listing` and `offset_delegate` are the source's PDAs and must not exist.
+/// The source certificates are burned and the source records closed to the buyer; the
+/// target's certificate is re-minted for the merged balance, as a split does for its source.
+#[derive(Accounts)]
+pub struct MergePurchases<'info> {
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    /// the position the others are merged into
+    #[account(
+        mut,
+        constraint = purchase.buyer == buyer.key() @ ContractError::NotPurchaseOwner,
+        seeds = [b"purchase", buyer.key().as_ref(), purchase.project.as_ref(), purchase.nft_mint.as_ref()],
+        bump = purchase.purchase_bump,
+    )]
+    pub purchase: Box<Account<'info, Purchase>>,
+
+    /// CHECK: the buyer's subscription to the project, if any; the position it fills can
+    /// only be a merge target
+    #[account(
+        seeds = [b"subscription", buyer.key().as_ref(), purchase.project.as_ref()],
+        bump,
+    )]
+    pub subscription: UncheckedAccount<'info>,
+
+    /// the target's current certificate - will be burned
+    #[account(mut, constraint = certificate_mint.key() == purchase.certificate_mint @ ContractError::InvalidNFTMint)]
+    pub certificate_mint: Box<Account<'info, Mint>>,
+    #[account(
+        mut,
+        token::mint = certificate_mint,
+        token::authority = buyer,
+        constraint = nft_account.amount > 0 @ ContractError::InvalidNFTAccount,
+    )]
+    pub nft_account: Box<Account<'info, TokenAccount>>,
+
+    /// certificate mint for the merged position (create off-chain)
+    #[account(
+        mut,
+        constraint = replacement_nft_mint.mint_authority == Some(buyer.key()).into() @ ContractError::InvalidNFTMint
+    )]
+    pub replacement_nft_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        token::mint = replacement_nft_mint,
+        token::authority = buyer,
+    )]
+    pub replacement_nft_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: initialized by the Token Metadata program via CPI
+    #[account(
+        mut,
+        seeds = [b"metadata", token_metadata_program.key().as_ref(), replacement_nft_mint.key().as_ref()],
+        bump,
+        seeds::program = token_metadata_program.key(),
+    )]
+    pub replacement_nft_metadata: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub token_metadata_program: Program<'info, Metadata>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+impl<'info> MergePurchases<'info> {
+    pub fn handler(&mut self, remaining_accounts: &'info [AccountInfo<'info>]) -> Result<()> {
+        let groups = remaining_accounts.chunks_exact(5);
+        require!(
+            !remaining_accounts.is_empty() && groups.remainder().is_empty(),
+            ContractError::InvalidMergePurchases
+        );
+
+        let mut merged: Vec<Pubkey> = Vec::new();
+        for group in groups {
+            let source = Account::<Purchase>::try_from(&group[0])?;
+            require!(
+                source.key() != self.purchase.key() && !merged.contains(&source.key()),
+                ContractError::InvalidMergePurchases
+            );
+            require_keys_eq!(source.buyer, self.buyer.key(), ContractError::NotPurchaseOwner);
+            require_keys_eq!(source.project, self.purchase.project, ContractError::InvalidProject);
+            require!(
+                !Subscription::fills(&self.subscription.to_account_info(), &source.key())?,
+                ContractError::PurchaseSubscribed
+            );
+
+            // a listed or delegated source still has tokens promised elsewhere
+            let (listing, _) = Pubkey::find_program_address(&[b"listing", source.key().as_ref()], &crate::ID);
+            require_keys_eq!(group[3].key(), listing, ContractError::InvalidMergePurchases);
+            require!(group[3].data_is_empty(), ContractError::PurchaseListed);
+            let (delegate, _) = Pubkey::find_program_address(&[b"offset_delegate", source.key().as_ref()], &crate::ID);
+            require_keys_eq!(group[4].key(), delegate, ContractError::InvalidMergePurchases);
+            require!(group[4].data_is_empty(), ContractError::PurchaseDelegated);
+
+            // 1) burn the source certificate
+            let nft_mint = Account::<Mint>::try_from(&group[1])?;
+            let nft_account = Account::<TokenAccount>::try_from(&group[2])?;
+            require_keys_eq!(nft_mint.key(), source.certificate_mint, ContractError::InvalidNFTMint);
+            require_keys_eq!(nft_account.mint, source.certificate_mint, ContractError::InvalidNFTAccount);
+            token::burn(
+                CpiContext::new(
+                    self.token_program.to_account_info(),
+                    Burn {
+                        mint: group[1].clone(),
+                        from: group[2].clone(),
+                        authority: self.buyer.to_account_info(),
+                    },
+                ),
+                1,
+            )?;
+
+            // 2) fold the position into the target
+            self.purchase.amount = self
+                .purchase
+                .amount
+                .checked_add(source.amount)
+                .ok_or(ContractError::ArithmeticOverflow)?;
+            self.purchase.remaining_amount = self
+                .purchase
+                .remaining_amount
+                .checked_add(source.remaining_amount)
+                .ok_or(ContractError::ArithmeticOverflow)?;
+
+            merged.push(source.key());
+            source.close(self.buyer.to_account_info())?;
+        }
+
+        // 3) replace the target's certificate, it no longer states the position's amount
+        token::burn(
+            CpiContext::new(
+                self.token_program.to_account_info(),
+                Burn {
+                    mint: self.certificate_mint.to_account_info(),
+                    from: self.nft_account.to_account_info(),
+                    authority: self.buyer.to_account_info(),
+                },
+            ),
+            1,
+        )?;
+        mint_purchase_nft(
+            PurchaseNft {
+                nft_mint: self.replacement_nft_mint.to_account_info(),
+                nft_account: self.replacement_nft_account.to_account_info(),
+                nft_metadata: self.replacement_nft_metadata.to_account_info(),
+                owner: self.buyer.to_account_info(),
+                token_program: self.token_program.to_account_info(),
+                token_metadata_program: self.token_metadata_program.to_account_info(),
+                system_program: self.system_program.to_account_info(),
+                rent: self.rent.to_account_info(),
+            },
+            format!("Carbon Credits - Remaining: {}", self.purchase.remaining_amount),
+            format!("https://carbonpay.com/purchases/{}/remaining", self.replacement_nft_mint.key()),
+        )?;
+        self.purchase.certificate_mint = self.replacement_nft_mint.key();
+        self.purchase.uncertified_offset = 0;
+
+        Ok(())
+    }
+}
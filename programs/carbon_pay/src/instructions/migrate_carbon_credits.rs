@@ -0,0 +1,91 @@
+use crate::errors::ContractError;
+use crate::state::CarbonCredits;
+use crate::utils::token_scale;
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{transfer, Transfer};
+use anchor_lang::Discriminator;
+
+/// CarbonCredits as written before `units_version`, when every project token was a whole
+/// tonne and the credit counters counted tonnes
+#[derive(AnchorDeserialize)]
+struct LegacyCarbonCredits {
+    authority: Pubkey,
+    total_credits: u64,
+    active_credits: u64,
+    offset_credits: u64,
+    projects_count: u64,
+    total_fees_earned: u64,
+    bump: u8,
+}
+
+#[derive(Accounts)]
+pub struct MigrateCarbonCredits<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// CHECK: still in the legacy layout, which `Account` can't deserialize; its size,
+    /// discriminator and authority are checked in the handler
+    #[account(mut, seeds = [b"carbon_credits"], bump)]
+    pub carbon_credits: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> MigrateCarbonCredits<'info> {
+    pub fn handler(&mut self) -> Result<()> {
+        let info = self.carbon_credits.to_account_info();
+
+        // 1) Only the legacy layout is migrated, and only once
+        require!(
+            info.data_len() == CarbonCredits::DISCRIMINATOR_SIZE + CarbonCredits::LEGACY_SPACE,
+            ContractError::CarbonCreditsMigrated
+        );
+        let legacy = {
+            let data = info.try_borrow_data()?;
+            require!(
+                data.starts_with(CarbonCredits::DISCRIMINATOR),
+                anchor_lang::error::ErrorCode::AccountDiscriminatorMismatch
+            );
+            LegacyCarbonCredits::deserialize(&mut &data[CarbonCredits::DISCRIMINATOR_SIZE..])?
+        };
+        require_keys_eq!(legacy.authority, self.authority.key(), ContractError::UnauthorizedAdmin);
+
+        // 2) Rescale the tonne counters to platform units; fees are lamports and stay as they are
+        let scale = token_scale(CarbonCredits::DECIMALS)?;
+        let to_units = |tonnes: u64| tonnes.checked_mul(scale).ok_or(ContractError::ArithmeticOverflow);
+        let migrated = CarbonCredits {
+            authority: legacy.authority,
+            total_credits: to_units(legacy.total_credits)?,
+            active_credits: to_units(legacy.active_credits)?,
+            offset_credits: to_units(legacy.offset_credits)?,
+            projects_count: legacy.projects_count,
+            total_fees_earned: legacy.total_fees_earned,
+            bump: legacy.bump,
+            units_version: CarbonCredits::UNITS_VERSION,
+        };
+
+        // 3) Grow the account, the authority tops up its rent
+        let new_len = CarbonCredits::DISCRIMINATOR_SIZE + CarbonCredits::INIT_SPACE;
+        let top_up = Rent::get()?
+            .minimum_balance(new_len)
+            .saturating_sub(info.lamports());
+        if top_up > 0 {
+            transfer(
+                CpiContext::new(
+                    self.system_program.to_account_info(),
+                    Transfer {
+                        from: self.authority.to_account_info(),
+                        to: info.clone(),
+                    },
+                ),
+                top_up,
+            )?;
+        }
+        info.realloc(new_len, false)?;
+
+        // 4) Write the current layout
+        migrated.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,147 @@
+use crate::errors::ContractError;
+use crate::state::{CarbonCredits, Project, ProjectCategory};
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{transfer, Transfer};
+use anchor_lang::Discriminator;
+use anchor_spl::token::{Mint, TokenAccount};
+
+/// Project as written before `layout_version`: no sale limits, pricing, category or vault,
+/// and every token a whole tonne
+#[derive(AnchorDeserialize)]
+struct LegacyProject {
+    owner: Pubkey,
+    mint: Pubkey,
+    token_mint: Pubkey,
+    token_bump: u8,
+    is_active: bool,
+    amount: u64,
+    remaining_amount: u64,
+    offset_amount: u64,
+    price_per_token: u64,
+    carbon_pay_fee: u64,
+    carbon_pay_authority: Pubkey,
+    project_bump: u8,
+}
+
+#[derive(Accounts)]
+pub struct MigrateProject<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [b"carbon_credits"],
+        bump = carbon_credits.bump,
+        has_one = authority @ ContractError::UnauthorizedAdmin,
+    )]
+    pub carbon_credits: Box<Account<'info, CarbonCredits>>,
+
+    /// CHECK: still in the legacy layout, which `Account` can't deserialize; its size,
+    /// discriminator and seeds are checked in the handler
+    #[account(mut, owner = crate::ID)]
+    pub project: UncheckedAccount<'info>,
+
+    /// The project's fungible token mint, checked against the legacy record in the handler
+    pub token_mint: Box<Account<'info, Mint>>,
+
+    /// ATA of the `carbon_credits` PDA holding the project's unsold tokens
+    #[account(
+        associated_token::mint = token_mint,
+        associated_token::authority = carbon_credits,
+    )]
+    pub vault: Box<Account<'info, TokenAccount>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> MigrateProject<'info> {
+    pub fn handler(&mut self) -> Result<()> {
+        let info = self.project.to_account_info();
+
+        // 1) Only the legacy layout is migrated, and only once
+        require!(
+            info.data_len() == Project::DISCRIMINATOR_SIZE + Project::LEGACY_SPACE,
+            ContractError::ProjectMigrated
+        );
+        let legacy = {
+            let data = info.try_borrow_data()?;
+            require!(
+                data.starts_with(Project::DISCRIMINATOR),
+                anchor_lang::error::ErrorCode::AccountDiscriminatorMismatch
+            );
+            LegacyProject::deserialize(&mut &data[Project::DISCRIMINATOR_SIZE..])?
+        };
+        let address = Pubkey::create_program_address(
+            &[
+                b"project",
+                legacy.owner.as_ref(),
+                legacy.mint.as_ref(),
+                &[legacy.project_bump],
+            ],
+            &crate::ID,
+        )
+        .map_err(|_| anchor_lang::error::ErrorCode::ConstraintSeeds)?;
+        require_keys_eq!(
+            address,
+            info.key(),
+            anchor_lang::error::ErrorCode::ConstraintSeeds
+        );
+        require_keys_eq!(
+            legacy.token_mint,
+            self.token_mint.key(),
+            ContractError::InvalidProjectMint
+        );
+
+        // 2) New fields start out as a project created today would, without limits
+        let migrated = Project {
+            owner: legacy.owner,
+            mint: legacy.mint,
+            token_mint: legacy.token_mint,
+            token_bump: legacy.token_bump,
+            is_active: legacy.is_active,
+            amount: legacy.amount,
+            remaining_amount: legacy.remaining_amount,
+            offset_amount: legacy.offset_amount,
+            price_per_token: legacy.price_per_token,
+            carbon_pay_fee: legacy.carbon_pay_fee,
+            carbon_pay_authority: legacy.carbon_pay_authority,
+            project_bump: legacy.project_bump,
+            allowlist_required: false,
+            min_purchase_amount: 0,
+            max_purchase_amount: 0,
+            max_per_buyer: 0,
+            sale_start: 0,
+            sale_end: 0,
+            early_access_start: 0,
+            price_tiers: Vec::new(),
+            auction: None,
+            category: ProjectCategory::Unspecified,
+            vault: self.vault.key(),
+            decimals: self.token_mint.decimals,
+            layout_version: Project::LAYOUT_VERSION,
+        };
+
+        // 3) Grow the account, the authority tops up its rent
+        let new_len = Project::DISCRIMINATOR_SIZE + Project::INIT_SPACE;
+        let top_up = Rent::get()?
+            .minimum_balance(new_len)
+            .saturating_sub(info.lamports());
+        if top_up > 0 {
+            transfer(
+                CpiContext::new(
+                    self.system_program.to_account_info(),
+                    Transfer {
+                        from: self.authority.to_account_info(),
+                        to: info.clone(),
+                    },
+                ),
+                top_up,
+            )?;
+        }
+        info.realloc(new_len, false)?;
+
+        // 4) Write the current layout
+        migrated.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])?;
+
+        Ok(())
+    }
+}
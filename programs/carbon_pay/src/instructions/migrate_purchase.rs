@@ -0,0 +1,127 @@
+use crate::errors::ContractError;
+use crate::state::{Holdings, Purchase};
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{transfer, Transfer};
+use anchor_lang::Discriminator;
+
+/// Purchase as written before `certificate_mint`: the certificate was always `nft_mint`
+#[derive(AnchorDeserialize)]
+struct LegacyPurchase {
+    buyer: Pubkey,
+    project: Pubkey,
+    amount: u64,
+    remaining_amount: u64,
+    purchase_date: i64,
+    purchase_bump: u8,
+    nft_mint: Pubkey,
+}
+
+#[derive(Accounts)]
+pub struct MigratePurchase<'info> {
+    /// anyone may migrate a purchase, the result only depends on the legacy record
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: still in the legacy layout, which `Account` can't deserialize; its size,
+    /// discriminator and seeds are checked in the handler
+    #[account(mut, owner = crate::ID)]
+    pub purchase: UncheckedAccount<'info>,
+
+    /// CHECK: the legacy record's buyer, checked in the handler
+    pub owner: UncheckedAccount<'info>,
+
+    /// CHECK: the legacy record's project, checked in the handler
+    pub project: UncheckedAccount<'info>,
+
+    /// tokens in the owner's wallet backing their purchases of this project
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = Holdings::DISCRIMINATOR_SIZE + Holdings::INIT_SPACE,
+        seeds = [b"holdings", owner.key().as_ref(), project.key().as_ref()],
+        bump
+    )]
+    pub holdings: Box<Account<'info, Holdings>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> MigratePurchase<'info> {
+    pub fn handler(&mut self, bumps: &MigratePurchaseBumps) -> Result<()> {
+        let info = self.purchase.to_account_info();
+
+        // 1) Only the legacy layout is migrated, and only once
+        require!(
+            info.data_len() == Purchase::DISCRIMINATOR_SIZE + Purchase::LEGACY_SPACE,
+            ContractError::PurchaseMigrated
+        );
+        let legacy = {
+            let data = info.try_borrow_data()?;
+            require!(
+                data.starts_with(Purchase::DISCRIMINATOR),
+                anchor_lang::error::ErrorCode::AccountDiscriminatorMismatch
+            );
+            LegacyPurchase::deserialize(&mut &data[Purchase::DISCRIMINATOR_SIZE..])?
+        };
+        let address = Pubkey::create_program_address(
+            &[
+                b"purchase",
+                legacy.buyer.as_ref(),
+                legacy.project.as_ref(),
+                legacy.nft_mint.as_ref(),
+                &[legacy.purchase_bump],
+            ],
+            &crate::ID,
+        )
+        .map_err(|_| anchor_lang::error::ErrorCode::ConstraintSeeds)?;
+        require_keys_eq!(
+            address,
+            info.key(),
+            anchor_lang::error::ErrorCode::ConstraintSeeds
+        );
+
+        require_keys_eq!(self.owner.key(), legacy.buyer, ContractError::NotPurchaseOwner);
+        require_keys_eq!(self.project.key(), legacy.project, ContractError::InvalidProject);
+
+        // 2) The purchase NFT is still the certificate
+        let migrated = Purchase {
+            buyer: legacy.buyer,
+            project: legacy.project,
+            amount: legacy.amount,
+            remaining_amount: legacy.remaining_amount,
+            purchase_date: legacy.purchase_date,
+            purchase_bump: legacy.purchase_bump,
+            nft_mint: legacy.nft_mint,
+            certificate_mint: legacy.nft_mint,
+            uncertified_offset: 0,
+        };
+
+        // 3) Grow the account, the payer tops up its rent
+        let new_len = Purchase::DISCRIMINATOR_SIZE + Purchase::INIT_SPACE;
+        let top_up = Rent::get()?
+            .minimum_balance(new_len)
+            .saturating_sub(info.lamports());
+        if top_up > 0 {
+            transfer(
+                CpiContext::new(
+                    self.system_program.to_account_info(),
+                    Transfer {
+                        from: self.payer.to_account_info(),
+                        to: info.clone(),
+                    },
+                ),
+                top_up,
+            )?;
+        }
+        info.realloc(new_len, false)?;
+
+        // 4) Write the current layout
+        migrated.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])?;
+
+        // 5) Its tokens back the purchase from now on
+        self.holdings.open(legacy.buyer, legacy.project, bumps.holdings);
+        self.holdings.add(legacy.remaining_amount)?;
+
+        Ok(())
+    }
+}
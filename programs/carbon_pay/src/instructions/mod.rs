@@ -2,8 +2,92 @@ pub mod initialize_project;
 pub mod request_offset;
 pub mod initialize_carbon_credits;
 pub mod purchase_carbon_credits;
+pub mod initialize_platform_config;
+pub mod update_offset_approvers;
+pub mod approve_offset_vote;
+pub mod add_allowlist_entry;
+pub mod set_allowlist_expiry;
+pub mod remove_allowlist_entry;
+pub mod set_allowlist_required;
+pub mod set_purchase_limits;
+pub mod set_sale_window;
+pub mod set_price_tiers;
+pub mod list_credits;
+pub mod buy_listing;
+pub mod cancel_listing;
+pub mod start_dutch_auction;
+pub mod settle_dutch_auction;
+pub mod claim_auction_refund;
+pub mod create_otc_offer;
+pub mod accept_otc_offer;
+pub mod cancel_otc_offer;
+pub mod create_subscription;
+pub mod fund_subscription;
+pub mod execute_subscription;
+pub mod cancel_subscription;
+pub mod set_project_category;
+pub mod create_basket;
+pub mod update_basket_eligibility;
+pub mod deposit_to_basket;
+pub mod redeem_from_basket;
+pub mod retire_from_basket;
+pub mod retire_held_credits;
+pub mod purchase_and_retire;
+pub mod approve_offset_delegate;
+pub mod revoke_offset_delegate;
+pub mod request_batch_offset;
+pub mod merge_purchases;
+pub mod split_purchase;
+pub mod transfer_purchase;
+pub mod claim_purchase;
+pub mod set_fee_policy;
+pub mod migrate_carbon_credits;
+pub mod migrate_project;
+pub mod migrate_purchase;
 
 pub use initialize_project::*;
 pub use request_offset::*;
 pub use initialize_carbon_credits::*;
 pub use purchase_carbon_credits::*;
+pub use initialize_platform_config::*;
+pub use update_offset_approvers::*;
+pub use approve_offset_vote::*;
+pub use add_allowlist_entry::*;
+pub use set_allowlist_expiry::*;
+pub use remove_allowlist_entry::*;
+pub use set_allowlist_required::*;
+pub use set_purchase_limits::*;
+pub use set_sale_window::*;
+pub use set_price_tiers::*;
+pub use list_credits::*;
+pub use buy_listing::*;
+pub use cancel_listing::*;
+pub use start_dutch_auction::*;
+pub use settle_dutch_auction::*;
+pub use claim_auction_refund::*;
+pub use create_otc_offer::*;
+pub use accept_otc_offer::*;
+pub use cancel_otc_offer::*;
+pub use create_subscription::*;
+pub use fund_subscription::*;
+pub use execute_subscription::*;
+pub use cancel_subscription::*;
+pub use set_project_category::*;
+pub use create_basket::*;
+pub use update_basket_eligibility::*;
+pub use deposit_to_basket::*;
+pub use redeem_from_basket::*;
+pub use retire_from_basket::*;
+pub use retire_held_credits::*;
+pub use purchase_and_retire::*;
+pub use approve_offset_delegate::*;
+pub use revoke_offset_delegate::*;
+pub use request_batch_offset::*;
+pub use merge_purchases::*;
+pub use split_purchase::*;
+pub use transfer_purchase::*;
+pub use claim_purchase::*;
+pub use set_fee_policy::*;
+pub use migrate_carbon_credits::*;
+pub use migrate_project::*;
+pub use migrate_purchase::*;
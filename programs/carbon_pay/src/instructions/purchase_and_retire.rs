@@ -0,0 +1,201 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, Token, TokenAccount};
+use crate::state::{
+    AllowlistEntry, BuyerStats, CarbonCredits, OffsetRequest, PlatformConfig, PlatformFee, Project,
+    RequestStatus,
+};
+use crate::errors::ContractError;
+use crate::events::CarbonCreditsPurchased;
+use crate::utils::{blended_price, burn_from_vault, pay_owner_and_platform};
+
+/// Buys credits and retires them straight from the vault.
+/// No purchase NFT or Purchase account is created; the OffsetRequest is the certificate,
+/// under its own `retirement` seeds and with `purchase` left as the default key.
+#[derive(Accounts)]
+#[instruction(amount: u64, request_id: String)]
+pub struct PurchaseAndRetire<'info> {
+    #[account(
+        mut,
+        constraint = project.is_active @ ContractError::ProjectInactive,
+        constraint = project.remaining_amount >= amount @ ContractError::InsufficientTokens,
+        seeds = [b"project", project.owner.as_ref(), project.mint.as_ref()],
+        bump = project.project_bump,
+    )]
+    pub project: Box<Account<'info, Project>>,
+
+    /// CHECK: project owner is the project owner
+    /// who receives the payment
+    #[account(
+        mut,
+        constraint = project_owner.key() == project.owner @ ContractError::InvalidProjectOwner
+    )]
+    pub project_owner: UncheckedAccount<'info>,
+
+    /// project's fungible token mint, the retired tokens are burned from its supply
+    #[account(
+        mut,
+        constraint = project_mint.key() == project.token_mint @ ContractError::InvalidProjectMint
+    )]
+    pub project_mint: Box<Account<'info, Mint>>,
+
+    /// CarbonCredits PDA
+    #[account(
+        mut,
+        seeds = [b"carbon_credits"], bump = carbon_credits.bump,
+        constraint = carbon_credits.key() == project.carbon_pay_authority @ ContractError::InvalidCarbonPayAuthority
+    )]
+    pub carbon_credits: Box<Account<'info, CarbonCredits>>,
+
+    /// project's vault ATA
+    #[account(
+        mut,
+        address = project.vault @ ContractError::InvalidProjectVault,
+        token::mint = project_mint,
+        token::authority = carbon_credits,
+        owner = token::ID
+    )]
+    pub project_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// retirement record, seeded apart from batch offsets which also key their requests by project
+    #[account(
+        init,
+        payer = buyer,
+        space = OffsetRequest::DISCRIMINATOR_SIZE + OffsetRequest::INIT_SPACE,
+        seeds = [b"retirement", buyer.key().as_ref(), project.key().as_ref(), request_id.as_bytes()],
+        bump
+    )]
+    pub offset_request: Box<Account<'info, OffsetRequest>>,
+
+    /// buyer's running totals for this project, used for the per-buyer cap
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        space = BuyerStats::DISCRIMINATOR_SIZE + BuyerStats::INIT_SPACE,
+        seeds = [b"buyer_stats", buyer.key().as_ref(), project.key().as_ref()],
+        bump
+    )]
+    pub buyer_stats: Box<Account<'info, BuyerStats>>,
+
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    /// platform-wide settings (allowlist requirement, fee rounding and minimum fee)
+    #[account(seeds = [b"platform_config"], bump = platform_config.bump)]
+    pub platform_config: Box<Account<'info, PlatformConfig>>,
+
+    /// buyer's allowlist entry, required when the project or platform enforces an allowlist
+    #[account(
+        seeds = [b"allowlist", allowlist_entry.scope.as_ref(), buyer.key().as_ref()],
+        bump = allowlist_entry.bump,
+    )]
+    pub allowlist_entry: Option<Box<Account<'info, AllowlistEntry>>>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> PurchaseAndRetire<'info> {
+    pub fn handler(
+        &mut self,
+        amount: u64,
+        request_id: String,
+        bumps: &PurchaseAndRetireBumps,
+    ) -> Result<()> {
+        require!(amount > 0, ContractError::InvalidAmount);
+
+        // 0) buyer gates: sale window, allowlist and purchase limits
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            self.project.active_auction().is_none(),
+            ContractError::AuctionActive
+        );
+        let early_access = self.project.check_sale_window(now)?;
+        if early_access || self.project.allowlist_required || self.platform_config.allowlist_required {
+            let entry = self
+                .allowlist_entry
+                .as_ref()
+                .ok_or(ContractError::BuyerNotAllowlisted)?;
+            entry.check_buyer(
+                &self.buyer.key(),
+                &self.project.key(),
+                &self.platform_config.key(),
+                now,
+            )?;
+        }
+        self.project
+            .check_purchase_limits(amount, self.buyer_stats.total_purchased)?;
+
+        // 1) payment
+        let total = self.project.total_price(amount)?;
+        let PlatformFee { fee, remainder } = self
+            .platform_config
+            .platform_fee(total, self.project.carbon_pay_fee)?;
+        let to_owner = total.checked_sub(fee).ok_or(ContractError::ArithmeticOverflow)?;
+        pay_owner_and_platform(
+            self.system_program.to_account_info(),
+            self.buyer.to_account_info(),
+            self.project_owner.to_account_info(),
+            self.carbon_credits.to_account_info(),
+            to_owner,
+            fee,
+        )?;
+
+        // 2) burn the credits straight from the vault
+        burn_from_vault(
+            self.token_program.to_account_info(),
+            self.project_mint.to_account_info(),
+            self.project_token_account.to_account_info(),
+            self.carbon_credits.to_account_info(),
+            self.carbon_credits.bump,
+            amount,
+        )?;
+
+        // 3) update on-chain state
+        self.project.record_purchase(amount)?;
+        self.project.record_offset(amount)?;
+        let credits = self.project.platform_units(amount)?;
+        self.carbon_credits.offset_credits = self
+            .carbon_credits
+            .offset_credits
+            .checked_add(credits)
+            .ok_or(ContractError::ArithmeticOverflow)?;
+        self.carbon_credits.add_fees(fee)?;
+
+        if self.buyer_stats.buyer == Pubkey::default() {
+            self.buyer_stats.buyer = self.buyer.key();
+            self.buyer_stats.project = self.project.key();
+            self.buyer_stats.bump = bumps.buyer_stats;
+        }
+        self.buyer_stats.record_purchase(amount)?;
+
+        // 4) record the retirement
+        self.offset_request.set_inner(OffsetRequest {
+            offset_requester: self.buyer.key(),
+            purchase: Pubkey::default(),
+            project: self.project.key(),
+            amount,
+            request_id,
+            status: RequestStatus::Pending,
+            request_date: now,
+            processed_date: 0,
+            request_bump: bumps.offset_request,
+            processor: None,
+        });
+
+        emit!(CarbonCreditsPurchased {
+            buyer: self.buyer.key(),
+            project: self.project.key(),
+            purchase: Pubkey::default(),
+            offset_request: Some(self.offset_request.key()),
+            amount,
+            decimals: self.project.decimals,
+            total_price: total,
+            fee,
+            fee_remainder: remainder,
+            blended_price_per_token: blended_price(total, amount, self.project.decimals)?,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+}
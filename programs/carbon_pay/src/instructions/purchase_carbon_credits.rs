@@ -1,9 +1,14 @@
 use anchor_lang::prelude::*;
 use anchor_spl::{
-    metadata::{create_metadata_accounts_v3, mpl_token_metadata::types::{Creator, DataV2}, CreateMetadataAccountsV3, Metadata}, token::{self, Mint, MintTo, Token, TokenAccount}
+    metadata::Metadata, token::{self, Mint, Token, TokenAccount}
 };
-use crate::state::{Project, Purchase, CarbonCredits};
+use crate::state::{AllowlistEntry, BuyerStats, CarbonCredits, Holdings, PlatformConfig, PlatformFee, Project, Purchase};
 use crate::errors::ContractError;
+use crate::events::CarbonCreditsPurchased;
+use crate::utils::{
+    blended_price, mint_purchase_nft, pay_owner_and_platform, price_for,
+    transfer_from_vault, PurchaseNft,
+};
 
 #[derive(Accounts)]
 #[instruction(amount: u64)]
@@ -44,6 +49,7 @@ pub struct PurchaseCarbonCredits<'info> {
     /// project's vault ATA (already created off-chain)
     #[account(
         mut,
+        address = project.vault @ ContractError::InvalidProjectVault,
         token::mint = project_mint,
         token::authority = carbon_credits,
         owner = token::ID
@@ -87,12 +93,48 @@ pub struct PurchaseCarbonCredits<'info> {
 
     /// purchase NFT metadata account (CPI will create)
     /// CHECK: This account will be initialized by the Token Metadata program via CPI. Safe because we're just passing it to the authorized CPI call.
-    #[account(mut)]
+    #[account(
+        mut,
+        seeds = [b"metadata", token_metadata_program.key().as_ref(), purchase_nft_mint.key().as_ref()],
+        bump,
+        seeds::program = token_metadata_program.key(),
+    )]
     pub purchase_metadata: UncheckedAccount<'info>,
 
+    /// buyer's running totals for this project, used for the per-buyer cap
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        space = BuyerStats::DISCRIMINATOR_SIZE + BuyerStats::INIT_SPACE,
+        seeds = [b"buyer_stats", buyer.key().as_ref(), project.key().as_ref()],
+        bump
+    )]
+    pub buyer_stats: Box<Account<'info, BuyerStats>>,
+
+    /// tokens in the buyer's wallet backing their purchases of this project
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        space = Holdings::DISCRIMINATOR_SIZE + Holdings::INIT_SPACE,
+        seeds = [b"holdings", buyer.key().as_ref(), project.key().as_ref()],
+        bump
+    )]
+    pub holdings: Box<Account<'info, Holdings>>,
+
     #[account(mut)]
     pub buyer: Signer<'info>,
 
+    /// platform-wide settings (allowlist requirement, fee rounding and minimum fee)
+    #[account(seeds = [b"platform_config"], bump = platform_config.bump)]
+    pub platform_config: Box<Account<'info, PlatformConfig>>,
+
+    /// buyer's allowlist entry, required when the project or platform enforces an allowlist
+    #[account(
+        seeds = [b"allowlist", allowlist_entry.scope.as_ref(), buyer.key().as_ref()],
+        bump = allowlist_entry.bump,
+    )]
+    pub allowlist_entry: Option<Box<Account<'info, AllowlistEntry>>>,
+
     pub token_program: Program<'info, Token>,
     pub token_metadata_program: Program<'info, Metadata>,
     pub system_program: Program<'info, System>,
@@ -101,105 +143,136 @@ pub struct PurchaseCarbonCredits<'info> {
 
 impl<'info> PurchaseCarbonCredits<'info> {
     pub fn purchase_carbon_credits(&mut self, amount: u64, bumps: &PurchaseCarbonCreditsBumps) -> Result<()> {
-        // 1) payments
-        let total = amount.checked_mul(self.project.price_per_token).ok_or(ContractError::ArithmeticOverflow)?;
-        let fee  = total.checked_mul(self.project.carbon_pay_fee as u64).ok_or(ContractError::ArithmeticOverflow)?
-                        .checked_div(10_000).ok_or(ContractError::ArithmeticOverflow)?;
+        require!(amount > 0, ContractError::InvalidAmount);
+
+        // 0) buyer gates: sale window, allowlist and purchase limits
+        let now = Clock::get()?.unix_timestamp;
+        let early_access = self.project.check_sale_window(now)?;
+        if early_access || self.project.allowlist_required || self.platform_config.allowlist_required {
+            let entry = self
+                .allowlist_entry
+                .as_ref()
+                .ok_or(ContractError::BuyerNotAllowlisted)?;
+            entry.check_buyer(
+                &self.buyer.key(),
+                &self.project.key(),
+                &self.platform_config.key(),
+                now,
+            )?;
+        }
+        self.project
+            .check_purchase_limits(amount, self.buyer_stats.total_purchased)?;
+
+        // 1) payments, auction sales are escrowed in the project PDA until the clearing price is known
+        let auction_price = match self.project.active_auction() {
+            Some(auction) => Some(auction.current_price(now)?),
+            None => None,
+        };
+        let total = match auction_price {
+            Some(price) => price_for(amount, price, self.project.decimals)?,
+            None => self.project.total_price(amount)?,
+        };
+        let PlatformFee { fee, remainder } = match auction_price {
+            Some(_) => PlatformFee { fee: 0, remainder: 0 },
+            None => self
+                .platform_config
+                .platform_fee(total, self.project.carbon_pay_fee)?,
+        };
         let to_owner = total.checked_sub(fee).ok_or(ContractError::ArithmeticOverflow)?;
 
         // 2) transfer SOL
-        anchor_lang::system_program::transfer(
-            CpiContext::new(
+        if auction_price.is_some() {
+            anchor_lang::system_program::transfer(
+                CpiContext::new(
+                    self.system_program.to_account_info(),
+                    anchor_lang::system_program::Transfer {
+                        from: self.buyer.to_account_info(),
+                        to:   self.project.to_account_info(),
+                    },
+                ),
+                total,
+            )?;
+        } else {
+            pay_owner_and_platform(
                 self.system_program.to_account_info(),
-                anchor_lang::system_program::Transfer {
-                    from: self.buyer.to_account_info(),
-                    to:   self.project_owner.to_account_info(),
-                },
-            ),
-            to_owner,
-        )?;
-        anchor_lang::system_program::transfer(
-            CpiContext::new(
-                self.system_program.to_account_info(),
-                anchor_lang::system_program::Transfer {
-                    from: self.buyer.to_account_info(),
-                    to:   self.carbon_credits.to_account_info(),
-                },
-            ),
-            fee,
-        )?;
+                self.buyer.to_account_info(),
+                self.project_owner.to_account_info(),
+                self.carbon_credits.to_account_info(),
+                to_owner,
+                fee,
+            )?;
+        }
 
-        // 3) mint the purchase NFT
-        token::mint_to(
-            CpiContext::new(
-                self.token_program.to_account_info(),
-                MintTo {
-                    mint:      self.purchase_nft_mint.to_account_info(),
-                    to:        self.buyer_nft_account.to_account_info(),
-                    authority: self.buyer.to_account_info(),
-                },
-            ),
-            1,
-        )?;
-
-        // 4) create NFT metadata
-        create_metadata_accounts_v3(
-            CpiContext::new(
-                self.token_metadata_program.to_account_info(),
-                CreateMetadataAccountsV3 {
-                    metadata:self.purchase_metadata.to_account_info(),
-                    mint: self.purchase_nft_mint.to_account_info(),
-                    mint_authority:self.buyer.to_account_info(),
-                    payer:self.buyer.to_account_info(),
-                    update_authority:self.buyer.to_account_info(),
-                    system_program:self.system_program.to_account_info(),
-                    rent:self.rent.to_account_info(),
-                },
-            ),
-            DataV2 {
-                name: format!("Carbon Credits Purchase - {}", amount),
-                symbol:"CRBN".to_string(),
-                uri: format!("https://carbonpay.com/purchases/{}", self.purchase_nft_mint.key()),
-                seller_fee_basis_points: 0,
-                creators: Some(vec![Creator{
-                    address:  self.buyer.key(),
-                    verified: true,
-                    share:    100,
-                }]),
-                collection: None,
-                uses:       None,
+        // 3) mint the purchase NFT with its metadata
+        mint_purchase_nft(
+            PurchaseNft {
+                nft_mint: self.purchase_nft_mint.to_account_info(),
+                nft_account: self.buyer_nft_account.to_account_info(),
+                nft_metadata: self.purchase_metadata.to_account_info(),
+                owner: self.buyer.to_account_info(),
+                token_program: self.token_program.to_account_info(),
+                token_metadata_program: self.token_metadata_program.to_account_info(),
+                system_program: self.system_program.to_account_info(),
+                rent: self.rent.to_account_info(),
             },
-            true,
-            true,
-            None,
+            format!("Carbon Credits Purchase - {}", amount),
+            format!("https://carbonpay.com/purchases/{}", self.purchase_nft_mint.key()),
         )?;
 
-        // 5) transfer the fungible tokens from vault to buyer
-        token::transfer(
-            CpiContext::new_with_signer(
-                self.token_program.to_account_info(),
-                token::Transfer {
-                    from:self.project_token_account.to_account_info(),
-                    to:self.buyer_token_account.to_account_info(),
-                    authority:self.carbon_credits.to_account_info(),
-                },
-                &[&[b"carbon_credits", &[self.carbon_credits.bump]]],
-            ),
+        // 4) transfer the fungible tokens from vault to buyer
+        transfer_from_vault(
+            self.token_program.to_account_info(),
+            self.project_token_account.to_account_info(),
+            self.buyer_token_account.to_account_info(),
+            self.carbon_credits.to_account_info(),
+            self.carbon_credits.bump,
             amount,
         )?;
 
-        // 6) update on-chain state
+        // 5) update on-chain state
         self.purchase.set_inner(Purchase {
             buyer:self.buyer.key(),
             project:self.project.key(),
             amount,
             remaining_amount:amount,
-            purchase_date:Clock::get()?.unix_timestamp,
+            purchase_date:now,
             purchase_bump:bumps.purchase,
             nft_mint:self.purchase_nft_mint.key(),
+            certificate_mint:self.purchase_nft_mint.key(),
+            uncertified_offset: 0,
         });
         self.project.remaining_amount = self.project.remaining_amount.checked_sub(amount).ok_or(ContractError::ArithmeticOverflow)?;
 
+        if self.buyer_stats.buyer == Pubkey::default() {
+            self.buyer_stats.buyer = self.buyer.key();
+            self.buyer_stats.project = self.project.key();
+            self.buyer_stats.bump = bumps.buyer_stats;
+        }
+        self.buyer_stats.record_purchase(amount)?;
+        self.holdings.open(self.buyer.key(), self.project.key(), bumps.holdings);
+        self.holdings.add(amount)?;
+        self.carbon_credits.add_fees(fee)?;
+
+        if let (Some(price), Some(auction)) = (auction_price, self.project.auction.as_mut()) {
+            auction.last_price = price;
+            auction.sold = auction.sold.checked_add(amount).ok_or(ContractError::ArithmeticOverflow)?;
+            self.buyer_stats.record_auction_purchase(amount, total)?;
+        }
+
+        emit!(CarbonCreditsPurchased {
+            buyer: self.buyer.key(),
+            project: self.project.key(),
+            purchase: self.purchase.key(),
+            offset_request: None,
+            amount,
+            decimals: self.project.decimals,
+            total_price: total,
+            fee,
+            fee_remainder: remainder,
+            blended_price_per_token: blended_price(total, amount, self.project.decimals)?,
+            timestamp: now,
+        });
+
     
         Ok(())
     }
@@ -0,0 +1,144 @@
+use crate::errors::ContractError;
+use crate::state::{pro_rata_shares, Basket, BasketReserve};
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Burn, Mint, Token, TokenAccount};
+
+/// Burns basket tokens for a pro-rata share of every project in the basket.
+/// `remaining_accounts` must hold, for every reserve of the basket:
+/// `[basket_reserve (mut), basket_vault (mut), redeemer_token_account (mut)]`
+/// The shares are plain project tokens, not part of any purchase: they are retired with
+/// `retire_held_credits` rather than `request_offset`.
+/// A reserve the redemption empties is closed to the redeemer, freeing its slot in the basket.
+#[derive(Accounts)]
+pub struct RedeemFromBasket<'info> {
+    #[account(mut)]
+    pub redeemer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"basket", basket.basket_id.to_le_bytes().as_ref()],
+        bump = basket.bump,
+    )]
+    pub basket: Box<Account<'info, Basket>>,
+
+    #[account(
+        mut,
+        seeds = [b"basket_mint", basket.key().as_ref()],
+        bump = basket.mint_bump,
+    )]
+    pub basket_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        token::mint = basket_mint,
+        token::authority = redeemer,
+    )]
+    pub redeemer_basket_account: Box<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Load every reserve of `basket` from groups of `group_size` remaining accounts,
+/// the reserve first and its vault second
+pub fn load_basket_reserves<'info>(
+    basket: &Account<'info, Basket>,
+    remaining_accounts: &'info [AccountInfo<'info>],
+    group_size: usize,
+) -> Result<Vec<Account<'info, BasketReserve>>> {
+    require!(
+        remaining_accounts.len() == group_size * basket.project_count as usize,
+        ContractError::InvalidBasketReserves
+    );
+    let mut reserves: Vec<Account<'info, BasketReserve>> = Vec::new();
+    for group in remaining_accounts.chunks(group_size) {
+        let reserve = Account::<BasketReserve>::try_from(&group[0])?;
+        require_keys_eq!(reserve.basket, basket.key(), ContractError::InvalidBasketReserves);
+        require_keys_eq!(reserve.vault, group[1].key(), ContractError::InvalidBasketReserves);
+        require!(
+            reserves.iter().all(|other| other.key() != reserve.key()),
+            ContractError::InvalidBasketReserves
+        );
+        reserves.push(reserve);
+    }
+    Ok(reserves)
+}
+
+/// Close `reserve` once its last tokens have left, so another project can take its slot
+pub fn release_empty_reserve<'info>(
+    basket: &mut Account<'info, Basket>,
+    reserve: &Account<'info, BasketReserve>,
+    destination: AccountInfo<'info>,
+) -> Result<()> {
+    if reserve.amount > 0 {
+        return reserve.exit(&crate::ID);
+    }
+    reserve.close(destination)?;
+    basket.project_count = basket
+        .project_count
+        .checked_sub(1)
+        .ok_or(ContractError::ArithmeticOverflow)?;
+    Ok(())
+}
+
+impl<'info> RedeemFromBasket<'info> {
+    pub fn handler(
+        &mut self,
+        amount: u64,
+        remaining_accounts: &'info [AccountInfo<'info>],
+    ) -> Result<()> {
+        require!(amount > 0, ContractError::InvalidAmount);
+        let mut reserves = load_basket_reserves(&self.basket, remaining_accounts, 3)?;
+        let balances: Vec<u64> = reserves.iter().map(|reserve| reserve.amount).collect();
+        let shares = pro_rata_shares(&balances, amount)?;
+
+        // 1) burn the basket tokens
+        token::burn(
+            CpiContext::new(
+                self.token_program.to_account_info(),
+                Burn {
+                    mint: self.basket_mint.to_account_info(),
+                    from: self.redeemer_basket_account.to_account_info(),
+                    authority: self.redeemer.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+
+        // 2) pay out each project's share from its vault
+        let basket_id = self.basket.basket_id.to_le_bytes();
+        let seeds: &[&[u8]] = &[b"basket", basket_id.as_ref(), &[self.basket.bump]];
+        for ((reserve, group), share) in reserves
+            .iter_mut()
+            .zip(remaining_accounts.chunks(3))
+            .zip(shares)
+        {
+            if share > 0 {
+                token::transfer(
+                    CpiContext::new_with_signer(
+                        self.token_program.to_account_info(),
+                        token::Transfer {
+                            from: group[1].clone(),
+                            to: group[2].clone(),
+                            authority: self.basket.to_account_info(),
+                        },
+                        &[seeds],
+                    ),
+                    share,
+                )?;
+                reserve.amount = reserve
+                    .amount
+                    .checked_sub(share)
+                    .ok_or(ContractError::ArithmeticOverflow)?;
+            }
+            release_empty_reserve(&mut self.basket, reserve, self.redeemer.to_account_info())?;
+        }
+
+        self.basket.total_deposited = self
+            .basket
+            .total_deposited
+            .checked_sub(amount)
+            .ok_or(ContractError::ArithmeticOverflow)?;
+
+        Ok(())
+    }
+}
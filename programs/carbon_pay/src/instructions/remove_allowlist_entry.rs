@@ -0,0 +1,25 @@
+use crate::errors::ContractError;
+use crate::state::{AllowlistEntry, PlatformConfig};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct RemoveAllowlistEntry<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [b"platform_config"],
+        bump = platform_config.bump,
+        constraint = platform_config.authority == authority.key() @ ContractError::UnauthorizedAdmin,
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+
+    /// entry is closed and its rent returned to the authority
+    #[account(
+        mut,
+        close = authority,
+        seeds = [b"allowlist", allowlist_entry.scope.as_ref(), allowlist_entry.wallet.as_ref()],
+        bump = allowlist_entry.bump,
+    )]
+    pub allowlist_entry: Account<'info, AllowlistEntry>,
+}
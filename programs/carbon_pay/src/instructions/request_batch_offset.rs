@@ -0,0 +1,189 @@
+use crate::errors::ContractError;
+use crate::state::{
+    CarbonCredits, Holdings, OffsetBatch, OffsetRequest, Project, Purchase, RequestStatus,
+};
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Burn, Mint, Token, TokenAccount};
+
+/// Offsets tokens from several of the requester's purchases of one project in a single request.
+/// `remaining_accounts` must hold, for every entry of `amounts`:
+/// `[purchase (mut), nft_account]`
+/// where `nft_account` holds the purchase's certificate. Certificates are kept rather than
+/// re-minted, each purchase records the offset in `uncertified_offset` instead. Large batches
+/// need a v0 transaction with the purchases in an address lookup table.
+#[derive(Accounts)]
+#[instruction(amounts: Vec<u64>, request_id: String)]
+pub struct RequestBatchOffset<'info> {
+    #[account(mut)]
+    pub offset_requester: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"project", project.owner.as_ref(), project.mint.as_ref()],
+        bump = project.project_bump,
+    )]
+    pub project: Box<Account<'info, Project>>,
+
+    /// The project's fungible token mint
+    #[account(
+        mut,
+        constraint = token_mint.key() == project.token_mint @ ContractError::InvalidProjectMint,
+    )]
+    pub token_mint: Box<Account<'info, Mint>>,
+
+    /// Requester's token account for the project's fungible tokens - tokens will be burned
+    #[account(
+        mut,
+        token::mint = token_mint,
+        token::authority = offset_requester,
+    )]
+    pub buyer_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// tokens in the requester's wallet backing their purchases of this project
+    #[account(
+        mut,
+        seeds = [b"holdings", offset_requester.key().as_ref(), project.key().as_ref()],
+        bump = holdings.bump,
+    )]
+    pub holdings: Box<Account<'info, Holdings>>,
+
+    #[account(
+        mut,
+        seeds = [b"carbon_credits"],
+        bump = carbon_credits.bump,
+    )]
+    pub carbon_credits: Box<Account<'info, CarbonCredits>>,
+
+    /// aggregated OffsetRequest, keyed by the project since it spans several purchases
+    #[account(
+        init,
+        payer = offset_requester,
+        space = OffsetRequest::DISCRIMINATOR_SIZE + OffsetRequest::INIT_SPACE,
+        seeds = [b"offset_request", offset_requester.key().as_ref(), project.key().as_ref(), request_id.as_bytes()],
+        bump
+    )]
+    pub offset_request: Box<Account<'info, OffsetRequest>>,
+
+    #[account(
+        init,
+        payer = offset_requester,
+        space = OffsetBatch::DISCRIMINATOR_SIZE + OffsetBatch::INIT_SPACE,
+        seeds = [b"offset_batch", offset_request.key().as_ref()],
+        bump
+    )]
+    pub offset_batch: Box<Account<'info, OffsetBatch>>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> RequestBatchOffset<'info> {
+    const ACCOUNTS_PER_PURCHASE: usize = 2;
+
+    pub fn handler(
+        &mut self,
+        amounts: Vec<u64>,
+        request_id: String,
+        remaining_accounts: &'info [AccountInfo<'info>],
+        bumps: &RequestBatchOffsetBumps,
+    ) -> Result<()> {
+        // 1) validate every purchase and take its share
+        require!(!amounts.is_empty(), ContractError::InvalidAmount);
+        require!(
+            amounts.len() <= OffsetBatch::MAX_PURCHASES,
+            ContractError::TooManyBatchPurchases
+        );
+        require!(
+            remaining_accounts.len() == amounts.len() * Self::ACCOUNTS_PER_PURCHASE,
+            ContractError::InvalidBatchPurchases
+        );
+
+        let mut purchases = Vec::with_capacity(amounts.len());
+        let mut total: u64 = 0;
+        let groups = remaining_accounts.chunks_exact(Self::ACCOUNTS_PER_PURCHASE);
+        for (group, &amount) in groups.zip(amounts.iter()) {
+            require!(amount > 0, ContractError::InvalidAmount);
+            require!(
+                !purchases.contains(&group[0].key()),
+                ContractError::InvalidBatchPurchases
+            );
+
+            let mut purchase = Account::<Purchase>::try_from(&group[0])?;
+            require_keys_eq!(
+                purchase.buyer,
+                self.offset_requester.key(),
+                ContractError::NotPurchaseOwner
+            );
+            require_keys_eq!(purchase.project, self.project.key(), ContractError::InvalidProject);
+
+            // the certificate must sit with the owner, as for a single offset
+            let nft_account = Account::<TokenAccount>::try_from(&group[1])?;
+            require_keys_eq!(nft_account.mint, purchase.certificate_mint, ContractError::InvalidNFTAccount);
+            require_keys_eq!(nft_account.owner, self.offset_requester.key(), ContractError::InvalidNFTAccount);
+            require!(nft_account.amount > 0, ContractError::InvalidNFTAccount);
+
+            purchase.remaining_amount = purchase
+                .remaining_amount
+                .checked_sub(amount)
+                .ok_or(ContractError::InsufficientRemainingTokens)?;
+            purchase.uncertified_offset = purchase
+                .uncertified_offset
+                .checked_add(amount)
+                .ok_or(ContractError::ArithmeticOverflow)?;
+            purchase.exit(&crate::ID)?;
+
+            purchases.push(purchase.key());
+            total = total.checked_add(amount).ok_or(ContractError::ArithmeticOverflow)?;
+        }
+        require!(
+            self.buyer_token_account.amount >= total,
+            ContractError::InsufficientFungibleTokens
+        );
+
+        // 2) burn the fungible tokens being offset
+        token::burn(
+            CpiContext::new(
+                self.token_program.to_account_info(),
+                Burn {
+                    mint: self.token_mint.to_account_info(),
+                    from: self.buyer_token_account.to_account_info(),
+                    authority: self.offset_requester.to_account_info(),
+                },
+            ),
+            total,
+        )?;
+
+        // 3) update on-chain state
+        let credits = self.project.platform_units(total)?;
+        self.carbon_credits.offset_credits = self
+            .carbon_credits
+            .offset_credits
+            .checked_add(credits)
+            .ok_or(ContractError::ArithmeticOverflow)?;
+        self.project.record_offset(total)?;
+        self.holdings.remove(total)?;
+
+        // 4) record the aggregated request
+        self.offset_request.set_inner(OffsetRequest {
+            offset_requester: self.offset_requester.key(),
+            purchase: Pubkey::default(),
+            project: self.project.key(),
+            amount: total,
+            request_id,
+            status: RequestStatus::Pending,
+            request_date: Clock::get()?.unix_timestamp,
+            processed_date: 0,
+            request_bump: bumps.offset_request,
+            processor: None,
+        });
+        self.offset_batch.set_inner(OffsetBatch {
+            offset_request: self.offset_request.key(),
+            purchases,
+            amounts,
+            bump: bumps.offset_batch,
+        });
+
+        msg!("Batch offset request for {} tokens", total);
+        Ok(())
+    }
+}
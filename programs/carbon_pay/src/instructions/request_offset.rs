@@ -1,8 +1,11 @@
-use crate::state::{CarbonCredits, OffsetRequest, Project, Purchase, RequestStatus};
+use crate::state::{
+    CarbonCredits, Holdings, OffsetDelegate, OffsetRequest, Project, Purchase, RequestStatus,
+};
 use crate::errors::ContractError;
 use anchor_lang::prelude::*;
 use anchor_spl::{
-    token::{self, Mint, Token, TokenAccount, MintTo, Burn},
+    associated_token::get_associated_token_address,
+    token::{self, Mint, Token, TokenAccount, MintTo, Burn, CloseAccount},
     metadata::{
         create_metadata_accounts_v3,
         mpl_token_metadata::types::{Creator, DataV2},
@@ -13,20 +16,35 @@ use anchor_spl::{
 #[derive(Accounts)]
 #[instruction(amount: u64, request_id: String)]
 pub struct RequestOffset<'info> {
-    /// who is asking for the offset
+    /// who is asking for the offset, the purchase owner or its approved delegate
     #[account(mut)]
     pub offset_requester: Signer<'info>,
 
-    /// the original Purchase, must belong to requester
+    /// the original Purchase, must belong to requester unless a delegate is acting
     #[account(
         mut,
-        constraint = purchase.buyer == offset_requester.key()      @ ContractError::NotPurchaseOwner,
+        constraint = purchase.buyer == offset_requester.key() || offset_delegate.is_some() @ ContractError::NotPurchaseOwner,
         constraint = purchase.remaining_amount >= amount           @ ContractError::InsufficientRemainingTokens,
-        seeds = [b"purchase", offset_requester.key().as_ref(), purchase.project.as_ref(), purchase.nft_mint.as_ref()],
+        seeds = [b"purchase", purchase.buyer.as_ref(), purchase.project.as_ref(), purchase.nft_mint.as_ref()],
         bump = purchase.purchase_bump,
     )]
     pub purchase: Box<Account<'info, Purchase>>,
 
+    /// owner's approval for a custodian to offset on their behalf, escrows the certificate and allowance
+    #[account(
+        mut,
+        seeds = [b"offset_delegate", purchase.key().as_ref()],
+        bump = offset_delegate.bump,
+    )]
+    pub offset_delegate: Option<Box<Account<'info, OffsetDelegate>>>,
+
+    /// CHECK: the purchase owner, passed when a delegate is acting to get back the rent of a used-up allowance escrow
+    #[account(
+        mut,
+        address = purchase.buyer @ ContractError::NotPurchaseOwner,
+    )]
+    pub purchase_owner: Option<UncheckedAccount<'info>>,
+
     /// the Project
     #[account(
         mut,
@@ -35,13 +53,21 @@ pub struct RequestOffset<'info> {
     )]
     pub project: Box<Account<'info, Project>>,
 
-    /// the original NFT mint & token account - will be burned
-    #[account(mut, constraint = original_nft_mint.key() == purchase.nft_mint @ ContractError::InvalidNFTMint)]
+    /// tokens in the owner's wallet backing their purchases of this project, unchanged when a delegate burns from its escrow
+    #[account(
+        mut,
+        seeds = [b"holdings", purchase.buyer.as_ref(), project.key().as_ref()],
+        bump = holdings.bump,
+    )]
+    pub holdings: Box<Account<'info, Holdings>>,
+
+    /// the current certificate mint & token account - will be burned
+    #[account(mut, constraint = original_nft_mint.key() == purchase.certificate_mint @ ContractError::InvalidNFTMint)]
     pub original_nft_mint: Box<Account<'info, Mint>>,
+    /// held by the purchase owner, or by the delegate PDA when a delegate is acting
     #[account(
         mut,
         token::mint = original_nft_mint,
-        token::authority = offset_requester,
         constraint = original_nft_account.amount > 0 @ ContractError::InvalidNFTAccount,
     )]
     pub original_nft_account: Box<Account<'info, TokenAccount>>,
@@ -49,16 +75,22 @@ pub struct RequestOffset<'info> {
     /// NEW NFT mint & ATA must be created client-side beforehand (for partial offsets)
     #[account(mut)]
     pub new_nft_mint: Box<Account<'info, Mint>>,
+    /// same holder as `original_nft_account`, so a delegation carries over to the new certificate.
+    /// For a delegate it must be the delegate PDA's ATA, the account `revoke_offset_delegate` hands back.
     #[account(
         mut,
         token::mint = new_nft_mint,
-        token::authority = offset_requester,
     )]
     pub new_nft_account: Box<Account<'info, TokenAccount>>,
 
     /// Metadata account for the new NFT
     /// CHECK: will be initialized by CPI
-    #[account(mut)]
+    #[account(
+        mut,
+        seeds = [b"metadata", token_metadata_program.key().as_ref(), new_nft_mint.key().as_ref()],
+        bump,
+        seeds::program = token_metadata_program.key(),
+    )]
     pub new_nft_metadata: UncheckedAccount<'info>,
 
     /// The project's fungible token mint
@@ -68,11 +100,10 @@ pub struct RequestOffset<'info> {
     )]
     pub token_mint: Box<Account<'info, Mint>>,
 
-    /// Buyer's token account for the project's fungible tokens - tokens will be burned
+    /// Buyer's token account for the project's fungible tokens, or the delegate's escrow - tokens will be burned
     #[account(
         mut, 
         token::mint = token_mint,
-        token::authority = offset_requester,
         constraint = buyer_token_account.amount >= amount @ ContractError::InsufficientFungibleTokens,
     )]
     pub buyer_token_account: Box<Account<'info, TokenAccount>>,
@@ -85,12 +116,12 @@ pub struct RequestOffset<'info> {
     )]
     pub carbon_credits: Box<Account<'info, CarbonCredits>>,
 
-    /// OffsetRequest record
+    /// OffsetRequest record, keyed by the purchase owner as beneficiary
     #[account(
         init,
         payer = offset_requester,
         space = OffsetRequest::DISCRIMINATOR_SIZE + OffsetRequest::INIT_SPACE,
-        seeds = [b"offset_request", offset_requester.key().as_ref(), purchase.key().as_ref(), request_id.as_bytes()],
+        seeds = [b"offset_request", purchase.buyer.as_ref(), purchase.key().as_ref(), request_id.as_bytes()],
         bump
     )]
     pub offset_request: Box<Account<'info, OffsetRequest>>,
@@ -102,6 +133,49 @@ pub struct RequestOffset<'info> {
 }
 
 impl<'info> RequestOffset<'info> {
+    /// The delegate PDA when a delegate is acting, `None` when the owner is
+    fn acting_delegate(&self) -> Option<&Account<'info, OffsetDelegate>> {
+        match &self.offset_delegate {
+            Some(delegate) if self.offset_requester.key() != self.purchase.buyer => Some(delegate),
+            _ => None,
+        }
+    }
+
+    /// Burn from the holder's account, signed by the owner or by the delegate PDA that escrows it
+    fn burn_owner_tokens(
+        &self,
+        mint: AccountInfo<'info>,
+        from: AccountInfo<'info>,
+        amount: u64,
+    ) -> Result<()> {
+        let accounts = Burn {
+            mint,
+            from,
+            authority: self.offset_requester.to_account_info(),
+        };
+        match self.acting_delegate() {
+            Some(delegate) => {
+                let purchase_key = self.purchase.key();
+                let seeds: &[&[u8]] = &[b"offset_delegate", purchase_key.as_ref(), &[delegate.bump]];
+                token::burn(
+                    CpiContext::new_with_signer(
+                        self.token_program.to_account_info(),
+                        Burn {
+                            authority: delegate.to_account_info(),
+                            ..accounts
+                        },
+                        &[seeds],
+                    ),
+                    amount,
+                )
+            }
+            None => token::burn(
+                CpiContext::new(self.token_program.to_account_info(), accounts),
+                amount,
+            ),
+        }
+    }
+
     pub fn handler(
         &mut self,
         amount: u64,
@@ -119,6 +193,28 @@ impl<'info> RequestOffset<'info> {
             ContractError::InsufficientFungibleTokens
         );
 
+        // a delegate burns through the OffsetDelegate PDA, within its allowance
+        if self.offset_requester.key() != self.purchase.buyer {
+            let delegate = self
+                .offset_delegate
+                .as_mut()
+                .ok_or(ContractError::NotPurchaseOwner)?;
+            delegate.consume(&self.offset_requester.key(), amount, Clock::get()?.unix_timestamp)?;
+        }
+
+        // the certificate and tokens must sit with the owner, or in the acting delegate's escrow
+        let holder = self.acting_delegate().map_or(self.purchase.buyer, |delegate| delegate.key());
+        require_keys_eq!(self.original_nft_account.owner, holder, ContractError::InvalidNFTAccount);
+        require_keys_eq!(self.new_nft_account.owner, holder, ContractError::InvalidNFTAccount);
+        require_keys_eq!(self.buyer_token_account.owner, holder, anchor_lang::error::ErrorCode::ConstraintTokenOwner);
+        if self.acting_delegate().is_some() {
+            require_keys_eq!(
+                self.new_nft_account.key(),
+                get_associated_token_address(&holder, &self.new_nft_mint.key()),
+                ContractError::InvalidNFTAccount
+            );
+        }
+
         // 2) compute new remaining
         let remaining = self
             .purchase
@@ -127,28 +223,16 @@ impl<'info> RequestOffset<'info> {
             .ok_or(ContractError::ArithmeticOverflow)?;
 
         // 3) burn original NFT
-        token::burn(
-            CpiContext::new(
-                self.token_program.to_account_info(),
-                Burn {
-                    mint: self.original_nft_mint.to_account_info(),
-                    from: self.original_nft_account.to_account_info(),
-                    authority: self.offset_requester.to_account_info(),
-                },
-            ),
+        self.burn_owner_tokens(
+            self.original_nft_mint.to_account_info(),
+            self.original_nft_account.to_account_info(),
             1,
         )?;
 
         // 4) burn fungible tokens that are being offset
-        token::burn(
-            CpiContext::new(
-                self.token_program.to_account_info(),
-                Burn {
-                    mint: self.token_mint.to_account_info(),
-                    from: self.buyer_token_account.to_account_info(),
-                    authority: self.offset_requester.to_account_info(),
-                },
-            ),
+        self.burn_owner_tokens(
+            self.token_mint.to_account_info(),
+            self.buyer_token_account.to_account_info(),
             amount,
         )?;
 
@@ -201,14 +285,60 @@ impl<'info> RequestOffset<'info> {
                 true,
                 None,
             )?;
+
+            // the new certificate now stands for the position
+            self.purchase.certificate_mint = self.new_nft_mint.key();
+            self.purchase.uncertified_offset = 0;
+
+            // the burned certificate's escrow is empty, its rent goes to the requester who funded the replacement
+            if let Some(delegate) = self.acting_delegate() {
+                let purchase_key = self.purchase.key();
+                let seeds: &[&[u8]] = &[b"offset_delegate", purchase_key.as_ref(), &[delegate.bump]];
+                token::close_account(CpiContext::new_with_signer(
+                    self.token_program.to_account_info(),
+                    CloseAccount {
+                        account: self.original_nft_account.to_account_info(),
+                        destination: self.offset_requester.to_account_info(),
+                        authority: delegate.to_account_info(),
+                    },
+                    &[seeds],
+                ))?;
+            }
+        }
+
+        // a used-up allowance leaves the escrow empty, its rent goes back to the owner who funded it
+        let used_up = self.acting_delegate().filter(|delegate| delegate.remaining_amount == 0);
+        if let Some((delegate, bump)) = used_up.map(|delegate| (delegate.to_account_info(), delegate.bump)) {
+            self.buyer_token_account.reload()?;
+            if self.buyer_token_account.amount == 0 {
+                let owner = self
+                    .purchase_owner
+                    .as_ref()
+                    .ok_or(ContractError::NotPurchaseOwner)?;
+                let purchase_key = self.purchase.key();
+                let seeds: &[&[u8]] = &[b"offset_delegate", purchase_key.as_ref(), &[bump]];
+                token::close_account(CpiContext::new_with_signer(
+                    self.token_program.to_account_info(),
+                    CloseAccount {
+                        account: self.buyer_token_account.to_account_info(),
+                        destination: owner.to_account_info(),
+                        authority: delegate,
+                    },
+                    &[seeds],
+                ))?;
+            }
         }
 
         // 6) update on-chain state
         self.purchase.remaining_amount = remaining;
+        if self.acting_delegate().is_none() {
+            self.holdings.remove(amount)?;
+        }
+        let credits = self.project.platform_units(amount)?;
         self.carbon_credits.offset_credits = self
             .carbon_credits
             .offset_credits
-            .checked_add(amount)
+            .checked_add(credits)
             .ok_or(ContractError::ArithmeticOverflow)?;
         
         // Update project's offset_amount
@@ -220,7 +350,7 @@ impl<'info> RequestOffset<'info> {
 
         // 7) record the Request
         self.offset_request.set_inner(OffsetRequest {
-            offset_requester: self.offset_requester.key(),
+            offset_requester: self.purchase.buyer,
             purchase: self.purchase.key(),
             project: self.project.key(),
             amount,
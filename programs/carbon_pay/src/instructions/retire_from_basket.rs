@@ -0,0 +1,133 @@
+use crate::errors::ContractError;
+use crate::events::BasketRetired;
+use crate::instructions::{load_basket_reserves, release_empty_reserve};
+use crate::state::{pro_rata_shares, Basket, CarbonCredits, Project};
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Burn, Mint, Token, TokenAccount};
+
+/// Burns basket tokens and retires a pro-rata share of every project in the basket.
+/// `remaining_accounts` must hold, for every reserve of the basket:
+/// `[basket_reserve (mut), basket_vault (mut), token_mint (mut), project (mut)]`
+/// A reserve the retirement empties is closed to the retiree, freeing its slot in the basket.
+#[derive(Accounts)]
+pub struct RetireFromBasket<'info> {
+    #[account(mut)]
+    pub retiree: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"basket", basket.basket_id.to_le_bytes().as_ref()],
+        bump = basket.bump,
+    )]
+    pub basket: Box<Account<'info, Basket>>,
+
+    #[account(
+        mut,
+        seeds = [b"basket_mint", basket.key().as_ref()],
+        bump = basket.mint_bump,
+    )]
+    pub basket_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        token::mint = basket_mint,
+        token::authority = retiree,
+    )]
+    pub retiree_basket_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [b"carbon_credits"],
+        bump = carbon_credits.bump,
+    )]
+    pub carbon_credits: Box<Account<'info, CarbonCredits>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> RetireFromBasket<'info> {
+    pub fn handler(
+        &mut self,
+        amount: u64,
+        remaining_accounts: &'info [AccountInfo<'info>],
+    ) -> Result<()> {
+        require!(amount > 0, ContractError::InvalidAmount);
+        let mut reserves = load_basket_reserves(&self.basket, remaining_accounts, 4)?;
+        let balances: Vec<u64> = reserves.iter().map(|reserve| reserve.amount).collect();
+        let shares = pro_rata_shares(&balances, amount)?;
+
+        // 1) burn the basket tokens
+        token::burn(
+            CpiContext::new(
+                self.token_program.to_account_info(),
+                Burn {
+                    mint: self.basket_mint.to_account_info(),
+                    from: self.retiree_basket_account.to_account_info(),
+                    authority: self.retiree.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+
+        // 2) burn each project's share from its vault and record the offset on the project
+        let basket_id = self.basket.basket_id.to_le_bytes();
+        let seeds: &[&[u8]] = &[b"basket", basket_id.as_ref(), &[self.basket.bump]];
+        let mut credits: u64 = 0;
+        for ((reserve, group), share) in reserves
+            .iter_mut()
+            .zip(remaining_accounts.chunks(4))
+            .zip(shares)
+        {
+            require_keys_eq!(reserve.token_mint, group[2].key(), ContractError::InvalidProjectMint);
+            require_keys_eq!(reserve.project, group[3].key(), ContractError::InvalidProject);
+            if share > 0 {
+                token::burn(
+                    CpiContext::new_with_signer(
+                        self.token_program.to_account_info(),
+                        Burn {
+                            mint: group[2].clone(),
+                            from: group[1].clone(),
+                            authority: self.basket.to_account_info(),
+                        },
+                        &[seeds],
+                    ),
+                    share,
+                )?;
+
+                let mut project = Account::<Project>::try_from(&group[3])?;
+                project.record_offset(share)?;
+                credits = credits
+                    .checked_add(project.platform_units(share)?)
+                    .ok_or(ContractError::ArithmeticOverflow)?;
+                project.exit(&crate::ID)?;
+
+                reserve.amount = reserve
+                    .amount
+                    .checked_sub(share)
+                    .ok_or(ContractError::ArithmeticOverflow)?;
+            }
+            release_empty_reserve(&mut self.basket, reserve, self.retiree.to_account_info())?;
+        }
+
+        // 3) platform totals
+        self.basket.total_deposited = self
+            .basket
+            .total_deposited
+            .checked_sub(amount)
+            .ok_or(ContractError::ArithmeticOverflow)?;
+        self.carbon_credits.offset_credits = self
+            .carbon_credits
+            .offset_credits
+            .checked_add(credits)
+            .ok_or(ContractError::ArithmeticOverflow)?;
+
+        emit!(BasketRetired {
+            basket: self.basket.key(),
+            retiree: self.retiree.key(),
+            amount,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+}
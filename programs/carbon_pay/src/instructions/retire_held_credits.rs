@@ -0,0 +1,120 @@
+use crate::errors::ContractError;
+use crate::state::{CarbonCredits, Holdings, OffsetRequest, Project, RequestStatus};
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Burn, Mint, Token, TokenAccount};
+
+/// Retires project tokens held outside any purchase, e.g. redeemed from a basket.
+/// Like `purchase_and_retire` there is no certificate to burn; the OffsetRequest is the
+/// record, under the same `retirement` seeds and with `purchase` left as the default key.
+/// Tokens that back one of the holder's purchases are not held outside it and are refused.
+#[derive(Accounts)]
+#[instruction(amount: u64, request_id: String)]
+pub struct RetireHeldCredits<'info> {
+    #[account(mut)]
+    pub holder: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"project", project.owner.as_ref(), project.mint.as_ref()],
+        bump = project.project_bump,
+    )]
+    pub project: Box<Account<'info, Project>>,
+
+    /// The project's fungible token mint
+    #[account(
+        mut,
+        constraint = token_mint.key() == project.token_mint @ ContractError::InvalidProjectMint,
+    )]
+    pub token_mint: Box<Account<'info, Mint>>,
+
+    /// Holder's token account for the project's fungible tokens - tokens will be burned
+    #[account(
+        mut,
+        token::mint = token_mint,
+        token::authority = holder,
+        constraint = holder_token_account.amount >= amount @ ContractError::InsufficientFungibleTokens,
+    )]
+    pub holder_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: tokens in the holder's wallet backing their purchases of this project, if any
+    #[account(
+        seeds = [b"holdings", holder.key().as_ref(), project.key().as_ref()],
+        bump,
+    )]
+    pub holdings: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"carbon_credits"],
+        bump = carbon_credits.bump,
+    )]
+    pub carbon_credits: Box<Account<'info, CarbonCredits>>,
+
+    /// retirement record, keyed by the holder as beneficiary
+    #[account(
+        init,
+        payer = holder,
+        space = OffsetRequest::DISCRIMINATOR_SIZE + OffsetRequest::INIT_SPACE,
+        seeds = [b"retirement", holder.key().as_ref(), project.key().as_ref(), request_id.as_bytes()],
+        bump
+    )]
+    pub offset_request: Box<Account<'info, OffsetRequest>>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> RetireHeldCredits<'info> {
+    pub fn handler(
+        &mut self,
+        amount: u64,
+        request_id: String,
+        bumps: &RetireHeldCreditsBumps,
+    ) -> Result<()> {
+        require!(amount > 0, ContractError::InvalidAmount);
+        let backed = Holdings::backed(&self.holdings.to_account_info())?;
+        require!(
+            self.holder_token_account.amount.saturating_sub(backed) >= amount,
+            ContractError::TokensBackPurchases
+        );
+
+        // 1) burn the tokens being retired
+        token::burn(
+            CpiContext::new(
+                self.token_program.to_account_info(),
+                Burn {
+                    mint: self.token_mint.to_account_info(),
+                    from: self.holder_token_account.to_account_info(),
+                    authority: self.holder.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+
+        // 2) update on-chain state
+        self.project.record_offset(amount)?;
+        let credits = self.project.platform_units(amount)?;
+        self.carbon_credits.offset_credits = self
+            .carbon_credits
+            .offset_credits
+            .checked_add(credits)
+            .ok_or(ContractError::ArithmeticOverflow)?;
+
+        // 3) record the retirement
+        self.offset_request.set_inner(OffsetRequest {
+            offset_requester: self.holder.key(),
+            purchase: Pubkey::default(),
+            project: self.project.key(),
+            amount,
+            request_id,
+            status: RequestStatus::Pending,
+            request_date: Clock::get()?.unix_timestamp,
+            processed_date: 0,
+            request_bump: bumps.offset_request,
+            processor: None,
+        });
+
+        msg!("Retired {} held tokens", amount);
+        Ok(())
+    }
+}
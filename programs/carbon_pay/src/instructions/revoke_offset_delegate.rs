@@ -0,0 +1,128 @@
+use crate::errors::ContractError;
+use crate::state::{Holdings, OffsetDelegate, Project, Purchase};
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{self, CloseAccount, Mint, Token, TokenAccount, Transfer},
+};
+
+#[derive(Accounts)]
+pub struct RevokeOffsetDelegate<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        constraint = purchase.buyer == owner.key() @ ContractError::NotPurchaseOwner,
+        seeds = [b"purchase", owner.key().as_ref(), purchase.project.as_ref(), purchase.nft_mint.as_ref()],
+        bump = purchase.purchase_bump,
+    )]
+    pub purchase: Box<Account<'info, Purchase>>,
+
+    #[account(
+        constraint = project.key() == purchase.project @ ContractError::InvalidProject,
+        seeds = [b"project", project.owner.as_ref(), project.mint.as_ref()],
+        bump = project.project_bump,
+    )]
+    pub project: Box<Account<'info, Project>>,
+
+    /// tokens in the owner's wallet backing their purchases of this project, the returned allowance counts again
+    #[account(
+        mut,
+        seeds = [b"holdings", owner.key().as_ref(), project.key().as_ref()],
+        bump = holdings.bump,
+    )]
+    pub holdings: Box<Account<'info, Holdings>>,
+
+    #[account(
+        mut,
+        close = owner,
+        seeds = [b"offset_delegate", purchase.key().as_ref()],
+        bump = offset_delegate.bump,
+    )]
+    pub offset_delegate: Box<Account<'info, OffsetDelegate>>,
+
+    /// the certificate currently held for the purchase, re-minted if the delegate offset part of it
+    #[account(constraint = certificate_mint.key() == purchase.certificate_mint @ ContractError::InvalidNFTMint)]
+    pub certificate_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        associated_token::mint = certificate_mint,
+        associated_token::authority = offset_delegate,
+    )]
+    pub delegate_nft_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = owner,
+        associated_token::mint = certificate_mint,
+        associated_token::authority = owner,
+    )]
+    pub nft_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(constraint = token_mint.key() == project.token_mint @ ContractError::InvalidProjectMint)]
+    pub token_mint: Box<Account<'info, Mint>>,
+
+    /// allowance escrow, already closed by `request_offset` once the delegate used it up
+    #[account(
+        mut,
+        associated_token::mint = token_mint,
+        associated_token::authority = offset_delegate,
+    )]
+    pub delegate_token_account: Option<Box<Account<'info, TokenAccount>>>,
+
+    #[account(
+        mut,
+        token::mint = token_mint,
+        token::authority = owner,
+    )]
+    pub owner_token_account: Box<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> RevokeOffsetDelegate<'info> {
+    pub fn handler(&mut self) -> Result<()> {
+        let purchase_key = self.purchase.key();
+        let seeds: &[&[u8]] = &[b"offset_delegate", purchase_key.as_ref(), &[self.offset_delegate.bump]];
+
+        let unused = self.delegate_token_account.as_ref().map_or(0, |escrow| escrow.amount);
+        self.holdings.add(unused)?;
+
+        // hand back the certificate and the unused allowance, then close the escrows
+        let escrows = [
+            Some((&self.delegate_nft_account, &self.nft_account)),
+            self.delegate_token_account
+                .as_ref()
+                .map(|escrow| (escrow, &self.owner_token_account)),
+        ];
+        for (from, to) in escrows.into_iter().flatten() {
+            if from.amount > 0 {
+                token::transfer(
+                    CpiContext::new_with_signer(
+                        self.token_program.to_account_info(),
+                        Transfer {
+                            from: from.to_account_info(),
+                            to: to.to_account_info(),
+                            authority: self.offset_delegate.to_account_info(),
+                        },
+                        &[seeds],
+                    ),
+                    from.amount,
+                )?;
+            }
+            token::close_account(CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                CloseAccount {
+                    account: from.to_account_info(),
+                    destination: self.owner.to_account_info(),
+                    authority: self.offset_delegate.to_account_info(),
+                },
+                &[seeds],
+            ))?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,29 @@
+use crate::errors::ContractError;
+use crate::state::{AllowlistEntry, PlatformConfig};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct SetAllowlistExpiry<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [b"platform_config"],
+        bump = platform_config.bump,
+        constraint = platform_config.authority == authority.key() @ ContractError::UnauthorizedAdmin,
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"allowlist", allowlist_entry.scope.as_ref(), allowlist_entry.wallet.as_ref()],
+        bump = allowlist_entry.bump,
+    )]
+    pub allowlist_entry: Account<'info, AllowlistEntry>,
+}
+
+impl<'info> SetAllowlistExpiry<'info> {
+    pub fn handler(&mut self, expires_at: i64) -> Result<()> {
+        self.allowlist_entry.expires_at = expires_at;
+        Ok(())
+    }
+}
@@ -0,0 +1,34 @@
+use crate::errors::ContractError;
+use crate::state::{PlatformConfig, Project};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct SetAllowlistRequired<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"platform_config"],
+        bump = platform_config.bump,
+        constraint = platform_config.authority == authority.key() @ ContractError::UnauthorizedAdmin,
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+
+    /// project to toggle, omit to toggle the platform-wide requirement
+    #[account(
+        mut,
+        seeds = [b"project", project.owner.as_ref(), project.mint.as_ref()],
+        bump = project.project_bump,
+    )]
+    pub project: Option<Box<Account<'info, Project>>>,
+}
+
+impl<'info> SetAllowlistRequired<'info> {
+    pub fn handler(&mut self, required: bool) -> Result<()> {
+        match &mut self.project {
+            Some(project) => project.allowlist_required = required,
+            None => self.platform_config.allowlist_required = required,
+        }
+        Ok(())
+    }
+}
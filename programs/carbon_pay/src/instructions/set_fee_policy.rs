@@ -0,0 +1,24 @@
+use crate::errors::ContractError;
+use crate::state::{FeeRounding, PlatformConfig};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct SetFeePolicy<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"platform_config"],
+        bump = platform_config.bump,
+        constraint = platform_config.authority == authority.key() @ ContractError::UnauthorizedAdmin,
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+}
+
+impl<'info> SetFeePolicy<'info> {
+    pub fn handler(&mut self, fee_rounding: FeeRounding, min_fee: u64) -> Result<()> {
+        self.platform_config.fee_rounding = fee_rounding;
+        self.platform_config.min_fee = min_fee;
+        Ok(())
+    }
+}
@@ -0,0 +1,34 @@
+use crate::errors::ContractError;
+use crate::state::{PriceTier, Project};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct SetPriceTiers<'info> {
+    pub project_owner: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = project.owner == project_owner.key() @ ContractError::InvalidProjectOwner,
+        seeds = [b"project", project.owner.as_ref(), project.mint.as_ref()],
+        bump = project.project_bump,
+    )]
+    pub project: Box<Account<'info, Project>>,
+}
+
+impl<'info> SetPriceTiers<'info> {
+    pub fn handler(&mut self, price_tiers: Vec<PriceTier>) -> Result<()> {
+        require!(
+            price_tiers.len() <= Project::MAX_PRICE_TIERS,
+            ContractError::InvalidPriceTiers
+        );
+        // breakpoints must be strictly increasing and above zero
+        let mut previous: u64 = 0;
+        for tier in price_tiers.iter() {
+            require!(tier.min_amount > previous, ContractError::InvalidPriceTiers);
+            previous = tier.min_amount;
+        }
+
+        self.project.price_tiers = price_tiers;
+        Ok(())
+    }
+}
@@ -0,0 +1,29 @@
+use crate::errors::ContractError;
+use crate::state::{PlatformConfig, Project, ProjectCategory};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct SetProjectCategory<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [b"platform_config"],
+        bump = platform_config.bump,
+        constraint = platform_config.authority == authority.key() @ ContractError::UnauthorizedAdmin,
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"project", project.owner.as_ref(), project.mint.as_ref()],
+        bump = project.project_bump,
+    )]
+    pub project: Box<Account<'info, Project>>,
+}
+
+impl<'info> SetProjectCategory<'info> {
+    pub fn handler(&mut self, category: ProjectCategory) -> Result<()> {
+        self.project.category = category;
+        Ok(())
+    }
+}
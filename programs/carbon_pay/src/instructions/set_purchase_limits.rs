@@ -0,0 +1,39 @@
+use crate::errors::ContractError;
+use crate::state::Project;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct SetPurchaseLimits<'info> {
+    pub project_owner: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = project.owner == project_owner.key() @ ContractError::InvalidProjectOwner,
+        seeds = [b"project", project.owner.as_ref(), project.mint.as_ref()],
+        bump = project.project_bump,
+    )]
+    pub project: Box<Account<'info, Project>>,
+}
+
+impl<'info> SetPurchaseLimits<'info> {
+    pub fn handler(
+        &mut self,
+        min_purchase_amount: u64,
+        max_purchase_amount: u64,
+        max_per_buyer: u64,
+    ) -> Result<()> {
+        require!(
+            max_purchase_amount == 0 || min_purchase_amount <= max_purchase_amount,
+            ContractError::InvalidPurchaseLimits
+        );
+        require!(
+            max_per_buyer == 0 || min_purchase_amount <= max_per_buyer,
+            ContractError::InvalidPurchaseLimits
+        );
+
+        self.project.min_purchase_amount = min_purchase_amount;
+        self.project.max_purchase_amount = max_purchase_amount;
+        self.project.max_per_buyer = max_per_buyer;
+        Ok(())
+    }
+}
@@ -0,0 +1,38 @@
+use crate::errors::ContractError;
+use crate::state::Project;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct SetSaleWindow<'info> {
+    pub project_owner: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = project.owner == project_owner.key() @ ContractError::InvalidProjectOwner,
+        seeds = [b"project", project.owner.as_ref(), project.mint.as_ref()],
+        bump = project.project_bump,
+    )]
+    pub project: Box<Account<'info, Project>>,
+}
+
+impl<'info> SetSaleWindow<'info> {
+    pub fn handler(&mut self, sale_start: i64, sale_end: i64, early_access_start: i64) -> Result<()> {
+        require!(
+            sale_start >= 0 && sale_end >= 0 && early_access_start >= 0,
+            ContractError::InvalidSaleWindow
+        );
+        require!(
+            sale_end == 0 || sale_start < sale_end,
+            ContractError::InvalidSaleWindow
+        );
+        require!(
+            early_access_start == 0 || early_access_start < sale_start,
+            ContractError::InvalidSaleWindow
+        );
+
+        self.project.sale_start = sale_start;
+        self.project.sale_end = sale_end;
+        self.project.early_access_start = early_access_start;
+        Ok(())
+    }
+}
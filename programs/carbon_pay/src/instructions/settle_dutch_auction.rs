@@ -0,0 +1,82 @@
+use crate::errors::ContractError;
+use crate::events::AuctionSettled;
+use crate::state::{CarbonCredits, PlatformConfig, PlatformFee, Project};
+use crate::utils::{price_for, transfer_lamports_from_program_account};
+use anchor_lang::prelude::*;
+
+/// Permissionless: pays the auction proceeds at the clearing price to the owner and platform.
+/// Whatever buyers paid above the clearing price stays in the project PDA for refunds.
+#[derive(Accounts)]
+pub struct SettleDutchAuction<'info> {
+    #[account(
+        mut,
+        seeds = [b"project", project.owner.as_ref(), project.mint.as_ref()],
+        bump = project.project_bump,
+    )]
+    pub project: Box<Account<'info, Project>>,
+
+    /// CHECK: project owner receives the proceeds
+    #[account(
+        mut,
+        constraint = project_owner.key() == project.owner @ ContractError::InvalidProjectOwner
+    )]
+    pub project_owner: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"carbon_credits"],
+        bump = carbon_credits.bump,
+        constraint = carbon_credits.key() == project.carbon_pay_authority @ ContractError::InvalidCarbonPayAuthority
+    )]
+    pub carbon_credits: Box<Account<'info, CarbonCredits>>,
+
+    /// platform-wide settings (fee rounding and minimum fee)
+    #[account(seeds = [b"platform_config"], bump = platform_config.bump)]
+    pub platform_config: Box<Account<'info, PlatformConfig>>,
+}
+
+impl<'info> SettleDutchAuction<'info> {
+    pub fn handler(&mut self) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let sold_out = self.project.remaining_amount == 0;
+        let auction = self
+            .project
+            .active_auction()
+            .copied()
+            .ok_or(ContractError::NoActiveAuction)?;
+        require!(
+            sold_out || now >= auction.end_time(),
+            ContractError::AuctionNotEnded
+        );
+
+        // 1) proceeds at the clearing price
+        let proceeds = price_for(auction.sold, auction.last_price, self.project.decimals)?;
+        let PlatformFee { fee, remainder } = self
+            .platform_config
+            .platform_fee(proceeds, self.project.carbon_pay_fee)?;
+        let to_owner = proceeds.checked_sub(fee).ok_or(ContractError::ArithmeticOverflow)?;
+
+        // 2) pay out from the escrowed lamports
+        let project_info = self.project.to_account_info();
+        transfer_lamports_from_program_account(&project_info, &self.project_owner.to_account_info(), to_owner)?;
+        transfer_lamports_from_program_account(&project_info, &self.carbon_credits.to_account_info(), fee)?;
+        self.carbon_credits.add_fees(fee)?;
+
+        // 3) fixed pricing resumes for any unsold tokens
+        if let Some(auction) = self.project.auction.as_mut() {
+            auction.settled = true;
+        }
+
+        emit!(AuctionSettled {
+            project: self.project.key(),
+            amount: auction.sold,
+            decimals: self.project.decimals,
+            total_price: proceeds,
+            fee,
+            fee_remainder: remainder,
+            clearing_price: auction.last_price,
+            timestamp: now,
+        });
+        Ok(())
+    }
+}
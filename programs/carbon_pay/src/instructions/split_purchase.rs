@@ -0,0 +1,196 @@
+use crate::errors::ContractError;
+use crate::state::{Project, Purchase, Subscription};
+use crate::utils::{mint_purchase_nft, PurchaseNft};
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    metadata::Metadata,
+    token::{self, Burn, Mint, Token, TokenAccount},
+};
+
+#[derive(Accounts)]
+#[instruction(amount: u64)]
+pub struct SplitPurchase<'info> {
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    #[account(
+        constraint = project.key() == purchase.project @ ContractError::InvalidProject,
+        seeds = [b"project", project.owner.as_ref(), project.mint.as_ref()],
+        bump = project.project_bump,
+    )]
+    pub project: Box<Account<'info, Project>>,
+
+    /// the position being divided, must belong to the buyer
+    #[account(
+        mut,
+        constraint = purchase.buyer == buyer.key() @ ContractError::NotPurchaseOwner,
+        constraint = purchase.remaining_amount >= amount @ ContractError::InsufficientRemainingTokens,
+        seeds = [b"purchase", buyer.key().as_ref(), project.key().as_ref(), purchase.nft_mint.as_ref()],
+        bump = purchase.purchase_bump,
+    )]
+    pub purchase: Box<Account<'info, Purchase>>,
+
+    /// the position's current certificate - will be burned
+    #[account(mut, constraint = certificate_mint.key() == purchase.certificate_mint @ ContractError::InvalidNFTMint)]
+    pub certificate_mint: Box<Account<'info, Mint>>,
+    #[account(
+        mut,
+        token::mint = certificate_mint,
+        token::authority = buyer,
+        constraint = nft_account.amount > 0 @ ContractError::InvalidNFTAccount,
+    )]
+    pub nft_account: Box<Account<'info, TokenAccount>>,
+
+    /// certificate mint for what the position keeps (create off-chain)
+    #[account(
+        mut,
+        constraint = replacement_nft_mint.mint_authority == Some(buyer.key()).into() @ ContractError::InvalidNFTMint
+    )]
+    pub replacement_nft_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        token::mint = replacement_nft_mint,
+        token::authority = buyer,
+    )]
+    pub replacement_nft_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: initialized by the Token Metadata program via CPI
+    #[account(
+        mut,
+        seeds = [b"metadata", token_metadata_program.key().as_ref(), replacement_nft_mint.key().as_ref()],
+        bump,
+        seeds::program = token_metadata_program.key(),
+    )]
+    pub replacement_nft_metadata: UncheckedAccount<'info>,
+
+    /// certificate mint for the carved-out position (create off-chain)
+    #[account(
+        mut,
+        constraint = new_nft_mint.mint_authority == Some(buyer.key()).into() @ ContractError::InvalidNFTMint
+    )]
+    pub new_nft_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        token::mint = new_nft_mint,
+        token::authority = buyer,
+    )]
+    pub new_nft_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: initialized by the Token Metadata program via CPI
+    #[account(
+        mut,
+        seeds = [b"metadata", token_metadata_program.key().as_ref(), new_nft_mint.key().as_ref()],
+        bump,
+        seeds::program = token_metadata_program.key(),
+    )]
+    pub new_nft_metadata: UncheckedAccount<'info>,
+
+    /// CHECK: a position a subscription fills stays whole with the subscriber
+    #[account(
+        seeds = [b"subscription", buyer.key().as_ref(), project.key().as_ref()],
+        bump,
+    )]
+    pub subscription: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        payer = buyer,
+        space = Purchase::DISCRIMINATOR_SIZE + Purchase::INIT_SPACE,
+        seeds = [b"purchase", buyer.key().as_ref(), project.key().as_ref(), new_nft_mint.key().as_ref()],
+        bump
+    )]
+    pub new_purchase: Box<Account<'info, Purchase>>,
+
+    pub token_program: Program<'info, Token>,
+    pub token_metadata_program: Program<'info, Metadata>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+impl<'info> SplitPurchase<'info> {
+    pub fn handler(&mut self, amount: u64, bumps: &SplitPurchaseBumps) -> Result<()> {
+        require!(amount > 0, ContractError::InvalidAmount);
+        require!(
+            !Subscription::fills(&self.subscription.to_account_info(), &self.purchase.key())?,
+            ContractError::PurchaseSubscribed
+        );
+        let remaining = self
+            .purchase
+            .remaining_amount
+            .checked_sub(amount)
+            .ok_or(ContractError::ArithmeticOverflow)?;
+
+        // 1) burn the source certificate, it no longer states the position's amount
+        token::burn(
+            CpiContext::new(
+                self.token_program.to_account_info(),
+                Burn {
+                    mint: self.certificate_mint.to_account_info(),
+                    from: self.nft_account.to_account_info(),
+                    authority: self.buyer.to_account_info(),
+                },
+            ),
+            1,
+        )?;
+
+        // 2) if anything is left, re-mint the source certificate for the remaining balance
+        if remaining > 0 {
+            mint_purchase_nft(
+                PurchaseNft {
+                    nft_mint: self.replacement_nft_mint.to_account_info(),
+                    nft_account: self.replacement_nft_account.to_account_info(),
+                    nft_metadata: self.replacement_nft_metadata.to_account_info(),
+                    owner: self.buyer.to_account_info(),
+                    token_program: self.token_program.to_account_info(),
+                    token_metadata_program: self.token_metadata_program.to_account_info(),
+                    system_program: self.system_program.to_account_info(),
+                    rent: self.rent.to_account_info(),
+                },
+                format!("Carbon Credits - Remaining: {}", remaining),
+                format!("https://carbonpay.com/purchases/{}/remaining", self.replacement_nft_mint.key()),
+            )?;
+            self.purchase.certificate_mint = self.replacement_nft_mint.key();
+            self.purchase.uncertified_offset = 0;
+        }
+
+        // 3) certificate for the new position
+        mint_purchase_nft(
+            PurchaseNft {
+                nft_mint: self.new_nft_mint.to_account_info(),
+                nft_account: self.new_nft_account.to_account_info(),
+                nft_metadata: self.new_nft_metadata.to_account_info(),
+                owner: self.buyer.to_account_info(),
+                token_program: self.token_program.to_account_info(),
+                token_metadata_program: self.token_metadata_program.to_account_info(),
+                system_program: self.system_program.to_account_info(),
+                rent: self.rent.to_account_info(),
+            },
+            format!("Carbon Credits Purchase - {}", amount),
+            format!("https://carbonpay.com/purchases/{}", self.new_nft_mint.key()),
+        )?;
+
+        // 4) move the amount between the records
+        self.purchase.amount = self
+            .purchase
+            .amount
+            .checked_sub(amount)
+            .ok_or(ContractError::ArithmeticOverflow)?;
+        self.purchase.remaining_amount = remaining;
+
+        self.new_purchase.set_inner(Purchase {
+            buyer: self.buyer.key(),
+            project: self.project.key(),
+            amount,
+            remaining_amount: amount,
+            purchase_date: self.purchase.purchase_date,
+            purchase_bump: bumps.new_purchase,
+            nft_mint: self.new_nft_mint.key(),
+            certificate_mint: self.new_nft_mint.key(),
+            uncertified_offset: 0,
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,49 @@
+use crate::errors::ContractError;
+use crate::state::{DutchAuction, Project};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct StartDutchAuction<'info> {
+    pub project_owner: Signer<'info>,
+
+    /// auctions are only for launches, nothing may have been sold yet
+    #[account(
+        mut,
+        constraint = project.owner == project_owner.key() @ ContractError::InvalidProjectOwner,
+        constraint = project.remaining_amount == project.amount @ ContractError::InvalidAuction,
+        constraint = project.auction.is_none() @ ContractError::InvalidAuction,
+        seeds = [b"project", project.owner.as_ref(), project.mint.as_ref()],
+        bump = project.project_bump,
+    )]
+    pub project: Box<Account<'info, Project>>,
+}
+
+impl<'info> StartDutchAuction<'info> {
+    pub fn handler(
+        &mut self,
+        start_price: u64,
+        floor_price: u64,
+        start_time: i64,
+        duration: i64,
+        decay_interval: i64,
+    ) -> Result<()> {
+        require!(floor_price <= start_price, ContractError::InvalidAuction);
+        require!(duration > 0, ContractError::InvalidAuction);
+        require!(
+            decay_interval > 0 && decay_interval <= duration,
+            ContractError::InvalidAuction
+        );
+
+        self.project.auction = Some(DutchAuction {
+            start_price,
+            floor_price,
+            start_time,
+            duration,
+            decay_interval,
+            last_price: start_price,
+            sold: 0,
+            settled: false,
+        });
+        Ok(())
+    }
+}
@@ -0,0 +1,184 @@
+use crate::errors::ContractError;
+use crate::state::{Holdings, Project, Purchase, Subscription};
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{self, Mint, Token, TokenAccount},
+};
+
+/// Hands a position to another wallet: the certificate NFT and the unoffset tokens move
+/// together and the Purchase record is re-keyed under the recipient.
+#[derive(Accounts)]
+pub struct TransferPurchase<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// CHECK: any wallet can receive a position
+    pub recipient: UncheckedAccount<'info>,
+
+    #[account(
+        constraint = project.key() == purchase.project @ ContractError::InvalidProject,
+        seeds = [b"project", project.owner.as_ref(), project.mint.as_ref()],
+        bump = project.project_bump,
+    )]
+    pub project: Box<Account<'info, Project>>,
+
+    /// the current record, closed in favour of `new_purchase`
+    #[account(
+        mut,
+        close = owner,
+        constraint = purchase.buyer == owner.key() @ ContractError::NotPurchaseOwner,
+        seeds = [b"purchase", owner.key().as_ref(), project.key().as_ref(), purchase.nft_mint.as_ref()],
+        bump = purchase.purchase_bump,
+    )]
+    pub purchase: Box<Account<'info, Purchase>>,
+
+    /// CHECK: a listed position must be cancelled before it can change hands
+    #[account(
+        seeds = [b"listing", purchase.key().as_ref()],
+        bump,
+        constraint = listing.data_is_empty() @ ContractError::PurchaseListed,
+    )]
+    pub listing: UncheckedAccount<'info>,
+
+    /// CHECK: a position a subscription fills must not change hands while it is active
+    #[account(
+        seeds = [b"subscription", owner.key().as_ref(), project.key().as_ref()],
+        bump,
+    )]
+    pub subscription: UncheckedAccount<'info>,
+
+    /// CHECK: an offset delegate must be revoked first, it would not follow the new record
+    #[account(
+        seeds = [b"offset_delegate", purchase.key().as_ref()],
+        bump,
+        constraint = offset_delegate.data_is_empty() @ ContractError::PurchaseDelegated,
+    )]
+    pub offset_delegate: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = Purchase::DISCRIMINATOR_SIZE + Purchase::INIT_SPACE,
+        seeds = [b"purchase", recipient.key().as_ref(), project.key().as_ref(), purchase.nft_mint.as_ref()],
+        bump
+    )]
+    pub new_purchase: Box<Account<'info, Purchase>>,
+
+    /// tokens in the owner's wallet backing their purchases of this project
+    #[account(
+        mut,
+        seeds = [b"holdings", owner.key().as_ref(), project.key().as_ref()],
+        bump = holdings.bump,
+    )]
+    pub holdings: Box<Account<'info, Holdings>>,
+
+    /// tokens in the recipient's wallet backing their purchases of this project
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = Holdings::DISCRIMINATOR_SIZE + Holdings::INIT_SPACE,
+        seeds = [b"holdings", recipient.key().as_ref(), project.key().as_ref()],
+        bump
+    )]
+    pub recipient_holdings: Box<Account<'info, Holdings>>,
+
+    /// the certificate currently held for the position
+    #[account(constraint = nft_mint.key() == purchase.certificate_mint @ ContractError::InvalidNFTMint)]
+    pub nft_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        token::mint = nft_mint,
+        token::authority = owner,
+        constraint = owner_nft_account.amount > 0 @ ContractError::InvalidNFTAccount,
+    )]
+    pub owner_nft_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = owner,
+        associated_token::mint = nft_mint,
+        associated_token::authority = recipient,
+    )]
+    pub recipient_nft_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(constraint = token_mint.key() == project.token_mint @ ContractError::InvalidProjectMint)]
+    pub token_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        token::mint = token_mint,
+        token::authority = owner,
+        constraint = owner_token_account.amount >= purchase.remaining_amount @ ContractError::InsufficientFungibleTokens,
+    )]
+    pub owner_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = owner,
+        associated_token::mint = token_mint,
+        associated_token::authority = recipient,
+    )]
+    pub recipient_token_account: Box<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> TransferPurchase<'info> {
+    pub fn handler(&mut self, bumps: &TransferPurchaseBumps) -> Result<()> {
+        require_keys_neq!(self.recipient.key(), self.owner.key(), ContractError::InvalidRecipient);
+        require!(
+            !Subscription::fills(&self.subscription.to_account_info(), &self.purchase.key())?,
+            ContractError::PurchaseSubscribed
+        );
+
+        // 1) move the certificate
+        token::transfer(
+            CpiContext::new(
+                self.token_program.to_account_info(),
+                token::Transfer {
+                    from: self.owner_nft_account.to_account_info(),
+                    to: self.recipient_nft_account.to_account_info(),
+                    authority: self.owner.to_account_info(),
+                },
+            ),
+            1,
+        )?;
+
+        // 2) move the tokens the position still holds
+        if self.purchase.remaining_amount > 0 {
+            token::transfer(
+                CpiContext::new(
+                    self.token_program.to_account_info(),
+                    token::Transfer {
+                        from: self.owner_token_account.to_account_info(),
+                        to: self.recipient_token_account.to_account_info(),
+                        authority: self.owner.to_account_info(),
+                    },
+                ),
+                self.purchase.remaining_amount,
+            )?;
+        }
+
+        // 3) re-key the record under the recipient
+        self.holdings.remove(self.purchase.remaining_amount)?;
+        self.recipient_holdings.open(self.recipient.key(), self.project.key(), bumps.recipient_holdings);
+        self.recipient_holdings.add(self.purchase.remaining_amount)?;
+        self.new_purchase.set_inner(Purchase {
+            buyer: self.recipient.key(),
+            project: self.project.key(),
+            amount: self.purchase.amount,
+            remaining_amount: self.purchase.remaining_amount,
+            purchase_date: self.purchase.purchase_date,
+            purchase_bump: bumps.new_purchase,
+            nft_mint: self.purchase.nft_mint,
+            certificate_mint: self.purchase.certificate_mint,
+            uncertified_offset: self.purchase.uncertified_offset,
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,24 @@
+use crate::errors::ContractError;
+use crate::state::Basket;
+use anchor_lang::prelude::*;
+
+/// Changes which project categories can be deposited; existing reserves are kept
+#[derive(Accounts)]
+pub struct UpdateBasketEligibility<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = basket.authority == authority.key() @ ContractError::UnauthorizedAdmin,
+        seeds = [b"basket", basket.basket_id.to_le_bytes().as_ref()],
+        bump = basket.bump,
+    )]
+    pub basket: Box<Account<'info, Basket>>,
+}
+
+impl<'info> UpdateBasketEligibility<'info> {
+    pub fn handler(&mut self, allowed_categories: u8) -> Result<()> {
+        self.basket.allowed_categories = allowed_categories;
+        Ok(())
+    }
+}
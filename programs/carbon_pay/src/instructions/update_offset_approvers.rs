@@ -0,0 +1,23 @@
+use crate::errors::ContractError;
+use crate::state::PlatformConfig;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct UpdateOffsetApprovers<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"platform_config"],
+        bump = platform_config.bump,
+        constraint = platform_config.authority == authority.key() @ ContractError::UnauthorizedAdmin,
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+}
+
+impl<'info> UpdateOffsetApprovers<'info> {
+    pub fn handler(&mut self, approvers: Vec<Pubkey>, approval_threshold: u8) -> Result<()> {
+        self.platform_config
+            .set_approvers(approvers, approval_threshold)
+    }
+}
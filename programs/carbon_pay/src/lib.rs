@@ -1,10 +1,13 @@
 use anchor_lang::prelude::*;
 
 mod instructions;
-mod state;
-mod errors;
+pub mod state;
+pub mod errors;
+pub mod events;
+mod utils;
 
 use instructions::*;
+use state::{FeeRounding, PriceTier, ProjectCategory};
 
 declare_id!("7Ju2yb323ApU1G6XCQ3YPRy4ihfDprR5FNJ8wHDmMZcJ");
 
@@ -52,4 +55,262 @@ pub mod carbon_pay {
     ) -> Result<()> {
         ctx.accounts.purchase_carbon_credits(amount, &ctx.bumps)
     }
+
+    pub fn initialize_platform_config(
+        ctx: Context<InitializePlatformConfig>,
+        approvers: Vec<Pubkey>,
+        approval_threshold: u8,
+    ) -> Result<()> {
+        ctx.accounts.handler(approvers, approval_threshold, &ctx.bumps)
+    }
+
+    pub fn update_offset_approvers(
+        ctx: Context<UpdateOffsetApprovers>,
+        approvers: Vec<Pubkey>,
+        approval_threshold: u8,
+    ) -> Result<()> {
+        ctx.accounts.handler(approvers, approval_threshold)
+    }
+
+    pub fn approve_offset_vote(ctx: Context<ApproveOffsetVote>) -> Result<()> {
+        ctx.accounts.handler(&ctx.bumps)
+    }
+
+    pub fn add_allowlist_entry(
+        ctx: Context<AddAllowlistEntry>,
+        scope: Pubkey,
+        wallet: Pubkey,
+        expires_at: i64,
+    ) -> Result<()> {
+        ctx.accounts.handler(scope, wallet, expires_at, &ctx.bumps)
+    }
+
+    pub fn set_allowlist_expiry(ctx: Context<SetAllowlistExpiry>, expires_at: i64) -> Result<()> {
+        ctx.accounts.handler(expires_at)
+    }
+
+    pub fn remove_allowlist_entry(_ctx: Context<RemoveAllowlistEntry>) -> Result<()> {
+        Ok(())
+    }
+
+    pub fn set_allowlist_required(ctx: Context<SetAllowlistRequired>, required: bool) -> Result<()> {
+        ctx.accounts.handler(required)
+    }
+
+    pub fn set_purchase_limits(
+        ctx: Context<SetPurchaseLimits>,
+        min_purchase_amount: u64,
+        max_purchase_amount: u64,
+        max_per_buyer: u64,
+    ) -> Result<()> {
+        ctx.accounts
+            .handler(min_purchase_amount, max_purchase_amount, max_per_buyer)
+    }
+
+    pub fn set_sale_window(
+        ctx: Context<SetSaleWindow>,
+        sale_start: i64,
+        sale_end: i64,
+        early_access_start: i64,
+    ) -> Result<()> {
+        ctx.accounts.handler(sale_start, sale_end, early_access_start)
+    }
+
+    pub fn set_price_tiers(ctx: Context<SetPriceTiers>, price_tiers: Vec<PriceTier>) -> Result<()> {
+        ctx.accounts.handler(price_tiers)
+    }
+
+    pub fn list_credits(
+        ctx: Context<ListCredits>,
+        amount: u64,
+        price_per_token: u64,
+    ) -> Result<()> {
+        ctx.accounts.handler(amount, price_per_token, &ctx.bumps)
+    }
+
+    pub fn buy_listing(ctx: Context<BuyListing>) -> Result<()> {
+        ctx.accounts.handler(&ctx.bumps)
+    }
+
+    pub fn cancel_listing(ctx: Context<CancelListing>) -> Result<()> {
+        ctx.accounts.handler()
+    }
+
+    pub fn start_dutch_auction(
+        ctx: Context<StartDutchAuction>,
+        start_price: u64,
+        floor_price: u64,
+        start_time: i64,
+        duration: i64,
+        decay_interval: i64,
+    ) -> Result<()> {
+        ctx.accounts
+            .handler(start_price, floor_price, start_time, duration, decay_interval)
+    }
+
+    pub fn settle_dutch_auction(ctx: Context<SettleDutchAuction>) -> Result<()> {
+        ctx.accounts.handler()
+    }
+
+    pub fn claim_auction_refund(ctx: Context<ClaimAuctionRefund>) -> Result<()> {
+        ctx.accounts.handler()
+    }
+
+    pub fn create_otc_offer(
+        ctx: Context<CreateOtcOffer>,
+        buyer: Pubkey,
+        offer_id: u64,
+        amount: u64,
+        price_per_token: u64,
+        expires_at: i64,
+    ) -> Result<()> {
+        ctx.accounts
+            .handler(buyer, offer_id, amount, price_per_token, expires_at, &ctx.bumps)
+    }
+
+    pub fn accept_otc_offer(ctx: Context<AcceptOtcOffer>) -> Result<()> {
+        ctx.accounts.handler(&ctx.bumps)
+    }
+
+    pub fn cancel_otc_offer(ctx: Context<CancelOtcOffer>) -> Result<()> {
+        ctx.accounts.handler()
+    }
+
+    pub fn create_subscription(
+        ctx: Context<CreateSubscription>,
+        amount_per_period: u64,
+        max_price_per_token: u64,
+        deposit: u64,
+    ) -> Result<()> {
+        ctx.accounts
+            .handler(amount_per_period, max_price_per_token, deposit, &ctx.bumps)
+    }
+
+    pub fn fund_subscription(ctx: Context<FundSubscription>, lamports: u64) -> Result<()> {
+        ctx.accounts.handler(lamports)
+    }
+
+    pub fn execute_subscription(ctx: Context<ExecuteSubscription>) -> Result<()> {
+        ctx.accounts.handler()
+    }
+
+    pub fn cancel_subscription(_ctx: Context<CancelSubscription>) -> Result<()> {
+        Ok(())
+    }
+
+    pub fn set_project_category(
+        ctx: Context<SetProjectCategory>,
+        category: ProjectCategory,
+    ) -> Result<()> {
+        ctx.accounts.handler(category)
+    }
+
+    pub fn create_basket(
+        ctx: Context<CreateBasket>,
+        basket_id: u64,
+        allowed_categories: u8,
+    ) -> Result<()> {
+        ctx.accounts.handler(basket_id, allowed_categories, &ctx.bumps)
+    }
+
+    pub fn update_basket_eligibility(
+        ctx: Context<UpdateBasketEligibility>,
+        allowed_categories: u8,
+    ) -> Result<()> {
+        ctx.accounts.handler(allowed_categories)
+    }
+
+    pub fn deposit_to_basket(ctx: Context<DepositToBasket>, amount: u64) -> Result<()> {
+        ctx.accounts.handler(amount, &ctx.bumps)
+    }
+
+    pub fn redeem_from_basket<'info>(
+        ctx: Context<'_, '_, 'info, 'info, RedeemFromBasket<'info>>,
+        amount: u64,
+    ) -> Result<()> {
+        ctx.accounts.handler(amount, ctx.remaining_accounts)
+    }
+
+    pub fn retire_from_basket<'info>(
+        ctx: Context<'_, '_, 'info, 'info, RetireFromBasket<'info>>,
+        amount: u64,
+    ) -> Result<()> {
+        ctx.accounts.handler(amount, ctx.remaining_accounts)
+    }
+
+    pub fn retire_held_credits(
+        ctx: Context<RetireHeldCredits>,
+        amount: u64,
+        request_id: String,
+    ) -> Result<()> {
+        ctx.accounts.handler(amount, request_id, &ctx.bumps)
+    }
+
+    pub fn purchase_and_retire(
+        ctx: Context<PurchaseAndRetire>,
+        amount: u64,
+        request_id: String,
+    ) -> Result<()> {
+        ctx.accounts.handler(amount, request_id, &ctx.bumps)
+    }
+
+    pub fn approve_offset_delegate(
+        ctx: Context<ApproveOffsetDelegate>,
+        delegate: Pubkey,
+        max_amount: u64,
+        expires_at: i64,
+    ) -> Result<()> {
+        ctx.accounts.handler(delegate, max_amount, expires_at, &ctx.bumps)
+    }
+
+    pub fn revoke_offset_delegate(ctx: Context<RevokeOffsetDelegate>) -> Result<()> {
+        ctx.accounts.handler()
+    }
+
+    pub fn request_batch_offset<'info>(
+        ctx: Context<'_, '_, 'info, 'info, RequestBatchOffset<'info>>,
+        amounts: Vec<u64>,
+        request_id: String,
+    ) -> Result<()> {
+        ctx.accounts
+            .handler(amounts, request_id, ctx.remaining_accounts, &ctx.bumps)
+    }
+
+    pub fn merge_purchases<'info>(
+        ctx: Context<'_, '_, 'info, 'info, MergePurchases<'info>>,
+    ) -> Result<()> {
+        ctx.accounts.handler(ctx.remaining_accounts)
+    }
+
+    pub fn split_purchase(ctx: Context<SplitPurchase>, amount: u64) -> Result<()> {
+        ctx.accounts.handler(amount, &ctx.bumps)
+    }
+
+    pub fn transfer_purchase(ctx: Context<TransferPurchase>) -> Result<()> {
+        ctx.accounts.handler(&ctx.bumps)
+    }
+
+    pub fn claim_purchase(ctx: Context<ClaimPurchase>) -> Result<()> {
+        ctx.accounts.handler(&ctx.bumps)
+    }
+
+    pub fn set_fee_policy(
+        ctx: Context<SetFeePolicy>,
+        fee_rounding: FeeRounding,
+        min_fee: u64,
+    ) -> Result<()> {
+        ctx.accounts.handler(fee_rounding, min_fee)
+    }
+
+    pub fn migrate_carbon_credits(ctx: Context<MigrateCarbonCredits>) -> Result<()> {
+        ctx.accounts.handler()
+    }
+
+    pub fn migrate_project(ctx: Context<MigrateProject>) -> Result<()> {
+        ctx.accounts.handler()
+    }
+
+    pub fn migrate_purchase(ctx: Context<MigratePurchase>) -> Result<()> {
+        ctx.accounts.handler(&ctx.bumps)
+    }
 }
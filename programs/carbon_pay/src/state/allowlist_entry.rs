@@ -0,0 +1,42 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::ContractError;
+
+/// AllowlistEntry marks a wallet as a verified (KYC'd) buyer.
+/// The scope is either a Project PDA (per-project allowlist) or the PlatformConfig PDA (platform-wide).
+#[account]
+pub struct AllowlistEntry {
+    pub scope: Pubkey,   // Project or PlatformConfig this entry applies to
+    pub wallet: Pubkey,  // The verified buyer wallet
+    pub expires_at: i64, // Unix timestamp after which the entry is no longer valid (0 = never expires)
+    pub bump: u8,        // The PDA bump
+}
+
+impl AllowlistEntry {
+    pub const DISCRIMINATOR_SIZE: usize = 8;
+    pub const INIT_SPACE: usize = 32 + // scope: Pubkey
+        32 + // wallet: Pubkey
+        8 +  // expires_at: i64
+        1; // bump: u8
+
+    pub fn is_expired(&self, now: i64) -> bool {
+        self.expires_at != 0 && now >= self.expires_at
+    }
+
+    /// Check that this entry admits `buyer` for `project` at time `now`
+    pub fn check_buyer(
+        &self,
+        buyer: &Pubkey,
+        project: &Pubkey,
+        platform_config: &Pubkey,
+        now: i64,
+    ) -> Result<()> {
+        require_keys_eq!(self.wallet, *buyer, ContractError::BuyerNotAllowlisted);
+        require!(
+            self.scope == *project || self.scope == *platform_config,
+            ContractError::InvalidAllowlistScope
+        );
+        require!(!self.is_expired(now), ContractError::AllowlistEntryExpired);
+        Ok(())
+    }
+}
@@ -0,0 +1,93 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::ContractError;
+use crate::state::ProjectCategory;
+
+/// Basket is an index of credits pooled from several projects.
+/// Depositors receive basket tokens 1:1 for the project tokens they deposit;
+/// basket tokens are redeemed or retired pro-rata across every project in the basket.
+#[account]
+pub struct Basket {
+    pub authority: Pubkey,       // Curator allowed to manage the basket (the platform authority)
+    pub basket_id: u64,          // Identifier used in the PDA seeds
+    pub basket_mint: Pubkey,     // SPL mint of the basket token, mint authority is this PDA
+    pub allowed_categories: u8,  // Bitmask of ProjectCategory values eligible for deposit
+    pub project_count: u64,      // Number of BasketReserve accounts (projects) in the basket
+    pub total_deposited: u64,    // Project tokens currently held across all reserves
+    pub bump: u8,                // The PDA bump
+    pub mint_bump: u8,           // The basket mint PDA bump
+}
+
+impl Basket {
+    /// Redeeming and retiring pass every reserve, up to four accounts each, in one transaction;
+    /// with them in an address lookup table, this is what fits the 64 accounts a transaction may lock
+    pub const MAX_PROJECTS: u64 = 14;
+
+    pub const DISCRIMINATOR_SIZE: usize = 8;
+    pub const INIT_SPACE: usize = 32 + // authority: Pubkey
+        8 +  // basket_id: u64
+        32 + // basket_mint: Pubkey
+        1 +  // allowed_categories: u8
+        8 +  // project_count: u64
+        8 +  // total_deposited: u64
+        1 +  // bump: u8
+        1; // mint_bump: u8
+
+    pub fn accepts(&self, category: ProjectCategory) -> bool {
+        self.allowed_categories & category.mask() != 0
+    }
+}
+
+/// BasketReserve holds one project's tokens inside a basket
+#[account]
+pub struct BasketReserve {
+    pub basket: Pubkey,     // The basket this reserve belongs to
+    pub project: Pubkey,    // The project whose tokens are held
+    pub token_mint: Pubkey, // The project's fungible token mint
+    pub vault: Pubkey,      // ATA of the basket PDA holding the tokens
+    pub amount: u64,        // Tokens currently held
+    pub bump: u8,           // The PDA bump
+}
+
+impl BasketReserve {
+    pub const DISCRIMINATOR_SIZE: usize = 8;
+    pub const INIT_SPACE: usize = 32 + // basket: Pubkey
+        32 + // project: Pubkey
+        32 + // token_mint: Pubkey
+        32 + // vault: Pubkey
+        8 +  // amount: u64
+        1; // bump: u8
+}
+
+/// Split `amount` across reserves in proportion to their balances.
+/// Shares are rounded down, then the remainder is handed out one token at a time
+/// to the reserves that still have balance left, so the shares always sum to `amount`.
+pub fn pro_rata_shares(balances: &[u64], amount: u64) -> Result<Vec<u64>> {
+    let total: u64 = balances
+        .iter()
+        .try_fold(0u64, |sum, balance| sum.checked_add(*balance))
+        .ok_or(ContractError::ArithmeticOverflow)?;
+    require!(
+        amount <= total,
+        ContractError::InsufficientTokens
+    );
+    if total == 0 {
+        return Ok(vec![0; balances.len()]);
+    }
+
+    let mut shares: Vec<u64> = balances
+        .iter()
+        .map(|balance| ((*balance as u128) * (amount as u128) / (total as u128)) as u64)
+        .collect();
+    let mut remainder = amount - shares.iter().sum::<u64>();
+    for (share, balance) in shares.iter_mut().zip(balances.iter()) {
+        if remainder == 0 {
+            break;
+        }
+        if *share < *balance {
+            *share += 1;
+            remainder -= 1;
+        }
+    }
+    Ok(shares)
+}
@@ -0,0 +1,45 @@
+use anchor_lang::prelude::*;
+
+/// BuyerStats tracks how many credits a single buyer has purchased from a single project.
+/// Used to enforce the project's per-buyer lifetime cap.
+#[account]
+pub struct BuyerStats {
+    pub buyer: Pubkey,        // The buyer wallet
+    pub project: Pubkey,      // The project PDA
+    pub total_purchased: u64, // Total tokens this buyer has purchased from the project
+    pub auction_amount: u64,  // Tokens bought in the project's Dutch auction, not yet refunded
+    pub auction_paid: u64,    // Lamports paid for those tokens at auction prices
+    pub bump: u8,             // The PDA bump
+}
+
+impl BuyerStats {
+    pub const DISCRIMINATOR_SIZE: usize = 8;
+    pub const INIT_SPACE: usize = 32 + // buyer: Pubkey
+        32 + // project: Pubkey
+        8 +  // total_purchased: u64
+        8 +  // auction_amount: u64
+        8 +  // auction_paid: u64
+        1; // bump: u8
+
+    /// Record a purchase by this buyer
+    pub fn record_purchase(&mut self, amount: u64) -> Result<()> {
+        self.total_purchased = self
+            .total_purchased
+            .checked_add(amount)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        Ok(())
+    }
+
+    /// Record tokens bought in a Dutch auction, refundable down to the clearing price
+    pub fn record_auction_purchase(&mut self, amount: u64, paid: u64) -> Result<()> {
+        self.auction_amount = self
+            .auction_amount
+            .checked_add(amount)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        self.auction_paid = self
+            .auction_paid
+            .checked_add(paid)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        Ok(())
+    }
+}
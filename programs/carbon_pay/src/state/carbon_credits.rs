@@ -3,6 +3,9 @@ use anchor_lang::prelude::*;
 /// CarbonCredits tracks the global metrics of all carbon credits across all projects in the platform.
 /// This serves as a central dashboard for platform-wide statistics and does not replace
 /// the individual tracking of credits within each Project.
+/// Credit counters are in base units of `DECIMALS` (grams of CO2), whatever the decimals of
+/// each project's token. Accounts created before `units_version` existed count whole tonnes
+/// and must go through `migrate_carbon_credits` once.
 
 #[account]
 pub struct CarbonCredits {
@@ -13,17 +16,24 @@ pub struct CarbonCredits {
     pub projects_count: u64, // Total number of projects created on the platform
     pub total_fees_earned: u64, // Total fees earned by the platform from all projects
     pub bump: u8,            // The PDA bump
+    pub units_version: u8,   // Unit of the credit counters, `UNITS_VERSION` once migrated
 }
 
 impl CarbonCredits {
     pub const DISCRIMINATOR_SIZE: usize = 8;
+    pub const DECIMALS: u8 = 6;
+    /// Counters in base units of `DECIMALS`
+    pub const UNITS_VERSION: u8 = 1;
+    /// Space of the layout before `units_version`, when counters were whole tonnes
+    pub const LEGACY_SPACE: usize = Self::INIT_SPACE - 1;
     pub const INIT_SPACE: usize = 32 + // authority: Pubkey
         8 +  // total_credits: u64
         8 +  // active_credits: u64
         8 +  // offset_credits: u64
         8 +  // projects_count: u64
         8 +  // total_fees_earned: u64
-        1; // bump: u8
+        1 +  // bump: u8
+        1; // units_version: u8
 
     /// Initialize the global platform dashboard
     pub fn initialize(&mut self, authority: Pubkey, bump: u8) -> Result<()> {
@@ -34,6 +44,7 @@ impl CarbonCredits {
         self.projects_count = 0;
         self.total_fees_earned = 0;
         self.bump = bump;
+        self.units_version = Self::UNITS_VERSION;
         Ok(())
     }
 
@@ -0,0 +1,59 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::ContractError;
+
+/// Holdings tallies the project tokens in an owner's wallet that back their purchases of one
+/// project: the sum of their purchases' `remaining_amount`, less what an offset delegate holds
+/// in escrow. Tokens beyond it are loose and free to retire or to back a claimed purchase.
+#[account]
+pub struct Holdings {
+    pub owner: Pubkey,       // The purchase owner
+    pub project: Pubkey,     // The project PDA
+    pub backed_amount: u64,  // Tokens in the owner's wallet accounted for by their purchases
+    pub bump: u8,            // The PDA bump
+}
+
+impl Holdings {
+    pub const DISCRIMINATOR_SIZE: usize = 8;
+    pub const INIT_SPACE: usize = 32 + // owner: Pubkey
+        32 + // project: Pubkey
+        8 +  // backed_amount: u64
+        1; // bump: u8
+
+    /// Fill in a freshly created (`init_if_needed`) record
+    pub fn open(&mut self, owner: Pubkey, project: Pubkey, bump: u8) {
+        if self.owner == Pubkey::default() {
+            self.owner = owner;
+            self.project = project;
+            self.bump = bump;
+        }
+    }
+
+    /// Tokens that came to back one of the owner's purchases
+    pub fn add(&mut self, amount: u64) -> Result<()> {
+        self.backed_amount = self
+            .backed_amount
+            .checked_add(amount)
+            .ok_or(ContractError::ArithmeticOverflow)?;
+        Ok(())
+    }
+
+    /// Tokens that no longer back one of the owner's purchases
+    pub fn remove(&mut self, amount: u64) -> Result<()> {
+        self.backed_amount = self
+            .backed_amount
+            .checked_sub(amount)
+            .ok_or(ContractError::ArithmeticOverflow)?;
+        Ok(())
+    }
+
+    /// What the Holdings PDA `info`, which need not exist, says backs the owner's purchases
+    pub fn backed(info: &AccountInfo) -> Result<u64> {
+        if info.data_is_empty() {
+            return Ok(0);
+        }
+        require_keys_eq!(*info.owner, crate::ID, ErrorCode::AccountOwnedByWrongProgram);
+        let holdings = Holdings::try_deserialize(&mut &info.try_borrow_data()?[..])?;
+        Ok(holdings.backed_amount)
+    }
+}
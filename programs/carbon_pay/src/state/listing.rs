@@ -0,0 +1,25 @@
+use anchor_lang::prelude::*;
+
+/// Listing offers part of a Purchase's tokens for resale on the secondary market.
+/// The listed tokens are held in an escrow ATA owned by the listing PDA.
+#[account]
+pub struct Listing {
+    pub seller: Pubkey,       // Owner of the purchase being resold
+    pub purchase: Pubkey,     // The purchase the tokens were taken from
+    pub project: Pubkey,      // The project the tokens belong to
+    pub amount: u64,          // Amount of tokens in escrow
+    pub price_per_token: u64, // Asking price per token in lamports
+    pub created_at: i64,      // When the listing was created
+    pub bump: u8,             // The PDA bump
+}
+
+impl Listing {
+    pub const DISCRIMINATOR_SIZE: usize = 8;
+    pub const INIT_SPACE: usize = 32 + // seller: Pubkey
+        32 + // purchase: Pubkey
+        32 + // project: Pubkey
+        8 +  // amount: u64
+        8 +  // price_per_token: u64
+        8 +  // created_at: i64
+        1; // bump: u8
+}
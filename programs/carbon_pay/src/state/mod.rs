@@ -1,9 +1,31 @@
+pub mod allowlist_entry;
+pub mod basket;
+pub mod buyer_stats;
 pub mod carbon_credits;
+pub mod holdings;
+pub mod listing;
+pub mod offset_approval;
+pub mod offset_batch;
+pub mod offset_delegate;
 pub mod offset_request;
+pub mod otc_offer;
+pub mod platform_config;
 pub mod project;
 pub mod purchase;
+pub mod subscription;
 
+pub use allowlist_entry::*;
+pub use basket::*;
+pub use buyer_stats::*;
 pub use carbon_credits::*;
+pub use holdings::*;
+pub use listing::*;
+pub use offset_approval::*;
+pub use offset_batch::*;
+pub use offset_delegate::*;
 pub use offset_request::*;
+pub use otc_offer::*;
+pub use platform_config::*;
 pub use project::*;
 pub use purchase::*;
+pub use subscription::*;
@@ -0,0 +1,33 @@
+use anchor_lang::prelude::*;
+
+use crate::state::PlatformConfig;
+
+/// OffsetApproval collects the approver votes cast on a single OffsetRequest.
+#[account]
+pub struct OffsetApproval {
+    pub offset_request: Pubkey, // The offset request being voted on
+    pub votes: Vec<Pubkey>,     // Approvers who have voted to approve
+    pub bump: u8,               // The PDA bump
+}
+
+impl OffsetApproval {
+    pub const DISCRIMINATOR_SIZE: usize = 8;
+    pub const INIT_SPACE: usize = 32 + // offset_request: Pubkey
+        4 + 32 * PlatformConfig::MAX_APPROVERS + // votes: Vec<Pubkey>
+        1; // bump: u8
+
+    pub fn has_voted(&self, approver: &Pubkey) -> bool {
+        self.votes.contains(approver)
+    }
+
+    /// Drop votes cast by wallets that are no longer approvers, freeing their slots after
+    /// the approver set is rotated
+    pub fn prune_stale_votes(&mut self, config: &PlatformConfig) {
+        self.votes.retain(|v| config.is_approver(v));
+    }
+
+    /// Number of votes cast by wallets that are still approvers in the current config
+    pub fn valid_votes(&self, config: &PlatformConfig) -> usize {
+        self.votes.iter().filter(|v| config.is_approver(v)).count()
+    }
+}
@@ -0,0 +1,21 @@
+use anchor_lang::prelude::*;
+
+/// OffsetBatch lists the purchases an aggregated OffsetRequest was drawn from.
+#[account]
+pub struct OffsetBatch {
+    pub offset_request: Pubkey, // The aggregated offset request
+    pub purchases: Vec<Pubkey>, // Purchases the offset was taken from
+    pub amounts: Vec<u64>,      // Tokens offset from each purchase, in the same order
+    pub bump: u8,               // The PDA bump
+}
+
+impl OffsetBatch {
+    pub const DISCRIMINATOR_SIZE: usize = 8;
+    /// each purchase brings two accounts; with them in an address lookup table, this is what
+    /// fits the 64 accounts a transaction may lock
+    pub const MAX_PURCHASES: usize = 26;
+    pub const INIT_SPACE: usize = 32 + // offset_request: Pubkey
+        4 + 32 * Self::MAX_PURCHASES + // purchases: Vec<Pubkey>
+        4 + 8 * Self::MAX_PURCHASES + // amounts: Vec<u64>
+        1; // bump: u8
+}
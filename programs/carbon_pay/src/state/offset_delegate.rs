@@ -0,0 +1,40 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::ContractError;
+
+/// OffsetDelegate lets a custodian wallet request offsets against a Purchase on the owner's behalf.
+/// The certificate and the allowance are escrowed in this PDA's token accounts, and it signs the burns.
+#[account]
+pub struct OffsetDelegate {
+    pub owner: Pubkey,         // Owner of the purchase granting the approval
+    pub purchase: Pubkey,      // The purchase the delegate may offset against
+    pub delegate: Pubkey,      // Wallet allowed to request offsets
+    pub remaining_amount: u64, // Tokens the delegate may still offset
+    pub expires_at: i64,       // Unix timestamp after which the approval is no longer valid (0 = never expires)
+    pub bump: u8,              // The PDA bump
+}
+
+impl OffsetDelegate {
+    pub const DISCRIMINATOR_SIZE: usize = 8;
+    pub const INIT_SPACE: usize = 32 + // owner: Pubkey
+        32 + // purchase: Pubkey
+        32 + // delegate: Pubkey
+        8 +  // remaining_amount: u64
+        8 +  // expires_at: i64
+        1; // bump: u8
+
+    pub fn is_expired(&self, now: i64) -> bool {
+        self.expires_at != 0 && now >= self.expires_at
+    }
+
+    /// Check that `delegate` may offset `amount` at time `now` and consume the allowance
+    pub fn consume(&mut self, delegate: &Pubkey, amount: u64, now: i64) -> Result<()> {
+        require_keys_eq!(self.delegate, *delegate, ContractError::NotPurchaseOwner);
+        require!(!self.is_expired(now), ContractError::OffsetDelegateExpired);
+        self.remaining_amount = self
+            .remaining_amount
+            .checked_sub(amount)
+            .ok_or(ContractError::OffsetDelegateAllowanceExceeded)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,26 @@
+use anchor_lang::prelude::*;
+
+/// OtcOffer is a privately negotiated sale from a project owner to one buyer.
+/// The offered tokens are reserved out of the project's remaining amount until
+/// the offer is accepted or cancelled; either way the offer account is closed.
+#[account]
+pub struct OtcOffer {
+    pub project: Pubkey,      // The project the tokens are sold from
+    pub buyer: Pubkey,        // The only wallet allowed to accept the offer
+    pub offer_id: u64,        // Identifier chosen by the owner, unique per (project, buyer)
+    pub amount: u64,          // Amount of tokens offered
+    pub price_per_token: u64, // Negotiated price per token in lamports
+    pub expires_at: i64,      // Unix timestamp after which the offer can no longer be accepted
+    pub bump: u8,             // The PDA bump
+}
+
+impl OtcOffer {
+    pub const DISCRIMINATOR_SIZE: usize = 8;
+    pub const INIT_SPACE: usize = 32 + // project: Pubkey
+        32 + // buyer: Pubkey
+        8 +  // offer_id: u64
+        8 +  // amount: u64
+        8 +  // price_per_token: u64
+        8 +  // expires_at: i64
+        1; // bump: u8
+}
@@ -0,0 +1,97 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::ContractError;
+use crate::utils::BPS_DENOMINATOR;
+
+/// How a platform fee that falls between two lamports is rounded
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FeeRounding {
+    #[default]
+    Floor,
+    Ceil,
+    /// Round half to even (banker's rounding)
+    HalfEven,
+}
+
+/// A platform fee in lamports and the fraction of a lamport it was rounded from
+pub struct PlatformFee {
+    pub fee: u64,
+    /// `total * fee_bps % 10_000`: the exact fee is `floor + remainder / 10_000` lamports
+    pub remainder: u64,
+}
+
+/// PlatformConfig holds the platform-wide settings managed by the CarbonPay authority.
+/// It is a singleton PDA, separate from the CarbonCredits dashboard which only tracks totals.
+#[account]
+pub struct PlatformConfig {
+    pub authority: Pubkey,      // The admin allowed to update this config (same as CarbonCredits.authority)
+    pub approvers: Vec<Pubkey>, // Wallets allowed to vote on offset requests
+    pub approval_threshold: u8, // Number of approver votes required to approve an offset request
+    pub allowlist_required: bool, // Whether every purchase on the platform requires an allowlisted buyer
+    pub bump: u8,               // The PDA bump
+    pub fee_rounding: FeeRounding, // How fractional lamports of a platform fee are rounded
+    pub min_fee: u64,           // Minimum platform fee in lamports on sales charging a fee
+}
+
+impl PlatformConfig {
+    pub const DISCRIMINATOR_SIZE: usize = 8;
+    pub const MAX_APPROVERS: usize = 10;
+    pub const INIT_SPACE: usize = 32 + // authority: Pubkey
+        4 + 32 * Self::MAX_APPROVERS + // approvers: Vec<Pubkey>
+        1 + // approval_threshold: u8
+        1 + // allowlist_required: bool
+        1 + // bump: u8
+        1 + // fee_rounding: FeeRounding
+        8; // min_fee: u64
+
+    /// Replace the approver set and threshold after validating them
+    pub fn set_approvers(&mut self, approvers: Vec<Pubkey>, approval_threshold: u8) -> Result<()> {
+        require!(
+            approvers.len() <= Self::MAX_APPROVERS,
+            ContractError::TooManyApprovers
+        );
+        require!(
+            approval_threshold > 0 && approval_threshold as usize <= approvers.len(),
+            ContractError::InvalidApprovalThreshold
+        );
+        for (i, approver) in approvers.iter().enumerate() {
+            require!(
+                !approvers[..i].contains(approver),
+                ContractError::DuplicateApprover
+            );
+        }
+        self.approvers = approvers;
+        self.approval_threshold = approval_threshold;
+        Ok(())
+    }
+
+    /// Platform fee on a sale of `total` lamports at `fee_bps` (500 = 5.00%), rounded with
+    /// `fee_rounding` and raised to `min_fee`. Fee-free projects stay fee-free and the fee
+    /// never exceeds `total`.
+    pub fn platform_fee(&self, total: u64, fee_bps: u64) -> Result<PlatformFee> {
+        let exact = (total as u128)
+            .checked_mul(fee_bps as u128)
+            .ok_or(ContractError::ArithmeticOverflow)?;
+        let remainder = (exact % BPS_DENOMINATOR as u128) as u64;
+        let mut fee = u64::try_from(exact / BPS_DENOMINATOR as u128)
+            .map_err(|_| ContractError::ArithmeticOverflow)?;
+        let round_up = match self.fee_rounding {
+            FeeRounding::Floor => false,
+            FeeRounding::Ceil => remainder > 0,
+            FeeRounding::HalfEven => {
+                remainder * 2 > BPS_DENOMINATOR || (remainder * 2 == BPS_DENOMINATOR && fee % 2 == 1)
+            }
+        };
+        if round_up {
+            fee = fee.checked_add(1).ok_or(ContractError::ArithmeticOverflow)?;
+        }
+        if fee_bps > 0 {
+            fee = fee.max(self.min_fee).min(total);
+        }
+        Ok(PlatformFee { fee, remainder })
+    }
+
+    pub fn is_approver(&self, key: &Pubkey) -> bool {
+        self.approvers.contains(key)
+    }
+}
@@ -1,7 +1,89 @@
 use anchor_lang::prelude::*;
 
+use crate::errors::ContractError;
+use crate::state::CarbonCredits;
+use crate::utils::token_scale;
+
+/// Kind of carbon project, used by baskets to decide eligibility
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum ProjectCategory {
+    Unspecified,
+    NatureBased,
+    Renewables,
+    EnergyEfficiency,
+    Other,
+}
+
+impl ProjectCategory {
+    /// Bit used for this category in a basket's `allowed_categories`
+    pub fn mask(self) -> u8 {
+        1 << (self as u8)
+    }
+}
+
+/// A volume price tier: tokens from `min_amount` onwards within a single purchase
+/// are charged `price_per_token` lamports per whole token, until the next tier's breakpoint.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub struct PriceTier {
+    pub min_amount: u64,      // Breakpoint (in base units) where this tier starts
+    pub price_per_token: u64, // Price per whole token in lamports within this tier
+}
+
+/// Dutch auction parameters for a project launch. The price decays from `start_price` to
+/// `floor_price` in equal steps every `decay_interval` seconds, reaching the floor in the
+/// auction's last interval.
+/// Buyers pay the current price into the project PDA; once the auction ends every buyer
+/// is settled at the clearing price (the last price paid) and can claim the difference back.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub struct DutchAuction {
+    pub start_price: u64,    // Price per whole token in lamports when the auction opens
+    pub floor_price: u64,    // Lowest price per whole token in lamports
+    pub start_time: i64,     // Unix timestamp when the auction opens
+    pub duration: i64,       // Seconds until the auction ends
+    pub decay_interval: i64, // Seconds between price steps
+    pub last_price: u64,     // Price of the most recent sale, the clearing price once ended
+    pub sold: u64,           // Tokens sold in the auction
+    pub settled: bool,       // Whether proceeds were paid out to the owner and platform
+}
+
+impl DutchAuction {
+    pub const INIT_SPACE: usize = 8 + // start_price: u64
+        8 +  // floor_price: u64
+        8 +  // start_time: i64
+        8 +  // duration: i64
+        8 +  // decay_interval: i64
+        8 +  // last_price: u64
+        8 +  // sold: u64
+        1; // settled: bool
+
+    pub fn end_time(&self) -> i64 {
+        self.start_time.saturating_add(self.duration)
+    }
+
+    /// Price per whole token at `now`, must be called while the auction is running
+    pub fn current_price(&self, now: i64) -> Result<u64> {
+        require!(now >= self.start_time, ContractError::SaleNotStarted);
+        require!(now < self.end_time(), ContractError::AuctionEnded);
+
+        // the interval containing the last second sells at the floor, even when
+        // `duration` is not a multiple of `decay_interval`
+        let steps = ((now - self.start_time) / self.decay_interval) as u128;
+        let last_step = ((self.duration - 1) / self.decay_interval) as u128;
+        if steps >= last_step {
+            return Ok(self.floor_price);
+        }
+        let decay = ((self.start_price - self.floor_price) as u128)
+            .checked_mul(steps)
+            .ok_or(ContractError::ArithmeticOverflow)?
+            / last_step;
+        Ok(self.start_price - decay as u64)
+    }
+}
+
 /// Project represents a specific carbon credit offering with its own tokens and tracking.
 /// Each project has its own independent accounting of credits, separate from other projects.
+/// Accounts created before `layout_version` existed only hold the fields up to `project_bump`
+/// and must go through `migrate_project` once.
 #[account]
 pub struct Project {
     pub owner: Pubkey, // The user who lists their carbon credits license in the platform
@@ -12,14 +94,34 @@ pub struct Project {
     pub amount: u64,   // Total amount of tokens minted for this project
     pub remaining_amount: u64, // Amount of tokens not yet sold in this project
     pub offset_amount: u64, // Amount of tokens that have been offset in this project
-    pub price_per_token: u64, // Price per token in lamports
+    pub price_per_token: u64, // Price per whole token (tonne) in lamports
     pub carbon_pay_fee: u64, // Fee percentage taken by CarbonPay (e.g. 500 = 5.00%)
     pub carbon_pay_authority: Pubkey, // Authority that can receive fees
     pub project_bump: u8, // Project bump
+    pub allowlist_required: bool, // Whether buyers must be allowlisted for this project
+    pub min_purchase_amount: u64, // Minimum tokens per purchase (0 = no minimum)
+    pub max_purchase_amount: u64, // Maximum tokens per purchase (0 = no maximum)
+    pub max_per_buyer: u64, // Lifetime cap of tokens per buyer (0 = no cap)
+    pub sale_start: i64, // Unix timestamp when public sales open (0 = open immediately)
+    pub sale_end: i64, // Unix timestamp when sales close (0 = never closes)
+    pub early_access_start: i64, // Unix timestamp when allowlisted buyers may start buying (0 = no early access)
+    pub price_tiers: Vec<PriceTier>, // Volume discount tiers above `price_per_token` (empty = flat pricing)
+    pub auction: Option<DutchAuction>, // Dutch auction launch; replaces fixed pricing until settled
+    pub category: ProjectCategory, // Project category assigned by the platform
+    pub vault: Pubkey, // ATA of the carbon_credits PDA holding the unsold tokens
+    pub decimals: u8, // Decimals of token_mint; amounts are in base units, prices per whole token (tonne)
+    pub layout_version: u8, // Account layout, `LAYOUT_VERSION` once migrated
 }
 
 impl Project {
     pub const DISCRIMINATOR_SIZE: usize = 8;
+    pub const MAX_PRICE_TIERS: usize = 5;
+    /// Finest token precision supported, in line with `CarbonCredits::DECIMALS`
+    pub const MAX_TOKEN_DECIMALS: u8 = CarbonCredits::DECIMALS;
+    /// Layout with sale limits, pricing, category, vault and decimals
+    pub const LAYOUT_VERSION: u8 = 1;
+    /// Space of the layout before `layout_version`, ending at `project_bump`
+    pub const LEGACY_SPACE: usize = 32 + 32 + 32 + 1 + 1 + 8 * 5 + 32 + 1;
     pub const INIT_SPACE: usize = 32 +  // project_owner: Pubkey
         32 +  // mint: Pubkey
         32 +  // token_mint: Pubkey
@@ -31,7 +133,20 @@ impl Project {
         8 +   // price_per_token: u64
         8 +   // carbon_pay_fee: u64
         32 +  // carbon_pay_authority: Pubkey
-        1; // project_bump: u8
+        1 +   // project_bump: u8
+        1 +   // allowlist_required: bool
+        8 +   // min_purchase_amount: u64
+        8 +   // max_purchase_amount: u64
+        8 +   // max_per_buyer: u64
+        8 +   // sale_start: i64
+        8 +   // sale_end: i64
+        8 +   // early_access_start: i64
+        4 + 16 * Self::MAX_PRICE_TIERS + // price_tiers: Vec<PriceTier>
+        1 + DutchAuction::INIT_SPACE + // auction: Option<DutchAuction>
+        1 +   // category: ProjectCategory
+        32 +  // vault: Pubkey
+        1 +   // decimals: u8
+        1; // layout_version: u8
 
     /// Initialize a new carbon credit project
     pub fn initialize(&mut self) -> Result<()> {
@@ -58,4 +173,85 @@ impl Project {
             .ok_or(ProgramError::ArithmeticOverflow)?;
         Ok(())
     }
+
+    /// Check a purchase of `amount` against the per-transaction and per-buyer limits
+    pub fn check_purchase_limits(&self, amount: u64, already_purchased: u64) -> Result<()> {
+        require!(
+            amount >= self.min_purchase_amount,
+            ContractError::PurchaseBelowMinimum
+        );
+        require!(
+            self.max_purchase_amount == 0 || amount <= self.max_purchase_amount,
+            ContractError::PurchaseAboveMaximum
+        );
+        self.check_buyer_cap(amount, already_purchased)
+    }
+
+    /// Check that `amount` keeps the buyer within `max_per_buyer`
+    pub fn check_buyer_cap(&self, amount: u64, already_purchased: u64) -> Result<()> {
+        if self.max_per_buyer > 0 {
+            let total = already_purchased
+                .checked_add(amount)
+                .ok_or(ContractError::ArithmeticOverflow)?;
+            require!(total <= self.max_per_buyer, ContractError::BuyerCapExceeded);
+        }
+        Ok(())
+    }
+
+    /// Check that sales are open at `now`.
+    /// Returns true while in the early-access phase, where only allowlisted buyers may purchase.
+    pub fn check_sale_window(&self, now: i64) -> Result<bool> {
+        require!(
+            self.sale_end == 0 || now < self.sale_end,
+            ContractError::SaleEnded
+        );
+        if now >= self.sale_start {
+            return Ok(false);
+        }
+        require!(
+            self.early_access_start != 0 && now >= self.early_access_start,
+            ContractError::SaleNotStarted
+        );
+        Ok(true)
+    }
+
+    /// Total price in lamports for `amount` base units, rounded up to the lamport.
+    /// The first tokens are charged `price_per_token`; each tier's price applies to the
+    /// tokens of this purchase from its breakpoint up to the next one.
+    pub fn total_price(&self, amount: u64) -> Result<u64> {
+        let mut total: u128 = 0;
+        let mut tier_start: u64 = 0;
+        let mut tier_price = self.price_per_token;
+        for tier in self.price_tiers.iter() {
+            if amount <= tier.min_amount {
+                break;
+            }
+            let tokens = tier.min_amount - tier_start;
+            total = total
+                .checked_add(tokens as u128 * tier_price as u128)
+                .ok_or(ContractError::ArithmeticOverflow)?;
+            tier_start = tier.min_amount;
+            tier_price = tier.price_per_token;
+        }
+        let tokens = amount - tier_start;
+        total = total
+            .checked_add(tokens as u128 * tier_price as u128)
+            .ok_or(ContractError::ArithmeticOverflow)?;
+        let total = total.div_ceil(token_scale(self.decimals)? as u128);
+        Ok(u64::try_from(total).map_err(|_| ContractError::ArithmeticOverflow)?)
+    }
+
+    /// `amount` base units of this project's token in the platform-wide precision of
+    /// `CarbonCredits` counters
+    pub fn platform_units(&self, amount: u64) -> Result<u64> {
+        let factor = token_scale(CarbonCredits::DECIMALS - self.decimals)?;
+        Ok(amount
+            .checked_mul(factor)
+            .ok_or(ContractError::ArithmeticOverflow)?)
+    }
+
+    /// The running auction, if any. Purchases are priced by the auction until it is settled.
+    pub fn active_auction(&self) -> Option<&DutchAuction> {
+        self.auction.as_ref().filter(|auction| !auction.settled)
+    }
 }
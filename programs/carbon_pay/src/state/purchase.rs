@@ -1,5 +1,7 @@
 use anchor_lang::prelude::*;
 
+/// Accounts created before `certificate_mint` and `uncertified_offset` existed end at `nft_mint` and must go through
+/// `migrate_purchase` once.
 #[account]
 
 pub struct Purchase {
@@ -10,15 +12,21 @@ pub struct Purchase {
     pub purchase_date: i64,    // Timestamp when purchase was made
     pub purchase_bump: u8,     // Bump for the purchase PDA
     pub nft_mint: Pubkey,      // Mint of the NFT representing this purchase
+    pub certificate_mint: Pubkey, // Mint of the NFT currently certifying the position, replaced on each partial offset
+    pub uncertified_offset: u64, // Tokens offset in batch requests since the certificate was minted, which it does not reflect
 }
 
 impl Purchase {
     pub const DISCRIMINATOR_SIZE: usize = 8;
+    /// Space of the layout before `certificate_mint` and `uncertified_offset`, ending at `nft_mint`
+    pub const LEGACY_SPACE: usize = 32 + 32 + 8 * 3 + 1 + 32;
     pub const INIT_SPACE: usize = 32 + // buyer: Pubkey
         32 + // project: Pubkey
         8 +  // amount: u64
         8 +  // remaining_amount: u64
         8 +  // purchase_date: i64
         1 +  // purchase_bump: u8
-        32; // nft_mint: Pubkey
+        32 + // nft_mint: Pubkey
+        32 + // certificate_mint: Pubkey
+        8; // uncertified_offset: u64
 }
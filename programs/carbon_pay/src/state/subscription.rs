@@ -0,0 +1,40 @@
+use anchor_lang::prelude::*;
+
+/// Subscription buys a fixed amount of a project's credits once per period on behalf of a buyer.
+/// The buyer prefunds it with lamports held by the subscription PDA; a permissionless crank
+/// executes each period's purchase into a single Purchase position created at subscription time.
+#[account]
+pub struct Subscription {
+    pub buyer: Pubkey,              // The subscribing wallet
+    pub project: Pubkey,            // The project credits are bought from
+    pub purchase: Pubkey,           // The purchase position that accumulates the credits
+    pub amount_per_period: u64,     // Tokens bought each period
+    pub max_price_per_token: u64,   // Highest price per token (lamports) the buyer accepts
+    pub next_execution: i64,        // Unix timestamp from which the next purchase can be executed
+    pub executions: u64,            // Number of purchases executed so far
+    pub bump: u8,                   // The PDA bump
+}
+
+impl Subscription {
+    pub const DISCRIMINATOR_SIZE: usize = 8;
+    /// One month
+    pub const PERIOD: i64 = 30 * 24 * 60 * 60;
+    pub const INIT_SPACE: usize = 32 + // buyer: Pubkey
+        32 + // project: Pubkey
+        32 + // purchase: Pubkey
+        8 +  // amount_per_period: u64
+        8 +  // max_price_per_token: u64
+        8 +  // next_execution: i64
+        8 +  // executions: u64
+        1; // bump: u8
+
+    /// Whether the subscription PDA `info`, which need not exist, fills `purchase`
+    pub fn fills(info: &AccountInfo, purchase: &Pubkey) -> Result<bool> {
+        if info.data_is_empty() {
+            return Ok(false);
+        }
+        require_keys_eq!(*info.owner, crate::ID, ErrorCode::AccountOwnedByWrongProgram);
+        let subscription = Subscription::try_deserialize(&mut &info.try_borrow_data()?[..])?;
+        Ok(subscription.purchase == *purchase)
+    }
+}
@@ -0,0 +1,202 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::ContractError;
+use anchor_spl::{
+    metadata::{
+        create_metadata_accounts_v3,
+        mpl_token_metadata::types::{Creator, DataV2},
+        CreateMetadataAccountsV3,
+    },
+    token::{self, Burn, MintTo},
+};
+
+/// Basis points in 100%
+pub const BPS_DENOMINATOR: u64 = 10_000;
+
+/// Base units in one whole token (one tonne) of a mint with `decimals`
+pub fn token_scale(decimals: u8) -> Result<u64> {
+    10u64
+        .checked_pow(decimals as u32)
+        .ok_or(ContractError::ArithmeticOverflow.into())
+}
+
+/// Lamports for `amount` base units at `price_per_token` lamports per whole token,
+/// rounded up so a fraction of a lamport is never given away
+pub fn price_for(amount: u64, price_per_token: u64, decimals: u8) -> Result<u64> {
+    let scale = token_scale(decimals)? as u128;
+    let total = (amount as u128)
+        .checked_mul(price_per_token as u128)
+        .ok_or(ContractError::ArithmeticOverflow)?
+        .div_ceil(scale);
+    u64::try_from(total).map_err(|_| ContractError::ArithmeticOverflow.into())
+}
+
+/// Average price per whole token of a purchase of `amount` base units for `total` lamports,
+/// rounded down
+pub fn blended_price(total: u64, amount: u64, decimals: u8) -> Result<u64> {
+    let scale = token_scale(decimals)? as u128;
+    let price = (total as u128)
+        .checked_mul(scale)
+        .ok_or(ContractError::ArithmeticOverflow)?
+        .checked_div(amount as u128)
+        .ok_or(ContractError::ArithmeticOverflow)?;
+    u64::try_from(price).map_err(|_| ContractError::ArithmeticOverflow.into())
+}
+
+/// Move lamports out of an account owned by this program
+pub fn transfer_lamports_from_program_account(
+    from: &AccountInfo,
+    to: &AccountInfo,
+    amount: u64,
+) -> Result<()> {
+    let from_balance = from
+        .lamports()
+        .checked_sub(amount)
+        .ok_or(ContractError::ArithmeticOverflow)?;
+    let to_balance = to
+        .lamports()
+        .checked_add(amount)
+        .ok_or(ContractError::ArithmeticOverflow)?;
+    **from.try_borrow_mut_lamports()? = from_balance;
+    **to.try_borrow_mut_lamports()? = to_balance;
+    Ok(())
+}
+
+/// Pay for credits: `to_owner` lamports to the project owner and `fee` lamports to the platform
+pub fn pay_owner_and_platform<'info>(
+    system_program: AccountInfo<'info>,
+    payer: AccountInfo<'info>,
+    project_owner: AccountInfo<'info>,
+    carbon_credits: AccountInfo<'info>,
+    to_owner: u64,
+    fee: u64,
+) -> Result<()> {
+    anchor_lang::system_program::transfer(
+        CpiContext::new(
+            system_program.clone(),
+            anchor_lang::system_program::Transfer {
+                from: payer.clone(),
+                to: project_owner,
+            },
+        ),
+        to_owner,
+    )?;
+    anchor_lang::system_program::transfer(
+        CpiContext::new(
+            system_program,
+            anchor_lang::system_program::Transfer {
+                from: payer,
+                to: carbon_credits,
+            },
+        ),
+        fee,
+    )?;
+    Ok(())
+}
+
+/// Transfer project tokens out of a vault owned by the carbon_credits PDA
+pub fn transfer_from_vault<'info>(
+    token_program: AccountInfo<'info>,
+    vault: AccountInfo<'info>,
+    to: AccountInfo<'info>,
+    carbon_credits: AccountInfo<'info>,
+    carbon_credits_bump: u8,
+    amount: u64,
+) -> Result<()> {
+    token::transfer(
+        CpiContext::new_with_signer(
+            token_program,
+            token::Transfer {
+                from: vault,
+                to,
+                authority: carbon_credits,
+            },
+            &[&[b"carbon_credits", &[carbon_credits_bump]]],
+        ),
+        amount,
+    )
+}
+
+/// Burn project tokens held in a vault owned by the carbon_credits PDA
+pub fn burn_from_vault<'info>(
+    token_program: AccountInfo<'info>,
+    mint: AccountInfo<'info>,
+    vault: AccountInfo<'info>,
+    carbon_credits: AccountInfo<'info>,
+    carbon_credits_bump: u8,
+    amount: u64,
+) -> Result<()> {
+    token::burn(
+        CpiContext::new_with_signer(
+            token_program,
+            Burn {
+                mint,
+                from: vault,
+                authority: carbon_credits,
+            },
+            &[&[b"carbon_credits", &[carbon_credits_bump]]],
+        ),
+        amount,
+    )
+}
+
+/// Accounts needed to mint a purchase certificate NFT.
+/// `owner` is the mint authority of `nft_mint`, pays for the metadata and is its sole creator.
+pub struct PurchaseNft<'info> {
+    pub nft_mint: AccountInfo<'info>,
+    pub nft_account: AccountInfo<'info>,
+    pub nft_metadata: AccountInfo<'info>,
+    pub owner: AccountInfo<'info>,
+    pub token_program: AccountInfo<'info>,
+    pub token_metadata_program: AccountInfo<'info>,
+    pub system_program: AccountInfo<'info>,
+    pub rent: AccountInfo<'info>,
+}
+
+/// Mint 1 NFT to the owner and create its metadata, as done for every purchase certificate
+pub fn mint_purchase_nft(accounts: PurchaseNft<'_>, name: String, uri: String) -> Result<()> {
+    token::mint_to(
+        CpiContext::new(
+            accounts.token_program.clone(),
+            MintTo {
+                mint: accounts.nft_mint.clone(),
+                to: accounts.nft_account.clone(),
+                authority: accounts.owner.clone(),
+            },
+        ),
+        1,
+    )?;
+
+    create_metadata_accounts_v3(
+        CpiContext::new(
+            accounts.token_metadata_program.clone(),
+            CreateMetadataAccountsV3 {
+                metadata: accounts.nft_metadata.clone(),
+                mint: accounts.nft_mint.clone(),
+                mint_authority: accounts.owner.clone(),
+                payer: accounts.owner.clone(),
+                update_authority: accounts.owner.clone(),
+                system_program: accounts.system_program.clone(),
+                rent: accounts.rent.clone(),
+            },
+        ),
+        DataV2 {
+            name,
+            symbol: "CRBN".to_string(),
+            uri,
+            seller_fee_basis_points: 0,
+            creators: Some(vec![Creator {
+                address: accounts.owner.key(),
+                verified: true,
+                share: 100,
+            }]),
+            collection: None,
+            uses: None,
+        },
+        true,
+        true,
+        None,
+    )?;
+
+    Ok(())
+}
@@ -0,0 +1,520 @@
+//! Random sequences of purchase, offset, listing, basket and position operations. A model
+//! predicts whether each operation succeeds and what it changes; after every step the chain
+//! must agree with the model and the accounting invariants must hold.
+
+mod common;
+
+use std::collections::HashMap;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_spl::associated_token::get_associated_token_address;
+use carbonpay::state::{pro_rata_shares, Basket, BasketReserve, CarbonCredits, Holdings, Listing, Project, Purchase};
+use carbonpay_client::{instructions as ix, pda};
+use common::{ProjectFixture, PurchaseFixture, TestEnv};
+use litesvm::types::TransactionResult;
+use proptest::prelude::*;
+use proptest::test_runner::{Config, TestCaseError, TestRunner};
+use solana_keypair::Keypair;
+use solana_signer::Signer;
+
+const BUYERS: usize = 3;
+const BASKET_ID: u64 = 1;
+const LISTING_PRICE: u64 = 1_000;
+
+#[derive(Clone, Debug)]
+enum Op {
+    CreateProject { amount: u64, price_per_token: u64, fee_bps: u64 },
+    Purchase { project: usize, buyer: usize, amount: u64 },
+    PurchaseAndRetire { project: usize, buyer: usize, amount: u64 },
+    Offset { position: usize, amount: u64 },
+    List { position: usize, amount: u64 },
+    BuyListing { position: usize, buyer: usize },
+    CancelListing { position: usize },
+    Deposit { position: usize, amount: u64 },
+    Redeem { buyer: usize, amount: u64 },
+    Retire { buyer: usize, amount: u64 },
+    Merge { target: usize, source: usize },
+    Split { position: usize, amount: u64 },
+    Transfer { position: usize, recipient: usize },
+}
+
+fn op() -> impl Strategy<Value = Op> {
+    prop_oneof![
+        1 => (1..200u64, 1..10_000u64, 0..=10_000u64).prop_map(|(amount, price_per_token, fee_bps)| {
+            Op::CreateProject { amount, price_per_token, fee_bps }
+        }),
+        3 => (any::<usize>(), 0..BUYERS, 1..60u64)
+            .prop_map(|(project, buyer, amount)| Op::Purchase { project, buyer, amount }),
+        1 => (any::<usize>(), 0..BUYERS, 1..30u64)
+            .prop_map(|(project, buyer, amount)| Op::PurchaseAndRetire { project, buyer, amount }),
+        2 => (any::<usize>(), 0..30u64).prop_map(|(position, amount)| Op::Offset { position, amount }),
+        1 => (any::<usize>(), 0..20u64).prop_map(|(position, amount)| Op::List { position, amount }),
+        1 => (any::<usize>(), 0..BUYERS).prop_map(|(position, buyer)| Op::BuyListing { position, buyer }),
+        1 => any::<usize>().prop_map(|position| Op::CancelListing { position }),
+        1 => (any::<usize>(), 0..20u64).prop_map(|(position, amount)| Op::Deposit { position, amount }),
+        1 => (0..BUYERS, 0..20u64).prop_map(|(buyer, amount)| Op::Redeem { buyer, amount }),
+        1 => (0..BUYERS, 0..20u64).prop_map(|(buyer, amount)| Op::Retire { buyer, amount }),
+        1 => (any::<usize>(), any::<usize>()).prop_map(|(target, source)| Op::Merge { target, source }),
+        1 => (any::<usize>(), 0..20u64).prop_map(|(position, amount)| Op::Split { position, amount }),
+        1 => (any::<usize>(), 0..BUYERS).prop_map(|(position, recipient)| Op::Transfer { position, recipient }),
+    ]
+}
+
+struct ProjectModel {
+    fixture: ProjectFixture,
+    /// tokens sold from the vault, including those retired at purchase
+    sold: u64,
+    remaining: u64,
+    offset: u64,
+}
+
+/// A live Purchase record as the model expects it
+struct Position {
+    address: Pubkey,
+    nft_mint: Pubkey,
+    owner: usize,
+    project: usize,
+    amount: u64,
+    remaining: u64,
+    /// whether a certificate NFT still stands for the position
+    certified: bool,
+    listed: Option<u64>,
+}
+
+impl Position {
+    fn fixture(&self) -> PurchaseFixture {
+        PurchaseFixture { address: self.address, nft_mint: self.nft_mint }
+    }
+}
+
+struct Model {
+    env: TestEnv,
+    buyers: Vec<Keypair>,
+    projects: Vec<ProjectModel>,
+    positions: Vec<Position>,
+    /// addresses of closed Purchase records
+    closed: Vec<Pubkey>,
+    /// (buyer, project) tokens held outside any position, paid out by basket redemptions
+    loose: HashMap<(usize, usize), u64>,
+    /// basket reserves in the order they were created: (project, amount)
+    reserves: Vec<(usize, u64)>,
+    basket_tokens: [u64; BUYERS],
+    requests: usize,
+    /// lamports held by carbon_credits before any fee was charged
+    carbon_credits_base: u64,
+}
+
+/// Assert that `result` matches the model's prediction and pass the prediction on
+fn outcome(op: &Op, result: &TransactionResult, expected: bool) -> Result<bool, TestCaseError> {
+    prop_assert_eq!(result.is_ok(), expected, "{:?} -> {:?}", op, result);
+    Ok(expected)
+}
+
+impl Model {
+    fn request_id(&mut self) -> String {
+        self.requests += 1;
+        format!("R{}", self.requests)
+    }
+
+    fn basket_reserves(&self) -> Vec<BasketReserve> {
+        let basket = pda::basket(BASKET_ID).0;
+        self.reserves
+            .iter()
+            .map(|(project, _)| {
+                self.env
+                    .account(&pda::basket_reserve(&basket, &self.projects[*project].fixture.address).0)
+            })
+            .collect()
+    }
+
+    fn apply(&mut self, op: &Op) -> Result<(), TestCaseError> {
+        // positions are picked among the live ones, operations with nothing to act on are skipped
+        let positions = self.positions.len();
+        let pick = |index: usize| (positions > 0).then(|| index % positions);
+        match *op {
+            Op::CreateProject { amount, price_per_token, fee_bps } => {
+                let fixture = self.env.create_project(amount, price_per_token, fee_bps);
+                self.projects.push(ProjectModel { fixture, sold: 0, remaining: amount, offset: 0 });
+            }
+            Op::Purchase { project, buyer, amount } => {
+                if self.projects.is_empty() {
+                    return Ok(());
+                }
+                let p = project % self.projects.len();
+                let expected = amount <= self.projects[p].remaining;
+                let (result, fixture) = self.env.try_purchase(&self.buyers[buyer], &self.projects[p].fixture, amount);
+                if outcome(op, &result, expected)? {
+                    self.projects[p].remaining -= amount;
+                    self.projects[p].sold += amount;
+                    self.positions.push(Position {
+                        address: fixture.address,
+                        nft_mint: fixture.nft_mint,
+                        owner: buyer,
+                        project: p,
+                        amount,
+                        remaining: amount,
+                        certified: true,
+                        listed: None,
+                    });
+                }
+            }
+            Op::PurchaseAndRetire { project, buyer, amount } => {
+                if self.projects.is_empty() {
+                    return Ok(());
+                }
+                let p = project % self.projects.len();
+                let expected = amount <= self.projects[p].remaining;
+                let request_id = self.request_id();
+                let address = self.projects[p].fixture.address;
+                let project_account: Project = self.env.account(&address);
+                let buyer = &self.buyers[buyer];
+                let result = self.env.send(
+                    &[ix::purchase_and_retire(&buyer.pubkey(), &address, &project_account, amount, request_id, None)],
+                    buyer,
+                    &[],
+                );
+                if outcome(op, &result, expected)? {
+                    self.projects[p].remaining -= amount;
+                    self.projects[p].sold += amount;
+                    self.projects[p].offset += amount;
+                }
+            }
+            Op::Offset { position, amount } => {
+                let Some(i) = pick(position) else { return Ok(()) };
+                let request_id = self.request_id();
+                let position = &self.positions[i];
+                let expected = position.certified && amount > 0 && amount <= position.remaining;
+                let (result, _, _) =
+                    self.env
+                        .try_request_offset(&self.buyers[position.owner], &position.fixture(), amount, &request_id);
+                if outcome(op, &result, expected)? {
+                    let position = &mut self.positions[i];
+                    position.remaining -= amount;
+                    position.certified = position.remaining > 0;
+                    self.projects[position.project].offset += amount;
+                }
+            }
+            Op::List { position, amount } => {
+                let Some(i) = pick(position) else { return Ok(()) };
+                let position = &self.positions[i];
+                let expected = position.listed.is_none() && amount > 0 && amount <= position.remaining;
+                let record: Purchase = self.env.account(&position.address);
+                let project_account: Project = self.env.account(&record.project);
+                let seller = &self.buyers[position.owner];
+                let result = self.env.send(
+                    &[ix::list_credits(&seller.pubkey(), &position.address, &record, &project_account, amount, LISTING_PRICE)],
+                    seller,
+                    &[],
+                );
+                if outcome(op, &result, expected)? {
+                    self.positions[i].remaining -= amount;
+                    self.positions[i].listed = Some(amount);
+                }
+            }
+            Op::BuyListing { position, buyer } => {
+                let Some(i) = pick(position) else { return Ok(()) };
+                let Some(amount) = self.positions[i].listed else { return Ok(()) };
+                let (seller, project) = (self.positions[i].owner, self.positions[i].project);
+                let buyer = if buyer == seller { (buyer + 1) % BUYERS } else { buyer };
+                let listing = pda::listing(&self.positions[i].address).0;
+                let (result, fixture) = self.env.try_buy_listing(&self.buyers[buyer], &listing, None);
+                if outcome(op, &result, true)? {
+                    self.positions[i].listed = None;
+                    self.positions.push(Position {
+                        address: fixture.address,
+                        nft_mint: fixture.nft_mint,
+                        owner: buyer,
+                        project,
+                        amount,
+                        remaining: amount,
+                        certified: true,
+                        listed: None,
+                    });
+                }
+            }
+            Op::CancelListing { position } => {
+                let Some(i) = pick(position) else { return Ok(()) };
+                let Some(amount) = self.positions[i].listed else { return Ok(()) };
+                let address = pda::listing(&self.positions[i].address).0;
+                let listing: Listing = self.env.account(&address);
+                let project_account: Project = self.env.account(&listing.project);
+                let seller = &self.buyers[self.positions[i].owner];
+                let result =
+                    self.env
+                        .send(&[ix::cancel_listing(&seller.pubkey(), &address, &listing, &project_account)], seller, &[]);
+                if outcome(op, &result, true)? {
+                    self.positions[i].remaining += amount;
+                    self.positions[i].listed = None;
+                }
+            }
+            Op::Deposit { position, amount } => {
+                let Some(i) = pick(position) else { return Ok(()) };
+                let position = &self.positions[i];
+                let has_reserve = self.reserves.iter().any(|(p, _)| *p == position.project);
+                let expected = position.certified
+                    && amount > 0
+                    && amount <= position.remaining
+                    && (has_reserve || (self.reserves.len() as u64) < Basket::MAX_PROJECTS);
+                let result =
+                    self.env
+                        .try_deposit_to_basket(&self.buyers[position.owner], BASKET_ID, &position.fixture(), amount);
+                if outcome(op, &result, expected)? {
+                    let (owner, project) = (position.owner, position.project);
+                    self.positions[i].remaining -= amount;
+                    self.positions[i].certified = self.positions[i].remaining > 0;
+                    match self.reserves.iter_mut().find(|(p, _)| *p == project) {
+                        Some((_, reserve)) => *reserve += amount,
+                        None => self.reserves.push((project, amount)),
+                    }
+                    self.basket_tokens[owner] += amount;
+                }
+            }
+            Op::Redeem { buyer, amount } | Op::Retire { buyer, amount } => {
+                let redeem = matches!(op, Op::Redeem { .. });
+                let expected = amount > 0 && amount <= self.basket_tokens[buyer];
+                let reserves = self.basket_reserves();
+                let holder = &self.buyers[buyer];
+                let result = if redeem {
+                    self.env
+                        .send(&ix::redeem_from_basket(&holder.pubkey(), BASKET_ID, &reserves, amount), holder, &[])
+                } else {
+                    self.env
+                        .send(&[ix::retire_from_basket(&holder.pubkey(), BASKET_ID, &reserves, amount)], holder, &[])
+                };
+                if outcome(op, &result, expected)? {
+                    let balances: Vec<u64> = self.reserves.iter().map(|(_, amount)| *amount).collect();
+                    let shares = pro_rata_shares(&balances, amount).unwrap();
+                    for ((project, reserve), share) in self.reserves.iter_mut().zip(shares) {
+                        *reserve -= share;
+                        if redeem {
+                            *self.loose.entry((buyer, *project)).or_default() += share;
+                        } else {
+                            self.projects[*project].offset += share;
+                        }
+                    }
+                    // emptied reserves are closed and free their slot
+                    self.reserves.retain(|(_, reserve)| *reserve > 0);
+                    self.basket_tokens[buyer] -= amount;
+                }
+            }
+            Op::Merge { target, source } => {
+                let (Some(t), Some(s)) = (pick(target), pick(source)) else { return Ok(()) };
+                let (target, source) = (&self.positions[t], &self.positions[s]);
+                let expected = t != s
+                    && target.owner == source.owner
+                    && target.project == source.project
+                    && source.listed.is_none()
+                    && source.certified
+                    && target.certified;
+                let target_record: Purchase = self.env.account(&target.address);
+                let source_record: Purchase = self.env.account(&source.address);
+                let owner = &self.buyers[target.owner];
+                let replacement = Keypair::new();
+                let result = self.env.send(
+                    &ix::merge_purchases(
+                        &owner.pubkey(),
+                        &target.address,
+                        &target_record,
+                        &replacement.pubkey(),
+                        &[(source.address, source_record)],
+                    ),
+                    owner,
+                    &[&replacement],
+                );
+                if outcome(op, &result, expected)? {
+                    let source = self.positions.remove(s);
+                    let target = &mut self.positions[if s < t { t - 1 } else { t }];
+                    target.amount += source.amount;
+                    target.remaining += source.remaining;
+                    self.closed.push(source.address);
+                }
+            }
+            Op::Split { position, amount } => {
+                let Some(i) = pick(position) else { return Ok(()) };
+                let position = &self.positions[i];
+                let expected = position.certified && amount > 0 && amount <= position.remaining;
+                let record: Purchase = self.env.account(&position.address);
+                let (new_nft_mint, replacement) = (Keypair::new(), Keypair::new());
+                let owner = &self.buyers[position.owner];
+                let result = self.env.send(
+                    &ix::split_purchase(&owner.pubkey(), &position.address, &record, &new_nft_mint.pubkey(), &replacement.pubkey(), amount),
+                    owner,
+                    &[&new_nft_mint, &replacement],
+                );
+                if outcome(op, &result, expected)? {
+                    let (owner, project) = (position.owner, position.project);
+                    let position = &mut self.positions[i];
+                    position.amount -= amount;
+                    position.remaining -= amount;
+                    position.certified = position.remaining > 0;
+                    let address = pda::purchase(&self.buyers[owner].pubkey(), &self.projects[project].fixture.address, &new_nft_mint.pubkey()).0;
+                    self.positions.push(Position {
+                        address,
+                        nft_mint: new_nft_mint.pubkey(),
+                        owner,
+                        project,
+                        amount,
+                        remaining: amount,
+                        certified: true,
+                        listed: None,
+                    });
+                }
+            }
+            Op::Transfer { position, recipient } => {
+                let Some(i) = pick(position) else { return Ok(()) };
+                let position = &self.positions[i];
+                let recipient = (position.owner + 1 + recipient % (BUYERS - 1)) % BUYERS;
+                let expected = position.certified && position.listed.is_none();
+                let record: Purchase = self.env.account(&position.address);
+                let project_account: Project = self.env.account(&record.project);
+                let owner = &self.buyers[position.owner];
+                let result = self.env.send(
+                    &[ix::transfer_purchase(
+                        &owner.pubkey(),
+                        &self.buyers[recipient].pubkey(),
+                        &position.address,
+                        &record,
+                        &project_account,
+                    )],
+                    owner,
+                    &[],
+                );
+                if outcome(op, &result, expected)? {
+                    let project = self.projects[position.project].fixture.address;
+                    let position = &mut self.positions[i];
+                    self.closed.push(position.address);
+                    position.address = pda::purchase(&self.buyers[recipient].pubkey(), &project, &position.nft_mint).0;
+                    position.owner = recipient;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn check_invariants(&self) -> Result<(), TestCaseError> {
+        let mut total_credits = 0;
+        let mut offset_credits = 0;
+        for model in &self.projects {
+            let project: Project = self.env.account(&model.fixture.address);
+            prop_assert_eq!(project.remaining_amount, model.remaining);
+            prop_assert_eq!(project.offset_amount, model.offset);
+            // what was sold plus what is left is the project's supply
+            prop_assert_eq!(model.sold + project.remaining_amount, project.amount);
+            prop_assert_eq!(self.env.token_balance(&model.fixture.vault()), project.remaining_amount);
+            // every offset burned its tokens
+            prop_assert_eq!(self.env.mint_supply(&model.fixture.token_mint) + project.offset_amount, project.amount);
+            total_credits += project.platform_units(project.amount).unwrap();
+            offset_credits += project.platform_units(project.offset_amount).unwrap();
+        }
+
+        for position in &self.positions {
+            let record: Purchase = self.env.account(&position.address);
+            prop_assert_eq!(record.buyer, self.buyers[position.owner].pubkey());
+            prop_assert_eq!(record.amount, position.amount);
+            prop_assert_eq!(record.remaining_amount, position.remaining);
+            prop_assert!(record.remaining_amount <= record.amount);
+            let listing = pda::listing(&position.address).0;
+            match position.listed {
+                Some(amount) => {
+                    let project = &self.projects[position.project].fixture;
+                    let escrow = get_associated_token_address(&listing, &project.token_mint);
+                    prop_assert_eq!(self.env.account::<Listing>(&listing).amount, amount);
+                    prop_assert_eq!(self.env.token_balance(&escrow), amount);
+                }
+                None => prop_assert!(!self.env.exists(&listing)),
+            }
+        }
+        for address in &self.closed {
+            if self.positions.iter().all(|position| position.address != *address) {
+                prop_assert!(!self.env.exists(address));
+            }
+        }
+
+        // a wallet holds exactly its positions' tokens plus what it redeemed from the basket,
+        // and its Holdings accounts for the positions' share
+        for (buyer, keypair) in self.buyers.iter().enumerate() {
+            for (p, model) in self.projects.iter().enumerate() {
+                let backed: u64 = self
+                    .positions
+                    .iter()
+                    .filter(|position| position.owner == buyer && position.project == p)
+                    .map(|position| position.remaining)
+                    .sum();
+                let held = backed + self.loose.get(&(buyer, p)).copied().unwrap_or(0);
+                let tokens = get_associated_token_address(&keypair.pubkey(), &model.fixture.token_mint);
+                let balance = if self.env.exists(&tokens) { self.env.token_balance(&tokens) } else { 0 };
+                prop_assert_eq!(balance, held);
+                let holdings = pda::holdings(&keypair.pubkey(), &model.fixture.address).0;
+                if self.env.exists(&holdings) {
+                    prop_assert_eq!(self.env.account::<Holdings>(&holdings).backed_amount, backed);
+                } else {
+                    prop_assert_eq!(backed, 0);
+                }
+            }
+        }
+
+        let address = pda::basket(BASKET_ID).0;
+        let basket: Basket = self.env.account(&address);
+        let deposited: u64 = self.reserves.iter().map(|(_, amount)| amount).sum();
+        prop_assert_eq!(basket.project_count, self.reserves.len() as u64);
+        prop_assert_eq!(basket.total_deposited, deposited);
+        let basket_mint = pda::basket_mint(&address).0;
+        prop_assert_eq!(self.env.mint_supply(&basket_mint), deposited);
+        for (reserve, (_, amount)) in self.basket_reserves().iter().zip(&self.reserves) {
+            prop_assert_eq!(reserve.amount, *amount);
+            prop_assert_eq!(self.env.token_balance(&reserve.vault), *amount);
+        }
+        for (keypair, expected) in self.buyers.iter().zip(self.basket_tokens) {
+            let tokens = get_associated_token_address(&keypair.pubkey(), &basket_mint);
+            let balance = if self.env.exists(&tokens) { self.env.token_balance(&tokens) } else { 0 };
+            prop_assert_eq!(balance, expected);
+        }
+
+        let carbon_credits: CarbonCredits = self.env.account(&pda::carbon_credits().0);
+        prop_assert_eq!(carbon_credits.total_credits, total_credits);
+        prop_assert_eq!(carbon_credits.offset_credits, offset_credits);
+        prop_assert_eq!(carbon_credits.active_credits, total_credits);
+        prop_assert_eq!(carbon_credits.projects_count, self.projects.len() as u64);
+        // every lamport paid to the platform is a fee it has recorded
+        prop_assert_eq!(
+            carbon_credits.total_fees_earned,
+            self.env.balance(&pda::carbon_credits().0) - self.carbon_credits_base
+        );
+        Ok(())
+    }
+}
+
+#[test]
+#[ignore = "needs target/deploy/carbonpay.so, run `anchor build` first"]
+fn accounting_invariants_hold_across_random_sequences() {
+    let mut runner = TestRunner::new(Config {
+        cases: 16,
+        ..Config::default()
+    });
+    runner
+        .run(&prop::collection::vec(op(), 1..40), |ops| {
+            let mut env = common::setup();
+            env.init_platform();
+            env.create_basket(BASKET_ID);
+            let buyers = (0..BUYERS).map(|_| env.funded_keypair()).collect();
+            let carbon_credits_base = env.balance(&pda::carbon_credits().0);
+            let mut model = Model {
+                env,
+                buyers,
+                projects: Vec::new(),
+                positions: Vec::new(),
+                closed: Vec::new(),
+                loose: HashMap::new(),
+                reserves: Vec::new(),
+                basket_tokens: [0; BUYERS],
+                requests: 0,
+                carbon_credits_base,
+            };
+            for op in &ops {
+                model.apply(op)?;
+                model.check_invariants()?;
+            }
+            Ok(())
+        })
+        .unwrap();
+}
@@ -0,0 +1,192 @@
+mod common;
+
+use carbonpay::errors::ContractError;
+use carbonpay::events::AuctionSettled;
+use carbonpay::state::{BuyerStats, DutchAuction, Project};
+use carbonpay_client::{instructions as ix, pda};
+use common::{FEE_BPS, PRICE_PER_TOKEN};
+use solana_signer::Signer;
+
+const START: i64 = 1_000;
+
+#[test]
+#[ignore = "needs target/deploy/carbonpay.so, run `anchor build` first"]
+fn dutch_auction_settles_at_the_clearing_price() {
+    let mut env = common::setup();
+    env.init_platform();
+    env.set_time(START);
+    let project = env.create_project(10, PRICE_PER_TOKEN, FEE_BPS);
+    let owner = project.owner.insecure_clone();
+    // 2_000_000 down to 1_000_000 in ten steps of 10 seconds, the last 10 seconds at the floor
+    env.send(
+        &[ix::start_dutch_auction(&owner.pubkey(), &project.address, 2_000_000, 1_000_000, START, 110, 10)],
+        &owner,
+        &[],
+    )
+    .unwrap();
+
+    let early = env.funded_keypair();
+    env.purchase(&early, &project, 4);
+    let result = env.send(&[ix::claim_auction_refund(&early.pubkey(), &project.address)], &early, &[]);
+    common::assert_contract_error(result, ContractError::AuctionNotSettled);
+    let project_account: Project = env.account(&project.address);
+    let cranker = env.admin.insecure_clone();
+    let result = env.send(&[ix::settle_dutch_auction(&project.address, &project_account)], &cranker, &[]);
+    common::assert_contract_error(result, ContractError::AuctionNotEnded);
+
+    // halfway down, the second buyer sells the auction out
+    env.set_time(START + 50);
+    let late = env.funded_keypair();
+    env.purchase(&late, &project, 6);
+    let owner_before = env.balance(&owner.pubkey());
+    let project_account: Project = env.account(&project.address);
+    let result = env.send(&[ix::settle_dutch_auction(&project.address, &project_account)], &cranker, &[]);
+    let event: AuctionSettled = common::event(result);
+    assert_eq!((event.amount, event.clearing_price, event.total_price), (10, 1_500_000, 15_000_000));
+    assert_eq!((event.fee, event.fee_remainder), (750_000, 0));
+    let auction = env.account::<Project>(&project.address).auction.unwrap();
+    assert!(auction.settled);
+    assert_eq!((auction.sold, auction.last_price), (10, 1_500_000));
+    // 10 tokens at the clearing price, less the platform's 5%
+    assert_eq!(env.balance(&owner.pubkey()) - owner_before, 14_250_000);
+
+    // the early buyer paid 2_000_000 per token, 500_000 above the clearing price
+    let escrow_before = env.balance(&project.address);
+    env.send(&[ix::claim_auction_refund(&early.pubkey(), &project.address)], &early, &[])
+        .unwrap();
+    assert_eq!(escrow_before - env.balance(&project.address), 2_000_000);
+    let stats: BuyerStats = env.account(&pda::buyer_stats(&early.pubkey(), &project.address).0);
+    assert_eq!((stats.auction_amount, stats.auction_paid), (0, 0));
+
+    let result = env.send(&[ix::claim_auction_refund(&early.pubkey(), &project.address)], &early, &[]);
+    common::assert_contract_error(result, ContractError::NothingToRefund);
+    let result = env.send(&[ix::claim_auction_refund(&late.pubkey(), &project.address)], &late, &[]);
+    common::assert_contract_error(result, ContractError::NothingToRefund);
+}
+
+#[test]
+fn auction_price_reaches_the_floor_in_the_last_interval() {
+    let auction = |duration, decay_interval| DutchAuction {
+        start_price: 2_000_000,
+        floor_price: 1_000_000,
+        start_time: START,
+        duration,
+        decay_interval,
+        last_price: 0,
+        sold: 0,
+        settled: false,
+    };
+    for (duration, decay_interval) in [(100, 10), (105, 10), (20, 10), (100, 1)] {
+        let auction = auction(duration, decay_interval);
+        assert_eq!(auction.current_price(START).unwrap(), 2_000_000);
+        assert_eq!(auction.current_price(auction.end_time() - 1).unwrap(), 1_000_000);
+        assert!(auction.current_price(auction.end_time()).is_err());
+    }
+    // ten intervals of 10 seconds: nine steps down, evenly spaced
+    let auction = auction(100, 10);
+    assert_eq!(auction.current_price(START + 45).unwrap(), 1_555_556);
+    assert_eq!(auction.current_price(START + 89).unwrap(), 1_111_112);
+}
+
+#[test]
+#[ignore = "needs target/deploy/carbonpay.so, run `anchor build` first"]
+fn rejects_invalid_auctions() {
+    let mut env = common::setup();
+    env.init_platform();
+    env.set_time(START);
+    let project = env.create_project(10, PRICE_PER_TOKEN, FEE_BPS);
+    let owner = project.owner.insecure_clone();
+
+    for (start_price, floor_price, duration, decay_interval) in
+        [(1_000, 2_000, 100, 10), (2_000, 1_000, 0, 10), (2_000, 1_000, 100, 0), (2_000, 1_000, 100, 101)]
+    {
+        let result = env.send(
+            &[ix::start_dutch_auction(
+                &owner.pubkey(),
+                &project.address,
+                start_price,
+                floor_price,
+                START,
+                duration,
+                decay_interval,
+            )],
+            &owner,
+            &[],
+        );
+        common::assert_contract_error(result, ContractError::InvalidAuction);
+    }
+
+    let stranger = env.funded_keypair();
+    let result = env.send(
+        &[ix::start_dutch_auction(&stranger.pubkey(), &project.address, 2_000, 1_000, START, 100, 10)],
+        &stranger,
+        &[],
+    );
+    common::assert_contract_error(result, ContractError::InvalidProjectOwner);
+
+    env.send(
+        &[ix::start_dutch_auction(&owner.pubkey(), &project.address, 2_000, 1_000, START, 100, 10)],
+        &owner,
+        &[],
+    )
+    .unwrap();
+    let result = env.send(
+        &[ix::start_dutch_auction(&owner.pubkey(), &project.address, 2_000, 1_000, START, 100, 10)],
+        &owner,
+        &[],
+    );
+    common::assert_contract_error(result, ContractError::InvalidAuction);
+
+    // a project that already sold can't be auctioned
+    let sold = env.create_project(10, PRICE_PER_TOKEN, FEE_BPS);
+    let buyer = env.funded_keypair();
+    env.purchase(&buyer, &sold, 1);
+    let sold_owner = sold.owner.insecure_clone();
+    let result = env.send(
+        &[ix::start_dutch_auction(&sold_owner.pubkey(), &sold.address, 2_000, 1_000, START, 100, 10)],
+        &sold_owner,
+        &[],
+    );
+    common::assert_contract_error(result, ContractError::InvalidAuction);
+
+    // no auction to settle or refund
+    let sold_account: Project = env.account(&sold.address);
+    let cranker = env.admin.insecure_clone();
+    let result = env.send(&[ix::settle_dutch_auction(&sold.address, &sold_account)], &cranker, &[]);
+    common::assert_contract_error(result, ContractError::NoActiveAuction);
+    let result = env.send(&[ix::claim_auction_refund(&buyer.pubkey(), &sold.address)], &buyer, &[]);
+    common::assert_contract_error(result, ContractError::NoActiveAuction);
+}
+
+#[test]
+#[ignore = "needs target/deploy/carbonpay.so, run `anchor build` first"]
+fn auction_stops_selling_once_it_ends() {
+    let mut env = common::setup();
+    env.init_platform();
+    env.set_time(START);
+    let project = env.create_project(10, PRICE_PER_TOKEN, FEE_BPS);
+    let owner = project.owner.insecure_clone();
+    env.send(
+        &[ix::start_dutch_auction(&owner.pubkey(), &project.address, 2_000_000, 1_000_000, START, 100, 10)],
+        &owner,
+        &[],
+    )
+    .unwrap();
+    let buyer = env.funded_keypair();
+    env.purchase(&buyer, &project, 3);
+
+    env.set_time(START + 100);
+    let (result, _) = env.try_purchase(&buyer, &project, 1);
+    common::assert_contract_error(result, ContractError::AuctionEnded);
+
+    let project_account: Project = env.account(&project.address);
+    let cranker = env.admin.insecure_clone();
+    env.send(&[ix::settle_dutch_auction(&project.address, &project_account)], &cranker, &[])
+        .unwrap();
+    let result = env.send(&[ix::settle_dutch_auction(&project.address, &project_account)], &cranker, &[]);
+    common::assert_contract_error(result, ContractError::NoActiveAuction);
+
+    // unsold tokens go back to fixed pricing
+    env.purchase(&buyer, &project, 1);
+    assert_eq!(env.account::<Project>(&project.address).remaining_amount, 6);
+}
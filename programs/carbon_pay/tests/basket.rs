@@ -0,0 +1,244 @@
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_spl::associated_token::get_associated_token_address;
+use carbonpay::errors::ContractError;
+use carbonpay::state::{
+    Basket, BasketReserve, CarbonCredits, Holdings, OffsetRequest, Project, ProjectCategory, Purchase,
+};
+use carbonpay_client::{instructions as ix, pda};
+use common::{FEE_BPS, PRICE_PER_TOKEN, PROJECT_AMOUNT};
+use solana_signer::Signer;
+
+const BASKET_ID: u64 = 7;
+
+#[test]
+#[ignore = "needs target/deploy/carbonpay.so, run `anchor build` first"]
+fn caps_projects_per_basket_within_transaction_limits() {
+    let mut env = common::setup();
+    env.init_platform();
+    env.create_basket(BASKET_ID);
+    let basket = pda::basket(BASKET_ID).0;
+    let depositor = env.funded_keypair();
+
+    let mut reserves = Vec::new();
+    for _ in 0..Basket::MAX_PROJECTS {
+        let project = env.create_project(PROJECT_AMOUNT, PRICE_PER_TOKEN, FEE_BPS);
+        let purchase = env.purchase(&depositor, &project, 10);
+        env.try_deposit_to_basket(&depositor, BASKET_ID, &purchase, 10).unwrap();
+        reserves.push(env.account::<BasketReserve>(&pda::basket_reserve(&basket, &project.address).0));
+    }
+
+    let project = env.create_project(PROJECT_AMOUNT, PRICE_PER_TOKEN, FEE_BPS);
+    let purchase = env.purchase(&depositor, &project, 10);
+    let result = env.try_deposit_to_basket(&depositor, BASKET_ID, &purchase, 10);
+    common::assert_contract_error(result, ContractError::BasketFull);
+
+    // A full basket still redeems and retires across every reserve, through a lookup table
+    env.redeem_from_basket(&depositor, BASKET_ID, &reserves, 10);
+    env.retire_from_basket(&depositor, BASKET_ID, &reserves, 10);
+    let basket: Basket = env.account(&basket);
+    assert_eq!(basket.project_count, Basket::MAX_PROJECTS);
+    assert_eq!(basket.total_deposited, 10 * Basket::MAX_PROJECTS - 20);
+}
+
+#[test]
+#[ignore = "needs target/deploy/carbonpay.so, run `anchor build` first"]
+fn retiring_from_a_basket_counts_offset_credits() {
+    let mut env = common::setup();
+    env.init_platform();
+    env.create_basket(BASKET_ID);
+    let basket = pda::basket(BASKET_ID).0;
+    let depositor = env.funded_keypair();
+    let project = env.create_project(PROJECT_AMOUNT, PRICE_PER_TOKEN, FEE_BPS);
+    let purchase = env.purchase(&depositor, &project, 10);
+    env.try_deposit_to_basket(&depositor, BASKET_ID, &purchase, 10).unwrap();
+    let reserve: BasketReserve = env.account(&pda::basket_reserve(&basket, &project.address).0);
+
+    env.send(&[ix::retire_from_basket(&depositor.pubkey(), BASKET_ID, &[reserve], 6)], &depositor, &[])
+        .unwrap();
+    let carbon_credits: CarbonCredits = env.account(&pda::carbon_credits().0);
+    assert_eq!(carbon_credits.offset_credits, 6_000_000);
+    assert_eq!(carbon_credits.active_credits, carbon_credits.total_credits);
+}
+
+#[test]
+#[ignore = "needs target/deploy/carbonpay.so, run `anchor build` first"]
+fn emptied_reserves_free_their_basket_slot() {
+    let mut env = common::setup();
+    env.init_platform();
+    env.create_basket(BASKET_ID);
+    let basket = pda::basket(BASKET_ID).0;
+    let depositor = env.funded_keypair();
+
+    let (mut projects, mut reserves) = (Vec::new(), Vec::new());
+    for _ in 0..Basket::MAX_PROJECTS {
+        let project = env.create_project(PROJECT_AMOUNT, PRICE_PER_TOKEN, FEE_BPS);
+        let purchase = env.purchase(&depositor, &project, 10);
+        env.try_deposit_to_basket(&depositor, BASKET_ID, &purchase, 10).unwrap();
+        reserves.push(env.account::<BasketReserve>(&pda::basket_reserve(&basket, &project.address).0));
+        projects.push(project);
+    }
+
+    // Redeeming and retiring everything empties and closes every reserve
+    let total = 10 * Basket::MAX_PROJECTS;
+    env.redeem_from_basket(&depositor, BASKET_ID, &reserves, total / 2);
+    env.retire_from_basket(&depositor, BASKET_ID, &reserves, total / 2);
+    let record: Basket = env.account(&basket);
+    assert_eq!((record.project_count, record.total_deposited), (0, 0));
+    for reserve in &reserves {
+        assert!(!env.exists(&pda::basket_reserve(&basket, &reserve.project).0));
+    }
+
+    // The freed slots take new projects, and an emptied project can come back
+    let project = env.create_project(PROJECT_AMOUNT, PRICE_PER_TOKEN, FEE_BPS);
+    let purchase = env.purchase(&depositor, &project, 10);
+    env.try_deposit_to_basket(&depositor, BASKET_ID, &purchase, 10).unwrap();
+    let purchase = env.purchase(&depositor, &projects[0], 3);
+    env.try_deposit_to_basket(&depositor, BASKET_ID, &purchase, 3).unwrap();
+    let record: Basket = env.account(&basket);
+    assert_eq!((record.project_count, record.total_deposited), (2, 13));
+}
+
+#[test]
+#[ignore = "needs target/deploy/carbonpay.so, run `anchor build` first"]
+fn redeemed_tokens_are_retired_without_a_purchase() {
+    let mut env = common::setup();
+    env.init_platform();
+    env.create_basket(BASKET_ID);
+    let basket = pda::basket(BASKET_ID).0;
+    let depositor = env.funded_keypair();
+    let project = env.create_project(PROJECT_AMOUNT, PRICE_PER_TOKEN, FEE_BPS);
+    let purchase = env.purchase(&depositor, &project, 10);
+    env.try_deposit_to_basket(&depositor, BASKET_ID, &purchase, 10).unwrap();
+    let reserve: BasketReserve = env.account(&pda::basket_reserve(&basket, &project.address).0);
+
+    // A holder who never bought the project redeems basket tokens and retires them
+    let holder = env.funded_keypair();
+    let basket_mint = pda::basket_mint(&basket).0;
+    env.send(
+        &[
+            ix::create_ata(&holder.pubkey(), &holder.pubkey(), &basket_mint),
+            anchor_spl::token::spl_token::instruction::transfer(
+                &anchor_spl::token::spl_token::ID,
+                &get_associated_token_address(&depositor.pubkey(), &basket_mint),
+                &get_associated_token_address(&holder.pubkey(), &basket_mint),
+                &depositor.pubkey(),
+                &[],
+                4,
+            )
+            .unwrap(),
+        ],
+        &depositor,
+        &[&holder],
+    )
+    .unwrap();
+    env.send(&ix::redeem_from_basket(&holder.pubkey(), BASKET_ID, &[reserve], 4), &holder, &[])
+        .unwrap();
+
+    let project_account: Project = env.account(&project.address);
+    let retire = |amount| {
+        ix::retire_held_credits(&holder.pubkey(), &project.address, &project_account, amount, "HELD".to_string())
+    };
+    let result = env.send(&[retire(5)], &holder, &[]);
+    common::assert_contract_error(result, ContractError::InsufficientFungibleTokens);
+    env.send(&[retire(4)], &holder, &[]).unwrap();
+
+    let request: OffsetRequest = env.account(&pda::retirement(&holder.pubkey(), &project.address, "HELD").0);
+    assert_eq!((request.offset_requester, request.purchase, request.amount), (holder.pubkey(), Pubkey::default(), 4));
+    assert_eq!(env.token_balance(&get_associated_token_address(&holder.pubkey(), &project.token_mint)), 0);
+    let project_account: Project = env.account(&project.address);
+    assert_eq!(project_account.offset_amount, 4);
+    let carbon_credits: CarbonCredits = env.account(&pda::carbon_credits().0);
+    assert_eq!(carbon_credits.offset_credits, 4_000_000);
+}
+
+#[test]
+#[ignore = "needs target/deploy/carbonpay.so, run `anchor build` first"]
+fn tokens_backing_a_purchase_are_not_retired_as_held() {
+    let mut env = common::setup();
+    env.init_platform();
+    env.create_basket(BASKET_ID);
+    let basket = pda::basket(BASKET_ID).0;
+    let buyer = env.funded_keypair();
+    let project = env.create_project(PROJECT_AMOUNT, PRICE_PER_TOKEN, FEE_BPS);
+    let purchase = env.purchase(&buyer, &project, 10);
+    let project_account: Project = env.account(&project.address);
+    let retire = |amount, id: &str| {
+        ix::retire_held_credits(&buyer.pubkey(), &project.address, &project_account, amount, id.to_string())
+    };
+    let result = env.send(&[retire(1, "BACKED")], &buyer, &[]);
+    common::assert_contract_error(result, ContractError::TokensBackPurchases);
+
+    // Depositing re-mints the certificate for what stays in the position
+    let before: Purchase = env.account(&purchase.address);
+    env.try_deposit_to_basket(&buyer, BASKET_ID, &purchase, 4).unwrap();
+    let after: Purchase = env.account(&purchase.address);
+    assert_eq!(after.remaining_amount, 6);
+    assert_ne!(after.certificate_mint, before.certificate_mint);
+    assert_eq!(env.token_balance(&get_associated_token_address(&buyer.pubkey(), &before.certificate_mint)), 0);
+    assert_eq!(env.token_balance(&get_associated_token_address(&buyer.pubkey(), &after.certificate_mint)), 1);
+    let holdings: Holdings = env.account(&pda::holdings(&buyer.pubkey(), &project.address).0);
+    assert_eq!(holdings.backed_amount, 6);
+
+    // Redeemed tokens are loose, the 6 still backing the purchase are not
+    let reserve: BasketReserve = env.account(&pda::basket_reserve(&basket, &project.address).0);
+    env.send(&ix::redeem_from_basket(&buyer.pubkey(), BASKET_ID, &[reserve], 4), &buyer, &[])
+        .unwrap();
+    let result = env.send(&[retire(5, "OVER")], &buyer, &[]);
+    common::assert_contract_error(result, ContractError::TokensBackPurchases);
+    env.send(&[retire(4, "LOOSE")], &buyer, &[]).unwrap();
+
+    // and are still offset through the purchase, under its new certificate
+    let (result, _, _) = env.try_request_offset(&buyer, &purchase, 6, "OFFSET");
+    result.unwrap();
+    let holdings: Holdings = env.account(&pda::holdings(&buyer.pubkey(), &project.address).0);
+    assert_eq!(holdings.backed_amount, 0);
+}
+
+#[test]
+#[ignore = "needs target/deploy/carbonpay.so, run `anchor build` first"]
+fn deposits_require_an_eligible_whole_tonne_project() {
+    let mut env = common::setup();
+    env.init_platform();
+    let admin = env.admin.insecure_clone();
+    env.send(&[ix::create_basket(&admin.pubkey(), BASKET_ID, ProjectCategory::NatureBased.mask())], &admin, &[])
+        .unwrap();
+    let depositor = env.funded_keypair();
+
+    let project = env.create_project(PROJECT_AMOUNT, PRICE_PER_TOKEN, FEE_BPS);
+    let purchase = env.purchase(&depositor, &project, 10);
+    let result = env.try_deposit_to_basket(&depositor, BASKET_ID, &purchase, 10);
+    common::assert_contract_error(result, ContractError::ProjectNotEligible);
+
+    env.send(&[ix::update_basket_eligibility(&admin.pubkey(), BASKET_ID, u8::MAX)], &admin, &[])
+        .unwrap();
+    let kilograms = env.create_project_with_decimals(2_000, PRICE_PER_TOKEN, FEE_BPS, 3);
+    let purchase = env.purchase(&depositor, &kilograms, 1_000);
+    let result = env.try_deposit_to_basket(&depositor, BASKET_ID, &purchase, 1_000);
+    common::assert_contract_error(result, ContractError::UnsupportedTokenDecimals);
+}
+
+#[test]
+#[ignore = "needs target/deploy/carbonpay.so, run `anchor build` first"]
+fn redemptions_must_pass_every_reserve() {
+    let mut env = common::setup();
+    env.init_platform();
+    env.create_basket(BASKET_ID);
+    let basket = pda::basket(BASKET_ID).0;
+    let depositor = env.funded_keypair();
+    let mut reserves = Vec::new();
+    for _ in 0..2 {
+        let project = env.create_project(PROJECT_AMOUNT, PRICE_PER_TOKEN, FEE_BPS);
+        let purchase = env.purchase(&depositor, &project, 10);
+        env.try_deposit_to_basket(&depositor, BASKET_ID, &purchase, 10).unwrap();
+        reserves.push(env.account::<BasketReserve>(&pda::basket_reserve(&basket, &project.address).0));
+    }
+
+    let partial = [reserves[0].clone()];
+    let result = env.send(&ix::redeem_from_basket(&depositor.pubkey(), BASKET_ID, &partial, 2), &depositor, &[]);
+    common::assert_contract_error(result, ContractError::InvalidBasketReserves);
+    let twice = [reserves[0].clone(), reserves[0].clone()];
+    let result = env.send(&[ix::retire_from_basket(&depositor.pubkey(), BASKET_ID, &twice, 2)], &depositor, &[]);
+    common::assert_contract_error(result, ContractError::InvalidBasketReserves);
+}
@@ -0,0 +1,666 @@
+//! In-process test harness: the compiled program and Metaplex run inside LiteSVM.
+//!
+//! The tests need the compiled program, so they are `#[ignore]`d by default. Build it with
+//! `anchor build` (or `cargo build-sbf`), then run `cargo test -p carbonpay -- --ignored`; a
+//! missing `target/deploy/carbonpay.so` fails every test. CI runs them in
+//! `.github/workflows/program-tests.yml`.
+//!
+//! Every `ContractError` an instruction can raise is asserted by at least one test;
+//! `InvalidRequestStatus`, `InvalidOffsetRequest`, `OffsetRequestExists` and `MathOverflow`
+//! are never raised.
+
+#![allow(dead_code, clippy::result_large_err)]
+
+use std::path::PathBuf;
+
+use anchor_lang::prelude::{Clock, Pubkey};
+use anchor_lang::solana_program::instruction::{Instruction, InstructionError};
+use anchor_lang::solana_program::message::{v0, AddressLookupTableAccount, VersionedMessage};
+use anchor_lang::solana_program::program_pack::Pack;
+use anchor_lang::{AccountDeserialize, AccountSerialize, AnchorDeserialize, Discriminator};
+use anchor_spl::associated_token::get_associated_token_address;
+use anchor_spl::metadata::mpl_token_metadata;
+use anchor_spl::token::spl_token;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use carbonpay::errors::ContractError;
+use carbonpay::events::CarbonCreditsPurchased;
+use carbonpay::state::{BasketReserve, FeeRounding, Listing, OtcOffer, Project, Purchase, Subscription};
+use carbonpay_client::instructions::{self as ix, ProjectArgs};
+use carbonpay_client::pda;
+use litesvm::types::TransactionResult;
+use litesvm::LiteSVM;
+use solana_account::Account;
+use solana_address_lookup_table_interface::program as address_lookup_table;
+use solana_address_lookup_table_interface::state::{AddressLookupTable, LookupTableMeta};
+use solana_keypair::Keypair;
+use solana_signer::Signer;
+use solana_transaction::versioned::VersionedTransaction;
+use solana_transaction::Transaction;
+use solana_transaction_error::TransactionError;
+
+pub const SOL: u64 = 1_000_000_000;
+pub const PRICE_PER_TOKEN: u64 = 1_000_000;
+pub const FEE_BPS: u64 = 500;
+pub const PROJECT_AMOUNT: u64 = 1_000;
+
+fn workspace_path(relative: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("../..")
+        .join(relative)
+}
+
+/// Start a VM with the program loaded, panicking when the program has not been built
+pub fn setup() -> TestEnv {
+    let program = workspace_path("target/deploy/carbonpay.so");
+    assert!(
+        program.exists(),
+        "{} not found, run `anchor build` first",
+        program.display()
+    );
+    let mut svm = LiteSVM::new();
+    svm.add_program_from_file(carbonpay::ID, program).unwrap();
+    svm.add_program_from_file(mpl_token_metadata::ID, workspace_path("tests/metadata.so"))
+        .unwrap();
+
+    let admin = Keypair::new();
+    svm.airdrop(&admin.pubkey(), 100 * SOL).unwrap();
+    TestEnv { svm, admin }
+}
+
+pub struct TestEnv {
+    pub svm: LiteSVM,
+    pub admin: Keypair,
+}
+
+pub struct ProjectFixture {
+    pub owner: Keypair,
+    pub address: Pubkey,
+    pub nft_mint: Pubkey,
+    pub token_mint: Pubkey,
+}
+
+impl ProjectFixture {
+    pub fn vault(&self) -> Pubkey {
+        get_associated_token_address(&pda::carbon_credits().0, &self.token_mint)
+    }
+}
+
+pub struct PurchaseFixture {
+    pub address: Pubkey,
+    pub nft_mint: Pubkey,
+}
+
+impl TestEnv {
+    pub fn send(
+        &mut self,
+        instructions: &[Instruction],
+        payer: &Keypair,
+        signers: &[&Keypair],
+    ) -> TransactionResult {
+        let mut all: Vec<&Keypair> = vec![payer];
+        all.extend(
+            signers
+                .iter()
+                .copied()
+                .filter(|k| k.pubkey() != payer.pubkey()),
+        );
+        let tx = Transaction::new_signed_with_payer(
+            instructions,
+            Some(&payer.pubkey()),
+            &all,
+            self.svm.latest_blockhash(),
+        );
+        let result = self.svm.send_transaction(tx);
+        self.svm.expire_blockhash();
+        result
+    }
+
+    /// Like `send`, in a v0 transaction resolving accounts through `lookup_tables`
+    pub fn send_v0(
+        &mut self,
+        instructions: &[Instruction],
+        payer: &Keypair,
+        signers: &[&Keypair],
+        lookup_tables: &[AddressLookupTableAccount],
+    ) -> TransactionResult {
+        let mut all: Vec<&Keypair> = vec![payer];
+        all.extend(
+            signers
+                .iter()
+                .copied()
+                .filter(|k| k.pubkey() != payer.pubkey()),
+        );
+        let message = v0::Message::try_compile(
+            &payer.pubkey(),
+            instructions,
+            lookup_tables,
+            self.svm.latest_blockhash(),
+        )
+        .unwrap();
+        let tx = VersionedTransaction::try_new(VersionedMessage::V0(message), &all).unwrap();
+        let result = self.svm.send_transaction(tx);
+        self.svm.expire_blockhash();
+        result
+    }
+
+    /// Write an address lookup table holding `addresses`. LiteSVM does not load the lookup
+    /// table program, so the table is written directly, as if extended before the current slot.
+    pub fn lookup_table(&mut self, addresses: &[Pubkey]) -> AddressLookupTableAccount {
+        let key = Pubkey::new_unique();
+        let data = AddressLookupTable {
+            meta: LookupTableMeta::new(self.admin.pubkey()),
+            addresses: addresses.into(),
+        }
+        .serialize_for_tests()
+        .unwrap();
+        let account = Account {
+            lamports: self.svm.minimum_balance_for_rent_exemption(data.len()),
+            data,
+            owner: address_lookup_table::ID,
+            executable: false,
+            rent_epoch: 0,
+        };
+        self.svm.set_account(key, account).unwrap();
+        let clock: Clock = self.svm.get_sysvar();
+        if clock.slot == 0 {
+            self.svm.warp_to_slot(1);
+        }
+        AddressLookupTableAccount {
+            key,
+            addresses: addresses.to_vec(),
+        }
+    }
+
+    pub fn funded_keypair(&mut self) -> Keypair {
+        let keypair = Keypair::new();
+        self.svm.airdrop(&keypair.pubkey(), 100 * SOL).unwrap();
+        keypair
+    }
+
+    pub fn account<T: AccountDeserialize>(&self, address: &Pubkey) -> T {
+        let account = self.svm.get_account(address).expect("account exists");
+        T::try_deserialize(&mut account.data.as_slice()).expect("account decodes")
+    }
+
+    pub fn exists(&self, address: &Pubkey) -> bool {
+        self.svm
+            .get_account(address)
+            .is_some_and(|account| account.lamports > 0)
+    }
+
+    pub fn balance(&self, address: &Pubkey) -> u64 {
+        self.svm.get_balance(address).unwrap_or(0)
+    }
+
+    pub fn token_balance(&self, address: &Pubkey) -> u64 {
+        let account = self.svm.get_account(address).expect("token account exists");
+        spl_token::state::Account::unpack(&account.data)
+            .unwrap()
+            .amount
+    }
+
+    pub fn mint_supply(&self, mint: &Pubkey) -> u64 {
+        let account = self.svm.get_account(mint).expect("mint exists");
+        spl_token::state::Mint::unpack(&account.data).unwrap().supply
+    }
+
+    /// Overwrite a program account's data, to reach states no instruction produces
+    pub fn write_account<T: AccountSerialize>(&mut self, address: &Pubkey, account: &T) {
+        let mut stored = self.svm.get_account(address).expect("account exists");
+        let mut data = Vec::new();
+        account.try_serialize(&mut data).unwrap();
+        stored.data[..data.len()].copy_from_slice(&data);
+        self.svm.set_account(*address, stored).unwrap();
+    }
+
+    /// Write an initialized token account at a non-ATA `address`
+    pub fn set_token_account(&mut self, address: &Pubkey, mint: &Pubkey, owner: &Pubkey) {
+        let mut data = vec![0; spl_token::state::Account::LEN];
+        spl_token::state::Account {
+            mint: *mint,
+            owner: *owner,
+            state: spl_token::state::AccountState::Initialized,
+            ..Default::default()
+        }
+        .pack_into_slice(&mut data);
+        let account = Account {
+            lamports: self.svm.minimum_balance_for_rent_exemption(data.len()),
+            data,
+            owner: spl_token::ID,
+            executable: false,
+            rent_epoch: 0,
+        };
+        self.svm.set_account(*address, account).unwrap();
+    }
+
+    pub fn set_time(&mut self, unix_timestamp: i64) {
+        let mut clock: Clock = self.svm.get_sysvar();
+        clock.unix_timestamp = unix_timestamp;
+        self.svm.set_sysvar(&clock);
+    }
+
+    /// carbon_credits and platform config, with the admin as the only approver
+    pub fn init_platform(&mut self) {
+        let admin = self.admin.insecure_clone();
+        self.send(
+            &[
+                ix::initialize_carbon_credits(&admin.pubkey()),
+                ix::initialize_platform_config(&admin.pubkey(), vec![admin.pubkey()], 1),
+            ],
+            &admin,
+            &[],
+        )
+        .unwrap();
+    }
+
+    pub fn set_fee_policy(&mut self, fee_rounding: FeeRounding, min_fee: u64) {
+        let admin = self.admin.insecure_clone();
+        self.send(
+            &[ix::set_fee_policy(&admin.pubkey(), fee_rounding, min_fee)],
+            &admin,
+            &[],
+        )
+        .unwrap();
+    }
+
+    pub fn create_project(
+        &mut self,
+        amount: u64,
+        price_per_token: u64,
+        fee_bps: u64,
+    ) -> ProjectFixture {
+        self.create_project_with_decimals(amount, price_per_token, fee_bps, 0)
+    }
+
+    /// A project whose token mint has `decimals`, with `amount` in base units
+    pub fn create_project_with_decimals(
+        &mut self,
+        amount: u64,
+        price_per_token: u64,
+        fee_bps: u64,
+        decimals: u8,
+    ) -> ProjectFixture {
+        let owner = self.funded_keypair();
+        let nft_mint = Keypair::new();
+        let token_mint = Keypair::new();
+        self.send(
+            &ix::initialize_project(
+                &owner.pubkey(),
+                &nft_mint.pubkey(),
+                &token_mint.pubkey(),
+                ProjectArgs {
+                    amount,
+                    price_per_token,
+                    carbon_pay_fee: fee_bps,
+                    decimals,
+                    uri: "https://carbonpay.com/projects/test".to_string(),
+                    name: "Test Project".to_string(),
+                    symbol: "TEST".to_string(),
+                },
+            ),
+            &owner,
+            &[&nft_mint, &token_mint],
+        )
+        .unwrap();
+        ProjectFixture {
+            address: pda::project(&owner.pubkey(), &nft_mint.pubkey()).0,
+            owner,
+            nft_mint: nft_mint.pubkey(),
+            token_mint: token_mint.pubkey(),
+        }
+    }
+
+    pub fn try_purchase(
+        &mut self,
+        buyer: &Keypair,
+        project: &ProjectFixture,
+        amount: u64,
+    ) -> (TransactionResult, PurchaseFixture) {
+        self.try_purchase_with_entry(buyer, project, amount, None)
+    }
+
+    /// Purchase presenting an allowlist entry for projects that gate buyers
+    pub fn try_purchase_with_entry(
+        &mut self,
+        buyer: &Keypair,
+        project: &ProjectFixture,
+        amount: u64,
+        allowlist_entry: Option<Pubkey>,
+    ) -> (TransactionResult, PurchaseFixture) {
+        let nft_mint = Keypair::new();
+        let project_account: Project = self.account(&project.address);
+        let result = self.send(
+            &ix::purchase_carbon_credits(
+                &buyer.pubkey(),
+                &project.address,
+                &project_account,
+                &nft_mint.pubkey(),
+                amount,
+                allowlist_entry,
+            ),
+            buyer,
+            &[&nft_mint],
+        );
+        let fixture = PurchaseFixture {
+            address: pda::purchase(&buyer.pubkey(), &project.address, &nft_mint.pubkey()).0,
+            nft_mint: nft_mint.pubkey(),
+        };
+        (result, fixture)
+    }
+
+    pub fn purchase(
+        &mut self,
+        buyer: &Keypair,
+        project: &ProjectFixture,
+        amount: u64,
+    ) -> PurchaseFixture {
+        let (result, fixture) = self.try_purchase(buyer, project, amount);
+        result.unwrap();
+        fixture
+    }
+
+    /// Request an offset, returning the residual NFT mint and the request address
+    pub fn try_request_offset(
+        &mut self,
+        requester: &Keypair,
+        purchase: &PurchaseFixture,
+        amount: u64,
+        request_id: &str,
+    ) -> (TransactionResult, Pubkey, Pubkey) {
+        let new_nft_mint = Keypair::new();
+        let purchase_account: Purchase = self.account(&purchase.address);
+        let project_account: Project = self.account(&purchase_account.project);
+        let result = self.send(
+            &ix::request_offset(
+                &requester.pubkey(),
+                &purchase.address,
+                &purchase_account,
+                &project_account,
+                &new_nft_mint.pubkey(),
+                amount,
+                request_id.to_string(),
+            ),
+            requester,
+            &[&new_nft_mint],
+        );
+        let request = pda::offset_request(&purchase_account.buyer, &purchase.address, request_id).0;
+        (result, new_nft_mint.pubkey(), request)
+    }
+
+    /// Redeem from a basket holding `reserves` in v0 transactions, the redeemer's token accounts
+    /// created first and every account but the redeemer's in a lookup table
+    pub fn redeem_from_basket(
+        &mut self,
+        redeemer: &Keypair,
+        basket_id: u64,
+        reserves: &[BasketReserve],
+        amount: u64,
+    ) {
+        let instructions = ix::redeem_from_basket(&redeemer.pubkey(), basket_id, reserves, amount);
+        let addresses: Vec<Pubkey> = instructions.iter().flat_map(ix::lookup_table_addresses).collect();
+        let table = self.lookup_table(&addresses);
+        let (redeem, create_atas) = instructions.split_last().unwrap();
+        self.send_v0(create_atas, redeemer, &[], std::slice::from_ref(&table)).unwrap();
+        self.send_v0(std::slice::from_ref(redeem), redeemer, &[], &[table]).unwrap();
+    }
+
+    /// Retire from a basket holding `reserves` in a v0 transaction, with every account but the
+    /// retiree's in a lookup table
+    pub fn retire_from_basket(&mut self, retiree: &Keypair, basket_id: u64, reserves: &[BasketReserve], amount: u64) {
+        let retire = ix::retire_from_basket(&retiree.pubkey(), basket_id, reserves, amount);
+        let table = self.lookup_table(&ix::lookup_table_addresses(&retire));
+        self.send_v0(&[retire], retiree, &[], &[table]).unwrap();
+    }
+
+    /// Batch-offset `(purchase, amount)` entries of `project` in a v0 transaction, with every
+    /// account but the owner's in a lookup table
+    pub fn try_request_batch_offset(
+        &mut self,
+        owner: &Keypair,
+        project: &Pubkey,
+        entries: &[(Pubkey, u64)],
+        request_id: &str,
+    ) -> TransactionResult {
+        let purchases: Vec<_> = entries
+            .iter()
+            .map(|(address, amount)| (*address, self.account::<Purchase>(address), *amount))
+            .collect();
+        let project_account: Project = self.account(project);
+        let batch = ix::request_batch_offset(&owner.pubkey(), project, &project_account, &purchases, request_id.to_string());
+        let table = self.lookup_table(&ix::lookup_table_addresses(&batch));
+        self.send_v0(&[batch], owner, &[], &[table])
+    }
+
+    /// Let `delegate` offset up to `max_amount` tokens of `purchase` on the owner's behalf
+    pub fn approve_offset_delegate(
+        &mut self,
+        owner: &Keypair,
+        purchase: &PurchaseFixture,
+        delegate: &Pubkey,
+        max_amount: u64,
+    ) {
+        let purchase_account: Purchase = self.account(&purchase.address);
+        let project_account: Project = self.account(&purchase_account.project);
+        self.send(
+            &[ix::approve_offset_delegate(
+                &owner.pubkey(),
+                &purchase.address,
+                &purchase_account,
+                &project_account,
+                delegate,
+                max_amount,
+                0,
+            )],
+            owner,
+            &[],
+        )
+        .unwrap();
+    }
+
+    /// Subscribe `buyer` to `project`, returning the position the subscription fills
+    pub fn create_subscription(
+        &mut self,
+        buyer: &Keypair,
+        project: &ProjectFixture,
+        amount_per_period: u64,
+        deposit: u64,
+    ) -> PurchaseFixture {
+        let nft_mint = Keypair::new();
+        let project_account: Project = self.account(&project.address);
+        self.send(
+            &ix::create_subscription(
+                &buyer.pubkey(),
+                &project.address,
+                &project_account,
+                &nft_mint.pubkey(),
+                amount_per_period,
+                project_account.price_per_token,
+                deposit,
+            ),
+            buyer,
+            &[&nft_mint],
+        )
+        .unwrap();
+        PurchaseFixture {
+            address: pda::purchase(&buyer.pubkey(), &project.address, &nft_mint.pubkey()).0,
+            nft_mint: nft_mint.pubkey(),
+        }
+    }
+
+    pub fn try_execute_subscription(
+        &mut self,
+        buyer: &Pubkey,
+        project: &ProjectFixture,
+        allowlist_entry: Option<Pubkey>,
+    ) -> TransactionResult {
+        let subscription: Subscription = self.account(&pda::subscription(buyer, &project.address).0);
+        let project_account: Project = self.account(&project.address);
+        let cranker = self.admin.insecure_clone();
+        self.send(
+            &[ix::execute_subscription(&subscription, &project_account, allowlist_entry)],
+            &cranker,
+            &[],
+        )
+    }
+
+    /// A basket open to every project category, curated by the admin
+    pub fn create_basket(&mut self, basket_id: u64) {
+        let admin = self.admin.insecure_clone();
+        self.send(&[ix::create_basket(&admin.pubkey(), basket_id, u8::MAX)], &admin, &[])
+            .unwrap();
+    }
+
+    pub fn try_deposit_to_basket(
+        &mut self,
+        depositor: &Keypair,
+        basket_id: u64,
+        purchase: &PurchaseFixture,
+        amount: u64,
+    ) -> TransactionResult {
+        let purchase_account: Purchase = self.account(&purchase.address);
+        let project_account: Project = self.account(&purchase_account.project);
+        let replacement_nft_mint = Keypair::new();
+        self.send(
+            &ix::deposit_to_basket(
+                &depositor.pubkey(),
+                basket_id,
+                &purchase.address,
+                &purchase_account,
+                &project_account,
+                &replacement_nft_mint.pubkey(),
+                amount,
+            ),
+            depositor,
+            &[&replacement_nft_mint],
+        )
+    }
+
+    /// List `amount` tokens of a purchase at `price_per_token`, returning the listing address
+    pub fn list_credits(
+        &mut self,
+        seller: &Keypair,
+        purchase: &PurchaseFixture,
+        amount: u64,
+        price_per_token: u64,
+    ) -> Pubkey {
+        let purchase_account: Purchase = self.account(&purchase.address);
+        let project_account: Project = self.account(&purchase_account.project);
+        self.send(
+            &[ix::list_credits(
+                &seller.pubkey(),
+                &purchase.address,
+                &purchase_account,
+                &project_account,
+                amount,
+                price_per_token,
+            )],
+            seller,
+            &[],
+        )
+        .unwrap();
+        pda::listing(&purchase.address).0
+    }
+
+    /// Buy a listing, returning the buyer's new purchase
+    pub fn try_buy_listing(
+        &mut self,
+        buyer: &Keypair,
+        listing: &Pubkey,
+        allowlist_entry: Option<Pubkey>,
+    ) -> (TransactionResult, PurchaseFixture) {
+        let nft_mint = Keypair::new();
+        let listing_account: Listing = self.account(listing);
+        let project_account: Project = self.account(&listing_account.project);
+        let result = self.send(
+            &ix::buy_listing(
+                &buyer.pubkey(),
+                listing,
+                &listing_account,
+                &project_account,
+                &nft_mint.pubkey(),
+                allowlist_entry,
+            ),
+            buyer,
+            &[&nft_mint],
+        );
+        let fixture = PurchaseFixture {
+            address: pda::purchase(&buyer.pubkey(), &listing_account.project, &nft_mint.pubkey()).0,
+            nft_mint: nft_mint.pubkey(),
+        };
+        (result, fixture)
+    }
+
+    /// Accept the OTC offer `offer_id` made to `buyer`
+    pub fn try_accept_otc_offer(
+        &mut self,
+        buyer: &Keypair,
+        project: &ProjectFixture,
+        offer_id: u64,
+        allowlist_entry: Option<Pubkey>,
+    ) -> TransactionResult {
+        let nft_mint = Keypair::new();
+        let offer: OtcOffer = self.account(&pda::otc_offer(&project.address, &buyer.pubkey(), offer_id).0);
+        let project_account: Project = self.account(&project.address);
+        self.send(
+            &ix::accept_otc_offer(&offer, &project_account, &nft_mint.pubkey(), allowlist_entry),
+            buyer,
+            &[&nft_mint],
+        )
+    }
+}
+
+/// Assert that a transaction failed with `expected`
+pub fn assert_contract_error(result: TransactionResult, expected: ContractError) {
+    let failed = result.expect_err("transaction should fail");
+    assert_eq!(
+        failed.err,
+        TransactionError::InstructionError(
+            failed_instruction_index(&failed.err),
+            InstructionError::Custom(expected.into())
+        ),
+        "expected {:?}, logs: {:#?}",
+        expected,
+        failed.meta.logs
+    );
+}
+
+/// Assert that a transaction failed with the Anchor framework error `expected`
+pub fn assert_anchor_error(result: TransactionResult, expected: anchor_lang::error::ErrorCode) {
+    let failed = result.expect_err("transaction should fail");
+    assert_eq!(
+        failed.err,
+        TransactionError::InstructionError(
+            failed_instruction_index(&failed.err),
+            InstructionError::Custom(expected.into())
+        ),
+        "expected {:?}, logs: {:#?}",
+        expected,
+        failed.meta.logs
+    );
+}
+
+/// The `CarbonCreditsPurchased` event logged by a successful transaction
+pub fn purchase_event(result: TransactionResult) -> CarbonCreditsPurchased {
+    event(result)
+}
+
+/// The first event of type `T` logged by a successful transaction
+pub fn event<T: AnchorDeserialize + Discriminator>(result: TransactionResult) -> T {
+    let meta = result.expect("transaction should succeed");
+    meta.logs
+        .iter()
+        .filter_map(|line| line.strip_prefix("Program data: "))
+        .filter_map(|data| STANDARD.decode(data).ok())
+        .find_map(|data| {
+            let payload = data.strip_prefix(T::DISCRIMINATOR)?;
+            T::try_from_slice(payload).ok()
+        })
+        .expect("event logged")
+}
+
+fn failed_instruction_index(err: &TransactionError) -> u8 {
+    match err {
+        TransactionError::InstructionError(index, _) => *index,
+        _ => 0,
+    }
+}
@@ -0,0 +1,181 @@
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::AccountMeta;
+use anchor_spl::associated_token::get_associated_token_address;
+use carbonpay::errors::ContractError;
+use carbonpay::state::{OffsetDelegate, Project, Purchase};
+use carbonpay_client::{instructions as ix, pda};
+use common::{FEE_BPS, PRICE_PER_TOKEN, PROJECT_AMOUNT};
+use solana_keypair::Keypair;
+use solana_signer::Signer;
+
+#[test]
+#[ignore = "needs target/deploy/carbonpay.so, run `anchor build` first"]
+fn delegation_follows_the_reminted_certificate() {
+    let mut env = common::setup();
+    env.init_platform();
+    let project = env.create_project(PROJECT_AMOUNT, PRICE_PER_TOKEN, FEE_BPS);
+    let owner = env.funded_keypair();
+    let custodian = env.funded_keypair();
+    let purchase = env.purchase(&owner, &project, 10);
+    env.approve_offset_delegate(&owner, &purchase, &custodian.pubkey(), 6);
+    let delegate = pda::offset_delegate(&purchase.address).0;
+
+    let (result, first, _) = env.try_request_offset(&custodian, &purchase, 2, "CUSTODY1");
+    result.unwrap();
+    assert_eq!(env.token_balance(&get_associated_token_address(&delegate, &first)), 1);
+    // The second offset burns the certificate minted by the first
+    let (result, second, _) = env.try_request_offset(&custodian, &purchase, 3, "CUSTODY2");
+    result.unwrap();
+    let record: Purchase = env.account(&purchase.address);
+    assert_eq!(record.certificate_mint, second);
+    assert_eq!(record.remaining_amount, 5);
+    assert!(!env.exists(&get_associated_token_address(&delegate, &first)));
+
+    let (result, _, _) = env.try_request_offset(&custodian, &purchase, 2, "CUSTODY3");
+    common::assert_contract_error(result, ContractError::OffsetDelegateAllowanceExceeded);
+
+    // Revoking hands back the current certificate and the unused allowance
+    let project_account: Project = env.account(&project.address);
+    let approval: OffsetDelegate = env.account(&delegate);
+    env.send(
+        &[ix::revoke_offset_delegate(&owner.pubkey(), &purchase.address, &record, &project_account, &approval)],
+        &owner,
+        &[],
+    )
+    .unwrap();
+    assert!(!env.exists(&delegate));
+    assert_eq!(env.token_balance(&get_associated_token_address(&owner.pubkey(), &second)), 1);
+    assert_eq!(env.token_balance(&get_associated_token_address(&owner.pubkey(), &project.token_mint)), 5);
+
+    let (result, _, _) = env.try_request_offset(&owner, &purchase, 1, "OWNER");
+    result.unwrap();
+}
+
+#[test]
+#[ignore = "needs target/deploy/carbonpay.so, run `anchor build` first"]
+fn delegation_only_reaches_its_own_purchase() {
+    let mut env = common::setup();
+    env.init_platform();
+    let project = env.create_project(PROJECT_AMOUNT, PRICE_PER_TOKEN, FEE_BPS);
+    let owner = env.funded_keypair();
+    let custodian = env.funded_keypair();
+    let delegated = env.purchase(&owner, &project, 3);
+    let kept = env.purchase(&owner, &project, 5);
+    env.approve_offset_delegate(&owner, &delegated, &custodian.pubkey(), 3);
+
+    // Only the delegated purchase's allowance leaves the shared token account
+    let owner_tokens = get_associated_token_address(&owner.pubkey(), &project.token_mint);
+    assert_eq!(env.token_balance(&owner_tokens), 5);
+    let delegate: OffsetDelegate = env.account(&pda::offset_delegate(&delegated.address).0);
+    assert_eq!(delegate.remaining_amount, 3);
+
+    let (result, _, _) = env.try_request_offset(&custodian, &delegated, 3, "ALL");
+    result.unwrap();
+    assert_eq!(env.token_balance(&owner_tokens), 5);
+
+    let (result, _, _) = env.try_request_offset(&custodian, &kept, 1, "OTHER");
+    assert!(result.is_err());
+    let record: Purchase = env.account(&kept.address);
+    assert_eq!(record.remaining_amount, 5);
+}
+
+#[test]
+#[ignore = "needs target/deploy/carbonpay.so, run `anchor build` first"]
+fn used_up_allowance_closes_its_escrow_and_still_revokes() {
+    let mut env = common::setup();
+    env.init_platform();
+    let project = env.create_project(PROJECT_AMOUNT, PRICE_PER_TOKEN, FEE_BPS);
+    let owner = env.funded_keypair();
+    let custodian = env.funded_keypair();
+    let purchase = env.purchase(&owner, &project, 5);
+    env.approve_offset_delegate(&owner, &purchase, &custodian.pubkey(), 2);
+    let delegate = pda::offset_delegate(&purchase.address).0;
+    let escrow = get_associated_token_address(&delegate, &project.token_mint);
+
+    let owner_before = env.balance(&owner.pubkey());
+    let (result, certificate, _) = env.try_request_offset(&custodian, &purchase, 2, "ALL");
+    result.unwrap();
+    assert!(!env.exists(&escrow));
+    assert!(env.balance(&owner.pubkey()) > owner_before);
+
+    let record: Purchase = env.account(&purchase.address);
+    let project_account: Project = env.account(&project.address);
+    let approval: OffsetDelegate = env.account(&delegate);
+    env.send(
+        &[ix::revoke_offset_delegate(&owner.pubkey(), &purchase.address, &record, &project_account, &approval)],
+        &owner,
+        &[],
+    )
+    .unwrap();
+    assert!(!env.exists(&delegate));
+    assert_eq!(env.token_balance(&get_associated_token_address(&owner.pubkey(), &certificate)), 1);
+}
+
+#[test]
+#[ignore = "needs target/deploy/carbonpay.so, run `anchor build` first"]
+fn delegate_mints_the_residual_certificate_into_its_escrow_ata() {
+    let mut env = common::setup();
+    env.init_platform();
+    let project = env.create_project(PROJECT_AMOUNT, PRICE_PER_TOKEN, FEE_BPS);
+    let owner = env.funded_keypair();
+    let custodian = env.funded_keypair();
+    let purchase = env.purchase(&owner, &project, 5);
+    env.approve_offset_delegate(&owner, &purchase, &custodian.pubkey(), 2);
+    let delegate = pda::offset_delegate(&purchase.address).0;
+
+    // a plain token account owned by the delegate PDA instead of its ATA
+    let new_nft_mint = Keypair::new();
+    let stray = Pubkey::new_unique();
+    env.set_token_account(&stray, &new_nft_mint.pubkey(), &delegate);
+    let record: Purchase = env.account(&purchase.address);
+    let project_account: Project = env.account(&project.address);
+    let mut ixs = ix::request_offset(
+        &custodian.pubkey(),
+        &purchase.address,
+        &record,
+        &project_account,
+        &new_nft_mint.pubkey(),
+        1,
+        "STRAY".to_string(),
+    );
+    // new_nft_account is the ninth account of request_offset
+    ixs.last_mut().unwrap().accounts[8] = AccountMeta::new(stray, false);
+    let result = env.send(&ixs, &custodian, &[&new_nft_mint]);
+    common::assert_contract_error(result, ContractError::InvalidNFTAccount);
+}
+
+#[test]
+#[ignore = "needs target/deploy/carbonpay.so, run `anchor build` first"]
+fn expired_delegation_no_longer_offsets() {
+    let mut env = common::setup();
+    env.init_platform();
+    env.set_time(1_000);
+    let project = env.create_project(PROJECT_AMOUNT, PRICE_PER_TOKEN, FEE_BPS);
+    let owner = env.funded_keypair();
+    let custodian = env.funded_keypair();
+    let purchase = env.purchase(&owner, &project, 5);
+    let record: Purchase = env.account(&purchase.address);
+    let project_account: Project = env.account(&project.address);
+    env.send(
+        &[ix::approve_offset_delegate(
+            &owner.pubkey(),
+            &purchase.address,
+            &record,
+            &project_account,
+            &custodian.pubkey(),
+            2,
+            2_000,
+        )],
+        &owner,
+        &[],
+    )
+    .unwrap();
+
+    let (result, _, _) = env.try_request_offset(&custodian, &purchase, 1, "BEFORE");
+    result.unwrap();
+    env.set_time(2_000);
+    let (result, _, _) = env.try_request_offset(&custodian, &purchase, 1, "AFTER");
+    common::assert_contract_error(result, ContractError::OffsetDelegateExpired);
+}
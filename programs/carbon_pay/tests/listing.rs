@@ -0,0 +1,132 @@
+mod common;
+
+use anchor_spl::associated_token::get_associated_token_address;
+use carbonpay::errors::ContractError;
+use carbonpay::events::ListingSold;
+use carbonpay::state::{CarbonCredits, FeeRounding, Listing, Project, Purchase};
+use carbonpay_client::{instructions as ix, pda};
+use common::{FEE_BPS, PRICE_PER_TOKEN, PROJECT_AMOUNT};
+use solana_signer::Signer;
+
+#[test]
+#[ignore = "needs target/deploy/carbonpay.so, run `anchor build` first"]
+fn buy_listing_requires_project_allowlist() {
+    let mut env = common::setup();
+    env.init_platform();
+    let project = env.create_project(PROJECT_AMOUNT, PRICE_PER_TOKEN, FEE_BPS);
+    let seller = env.funded_keypair();
+    let purchase = env.purchase(&seller, &project, 4);
+    let listing = env.list_credits(&seller, &purchase, 4, PRICE_PER_TOKEN);
+
+    let admin = env.admin.insecure_clone();
+    env.send(&[ix::set_allowlist_required(&admin.pubkey(), Some(project.address), true)], &admin, &[])
+        .unwrap();
+
+    let buyer = env.funded_keypair();
+    let (result, _) = env.try_buy_listing(&buyer, &listing, None);
+    common::assert_contract_error(result, ContractError::BuyerNotAllowlisted);
+
+    env.send(
+        &[ix::add_allowlist_entry(&admin.pubkey(), Some(project.address), &buyer.pubkey(), 0)],
+        &admin,
+        &[],
+    )
+    .unwrap();
+    let entry = pda::allowlist_entry(&project.address, &buyer.pubkey()).0;
+    let (result, _) = env.try_buy_listing(&buyer, &listing, Some(entry));
+    result.unwrap();
+    assert_eq!(
+        env.token_balance(&get_associated_token_address(&buyer.pubkey(), &project.token_mint)),
+        4
+    );
+}
+
+#[test]
+#[ignore = "needs target/deploy/carbonpay.so, run `anchor build` first"]
+fn listing_sale_reports_its_fee() {
+    let mut env = common::setup();
+    env.init_platform();
+    env.set_fee_policy(FeeRounding::Ceil, 0);
+    let project = env.create_project(PROJECT_AMOUNT, 1, FEE_BPS);
+    let seller = env.funded_keypair();
+    let purchase = env.purchase(&seller, &project, 19);
+    let listing = env.list_credits(&seller, &purchase, 19, 1);
+    let fees_before = env.account::<CarbonCredits>(&pda::carbon_credits().0).total_fees_earned;
+
+    let buyer = env.funded_keypair();
+    let (result, bought) = env.try_buy_listing(&buyer, &listing, None);
+    let event: ListingSold = common::event(result);
+    assert_eq!((event.seller, event.purchase), (seller.pubkey(), bought.address));
+    assert_eq!(event.total_price, 19);
+    // 19 * 500 = 9_500: 0.95 lamports rounded up to 1
+    assert_eq!((event.fee, event.fee_remainder), (1, 9_500));
+    let fees_after = env.account::<CarbonCredits>(&pda::carbon_credits().0).total_fees_earned;
+    assert_eq!(fees_after - fees_before, event.fee);
+}
+
+#[test]
+#[ignore = "needs target/deploy/carbonpay.so, run `anchor build` first"]
+fn only_the_seller_cancels_a_listing_into_its_own_purchase() {
+    let mut env = common::setup();
+    env.init_platform();
+    let project = env.create_project(PROJECT_AMOUNT, PRICE_PER_TOKEN, FEE_BPS);
+    let seller = env.funded_keypair();
+    let purchase = env.purchase(&seller, &project, 4);
+    let other = env.purchase(&seller, &project, 2);
+    let listing = env.list_credits(&seller, &purchase, 4, PRICE_PER_TOKEN);
+    let project_account: Project = env.account(&project.address);
+    let listing_account: Listing = env.account(&listing);
+
+    let intruder = env.funded_keypair();
+    let result = env.send(
+        &[ix::cancel_listing(&intruder.pubkey(), &listing, &listing_account, &project_account)],
+        &intruder,
+        &[],
+    );
+    common::assert_contract_error(result, ContractError::Unauthorized);
+
+    // A listing recording another purchase than the one it was derived from
+    let cancel = ix::cancel_listing(&seller.pubkey(), &listing, &listing_account, &project_account);
+    env.write_account(
+        &listing,
+        &Listing {
+            purchase: other.address,
+            ..listing_account
+        },
+    );
+    let result = env.send(&[cancel], &seller, &[]);
+    common::assert_contract_error(result, ContractError::InvalidListing);
+}
+
+#[test]
+#[ignore = "needs target/deploy/carbonpay.so, run `anchor build` first"]
+fn delegated_or_uncertified_positions_are_not_listed() {
+    let mut env = common::setup();
+    env.init_platform();
+    let project = env.create_project(PROJECT_AMOUNT, PRICE_PER_TOKEN, FEE_BPS);
+    let seller = env.funded_keypair();
+    let custodian = env.funded_keypair();
+    let delegated = env.purchase(&seller, &project, 3);
+    let purchase = env.purchase(&seller, &project, 5);
+    env.approve_offset_delegate(&seller, &delegated, &custodian.pubkey(), 1);
+    let project_account: Project = env.account(&project.address);
+
+    let record: Purchase = env.account(&delegated.address);
+    let result = env.send(
+        &[ix::list_credits(&seller.pubkey(), &delegated.address, &record, &project_account, 1, PRICE_PER_TOKEN)],
+        &seller,
+        &[],
+    );
+    common::assert_contract_error(result, ContractError::PurchaseDelegated);
+
+    // The certificate has left the seller's wallet
+    let record: Purchase = env.account(&purchase.address);
+    let certificate = get_associated_token_address(&seller.pubkey(), &record.certificate_mint);
+    env.set_token_account(&certificate, &record.certificate_mint, &seller.pubkey());
+    let result = env.send(
+        &[ix::list_credits(&seller.pubkey(), &purchase.address, &record, &project_account, 1, PRICE_PER_TOKEN)],
+        &seller,
+        &[],
+    );
+    common::assert_contract_error(result, ContractError::InvalidNFTAccount);
+}
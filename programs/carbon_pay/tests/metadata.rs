@@ -0,0 +1,134 @@
+//! Spoofed Metaplex accounts must be rejected by the program's own seeds checks, not only
+//! by the Token Metadata CPI.
+
+mod common;
+
+use anchor_lang::error::ErrorCode;
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_spl::metadata::mpl_token_metadata;
+use carbonpay::state::{Project, Purchase};
+use carbonpay_client::instructions::{self as ix, ProjectArgs};
+use carbonpay_client::pda;
+use common::{FEE_BPS, PRICE_PER_TOKEN, PROJECT_AMOUNT};
+use solana_keypair::Keypair;
+use solana_signer::Signer;
+
+/// Point every use of `real` in the program instruction at `fake`
+fn spoof(ixs: &mut [Instruction], real: Pubkey, fake: Pubkey) {
+    let program_ix = ixs.last_mut().unwrap();
+    let meta = program_ix
+        .accounts
+        .iter_mut()
+        .find(|meta| meta.pubkey == real)
+        .expect("instruction uses the real account");
+    meta.pubkey = fake;
+}
+
+/// The metadata address `mint` would have under a program other than Token Metadata
+fn foreign_metadata(mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"metadata", mpl_token_metadata::ID.as_ref(), mint.as_ref()],
+        &carbonpay::ID,
+    )
+    .0
+}
+
+fn project_instructions(owner: &Keypair, nft_mint: &Keypair, token_mint: &Keypair) -> Vec<Instruction> {
+    ix::initialize_project(
+        &owner.pubkey(),
+        &nft_mint.pubkey(),
+        &token_mint.pubkey(),
+        ProjectArgs {
+            amount: PROJECT_AMOUNT,
+            price_per_token: PRICE_PER_TOKEN,
+            carbon_pay_fee: FEE_BPS,
+            decimals: 0,
+            uri: "https://carbonpay.com/projects/test".to_string(),
+            name: "Test Project".to_string(),
+            symbol: "TEST".to_string(),
+        },
+    )
+}
+
+#[test]
+#[ignore = "needs target/deploy/carbonpay.so, run `anchor build` first"]
+fn initialize_project_rejects_spoofed_metadata_and_edition() {
+    let mut env = common::setup();
+    env.init_platform();
+    let owner = env.funded_keypair();
+    let (nft_mint, token_mint) = (Keypair::new(), Keypair::new());
+
+    let mut ixs = project_instructions(&owner, &nft_mint, &token_mint);
+    spoof(&mut ixs, pda::metadata(&nft_mint.pubkey()), foreign_metadata(&nft_mint.pubkey()));
+    let result = env.send(&ixs, &owner, &[&nft_mint, &token_mint]);
+    common::assert_anchor_error(result, ErrorCode::ConstraintSeeds);
+
+    // The master edition of another mint
+    let mut ixs = project_instructions(&owner, &nft_mint, &token_mint);
+    spoof(
+        &mut ixs,
+        pda::master_edition(&nft_mint.pubkey()),
+        pda::master_edition(&token_mint.pubkey()),
+    );
+    let result = env.send(&ixs, &owner, &[&nft_mint, &token_mint]);
+    common::assert_anchor_error(result, ErrorCode::ConstraintSeeds);
+}
+
+#[test]
+#[ignore = "needs target/deploy/carbonpay.so, run `anchor build` first"]
+fn purchase_rejects_spoofed_metadata() {
+    let mut env = common::setup();
+    env.init_platform();
+    let project = env.create_project(PROJECT_AMOUNT, PRICE_PER_TOKEN, FEE_BPS);
+    let project_account: Project = env.account(&project.address);
+    let buyer = env.funded_keypair();
+    let nft_mint = Keypair::new();
+
+    // The metadata of the project NFT, which already exists
+    let mut ixs = ix::purchase_carbon_credits(
+        &buyer.pubkey(),
+        &project.address,
+        &project_account,
+        &nft_mint.pubkey(),
+        1,
+        None,
+    );
+    spoof(&mut ixs, pda::metadata(&nft_mint.pubkey()), pda::metadata(&project.nft_mint));
+    let result = env.send(&ixs, &buyer, &[&nft_mint]);
+    common::assert_anchor_error(result, ErrorCode::ConstraintSeeds);
+    assert!(!env.exists(&pda::purchase(&buyer.pubkey(), &project.address, &nft_mint.pubkey()).0));
+}
+
+#[test]
+#[ignore = "needs target/deploy/carbonpay.so, run `anchor build` first"]
+fn request_offset_rejects_spoofed_metadata() {
+    let mut env = common::setup();
+    env.init_platform();
+    let project = env.create_project(PROJECT_AMOUNT, PRICE_PER_TOKEN, FEE_BPS);
+    let buyer = env.funded_keypair();
+    let purchase = env.purchase(&buyer, &project, 10);
+    let purchase_account: Purchase = env.account(&purchase.address);
+    let project_account: Project = env.account(&project.address);
+    let new_nft_mint = Keypair::new();
+
+    let mut ixs = ix::request_offset(
+        &buyer.pubkey(),
+        &purchase.address,
+        &purchase_account,
+        &project_account,
+        &new_nft_mint.pubkey(),
+        4,
+        "REQ1".to_string(),
+    );
+    spoof(
+        &mut ixs,
+        pda::metadata(&new_nft_mint.pubkey()),
+        foreign_metadata(&new_nft_mint.pubkey()),
+    );
+    let result = env.send(&ixs, &buyer, &[&new_nft_mint]);
+    common::assert_anchor_error(result, ErrorCode::ConstraintSeeds);
+
+    let record: Purchase = env.account(&purchase.address);
+    assert_eq!(record.remaining_amount, 10);
+}
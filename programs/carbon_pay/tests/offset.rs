@@ -0,0 +1,480 @@
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::hash::Hash;
+use anchor_lang::solana_program::instruction::AccountMeta;
+use anchor_lang::solana_program::message::{v0, AddressLookupTableAccount, VersionedMessage};
+use anchor_spl::associated_token::get_associated_token_address;
+use carbonpay::errors::ContractError;
+use carbonpay::state::{
+    CarbonCredits, OffsetBatch, OffsetRequest, Project, ProjectCategory, Purchase, RequestStatus,
+};
+use carbonpay_client::{instructions as ix, pda};
+use common::{FEE_BPS, PRICE_PER_TOKEN, PROJECT_AMOUNT, SOL};
+use solana_keypair::Keypair;
+use solana_signer::Signer;
+
+#[test]
+#[ignore = "needs target/deploy/carbonpay.so, run `anchor build` first"]
+fn partial_offset_burns_and_remints_certificate() {
+    let mut env = common::setup();
+    env.init_platform();
+    let project = env.create_project(PROJECT_AMOUNT, PRICE_PER_TOKEN, FEE_BPS);
+    let buyer = env.funded_keypair();
+    let purchase = env.purchase(&buyer, &project, 10);
+
+    let (result, new_nft_mint, request) = env.try_request_offset(&buyer, &purchase, 4, "REQ1");
+    result.unwrap();
+
+    let record: Purchase = env.account(&purchase.address);
+    assert_eq!(record.remaining_amount, 6);
+    assert_eq!(
+        env.token_balance(&get_associated_token_address(&buyer.pubkey(), &purchase.nft_mint)),
+        0
+    );
+    assert_eq!(
+        env.token_balance(&get_associated_token_address(&buyer.pubkey(), &new_nft_mint)),
+        1
+    );
+    assert_eq!(
+        env.token_balance(&get_associated_token_address(&buyer.pubkey(), &project.token_mint)),
+        6
+    );
+
+    let offset: OffsetRequest = env.account(&request);
+    assert_eq!(offset.amount, 4);
+    assert_eq!(offset.offset_requester, buyer.pubkey());
+    assert!(offset.status == RequestStatus::Pending);
+
+    let carbon_credits: CarbonCredits = env.account(&pda::carbon_credits().0);
+    // Whole-tonne project: 4 tokens are 4 tonnes in platform units
+    assert_eq!(carbon_credits.offset_credits, 4_000_000);
+    let project_account: Project = env.account(&project.address);
+    assert_eq!(project_account.offset_amount, 4);
+}
+
+#[test]
+#[ignore = "needs target/deploy/carbonpay.so, run `anchor build` first"]
+fn full_offset_does_not_remint() {
+    let mut env = common::setup();
+    env.init_platform();
+    let project = env.create_project(PROJECT_AMOUNT, PRICE_PER_TOKEN, FEE_BPS);
+    let buyer = env.funded_keypair();
+    let purchase = env.purchase(&buyer, &project, 3);
+
+    let (result, new_nft_mint, _) = env.try_request_offset(&buyer, &purchase, 3, "ALL");
+    result.unwrap();
+
+    let record: Purchase = env.account(&purchase.address);
+    assert_eq!(record.remaining_amount, 0);
+    assert_eq!(
+        env.token_balance(&get_associated_token_address(&buyer.pubkey(), &new_nft_mint)),
+        0
+    );
+}
+
+#[test]
+#[ignore = "needs target/deploy/carbonpay.so, run `anchor build` first"]
+fn rejects_invalid_offsets() {
+    let mut env = common::setup();
+    env.init_platform();
+    let project = env.create_project(PROJECT_AMOUNT, PRICE_PER_TOKEN, FEE_BPS);
+    let buyer = env.funded_keypair();
+    let purchase = env.purchase(&buyer, &project, 3);
+
+    let (result, _, _) = env.try_request_offset(&buyer, &purchase, 0, "ZERO");
+    common::assert_contract_error(result, ContractError::InvalidAmount);
+
+    let (result, _, _) = env.try_request_offset(&buyer, &purchase, 4, "TOO_MANY");
+    common::assert_contract_error(result, ContractError::InsufficientRemainingTokens);
+}
+
+#[test]
+#[ignore = "needs target/deploy/carbonpay.so, run `anchor build` first"]
+fn rejects_offset_by_non_owner() {
+    let mut env = common::setup();
+    env.init_platform();
+    let project = env.create_project(PROJECT_AMOUNT, PRICE_PER_TOKEN, FEE_BPS);
+    let buyer = env.funded_keypair();
+    let purchase = env.purchase(&buyer, &project, 3);
+    let stranger = env.funded_keypair();
+
+    let new_nft_mint = Keypair::new();
+    let purchase_account: Purchase = env.account(&purchase.address);
+    let project_account: Project = env.account(&project.address);
+    let mut ixs = ix::request_offset(
+        &stranger.pubkey(),
+        &purchase.address,
+        &purchase_account,
+        &project_account,
+        &new_nft_mint.pubkey(),
+        1,
+        "STRANGER".to_string(),
+    );
+    // no offset delegate: the optional account slot holds the program id
+    ixs.last_mut().unwrap().accounts[2] = AccountMeta::new_readonly(carbonpay::ID, false);
+    let result = env.send(&ixs, &stranger, &[&new_nft_mint]);
+    common::assert_contract_error(result, ContractError::NotPurchaseOwner);
+}
+
+#[test]
+#[ignore = "needs target/deploy/carbonpay.so, run `anchor build` first"]
+fn approves_offsets_by_vote() {
+    let mut env = common::setup();
+    env.init_platform();
+    let project = env.create_project(PROJECT_AMOUNT, PRICE_PER_TOKEN, FEE_BPS);
+    let buyer = env.funded_keypair();
+    let purchase = env.purchase(&buyer, &project, 5);
+    let (result, _, request) = env.try_request_offset(&buyer, &purchase, 2, "VOTE");
+    result.unwrap();
+
+    let outsider = env.funded_keypair();
+    let result = env.send(&[ix::approve_offset_vote(&outsider.pubkey(), &request)], &outsider, &[]);
+    common::assert_contract_error(result, ContractError::NotAnApprover);
+
+    let admin = env.admin.insecure_clone();
+    env.send(&[ix::approve_offset_vote(&admin.pubkey(), &request)], &admin, &[])
+        .unwrap();
+    let offset: OffsetRequest = env.account(&request);
+    assert!(offset.status == RequestStatus::Approved);
+
+    let result = env.send(&[ix::approve_offset_vote(&admin.pubkey(), &request)], &admin, &[]);
+    common::assert_contract_error(result, ContractError::RequestAlreadyProcessed);
+}
+
+#[test]
+#[ignore = "needs target/deploy/carbonpay.so, run `anchor build` first"]
+fn rejects_duplicate_votes() {
+    let mut env = common::setup();
+    env.init_platform();
+    let admin = env.admin.insecure_clone();
+    let second = Keypair::new();
+    env.send(
+        &[ix::update_offset_approvers(&admin.pubkey(), vec![admin.pubkey(), second.pubkey()], 2)],
+        &admin,
+        &[],
+    )
+    .unwrap();
+    let project = env.create_project(PROJECT_AMOUNT, PRICE_PER_TOKEN, FEE_BPS);
+    let buyer = env.funded_keypair();
+    let purchase = env.purchase(&buyer, &project, 5);
+    let (result, _, request) = env.try_request_offset(&buyer, &purchase, 2, "TWICE");
+    result.unwrap();
+
+    env.send(&[ix::approve_offset_vote(&admin.pubkey(), &request)], &admin, &[])
+        .unwrap();
+    let result = env.send(&[ix::approve_offset_vote(&admin.pubkey(), &request)], &admin, &[]);
+    common::assert_contract_error(result, ContractError::DuplicateVote);
+}
+
+#[test]
+#[ignore = "needs target/deploy/carbonpay.so, run `anchor build` first"]
+fn votes_of_removed_approvers_free_their_slots() {
+    let mut env = common::setup();
+    env.init_platform();
+    let admin = env.admin.insecure_clone();
+    let max = carbonpay::state::PlatformConfig::MAX_APPROVERS;
+    let old: Vec<Keypair> = (0..max).map(|_| env.funded_keypair()).collect();
+    env.send(
+        &[ix::update_offset_approvers(&admin.pubkey(), old.iter().map(|k| k.pubkey()).collect(), max as u8)],
+        &admin,
+        &[],
+    )
+    .unwrap();
+    let project = env.create_project(PROJECT_AMOUNT, PRICE_PER_TOKEN, FEE_BPS);
+    let buyer = env.funded_keypair();
+    let purchase = env.purchase(&buyer, &project, 5);
+    let (result, _, request) = env.try_request_offset(&buyer, &purchase, 2, "ROTATE");
+    result.unwrap();
+    for approver in &old[..max - 1] {
+        env.send(&[ix::approve_offset_vote(&approver.pubkey(), &request)], approver, &[])
+            .unwrap();
+    }
+
+    // The whole set is rotated before the request is approved
+    let new: Vec<Keypair> = (0..2).map(|_| env.funded_keypair()).collect();
+    env.send(
+        &[ix::update_offset_approvers(&admin.pubkey(), new.iter().map(|k| k.pubkey()).collect(), 2)],
+        &admin,
+        &[],
+    )
+    .unwrap();
+    for approver in &new {
+        env.send(&[ix::approve_offset_vote(&approver.pubkey(), &request)], approver, &[])
+            .unwrap();
+    }
+    let offset: OffsetRequest = env.account(&request);
+    assert!(offset.status == RequestStatus::Approved);
+}
+
+#[test]
+#[ignore = "needs target/deploy/carbonpay.so, run `anchor build` first"]
+fn purchase_and_retire_keeps_its_own_request_seeds() {
+    let mut env = common::setup();
+    env.init_platform();
+    let project = env.create_project(PROJECT_AMOUNT, PRICE_PER_TOKEN, FEE_BPS);
+    let buyer = env.funded_keypair();
+    let project_account: Project = env.account(&project.address);
+
+    let result = env.send(
+        &[ix::purchase_and_retire(&buyer.pubkey(), &project.address, &project_account, 2, "SAME".to_string(), None)],
+        &buyer,
+        &[],
+    );
+    let event = common::purchase_event(result);
+    let retirement = pda::retirement(&buyer.pubkey(), &project.address, "SAME").0;
+    assert_eq!(event.purchase, Pubkey::default());
+    assert_eq!(event.offset_request, Some(retirement));
+    let record: OffsetRequest = env.account(&retirement);
+    assert_eq!(record.amount, 2);
+
+    // A batch offset may reuse the request id
+    let purchase = env.purchase(&buyer, &project, 3);
+    env.try_request_batch_offset(&buyer, &project.address, &[(purchase.address, 3)], "SAME")
+        .unwrap();
+    let batch: OffsetRequest = env.account(&pda::offset_request(&buyer.pubkey(), &project.address, "SAME").0);
+    assert_eq!(batch.amount, 3);
+}
+
+#[test]
+#[ignore = "needs target/deploy/carbonpay.so, run `anchor build` first"]
+fn every_offset_path_counts_offset_credits() {
+    let mut env = common::setup();
+    env.init_platform();
+    let project = env.create_project(PROJECT_AMOUNT, PRICE_PER_TOKEN, FEE_BPS);
+    let buyer = env.funded_keypair();
+    let purchase = env.purchase(&buyer, &project, 10);
+    let project_account: Project = env.account(&project.address);
+    let totals = |env: &common::TestEnv| {
+        let carbon_credits: CarbonCredits = env.account(&pda::carbon_credits().0);
+        (carbon_credits.active_credits, carbon_credits.offset_credits)
+    };
+    assert_eq!(totals(&env), (1_000_000_000, 0));
+
+    let (result, _, _) = env.try_request_offset(&buyer, &purchase, 4, "SINGLE");
+    result.unwrap();
+    assert_eq!(totals(&env), (1_000_000_000, 4_000_000));
+
+    env.try_request_batch_offset(&buyer, &project.address, &[(purchase.address, 3)], "BATCH")
+        .unwrap();
+    assert_eq!(totals(&env), (1_000_000_000, 7_000_000));
+
+    env.send(
+        &[ix::purchase_and_retire(&buyer.pubkey(), &project.address, &project_account, 2, "RETIRE".to_string(), None)],
+        &buyer,
+        &[],
+    )
+    .unwrap();
+    assert_eq!(totals(&env), (1_000_000_000, 9_000_000));
+}
+
+#[test]
+#[ignore = "needs target/deploy/carbonpay.so, run `anchor build` first"]
+fn batch_offset_records_the_offset_on_each_purchase() {
+    let mut env = common::setup();
+    env.init_platform();
+    let project = env.create_project(PROJECT_AMOUNT, PRICE_PER_TOKEN, FEE_BPS);
+    let buyer = env.funded_keypair();
+    let partial = env.purchase(&buyer, &project, 4);
+    let full = env.purchase(&buyer, &project, 3);
+
+    env.try_request_batch_offset(&buyer, &project.address, &[(partial.address, 1), (full.address, 3)], "BATCH")
+        .unwrap();
+
+    let request: OffsetRequest = env.account(&pda::offset_request(&buyer.pubkey(), &project.address, "BATCH").0);
+    assert_eq!(request.amount, 4);
+    let kept: Purchase = env.account(&partial.address);
+    assert_eq!((kept.remaining_amount, kept.uncertified_offset, kept.certificate_mint), (3, 1, partial.nft_mint));
+    let used_up: Purchase = env.account(&full.address);
+    assert_eq!((used_up.remaining_amount, used_up.uncertified_offset), (0, 3));
+    // The certificates are left where they were
+    let balances: Vec<u64> = [partial.nft_mint, full.nft_mint]
+        .iter()
+        .map(|mint| env.token_balance(&get_associated_token_address(&buyer.pubkey(), mint)))
+        .collect();
+    assert_eq!(balances, [1, 1]);
+
+    // A single offset re-mints the certificate for what is left, which catches it up
+    let (result, new_nft_mint, _) = env.try_request_offset(&buyer, &partial, 1, "AFTER_BATCH");
+    result.unwrap();
+    let kept: Purchase = env.account(&partial.address);
+    assert_eq!((kept.remaining_amount, kept.uncertified_offset, kept.certificate_mint), (2, 0, new_nft_mint));
+}
+
+#[test]
+#[ignore = "needs target/deploy/carbonpay.so, run `anchor build` first"]
+fn largest_batch_lands_in_one_v0_transaction() {
+    let mut env = common::setup();
+    env.init_platform();
+    let project = env.create_project(PROJECT_AMOUNT, PRICE_PER_TOKEN, FEE_BPS);
+    let buyer = env.funded_keypair();
+    let entries: Vec<_> = (0..OffsetBatch::MAX_PURCHASES)
+        .map(|_| (env.purchase(&buyer, &project, 2).address, 1))
+        .collect();
+
+    env.try_request_batch_offset(&buyer, &project.address, &entries, "FULL")
+        .unwrap();
+    let request: OffsetRequest = env.account(&pda::offset_request(&buyer.pubkey(), &project.address, "FULL").0);
+    assert_eq!(request.amount, OffsetBatch::MAX_PURCHASES as u64);
+    let batch: OffsetBatch = env.account(&pda::offset_batch(&pda::offset_request(&buyer.pubkey(), &project.address, "FULL").0).0);
+    assert_eq!(batch.purchases.len(), OffsetBatch::MAX_PURCHASES);
+}
+
+#[test]
+#[ignore = "needs target/deploy/carbonpay.so, run `anchor build` first"]
+fn batch_offset_rejects_malformed_batches() {
+    let mut env = common::setup();
+    env.init_platform();
+    let project = env.create_project(PROJECT_AMOUNT, PRICE_PER_TOKEN, FEE_BPS);
+    let buyer = env.funded_keypair();
+    let purchase = env.purchase(&buyer, &project, 2);
+    let project_account: Project = env.account(&project.address);
+    let record: Purchase = env.account(&purchase.address);
+
+    // more amounts than a batch takes; only the first purchase's accounts are sent, as the
+    // full set would not fit a transaction
+    let too_many = vec![(purchase.address, record.clone(), 1); OffsetBatch::MAX_PURCHASES + 1];
+    let mut batch =
+        ix::request_batch_offset(&buyer.pubkey(), &project.address, &project_account, &too_many, "TOO_MANY".to_string());
+    batch.accounts.truncate(batch.accounts.len() - 2 * OffsetBatch::MAX_PURCHASES);
+    let result = env.send(&[batch], &buyer, &[]);
+    common::assert_contract_error(result, ContractError::TooManyBatchPurchases);
+
+    let result =
+        env.try_request_batch_offset(&buyer, &project.address, &[(purchase.address, 1), (purchase.address, 1)], "TWICE");
+    common::assert_contract_error(result, ContractError::InvalidBatchPurchases);
+
+    // a purchase without its certificate account
+    let mut batch = ix::request_batch_offset(
+        &buyer.pubkey(),
+        &project.address,
+        &project_account,
+        &[(purchase.address, record.clone(), 1)],
+        "SHORT".to_string(),
+    );
+    batch.accounts.pop();
+    let result = env.send(&[batch], &buyer, &[]);
+    common::assert_contract_error(result, ContractError::InvalidBatchPurchases);
+
+    // a certificate account of another mint
+    let other = env.purchase(&buyer, &project, 2);
+    let mut batch = ix::request_batch_offset(
+        &buyer.pubkey(),
+        &project.address,
+        &project_account,
+        &[(purchase.address, record, 1)],
+        "FOREIGN".to_string(),
+    );
+    let last = batch.accounts.len() - 1;
+    batch.accounts[last].pubkey = get_associated_token_address(&buyer.pubkey(), &other.nft_mint);
+    let result = env.send(&[batch], &buyer, &[]);
+    common::assert_contract_error(result, ContractError::InvalidNFTAccount);
+}
+
+#[test]
+#[ignore = "needs target/deploy/carbonpay.so, run `anchor build` first"]
+fn batch_and_single_offsets_apply_the_same_rules() {
+    let mut env = common::setup();
+    env.init_platform();
+    let project = env.create_project(PROJECT_AMOUNT, PRICE_PER_TOKEN, FEE_BPS);
+    let buyer = env.funded_keypair();
+    let listed = env.purchase(&buyer, &project, 3);
+    env.list_credits(&buyer, &listed, 1, PRICE_PER_TOKEN);
+    let subscribed = env.create_subscription(&buyer, &project, 2, 10 * SOL);
+    env.try_execute_subscription(&buyer.pubkey(), &project, None).unwrap();
+    let delegated = env.purchase(&buyer, &project, 3);
+    env.approve_offset_delegate(&buyer, &delegated, &Keypair::new().pubkey(), 1);
+
+    // What is left of a listed or subscribed position can be offset either way
+    for (purchase, request_id) in [(&listed, "LISTED"), (&subscribed, "SUBSCRIBED")] {
+        env.try_request_batch_offset(&buyer, &project.address, &[(purchase.address, 1)], request_id)
+            .unwrap();
+        let (result, _, _) = env.try_request_offset(&buyer, purchase, 1, "SINGLE");
+        result.unwrap();
+        let record: Purchase = env.account(&purchase.address);
+        assert_eq!(record.remaining_amount, 0);
+    }
+
+    // A delegated position's certificate sits in the delegate's escrow, so the owner can't
+    let result = env.try_request_batch_offset(&buyer, &project.address, &[(delegated.address, 1)], "DELEGATED");
+    common::assert_contract_error(result, ContractError::InvalidNFTAccount);
+    let (result, _, _) = env.try_request_offset(&buyer, &delegated, 1, "SINGLE");
+    common::assert_contract_error(result, ContractError::InvalidNFTAccount);
+}
+
+/// With every account but the requester's in a lookup table, the batch limit is what a
+/// transaction may lock, and the largest batch still fits a packet with the longest request id
+/// the offset request seeds allow
+#[test]
+fn largest_batch_fits_a_v0_transaction() {
+    const PACKET_DATA_SIZE: usize = 1232;
+    const MAX_TX_ACCOUNT_LOCKS: usize = 64;
+    let requester = Keypair::new();
+    let project = Pubkey::new_unique();
+    let project_account = Project {
+        owner: Pubkey::new_unique(),
+        mint: Pubkey::new_unique(),
+        token_mint: Pubkey::new_unique(),
+        token_bump: 255,
+        is_active: true,
+        amount: PROJECT_AMOUNT,
+        remaining_amount: PROJECT_AMOUNT,
+        offset_amount: 0,
+        price_per_token: PRICE_PER_TOKEN,
+        carbon_pay_fee: FEE_BPS,
+        carbon_pay_authority: pda::carbon_credits().0,
+        project_bump: 255,
+        allowlist_required: false,
+        min_purchase_amount: 0,
+        max_purchase_amount: 0,
+        max_per_buyer: 0,
+        sale_start: 0,
+        sale_end: 0,
+        early_access_start: 0,
+        price_tiers: vec![],
+        auction: None,
+        category: ProjectCategory::Unspecified,
+        vault: Pubkey::new_unique(),
+        decimals: 0,
+        layout_version: Project::LAYOUT_VERSION,
+    };
+    // (transaction size, accounts locked)
+    let transaction = |count: usize| {
+        let purchases: Vec<_> = (0..count)
+            .map(|_| {
+                let nft_mint = Pubkey::new_unique();
+                let purchase = Purchase {
+                    buyer: requester.pubkey(),
+                    project,
+                    amount: 2,
+                    remaining_amount: 2,
+                    purchase_date: 0,
+                    purchase_bump: 255,
+                    nft_mint,
+                    certificate_mint: nft_mint,
+                    uncertified_offset: 0,
+                };
+                (Pubkey::new_unique(), purchase, 1)
+            })
+            .collect();
+        let batch = ix::request_batch_offset(&requester.pubkey(), &project, &project_account, &purchases, "R".repeat(32));
+        let table = AddressLookupTableAccount {
+            key: Pubkey::new_unique(),
+            addresses: ix::lookup_table_addresses(&batch),
+        };
+        let message = v0::Message::try_compile(&requester.pubkey(), &[batch], &[table], Hash::default()).unwrap();
+        let locked = message.account_keys.len()
+            + message
+                .address_table_lookups
+                .iter()
+                .map(|lookup| lookup.writable_indexes.len() + lookup.readonly_indexes.len())
+                .sum::<usize>();
+        // compact signature count, one signature, then the message
+        (1 + 64 + VersionedMessage::V0(message).serialize().len(), locked)
+    };
+    let (size, locked) = transaction(OffsetBatch::MAX_PURCHASES);
+    assert!(size <= PACKET_DATA_SIZE);
+    assert!(locked <= MAX_TX_ACCOUNT_LOCKS);
+    let (_, locked) = transaction(OffsetBatch::MAX_PURCHASES + 1);
+    assert!(locked > MAX_TX_ACCOUNT_LOCKS);
+}
@@ -0,0 +1,125 @@
+mod common;
+
+use carbonpay::errors::ContractError;
+use carbonpay::state::{BuyerStats, OtcOffer, Project};
+use carbonpay_client::{instructions as ix, pda};
+use common::{FEE_BPS, PRICE_PER_TOKEN, PROJECT_AMOUNT};
+use solana_signer::Signer;
+
+#[test]
+#[ignore = "needs target/deploy/carbonpay.so, run `anchor build` first"]
+fn rejects_offers_during_an_auction() {
+    let mut env = common::setup();
+    env.init_platform();
+    env.set_time(1_000);
+    let project = env.create_project(PROJECT_AMOUNT, PRICE_PER_TOKEN, FEE_BPS);
+    let owner = project.owner.insecure_clone();
+    env.send(
+        &[ix::start_dutch_auction(&owner.pubkey(), &project.address, 2 * PRICE_PER_TOKEN, PRICE_PER_TOKEN, 1_000, 600, 60)],
+        &owner,
+        &[],
+    )
+    .unwrap();
+
+    let buyer = env.funded_keypair();
+    let result = env.send(
+        &[ix::create_otc_offer(&owner.pubkey(), &project.address, &buyer.pubkey(), 1, 5, PRICE_PER_TOKEN, 2_000)],
+        &owner,
+        &[],
+    );
+    common::assert_contract_error(result, ContractError::AuctionActive);
+}
+
+#[test]
+#[ignore = "needs target/deploy/carbonpay.so, run `anchor build` first"]
+fn anyone_cancels_an_expired_offer() {
+    let mut env = common::setup();
+    env.init_platform();
+    env.set_time(1_000);
+    let project = env.create_project(PROJECT_AMOUNT, PRICE_PER_TOKEN, FEE_BPS);
+    let owner = project.owner.insecure_clone();
+    let buyer = env.funded_keypair();
+    env.send(
+        &[ix::create_otc_offer(&owner.pubkey(), &project.address, &buyer.pubkey(), 1, 5, PRICE_PER_TOKEN, 2_000)],
+        &owner,
+        &[],
+    )
+    .unwrap();
+    let address = pda::otc_offer(&project.address, &buyer.pubkey(), 1).0;
+    let offer: OtcOffer = env.account(&address);
+    let project_account: Project = env.account(&project.address);
+    assert_eq!(project_account.remaining_amount, PROJECT_AMOUNT - 5);
+
+    let stranger = env.funded_keypair();
+    let result = env.send(&[ix::cancel_otc_offer(&stranger.pubkey(), &offer, &project_account)], &stranger, &[]);
+    common::assert_contract_error(result, ContractError::OtcOfferNotExpired);
+
+    env.set_time(2_000);
+    let owner_before = env.balance(&owner.pubkey());
+    env.send(&[ix::cancel_otc_offer(&stranger.pubkey(), &offer, &project_account)], &stranger, &[])
+        .unwrap();
+    assert!(!env.exists(&address));
+    assert!(env.balance(&owner.pubkey()) > owner_before);
+    let project_account: Project = env.account(&project.address);
+    assert_eq!(project_account.remaining_amount, PROJECT_AMOUNT);
+}
+
+#[test]
+#[ignore = "needs target/deploy/carbonpay.so, run `anchor build` first"]
+fn offers_skip_sale_limits_but_not_the_allowlist_or_buyer_cap() {
+    let mut env = common::setup();
+    env.init_platform();
+    env.set_time(1_000);
+    let project = env.create_project(PROJECT_AMOUNT, PRICE_PER_TOKEN, FEE_BPS);
+    let owner = project.owner.insecure_clone();
+    let admin = env.admin.insecure_clone();
+    let buyer = env.funded_keypair();
+    env.send(&[ix::set_allowlist_required(&admin.pubkey(), Some(project.address), true)], &admin, &[])
+        .unwrap();
+    // a public sale of at most 2 tokens at a time does not bind negotiated offers
+    env.send(&[ix::set_purchase_limits(&owner.pubkey(), &project.address, 0, 2, 6)], &owner, &[])
+        .unwrap();
+    for (offer_id, amount) in [(1, 4), (2, 3)] {
+        env.send(
+            &[ix::create_otc_offer(&owner.pubkey(), &project.address, &buyer.pubkey(), offer_id, amount, PRICE_PER_TOKEN, 2_000)],
+            &owner,
+            &[],
+        )
+        .unwrap();
+    }
+
+    let result = env.try_accept_otc_offer(&buyer, &project, 1, None);
+    common::assert_contract_error(result, ContractError::BuyerNotAllowlisted);
+
+    env.send(&[ix::add_allowlist_entry(&admin.pubkey(), Some(project.address), &buyer.pubkey(), 0)], &admin, &[])
+        .unwrap();
+    let entry = pda::allowlist_entry(&project.address, &buyer.pubkey()).0;
+    env.try_accept_otc_offer(&buyer, &project, 1, Some(entry)).unwrap();
+    let stats: BuyerStats = env.account(&pda::buyer_stats(&buyer.pubkey(), &project.address).0);
+    assert_eq!(stats.total_purchased, 4);
+
+    // 4 + 3 is over the cap of 6
+    let result = env.try_accept_otc_offer(&buyer, &project, 2, Some(entry));
+    common::assert_contract_error(result, ContractError::BuyerCapExceeded);
+}
+
+#[test]
+#[ignore = "needs target/deploy/carbonpay.so, run `anchor build` first"]
+fn rejects_expired_offers() {
+    let mut env = common::setup();
+    env.init_platform();
+    env.set_time(1_000);
+    let project = env.create_project(PROJECT_AMOUNT, PRICE_PER_TOKEN, FEE_BPS);
+    let owner = project.owner.insecure_clone();
+    let buyer = env.funded_keypair();
+    env.send(
+        &[ix::create_otc_offer(&owner.pubkey(), &project.address, &buyer.pubkey(), 1, 5, PRICE_PER_TOKEN, 2_000)],
+        &owner,
+        &[],
+    )
+    .unwrap();
+
+    env.set_time(2_000);
+    let result = env.try_accept_otc_offer(&buyer, &project, 1, None);
+    common::assert_contract_error(result, ContractError::OtcOfferExpired);
+}
@@ -0,0 +1,222 @@
+mod common;
+
+use anchor_lang::error::ErrorCode;
+use anchor_lang::prelude::{AccountMeta, Pubkey};
+use carbonpay::errors::ContractError;
+use carbonpay::state::{CarbonCredits, Holdings, PlatformConfig, Project, Purchase};
+use carbonpay_client::{instructions as ix, pda};
+use solana_keypair::Keypair;
+use solana_signer::Signer;
+
+#[test]
+#[ignore = "needs target/deploy/carbonpay.so, run `anchor build` first"]
+fn initializes_platform() {
+    let mut env = common::setup();
+    env.init_platform();
+
+    let carbon_credits: CarbonCredits = env.account(&pda::carbon_credits().0);
+    assert_eq!(carbon_credits.authority, env.admin.pubkey());
+    assert_eq!(carbon_credits.total_credits, 0);
+    assert_eq!(carbon_credits.units_version, CarbonCredits::UNITS_VERSION);
+
+    let config: PlatformConfig = env.account(&pda::platform_config().0);
+    assert_eq!(config.approvers, vec![env.admin.pubkey()]);
+    assert_eq!(config.approval_threshold, 1);
+}
+
+#[test]
+#[ignore = "needs target/deploy/carbonpay.so, run `anchor build` first"]
+fn rejects_invalid_approver_sets() {
+    let mut env = common::setup();
+    env.init_platform();
+    let admin = env.admin.insecure_clone();
+    let other = Keypair::new().pubkey();
+
+    let result = env.send(&[ix::update_offset_approvers(&admin.pubkey(), vec![other], 0)], &admin, &[]);
+    common::assert_contract_error(result, ContractError::InvalidApprovalThreshold);
+
+    let result = env.send(&[ix::update_offset_approvers(&admin.pubkey(), vec![other], 2)], &admin, &[]);
+    common::assert_contract_error(result, ContractError::InvalidApprovalThreshold);
+
+    let result = env.send(
+        &[ix::update_offset_approvers(&admin.pubkey(), vec![other, other], 1)],
+        &admin,
+        &[],
+    );
+    common::assert_contract_error(result, ContractError::DuplicateApprover);
+
+    let too_many = (0..=PlatformConfig::MAX_APPROVERS).map(|_| Keypair::new().pubkey()).collect();
+    let result = env.send(&[ix::update_offset_approvers(&admin.pubkey(), too_many, 1)], &admin, &[]);
+    common::assert_contract_error(result, ContractError::TooManyApprovers);
+}
+
+#[test]
+#[ignore = "needs target/deploy/carbonpay.so, run `anchor build` first"]
+fn only_the_admin_updates_approvers() {
+    let mut env = common::setup();
+    env.init_platform();
+    let intruder = env.funded_keypair();
+
+    let result = env.send(
+        &[ix::update_offset_approvers(&intruder.pubkey(), vec![intruder.pubkey()], 1)],
+        &intruder,
+        &[],
+    );
+    common::assert_contract_error(result, ContractError::UnauthorizedAdmin);
+}
+
+#[test]
+#[ignore = "needs target/deploy/carbonpay.so, run `anchor build` first"]
+fn migrates_legacy_carbon_credits_to_platform_units() {
+    let mut env = common::setup();
+    env.init_platform();
+    let admin = env.admin.insecure_clone();
+    let address = pda::carbon_credits().0;
+
+    // The layout before units_version: no trailing byte, counters in whole tonnes
+    let legacy = CarbonCredits::DISCRIMINATOR_SIZE + CarbonCredits::LEGACY_SPACE;
+    let mut account = env.svm.get_account(&address).unwrap();
+    account.data.truncate(legacy);
+    let counters = [5u64, 3, 2, 1].map(u64::to_le_bytes).concat();
+    account.data[40..72].copy_from_slice(&counters);
+    account.lamports = env.svm.minimum_balance_for_rent_exemption(legacy);
+    env.svm.set_account(address, account).unwrap();
+
+    let intruder = env.funded_keypair();
+    let result = env.send(&[ix::migrate_carbon_credits(&intruder.pubkey())], &intruder, &[]);
+    common::assert_contract_error(result, ContractError::UnauthorizedAdmin);
+
+    env.send(&[ix::migrate_carbon_credits(&admin.pubkey())], &admin, &[])
+        .unwrap();
+    let carbon_credits: CarbonCredits = env.account(&address);
+    assert_eq!(carbon_credits.authority, admin.pubkey());
+    assert_eq!(carbon_credits.units_version, CarbonCredits::UNITS_VERSION);
+    assert_eq!(carbon_credits.total_credits, 5_000_000);
+    assert_eq!(carbon_credits.active_credits, 3_000_000);
+    assert_eq!(carbon_credits.offset_credits, 2_000_000);
+    assert_eq!(carbon_credits.projects_count, 1);
+
+    let result = env.send(&[ix::migrate_carbon_credits(&admin.pubkey())], &admin, &[]);
+    common::assert_contract_error(result, ContractError::CarbonCreditsMigrated);
+}
+
+#[test]
+#[ignore = "needs target/deploy/carbonpay.so, run `anchor build` first"]
+fn migrates_legacy_projects_to_the_current_layout() {
+    let mut env = common::setup();
+    env.init_platform();
+    let admin = env.admin.insecure_clone();
+    let project = env.create_project(1000, 1_000_000, 500);
+    let before: Project = env.account(&project.address);
+
+    // The layout before layout_version: nothing after project_bump
+    let legacy = Project::DISCRIMINATOR_SIZE + Project::LEGACY_SPACE;
+    let mut account = env.svm.get_account(&project.address).unwrap();
+    account.data.truncate(legacy);
+    account.lamports = env.svm.minimum_balance_for_rent_exemption(legacy);
+    env.svm.set_account(project.address, account).unwrap();
+
+    let intruder = env.funded_keypair();
+    let result = env.send(
+        &[ix::migrate_project(&intruder.pubkey(), &project.address, &project.token_mint)],
+        &intruder,
+        &[],
+    );
+    common::assert_contract_error(result, ContractError::UnauthorizedAdmin);
+
+    let other = env.create_project(10, 1_000_000, 500);
+    let result = env.send(
+        &[ix::migrate_project(&admin.pubkey(), &project.address, &other.token_mint)],
+        &admin,
+        &[],
+    );
+    common::assert_contract_error(result, ContractError::InvalidProjectMint);
+
+    // a carbon_credits token account other than the canonical ATA is not a vault
+    let stray = Pubkey::new_unique();
+    env.set_token_account(&stray, &project.token_mint, &pda::carbon_credits().0);
+    let mut migrate = ix::migrate_project(&admin.pubkey(), &project.address, &project.token_mint);
+    // vault is the fifth account of migrate_project
+    migrate.accounts[4] = AccountMeta::new_readonly(stray, false);
+    let result = env.send(&[migrate], &admin, &[]);
+    common::assert_anchor_error(result, ErrorCode::ConstraintAssociated);
+
+    let migrate = ix::migrate_project(&admin.pubkey(), &project.address, &project.token_mint);
+    env.send(std::slice::from_ref(&migrate), &admin, &[]).unwrap();
+    let migrated: Project = env.account(&project.address);
+    assert_eq!(migrated.layout_version, Project::LAYOUT_VERSION);
+    assert_eq!(migrated.owner, before.owner);
+    assert_eq!(migrated.amount, before.amount);
+    assert_eq!(migrated.remaining_amount, before.remaining_amount);
+    assert_eq!(migrated.price_per_token, before.price_per_token);
+    assert_eq!(migrated.vault, project.vault());
+    assert_eq!(migrated.decimals, 0);
+    assert!(migrated.price_tiers.is_empty());
+    assert!(migrated.auction.is_none());
+
+    // Purchases work against the migrated account
+    let buyer = env.funded_keypair();
+    env.purchase(&buyer, &project, 10);
+
+    let result = env.send(&[migrate], &admin, &[]);
+    common::assert_contract_error(result, ContractError::ProjectMigrated);
+}
+
+#[test]
+#[ignore = "needs target/deploy/carbonpay.so, run `anchor build` first"]
+fn migrates_legacy_purchases_to_the_current_layout() {
+    let mut env = common::setup();
+    env.init_platform();
+    let project = env.create_project(1000, 1_000_000, 500);
+    let buyer = env.funded_keypair();
+    let purchase = env.purchase(&buyer, &project, 10);
+    let before: Purchase = env.account(&purchase.address);
+
+    // The layout before certificate_mint: nothing after nft_mint
+    let legacy = Purchase::DISCRIMINATOR_SIZE + Purchase::LEGACY_SPACE;
+    let mut account = env.svm.get_account(&purchase.address).unwrap();
+    account.data.truncate(legacy);
+    account.lamports = env.svm.minimum_balance_for_rent_exemption(legacy);
+    env.svm.set_account(purchase.address, account.clone()).unwrap();
+    // and before Holdings
+    let holdings = pda::holdings(&buyer.pubkey(), &project.address).0;
+    env.svm.set_account(holdings, Default::default()).unwrap();
+    let (result, _, _) = env.try_request_offset(&buyer, &purchase, 1, "LEGACY");
+    common::assert_anchor_error(result, ErrorCode::AccountDidNotDeserialize);
+
+    // a copy away from the purchase PDA is not migrated
+    let copy = Pubkey::new_unique();
+    env.svm.set_account(copy, account).unwrap();
+    let payer = env.funded_keypair();
+    let result = env.send(
+        &[ix::migrate_purchase(&payer.pubkey(), &copy, &buyer.pubkey(), &project.address)],
+        &payer,
+        &[],
+    );
+    common::assert_anchor_error(result, ErrorCode::ConstraintSeeds);
+
+    // the Holdings credited must be the buyer's
+    let result = env.send(
+        &[ix::migrate_purchase(&payer.pubkey(), &purchase.address, &payer.pubkey(), &project.address)],
+        &payer,
+        &[],
+    );
+    common::assert_contract_error(result, ContractError::NotPurchaseOwner);
+
+    let migrate = ix::migrate_purchase(&payer.pubkey(), &purchase.address, &buyer.pubkey(), &project.address);
+    env.send(std::slice::from_ref(&migrate), &payer, &[]).unwrap();
+    let migrated: Purchase = env.account(&purchase.address);
+    assert_eq!(migrated.certificate_mint, before.nft_mint);
+    assert_eq!(migrated.buyer, before.buyer);
+    assert_eq!(migrated.remaining_amount, before.remaining_amount);
+    assert_eq!(migrated.purchase_date, before.purchase_date);
+    let holdings: Holdings = env.account(&holdings);
+    assert_eq!(holdings.backed_amount, before.remaining_amount);
+
+    // Offsets work against the migrated account
+    let (result, _, _) = env.try_request_offset(&buyer, &purchase, 1, "MIGRATED");
+    result.unwrap();
+
+    let result = env.send(&[migrate], &payer, &[]);
+    common::assert_contract_error(result, ContractError::PurchaseMigrated);
+}
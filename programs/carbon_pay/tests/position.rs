@@ -0,0 +1,317 @@
+mod common;
+
+use anchor_lang::prelude::{AccountMeta, Pubkey};
+use anchor_spl::associated_token::get_associated_token_address;
+use anchor_spl::token::spl_token;
+use carbonpay::errors::ContractError;
+use carbonpay::state::{Holdings, Project, Purchase};
+use carbonpay_client::{instructions as ix, pda};
+use common::{PurchaseFixture, FEE_BPS, PRICE_PER_TOKEN, PROJECT_AMOUNT};
+use solana_keypair::Keypair;
+use solana_signer::Signer;
+
+#[test]
+#[ignore = "needs target/deploy/carbonpay.so, run `anchor build` first"]
+fn split_remints_the_source_certificate() {
+    let mut env = common::setup();
+    env.init_platform();
+    let project = env.create_project(PROJECT_AMOUNT, PRICE_PER_TOKEN, FEE_BPS);
+    let buyer = env.funded_keypair();
+    let purchase = env.purchase(&buyer, &project, 10);
+    let record: Purchase = env.account(&purchase.address);
+
+    let (new_nft_mint, replacement) = (Keypair::new(), Keypair::new());
+    env.send(
+        &ix::split_purchase(&buyer.pubkey(), &purchase.address, &record, &new_nft_mint.pubkey(), &replacement.pubkey(), 4),
+        &buyer,
+        &[&new_nft_mint, &replacement],
+    )
+    .unwrap();
+
+    let record: Purchase = env.account(&purchase.address);
+    assert_eq!(record.remaining_amount, 6);
+    assert_eq!(record.nft_mint, purchase.nft_mint);
+    assert_eq!(record.certificate_mint, replacement.pubkey());
+    assert_eq!(env.token_balance(&get_associated_token_address(&buyer.pubkey(), &purchase.nft_mint)), 0);
+    assert_eq!(env.token_balance(&get_associated_token_address(&buyer.pubkey(), &replacement.pubkey())), 1);
+    let carved: Purchase = env.account(&pda::purchase(&buyer.pubkey(), &project.address, &new_nft_mint.pubkey()).0);
+    assert_eq!(carved.remaining_amount, 4);
+
+    // The source keeps offsetting against its re-minted certificate
+    let (result, _, _) = env.try_request_offset(&buyer, &purchase, 2, "AFTER_SPLIT");
+    result.unwrap();
+}
+
+#[test]
+#[ignore = "needs target/deploy/carbonpay.so, run `anchor build` first"]
+fn split_rejects_certificate_mints_the_buyer_does_not_control() {
+    let mut env = common::setup();
+    env.init_platform();
+    let project = env.create_project(PROJECT_AMOUNT, PRICE_PER_TOKEN, FEE_BPS);
+    let buyer = env.funded_keypair();
+    let purchase = env.purchase(&buyer, &project, 10);
+    let record: Purchase = env.account(&purchase.address);
+
+    let (new_nft_mint, replacement) = (Keypair::new(), Keypair::new());
+    let mut ixs = ix::split_purchase(&buyer.pubkey(), &purchase.address, &record, &new_nft_mint.pubkey(), &replacement.pubkey(), 4);
+    // the replacement mint is initialized with someone else as its mint authority
+    ixs[4] = spl_token::instruction::initialize_mint2(&spl_token::ID, &replacement.pubkey(), &Pubkey::new_unique(), None, 0)
+        .unwrap();
+    let result = env.send(&ixs, &buyer, &[&new_nft_mint, &replacement]);
+    common::assert_contract_error(result, ContractError::InvalidNFTMint);
+}
+
+#[test]
+#[ignore = "needs target/deploy/carbonpay.so, run `anchor build` first"]
+fn merge_rejects_listed_or_delegated_sources() {
+    let mut env = common::setup();
+    env.init_platform();
+    let project = env.create_project(PROJECT_AMOUNT, PRICE_PER_TOKEN, FEE_BPS);
+    let buyer = env.funded_keypair();
+    let target = env.purchase(&buyer, &project, 2);
+    let listed = env.purchase(&buyer, &project, 3);
+    let delegated = env.purchase(&buyer, &project, 4);
+    env.list_credits(&buyer, &listed, 1, PRICE_PER_TOKEN);
+    env.approve_offset_delegate(&buyer, &delegated, &Keypair::new().pubkey(), 1);
+    let target_record: Purchase = env.account(&target.address);
+
+    for (source, error) in [(&listed, ContractError::PurchaseListed), (&delegated, ContractError::PurchaseDelegated)] {
+        let record: Purchase = env.account(&source.address);
+        let replacement = Keypair::new();
+        let result = env.send(
+            &ix::merge_purchases(&buyer.pubkey(), &target.address, &target_record, &replacement.pubkey(), &[(source.address, record)]),
+            &buyer,
+            &[&replacement],
+        );
+        common::assert_contract_error(result, error);
+    }
+}
+
+#[test]
+#[ignore = "needs target/deploy/carbonpay.so, run `anchor build` first"]
+fn merge_remints_the_target_certificate() {
+    let mut env = common::setup();
+    env.init_platform();
+    let project = env.create_project(PROJECT_AMOUNT, PRICE_PER_TOKEN, FEE_BPS);
+    let buyer = env.funded_keypair();
+    let target = env.purchase(&buyer, &project, 2);
+    let source = env.purchase(&buyer, &project, 3);
+    let target_record: Purchase = env.account(&target.address);
+    let source_record: Purchase = env.account(&source.address);
+
+    let replacement = Keypair::new();
+    env.send(
+        &ix::merge_purchases(&buyer.pubkey(), &target.address, &target_record, &replacement.pubkey(), &[(source.address, source_record)]),
+        &buyer,
+        &[&replacement],
+    )
+    .unwrap();
+
+    let record: Purchase = env.account(&target.address);
+    assert_eq!(record.remaining_amount, 5);
+    assert_eq!(record.nft_mint, target.nft_mint);
+    assert_eq!(record.certificate_mint, replacement.pubkey());
+    assert!(!env.exists(&source.address));
+    for (mint, balance) in [(target.nft_mint, 0), (source.nft_mint, 0), (replacement.pubkey(), 1)] {
+        assert_eq!(env.token_balance(&get_associated_token_address(&buyer.pubkey(), &mint)), balance);
+    }
+
+    // The merged position offsets against its re-minted certificate
+    let (result, _, _) = env.try_request_offset(&buyer, &target, 5, "AFTER_MERGE");
+    result.unwrap();
+}
+
+type Tamper = fn(&mut Vec<AccountMeta>);
+
+#[test]
+#[ignore = "needs target/deploy/carbonpay.so, run `anchor build` first"]
+fn merge_rejects_malformed_source_lists() {
+    let mut env = common::setup();
+    env.init_platform();
+    let project = env.create_project(PROJECT_AMOUNT, PRICE_PER_TOKEN, FEE_BPS);
+    let buyer = env.funded_keypair();
+    let target = env.purchase(&buyer, &project, 2);
+    let source = env.purchase(&buyer, &project, 3);
+    let target_record: Purchase = env.account(&target.address);
+    let source_record: Purchase = env.account(&source.address);
+
+    let target_source = [(target.address, target_record.clone())];
+    let other_source = [(source.address, source_record)];
+    let cases: [(&[_], Tamper); 4] = [
+        // no sources at all
+        (&other_source, |accounts| accounts.truncate(accounts.len() - 5)),
+        // a source group missing its offset delegate PDA
+        (&other_source, |accounts| accounts.truncate(accounts.len() - 1)),
+        // the target merged into itself
+        (&target_source, |_| {}),
+        // a listing account that is not the source's listing PDA
+        (&other_source, |accounts| {
+            let listing = accounts.len() - 2;
+            accounts[listing] = AccountMeta::new_readonly(Pubkey::new_unique(), false);
+        }),
+    ];
+    for (source, tamper) in cases {
+        let replacement = Keypair::new();
+        let mut ixs = ix::merge_purchases(&buyer.pubkey(), &target.address, &target_record, &replacement.pubkey(), source);
+        tamper(&mut ixs.last_mut().unwrap().accounts);
+        let result = env.send(&ixs, &buyer, &[&replacement]);
+        common::assert_contract_error(result, ContractError::InvalidMergePurchases);
+    }
+}
+
+#[test]
+#[ignore = "needs target/deploy/carbonpay.so, run `anchor build` first"]
+fn holder_claims_a_certificate_sent_outside_transfer() {
+    let mut env = common::setup();
+    env.init_platform();
+    let project = env.create_project(PROJECT_AMOUNT, PRICE_PER_TOKEN, FEE_BPS);
+    let buyer = env.funded_keypair();
+    let holder = env.funded_keypair();
+    let purchase = env.purchase(&buyer, &project, 3);
+
+    // The certificate and tokens leave with plain SPL transfers, so the record is stale
+    let mut ixs = Vec::new();
+    for (mint, amount) in [(purchase.nft_mint, 1), (project.token_mint, 3)] {
+        ixs.push(ix::create_ata(&buyer.pubkey(), &holder.pubkey(), &mint));
+        ixs.push(
+            spl_token::instruction::transfer(
+                &spl_token::ID,
+                &get_associated_token_address(&buyer.pubkey(), &mint),
+                &get_associated_token_address(&holder.pubkey(), &mint),
+                &buyer.pubkey(),
+                &[],
+                amount,
+            )
+            .unwrap(),
+        );
+    }
+    env.send(&ixs, &buyer, &[]).unwrap();
+
+    let record: Purchase = env.account(&purchase.address);
+    let project_account: Project = env.account(&project.address);
+    let result = env.send(&[ix::claim_purchase(&buyer.pubkey(), &purchase.address, &record, &project_account)], &buyer, &[]);
+    common::assert_contract_error(result, ContractError::InvalidNFTAccount);
+    env.send(&[ix::claim_purchase(&holder.pubkey(), &purchase.address, &record, &project_account)], &holder, &[])
+        .unwrap();
+
+    assert!(!env.exists(&purchase.address));
+    let claimed = PurchaseFixture {
+        address: pda::purchase(&holder.pubkey(), &project.address, &purchase.nft_mint).0,
+        nft_mint: purchase.nft_mint,
+    };
+    let record: Purchase = env.account(&claimed.address);
+    assert_eq!(record.buyer, holder.pubkey());
+    assert_eq!(record.remaining_amount, 3);
+    let (result, _, _) = env.try_request_offset(&holder, &claimed, 1, "CLAIMED");
+    result.unwrap();
+}
+
+#[test]
+#[ignore = "needs target/deploy/carbonpay.so, run `anchor build` first"]
+fn one_balance_does_not_claim_several_positions() {
+    let mut env = common::setup();
+    env.init_platform();
+    let project = env.create_project(PROJECT_AMOUNT, PRICE_PER_TOKEN, FEE_BPS);
+    let buyer = env.funded_keypair();
+    let holder = env.funded_keypair();
+    let first = env.purchase(&buyer, &project, 3);
+    let second = env.purchase(&buyer, &project, 3);
+
+    // Both certificates leave, but only one position's tokens do
+    let mut ixs = Vec::new();
+    for (mint, amount) in [(first.nft_mint, 1), (second.nft_mint, 1), (project.token_mint, 3)] {
+        ixs.push(ix::create_ata(&buyer.pubkey(), &holder.pubkey(), &mint));
+        ixs.push(
+            spl_token::instruction::transfer(
+                &spl_token::ID,
+                &get_associated_token_address(&buyer.pubkey(), &mint),
+                &get_associated_token_address(&holder.pubkey(), &mint),
+                &buyer.pubkey(),
+                &[],
+                amount,
+            )
+            .unwrap(),
+        );
+    }
+    env.send(&ixs, &buyer, &[]).unwrap();
+
+    let project_account: Project = env.account(&project.address);
+    let record: Purchase = env.account(&first.address);
+    env.send(&[ix::claim_purchase(&holder.pubkey(), &first.address, &record, &project_account)], &holder, &[])
+        .unwrap();
+    // the 3 tokens now back the claimed position
+    let record: Purchase = env.account(&second.address);
+    let claim_second = ix::claim_purchase(&holder.pubkey(), &second.address, &record, &project_account);
+    let result = env.send(std::slice::from_ref(&claim_second), &holder, &[]);
+    common::assert_contract_error(result, ContractError::InsufficientFungibleTokens);
+
+    // once the rest of the tokens arrive the second claim goes through
+    env.send(
+        &[spl_token::instruction::transfer(
+            &spl_token::ID,
+            &get_associated_token_address(&buyer.pubkey(), &project.token_mint),
+            &get_associated_token_address(&holder.pubkey(), &project.token_mint),
+            &buyer.pubkey(),
+            &[],
+            3,
+        )
+        .unwrap()],
+        &buyer,
+        &[],
+    )
+    .unwrap();
+    env.send(&[claim_second], &holder, &[]).unwrap();
+    let holdings: Holdings = env.account(&pda::holdings(&holder.pubkey(), &project.address).0);
+    assert_eq!(holdings.backed_amount, 6);
+    let holdings: Holdings = env.account(&pda::holdings(&buyer.pubkey(), &project.address).0);
+    assert_eq!(holdings.backed_amount, 0);
+}
+
+#[test]
+#[ignore = "needs target/deploy/carbonpay.so, run `anchor build` first"]
+fn transfer_refuses_a_delegated_purchase() {
+    let mut env = common::setup();
+    env.init_platform();
+    let project = env.create_project(PROJECT_AMOUNT, PRICE_PER_TOKEN, FEE_BPS);
+    let owner = env.funded_keypair();
+    let purchase = env.purchase(&owner, &project, 3);
+    env.approve_offset_delegate(&owner, &purchase, &Keypair::new().pubkey(), 1);
+
+    let record: Purchase = env.account(&purchase.address);
+    let project_account: Project = env.account(&project.address);
+    let recipient = Keypair::new();
+    let result = env.send(
+        &[ix::transfer_purchase(&owner.pubkey(), &recipient.pubkey(), &purchase.address, &record, &project_account)],
+        &owner,
+        &[],
+    );
+    common::assert_contract_error(result, ContractError::PurchaseDelegated);
+}
+
+#[test]
+#[ignore = "needs target/deploy/carbonpay.so, run `anchor build` first"]
+fn transfer_needs_another_wallet_and_the_purchase_project() {
+    let mut env = common::setup();
+    env.init_platform();
+    let project = env.create_project(PROJECT_AMOUNT, PRICE_PER_TOKEN, FEE_BPS);
+    let other_project = env.create_project(PROJECT_AMOUNT, PRICE_PER_TOKEN, FEE_BPS);
+    let owner = env.funded_keypair();
+    let purchase = env.purchase(&owner, &project, 3);
+    let record: Purchase = env.account(&purchase.address);
+    let project_account: Project = env.account(&project.address);
+
+    let result = env.send(
+        &[ix::transfer_purchase(&owner.pubkey(), &owner.pubkey(), &purchase.address, &record, &project_account)],
+        &owner,
+        &[],
+    );
+    common::assert_contract_error(result, ContractError::InvalidRecipient);
+
+    let recipient = Keypair::new();
+    let mut transfer =
+        ix::transfer_purchase(&owner.pubkey(), &recipient.pubkey(), &purchase.address, &record, &project_account);
+    // project is the third account of transfer_purchase
+    transfer.accounts[2] = AccountMeta::new_readonly(other_project.address, false);
+    let result = env.send(&[transfer], &owner, &[]);
+    common::assert_contract_error(result, ContractError::InvalidProject);
+}
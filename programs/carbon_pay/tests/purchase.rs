@@ -0,0 +1,431 @@
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::AccountMeta;
+use anchor_spl::associated_token::get_associated_token_address;
+use carbonpay::errors::ContractError;
+use carbonpay::state::{CarbonCredits, FeeRounding, PriceTier, Project, Purchase};
+use carbonpay_client::instructions::{self as ix, ProjectArgs};
+use carbonpay_client::pda;
+use common::{FEE_BPS, PRICE_PER_TOKEN, PROJECT_AMOUNT};
+use solana_keypair::Keypair;
+use solana_signer::Signer;
+
+/// Payment split of a purchase: (to project owner, to platform)
+fn payment_split(env: &mut common::TestEnv, price_per_token: u64, fee_bps: u64, amount: u64) -> (u64, u64) {
+    let project = env.create_project(PROJECT_AMOUNT, price_per_token, fee_bps);
+    let buyer = env.funded_keypair();
+    let carbon_credits = pda::carbon_credits().0;
+    let owner_before = env.balance(&project.owner.pubkey());
+    let platform_before = env.balance(&carbon_credits);
+    env.purchase(&buyer, &project, amount);
+    (
+        env.balance(&project.owner.pubkey()) - owner_before,
+        env.balance(&carbon_credits) - platform_before,
+    )
+}
+
+#[test]
+#[ignore = "needs target/deploy/carbonpay.so, run `anchor build` first"]
+fn purchase_transfers_tokens_and_splits_payment() {
+    let mut env = common::setup();
+    env.init_platform();
+    let project = env.create_project(PROJECT_AMOUNT, PRICE_PER_TOKEN, FEE_BPS);
+    let buyer = env.funded_keypair();
+
+    let owner_before = env.balance(&project.owner.pubkey());
+    let purchase = env.purchase(&buyer, &project, 10);
+
+    let total = 10 * PRICE_PER_TOKEN;
+    let fee = total * FEE_BPS / 10_000;
+    assert_eq!(env.balance(&project.owner.pubkey()) - owner_before, total - fee);
+    let carbon_credits: CarbonCredits = env.account(&pda::carbon_credits().0);
+    assert_eq!(carbon_credits.total_fees_earned, fee);
+
+    let record: Purchase = env.account(&purchase.address);
+    assert_eq!(record.buyer, buyer.pubkey());
+    assert_eq!(record.amount, 10);
+    assert_eq!(record.remaining_amount, 10);
+
+    let project_account: Project = env.account(&project.address);
+    assert_eq!(project_account.remaining_amount, PROJECT_AMOUNT - 10);
+    assert_eq!(env.token_balance(&project.vault()), PROJECT_AMOUNT - 10);
+    assert_eq!(
+        env.token_balance(&get_associated_token_address(&buyer.pubkey(), &project.token_mint)),
+        10
+    );
+    assert_eq!(
+        env.token_balance(&get_associated_token_address(&buyer.pubkey(), &purchase.nft_mint)),
+        1
+    );
+}
+
+#[test]
+#[ignore = "needs target/deploy/carbonpay.so, run `anchor build` first"]
+fn fee_math_edge_cases() {
+    let mut env = common::setup();
+    env.init_platform();
+
+    // fees round down: 19 lamports at 5% is 0.95
+    assert_eq!(payment_split(&mut env, 1, 500, 19), (19, 0));
+    assert_eq!(payment_split(&mut env, 1, 500, 20), (19, 1));
+    // no fee and a 100% fee
+    assert_eq!(payment_split(&mut env, 7, 0, 3), (21, 0));
+    assert_eq!(payment_split(&mut env, 7, 10_000, 3), (0, 21));
+}
+
+#[test]
+#[ignore = "needs target/deploy/carbonpay.so, run `anchor build` first"]
+fn rejects_purchase_beyond_supply() {
+    let mut env = common::setup();
+    env.init_platform();
+    let project = env.create_project(5, PRICE_PER_TOKEN, FEE_BPS);
+    let buyer = env.funded_keypair();
+
+    let (result, _) = env.try_purchase(&buyer, &project, 6);
+    common::assert_contract_error(result, ContractError::InsufficientTokens);
+}
+
+#[test]
+#[ignore = "needs target/deploy/carbonpay.so, run `anchor build` first"]
+fn rejects_zero_amount_purchase() {
+    let mut env = common::setup();
+    env.init_platform();
+    let project = env.create_project(PROJECT_AMOUNT, PRICE_PER_TOKEN, FEE_BPS);
+    let buyer = env.funded_keypair();
+
+    let (result, _) = env.try_purchase(&buyer, &project, 0);
+    common::assert_contract_error(result, ContractError::InvalidAmount);
+}
+
+#[test]
+#[ignore = "needs target/deploy/carbonpay.so, run `anchor build` first"]
+fn rejects_wrong_project_owner() {
+    let mut env = common::setup();
+    env.init_platform();
+    let project = env.create_project(PROJECT_AMOUNT, PRICE_PER_TOKEN, FEE_BPS);
+    let buyer = env.funded_keypair();
+    let nft_mint = Keypair::new();
+    let project_account: Project = env.account(&project.address);
+
+    let mut ixs = ix::purchase_carbon_credits(
+        &buyer.pubkey(),
+        &project.address,
+        &project_account,
+        &nft_mint.pubkey(),
+        1,
+        None,
+    );
+    // project_owner is the second account of purchase_carbon_credits
+    ixs.last_mut().unwrap().accounts[1] = AccountMeta::new(buyer.pubkey(), false);
+    let result = env.send(&ixs, &buyer, &[&nft_mint]);
+    common::assert_contract_error(result, ContractError::InvalidProjectOwner);
+}
+
+#[test]
+#[ignore = "needs target/deploy/carbonpay.so, run `anchor build` first"]
+fn enforces_purchase_limits() {
+    let mut env = common::setup();
+    env.init_platform();
+    let project = env.create_project(PROJECT_AMOUNT, PRICE_PER_TOKEN, FEE_BPS);
+    let owner = project.owner.insecure_clone();
+    env.send(
+        &[ix::program_instruction(
+            carbonpay::accounts::SetPurchaseLimits {
+                project_owner: owner.pubkey(),
+                project: project.address,
+            },
+            carbonpay::instruction::SetPurchaseLimits {
+                min_purchase_amount: 2,
+                max_purchase_amount: 5,
+                max_per_buyer: 6,
+            },
+        )],
+        &owner,
+        &[],
+    )
+    .unwrap();
+    let buyer = env.funded_keypair();
+
+    let (result, _) = env.try_purchase(&buyer, &project, 1);
+    common::assert_contract_error(result, ContractError::PurchaseBelowMinimum);
+    let (result, _) = env.try_purchase(&buyer, &project, 6);
+    common::assert_contract_error(result, ContractError::PurchaseAboveMaximum);
+
+    env.purchase(&buyer, &project, 5);
+    let (result, _) = env.try_purchase(&buyer, &project, 2);
+    common::assert_contract_error(result, ContractError::BuyerCapExceeded);
+}
+
+#[test]
+#[ignore = "needs target/deploy/carbonpay.so, run `anchor build` first"]
+fn enforces_sale_window() {
+    let mut env = common::setup();
+    env.init_platform();
+    let project = env.create_project(PROJECT_AMOUNT, PRICE_PER_TOKEN, FEE_BPS);
+    let owner = project.owner.insecure_clone();
+    env.set_time(1_000);
+    env.send(
+        &[ix::program_instruction(
+            carbonpay::accounts::SetSaleWindow {
+                project_owner: owner.pubkey(),
+                project: project.address,
+            },
+            carbonpay::instruction::SetSaleWindow {
+                sale_start: 2_000,
+                sale_end: 3_000,
+                early_access_start: 0,
+            },
+        )],
+        &owner,
+        &[],
+    )
+    .unwrap();
+    let buyer = env.funded_keypair();
+
+    let (result, _) = env.try_purchase(&buyer, &project, 1);
+    common::assert_contract_error(result, ContractError::SaleNotStarted);
+
+    env.set_time(2_500);
+    env.purchase(&buyer, &project, 1);
+
+    env.set_time(3_000);
+    let (result, _) = env.try_purchase(&buyer, &project, 1);
+    common::assert_contract_error(result, ContractError::SaleEnded);
+}
+
+#[test]
+#[ignore = "needs target/deploy/carbonpay.so, run `anchor build` first"]
+fn requires_allowlist_when_enabled() {
+    let mut env = common::setup();
+    env.init_platform();
+    let project = env.create_project(PROJECT_AMOUNT, PRICE_PER_TOKEN, FEE_BPS);
+    let admin = env.admin.insecure_clone();
+    env.send(
+        &[ix::program_instruction(
+            carbonpay::accounts::SetAllowlistRequired {
+                authority: admin.pubkey(),
+                platform_config: pda::platform_config().0,
+                project: None,
+            },
+            carbonpay::instruction::SetAllowlistRequired { required: true },
+        )],
+        &admin,
+        &[],
+    )
+    .unwrap();
+    let buyer = env.funded_keypair();
+
+    let (result, _) = env.try_purchase(&buyer, &project, 1);
+    common::assert_contract_error(result, ContractError::BuyerNotAllowlisted);
+}
+
+#[test]
+#[ignore = "needs target/deploy/carbonpay.so, run `anchor build` first"]
+fn kilogram_tokens_are_priced_per_tonne() {
+    let mut env = common::setup();
+    env.init_platform();
+    // 2 tonnes in kilograms at 1_000_003 lamports per tonne
+    let project = env.create_project_with_decimals(2_000, 1_000_003, FEE_BPS, 3);
+    let project_account: Project = env.account(&project.address);
+    assert_eq!(project_account.decimals, 3);
+
+    let buyer = env.funded_keypair();
+    let owner_before = env.balance(&project.owner.pubkey());
+    let platform_before = env.balance(&pda::carbon_credits().0);
+    env.purchase(&buyer, &project, 1_500);
+
+    // 1.5 tonnes cost 1_500_004.5 lamports, rounded up in the project's favour
+    let total = 1_500_005;
+    let fee = total * FEE_BPS / 10_000;
+    assert_eq!(env.balance(&project.owner.pubkey()) - owner_before, total - fee);
+    assert_eq!(env.balance(&pda::carbon_credits().0) - platform_before, fee);
+    assert_eq!(
+        env.token_balance(&get_associated_token_address(&buyer.pubkey(), &project.token_mint)),
+        1_500
+    );
+
+    // Platform counters are in grams regardless of the project's decimals
+    let carbon_credits: CarbonCredits = env.account(&pda::carbon_credits().0);
+    assert_eq!(carbon_credits.total_credits, 2_000_000);
+}
+
+#[test]
+#[ignore = "needs target/deploy/carbonpay.so, run `anchor build` first"]
+fn fee_rounding_policy_and_minimum_fee() {
+    let mut env = common::setup();
+    env.init_platform();
+
+    // ceil: 0.95 lamports of fee becomes 1
+    env.set_fee_policy(FeeRounding::Ceil, 0);
+    assert_eq!(payment_split(&mut env, 1, 500, 19), (18, 1));
+    assert_eq!(payment_split(&mut env, 1, 500, 20), (19, 1));
+
+    // banker's rounding: halves go to the even lamport
+    env.set_fee_policy(FeeRounding::HalfEven, 0);
+    assert_eq!(payment_split(&mut env, 1, 500, 10), (10, 0));
+    assert_eq!(payment_split(&mut env, 1, 500, 30), (28, 2));
+    assert_eq!(payment_split(&mut env, 1, 500, 50), (48, 2));
+    assert_eq!(payment_split(&mut env, 1, 500, 31), (29, 2));
+
+    // the minimum fee lifts small fees, never exceeds the payment and spares fee-free projects
+    env.set_fee_policy(FeeRounding::Floor, 3);
+    assert_eq!(payment_split(&mut env, 1, 500, 20), (17, 3));
+    assert_eq!(payment_split(&mut env, 1, 500, 100), (95, 5));
+    assert_eq!(payment_split(&mut env, 1, 500, 2), (0, 2));
+    assert_eq!(payment_split(&mut env, 7, 0, 3), (21, 0));
+}
+
+#[test]
+#[ignore = "needs target/deploy/carbonpay.so, run `anchor build` first"]
+fn purchase_event_reports_fee_remainder() {
+    let mut env = common::setup();
+    env.init_platform();
+    env.set_fee_policy(FeeRounding::Ceil, 0);
+    let project = env.create_project(PROJECT_AMOUNT, 1, FEE_BPS);
+    let buyer = env.funded_keypair();
+
+    let (result, purchase) = env.try_purchase(&buyer, &project, 19);
+    let event = common::purchase_event(result);
+    assert_eq!(event.purchase, purchase.address);
+    assert_eq!(event.total_price, 19);
+    // 19 * 500 = 9_500: 0.95 lamports rounded up to 1
+    assert_eq!(event.fee, 1);
+    assert_eq!(event.fee_remainder, 9_500);
+}
+
+#[test]
+#[ignore = "needs target/deploy/carbonpay.so, run `anchor build` first"]
+fn rejects_invalid_sale_settings() {
+    let mut env = common::setup();
+    env.init_platform();
+    let project = env.create_project(PROJECT_AMOUNT, PRICE_PER_TOKEN, FEE_BPS);
+    let owner = project.owner.insecure_clone();
+
+    for (min, max, per_buyer) in [(5, 2, 0), (5, 0, 3)] {
+        let result = env.send(
+            &[ix::set_purchase_limits(&owner.pubkey(), &project.address, min, max, per_buyer)],
+            &owner,
+            &[],
+        );
+        common::assert_contract_error(result, ContractError::InvalidPurchaseLimits);
+    }
+
+    for (start, end, early_access) in [(-1, 0, 0), (2_000, 1_000, 0), (2_000, 3_000, 2_500)] {
+        let result = env.send(
+            &[ix::set_sale_window(&owner.pubkey(), &project.address, start, end, early_access)],
+            &owner,
+            &[],
+        );
+        common::assert_contract_error(result, ContractError::InvalidSaleWindow);
+    }
+
+    let tier = |min_amount| PriceTier { min_amount, price_per_token: PRICE_PER_TOKEN };
+    let too_many = (1..=Project::MAX_PRICE_TIERS as u64 + 1).map(|n| tier(n * 10)).collect();
+    for tiers in [vec![tier(0)], vec![tier(10), tier(10)], vec![tier(20), tier(10)], too_many] {
+        let result = env.send(&[ix::set_price_tiers(&owner.pubkey(), &project.address, tiers)], &owner, &[]);
+        common::assert_contract_error(result, ContractError::InvalidPriceTiers);
+    }
+}
+
+#[test]
+#[ignore = "needs target/deploy/carbonpay.so, run `anchor build` first"]
+fn allowlist_entries_are_scoped_and_expire() {
+    let mut env = common::setup();
+    env.init_platform();
+    env.set_time(1_000);
+    let project = env.create_project(PROJECT_AMOUNT, PRICE_PER_TOKEN, FEE_BPS);
+    let other = env.create_project(PROJECT_AMOUNT, PRICE_PER_TOKEN, FEE_BPS);
+    let admin = env.admin.insecure_clone();
+    let buyer = env.funded_keypair();
+    env.send(&[ix::set_allowlist_required(&admin.pubkey(), Some(project.address), true)], &admin, &[])
+        .unwrap();
+
+    // a platform-wide scope can't be written under a project
+    let mut add = ix::add_allowlist_entry(&admin.pubkey(), None, &buyer.pubkey(), 0);
+    add.accounts[2] = AccountMeta::new_readonly(project.address, false);
+    let result = env.send(&[add], &admin, &[]);
+    common::assert_contract_error(result, ContractError::InvalidAllowlistScope);
+
+    // an entry for another project does not admit the buyer
+    env.send(&[ix::add_allowlist_entry(&admin.pubkey(), Some(other.address), &buyer.pubkey(), 0)], &admin, &[])
+        .unwrap();
+    let other_entry = pda::allowlist_entry(&other.address, &buyer.pubkey()).0;
+    let (result, _) = env.try_purchase_with_entry(&buyer, &project, 1, Some(other_entry));
+    common::assert_contract_error(result, ContractError::InvalidAllowlistScope);
+
+    env.send(&[ix::add_allowlist_entry(&admin.pubkey(), Some(project.address), &buyer.pubkey(), 2_000)], &admin, &[])
+        .unwrap();
+    let entry = pda::allowlist_entry(&project.address, &buyer.pubkey()).0;
+    let (result, _) = env.try_purchase_with_entry(&buyer, &project, 1, Some(entry));
+    result.unwrap();
+
+    env.set_time(2_000);
+    let (result, _) = env.try_purchase_with_entry(&buyer, &project, 1, Some(entry));
+    common::assert_contract_error(result, ContractError::AllowlistEntryExpired);
+}
+
+#[test]
+#[ignore = "needs target/deploy/carbonpay.so, run `anchor build` first"]
+fn rejects_purchases_from_inactive_or_tampered_projects() {
+    let mut env = common::setup();
+    env.init_platform();
+    let project = env.create_project(PROJECT_AMOUNT, PRICE_PER_TOKEN, FEE_BPS);
+    let other = env.create_project(PROJECT_AMOUNT, PRICE_PER_TOKEN, FEE_BPS);
+    let buyer = env.funded_keypair();
+
+    // project_mint is the third account of purchase_carbon_credits
+    let nft_mint = Keypair::new();
+    let project_account: Project = env.account(&project.address);
+    let mut ixs =
+        ix::purchase_carbon_credits(&buyer.pubkey(), &project.address, &project_account, &nft_mint.pubkey(), 1, None);
+    ixs.last_mut().unwrap().accounts[2] = AccountMeta::new(other.token_mint, false);
+    let result = env.send(&ixs, &buyer, &[&nft_mint]);
+    common::assert_contract_error(result, ContractError::InvalidProjectMint);
+
+    // no instruction deactivates a project or repoints its authority, so write the states
+    let mut tampered = project_account.clone();
+    tampered.carbon_pay_authority = Pubkey::new_unique();
+    env.write_account(&project.address, &tampered);
+    let (result, _) = env.try_purchase(&buyer, &project, 1);
+    common::assert_contract_error(result, ContractError::InvalidCarbonPayAuthority);
+
+    let mut inactive = project_account;
+    inactive.is_active = false;
+    env.write_account(&project.address, &inactive);
+    let (result, _) = env.try_purchase(&buyer, &project, 1);
+    common::assert_contract_error(result, ContractError::ProjectInactive);
+}
+
+#[test]
+#[ignore = "needs target/deploy/carbonpay.so, run `anchor build` first"]
+fn rejects_overflowing_prices_and_too_fine_mints() {
+    let mut env = common::setup();
+    env.init_platform();
+    let project = env.create_project(PROJECT_AMOUNT, u64::MAX, FEE_BPS);
+    let buyer = env.funded_keypair();
+    let (result, _) = env.try_purchase(&buyer, &project, 2);
+    common::assert_contract_error(result, ContractError::ArithmeticOverflow);
+
+    let owner = env.funded_keypair();
+    let (nft_mint, token_mint) = (Keypair::new(), Keypair::new());
+    let result = env.send(
+        &ix::initialize_project(
+            &owner.pubkey(),
+            &nft_mint.pubkey(),
+            &token_mint.pubkey(),
+            ProjectArgs {
+                amount: PROJECT_AMOUNT,
+                price_per_token: PRICE_PER_TOKEN,
+                carbon_pay_fee: FEE_BPS,
+                decimals: Project::MAX_TOKEN_DECIMALS + 1,
+                uri: "https://carbonpay.com/projects/test".to_string(),
+                name: "Test Project".to_string(),
+                symbol: "TEST".to_string(),
+            },
+        ),
+        &owner,
+        &[&nft_mint, &token_mint],
+    );
+    common::assert_contract_error(result, ContractError::InvalidTokenDecimals);
+}
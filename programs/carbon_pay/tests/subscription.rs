@@ -0,0 +1,183 @@
+mod common;
+
+use carbonpay::errors::ContractError;
+use carbonpay::state::{BuyerStats, Project, Purchase, Subscription};
+use carbonpay_client::{instructions as ix, pda};
+use common::{FEE_BPS, PRICE_PER_TOKEN, PROJECT_AMOUNT, SOL};
+use solana_keypair::Keypair;
+use solana_signer::Signer;
+
+const START: i64 = 1_000;
+
+#[test]
+#[ignore = "needs target/deploy/carbonpay.so, run `anchor build` first"]
+fn executions_pass_the_purchase_gates() {
+    let mut env = common::setup();
+    env.init_platform();
+    env.set_time(START);
+    let project = env.create_project(PROJECT_AMOUNT, PRICE_PER_TOKEN, FEE_BPS);
+    let owner = project.owner.insecure_clone();
+    let admin = env.admin.insecure_clone();
+    let buyer = env.funded_keypair();
+    env.create_subscription(&buyer, &project, 2, 10 * SOL);
+
+    env.send(&[ix::set_allowlist_required(&admin.pubkey(), Some(project.address), true)], &admin, &[])
+        .unwrap();
+    let result = env.try_execute_subscription(&buyer.pubkey(), &project, None);
+    common::assert_contract_error(result, ContractError::BuyerNotAllowlisted);
+
+    env.send(
+        &[ix::add_allowlist_entry(&admin.pubkey(), Some(project.address), &buyer.pubkey(), 0)],
+        &admin,
+        &[],
+    )
+    .unwrap();
+    let entry = pda::allowlist_entry(&project.address, &buyer.pubkey()).0;
+    env.send(&[ix::set_purchase_limits(&owner.pubkey(), &project.address, 0, 0, 3)], &owner, &[])
+        .unwrap();
+    env.try_execute_subscription(&buyer.pubkey(), &project, Some(entry))
+        .unwrap();
+    let stats: BuyerStats = env.account(&pda::buyer_stats(&buyer.pubkey(), &project.address).0);
+    assert_eq!(stats.total_purchased, 2);
+
+    // The second period would take the buyer past the per-buyer cap
+    env.set_time(START + Subscription::PERIOD);
+    let result = env.try_execute_subscription(&buyer.pubkey(), &project, Some(entry));
+    common::assert_contract_error(result, ContractError::BuyerCapExceeded);
+
+    env.send(&[ix::set_sale_window(&owner.pubkey(), &project.address, 0, START, 0)], &owner, &[])
+        .unwrap();
+    env.send(&[ix::set_purchase_limits(&owner.pubkey(), &project.address, 0, 0, 0)], &owner, &[])
+        .unwrap();
+    let result = env.try_execute_subscription(&buyer.pubkey(), &project, Some(entry));
+    common::assert_contract_error(result, ContractError::SaleEnded);
+}
+
+#[test]
+#[ignore = "needs target/deploy/carbonpay.so, run `anchor build` first"]
+fn late_executions_do_not_catch_up() {
+    let mut env = common::setup();
+    env.init_platform();
+    env.set_time(START);
+    let project = env.create_project(PROJECT_AMOUNT, PRICE_PER_TOKEN, FEE_BPS);
+    let buyer = env.funded_keypair();
+    let position = env.create_subscription(&buyer, &project, 2, 10 * SOL);
+    let subscription = pda::subscription(&buyer.pubkey(), &project.address).0;
+
+    env.try_execute_subscription(&buyer.pubkey(), &project, None).unwrap();
+    // Cranked several periods late: one execution, the next due a full period later
+    let late = START + 3 * Subscription::PERIOD + 10 * 24 * 60 * 60;
+    env.set_time(late);
+    env.try_execute_subscription(&buyer.pubkey(), &project, None).unwrap();
+    let record: Subscription = env.account(&subscription);
+    assert_eq!(record.next_execution, late + Subscription::PERIOD);
+    let result = env.try_execute_subscription(&buyer.pubkey(), &project, None);
+    common::assert_contract_error(result, ContractError::SubscriptionNotDue);
+
+    env.set_time(late + Subscription::PERIOD);
+    env.try_execute_subscription(&buyer.pubkey(), &project, None).unwrap();
+    let purchase: Purchase = env.account(&position.address);
+    assert_eq!(purchase.remaining_amount, 6);
+}
+
+#[test]
+#[ignore = "needs target/deploy/carbonpay.so, run `anchor build` first"]
+fn subscribed_position_stays_with_the_subscriber() {
+    let mut env = common::setup();
+    env.init_platform();
+    env.set_time(START);
+    let project = env.create_project(PROJECT_AMOUNT, PRICE_PER_TOKEN, FEE_BPS);
+    let buyer = env.funded_keypair();
+    let position = env.create_subscription(&buyer, &project, 2, 10 * SOL);
+    env.try_execute_subscription(&buyer.pubkey(), &project, None).unwrap();
+    let record: Purchase = env.account(&position.address);
+    let project_account: Project = env.account(&project.address);
+
+    let recipient = Keypair::new();
+    let result = env.send(
+        &[ix::transfer_purchase(&buyer.pubkey(), &recipient.pubkey(), &position.address, &record, &project_account)],
+        &buyer,
+        &[],
+    );
+    common::assert_contract_error(result, ContractError::PurchaseSubscribed);
+    let result = env.send(
+        &[ix::list_credits(&buyer.pubkey(), &position.address, &record, &project_account, 1, PRICE_PER_TOKEN)],
+        &buyer,
+        &[],
+    );
+    common::assert_contract_error(result, ContractError::PurchaseSubscribed);
+    let (new_nft_mint, replacement) = (Keypair::new(), Keypair::new());
+    let result = env.send(
+        &ix::split_purchase(&buyer.pubkey(), &position.address, &record, &new_nft_mint.pubkey(), &replacement.pubkey(), 1),
+        &buyer,
+        &[&new_nft_mint, &replacement],
+    );
+    common::assert_contract_error(result, ContractError::PurchaseSubscribed);
+
+    // It can absorb other positions but not be merged into one
+    let other = env.purchase(&buyer, &project, 3);
+    let other_record: Purchase = env.account(&other.address);
+    let replacement = Keypair::new();
+    let result = env.send(
+        &ix::merge_purchases(
+            &buyer.pubkey(),
+            &other.address,
+            &other_record,
+            &replacement.pubkey(),
+            &[(position.address, record.clone())],
+        ),
+        &buyer,
+        &[&replacement],
+    );
+    common::assert_contract_error(result, ContractError::PurchaseSubscribed);
+    let replacement = Keypair::new();
+    env.send(
+        &ix::merge_purchases(
+            &buyer.pubkey(),
+            &position.address,
+            &record,
+            &replacement.pubkey(),
+            &[(other.address, other_record)],
+        ),
+        &buyer,
+        &[&replacement],
+    )
+    .unwrap();
+    let record: Purchase = env.account(&position.address);
+    assert_eq!(record.remaining_amount, 5);
+
+    // The crank cannot fill any other purchase of the subscriber
+    let mut subscription: Subscription = env.account(&pda::subscription(&buyer.pubkey(), &project.address).0);
+    subscription.purchase = env.purchase(&buyer, &project, 1).address;
+    let cranker = env.admin.insecure_clone();
+    env.set_time(START + Subscription::PERIOD);
+    let result = env.send(&[ix::execute_subscription(&subscription, &project_account, None)], &cranker, &[]);
+    common::assert_contract_error(result, ContractError::InvalidSubscriptionPurchase);
+}
+
+#[test]
+#[ignore = "needs target/deploy/carbonpay.so, run `anchor build` first"]
+fn executions_stay_within_the_escrow_and_price_limit() {
+    let mut env = common::setup();
+    env.init_platform();
+    env.set_time(START);
+    let project = env.create_project(PROJECT_AMOUNT, PRICE_PER_TOKEN, FEE_BPS);
+    let buyer = env.funded_keypair();
+    // One token's worth escrowed for a two-token period
+    env.create_subscription(&buyer, &project, 2, PRICE_PER_TOKEN);
+    let result = env.try_execute_subscription(&buyer.pubkey(), &project, None);
+    common::assert_contract_error(result, ContractError::InsufficientSubscriptionFunds);
+
+    env.send(&[ix::fund_subscription(&buyer.pubkey(), &project.address, 10 * SOL)], &buyer, &[])
+        .unwrap();
+    // The price rose above the subscriber's `max_price_per_token`
+    let mut project_account: Project = env.account(&project.address);
+    project_account.price_per_token = 2 * PRICE_PER_TOKEN;
+    env.write_account(&project.address, &project_account);
+    let result = env.try_execute_subscription(&buyer.pubkey(), &project, None);
+    common::assert_contract_error(result, ContractError::SubscriptionPriceTooHigh);
+
+    project_account.price_per_token = PRICE_PER_TOKEN;
+    env.write_account(&project.address, &project_account);
+    env.try_execute_subscription(&buyer.pubkey(), &project, None).unwrap();
+}
@@ -0,0 +1,86 @@
+//! Projects sell from the canonical `carbon_credits` vault only, and that vault holds the
+//! token mint's entire supply.
+
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_spl::associated_token::get_associated_token_address;
+use anchor_spl::token::spl_token;
+use carbonpay::errors::ContractError;
+use carbonpay::state::Project;
+use carbonpay_client::instructions::{self as ix, ProjectArgs};
+use common::{FEE_BPS, PRICE_PER_TOKEN, PROJECT_AMOUNT};
+use solana_keypair::Keypair;
+use solana_signer::Signer;
+
+#[test]
+#[ignore = "needs target/deploy/carbonpay.so, run `anchor build` first"]
+fn initialize_project_rejects_premined_token_mint() {
+    let mut env = common::setup();
+    env.init_platform();
+    let owner = env.funded_keypair();
+    let (nft_mint, token_mint) = (Keypair::new(), Keypair::new());
+
+    let mut ixs = ix::initialize_project(
+        &owner.pubkey(),
+        &nft_mint.pubkey(),
+        &token_mint.pubkey(),
+        ProjectArgs {
+            amount: PROJECT_AMOUNT,
+            price_per_token: PRICE_PER_TOKEN,
+            carbon_pay_fee: FEE_BPS,
+            decimals: 0,
+            uri: "https://carbonpay.com/projects/test".to_string(),
+            name: "Test Project".to_string(),
+            symbol: "TEST".to_string(),
+        },
+    );
+    // The owner keeps a token minted before the project exists
+    let owner_tokens = get_associated_token_address(&owner.pubkey(), &token_mint.pubkey());
+    let program_ix = ixs.pop().unwrap();
+    ixs.push(ix::create_ata(&owner.pubkey(), &owner.pubkey(), &token_mint.pubkey()));
+    ixs.push(
+        spl_token::instruction::mint_to(&spl_token::ID, &token_mint.pubkey(), &owner_tokens, &owner.pubkey(), &[], 1)
+            .unwrap(),
+    );
+    ixs.push(program_ix);
+
+    let result = env.send(&ixs, &owner, &[&nft_mint, &token_mint]);
+    common::assert_contract_error(result, ContractError::TokenMintHasSupply);
+}
+
+#[test]
+#[ignore = "needs target/deploy/carbonpay.so, run `anchor build` first"]
+fn purchase_rejects_token_account_other_than_the_vault() {
+    let mut env = common::setup();
+    env.init_platform();
+    let project = env.create_project(PROJECT_AMOUNT, PRICE_PER_TOKEN, FEE_BPS);
+
+    // Same mint and authority as the vault, at another address
+    let decoy = Pubkey::new_unique();
+    let vault_account = env.svm.get_account(&project.vault()).unwrap();
+    env.svm.set_account(decoy, vault_account).unwrap();
+
+    let buyer = env.funded_keypair();
+    let nft_mint = Keypair::new();
+    let project_account: Project = env.account(&project.address);
+    let mut ixs = ix::purchase_carbon_credits(
+        &buyer.pubkey(),
+        &project.address,
+        &project_account,
+        &nft_mint.pubkey(),
+        1,
+        None,
+    );
+    let meta = ixs
+        .last_mut()
+        .unwrap()
+        .accounts
+        .iter_mut()
+        .find(|meta| meta.pubkey == project.vault())
+        .unwrap();
+    meta.pubkey = decoy;
+
+    let result = env.send(&ixs, &buyer, &[&nft_mint]);
+    common::assert_contract_error(result, ContractError::InvalidProjectVault);
+}
@@ -31,6 +31,9 @@ describe("🌳 CarbonPay Program Test Suite", () => {
   // CarbonCredits PDA and bump
   let carbonCreditsPda: PublicKey;
   let carbonCreditsBump: number;
+
+  // PlatformConfig PDA
+  let platformConfigPda: PublicKey;
   
   // Metadata program constant
   const METADATA_PROGRAM_ID = new PublicKey("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s");
@@ -53,12 +56,201 @@ describe("🌳 CarbonPay Program Test Suite", () => {
   const PROJECT_NAME = "MyProject";
   const PROJECT_SYMBOL = "MPRJ";
 
+  type ProjectFixture = {
+    project: PublicKey;
+    owner: Keypair;
+    tokenMint: PublicKey;
+    vault: PublicKey;
+  };
+
+  // The project created in test 2
+  const mainProject = (): ProjectFixture => ({
+    project: projectPda,
+    owner: projectOwner,
+    tokenMint,
+    vault: vaultAta,
+  });
+
+  const metadataPda = (mint: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("metadata"), METADATA_PROGRAM_ID.toBuffer(), mint.toBuffer()],
+      METADATA_PROGRAM_ID
+    )[0];
+
+  const masterEditionPda = (mint: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("metadata"),
+        METADATA_PROGRAM_ID.toBuffer(),
+        mint.toBuffer(),
+        Buffer.from("edition"),
+      ],
+      METADATA_PROGRAM_ID
+    )[0];
+
+  // Creates a fresh project owned by `owner` with the default parameters.
+  // The vault is created by the program unless `vaultOwner` asks for a different token account.
+  // `amount` is in base units of a token mint with `decimals`.
+  const createProject = async (
+    owner: Keypair,
+    amount = PROJECT_AMOUNT,
+    vaultOwner = carbonCreditsPda,
+    decimals = 0,
+    pricePerToken = PRICE_PER_TOKEN
+  ): Promise<ProjectFixture> => {
+    const ownerNftMint = await createMint(connection, owner, owner.publicKey, owner.publicKey, 0);
+    const ownerTokenMint = await createMint(connection, owner, owner.publicKey, owner.publicKey, decimals);
+    const ownerNftAta = await getAssociatedTokenAddress(ownerNftMint, owner.publicKey);
+    const vault = await getAssociatedTokenAddress(ownerTokenMint, vaultOwner, true);
+    const tx = new Transaction().add(
+      createAssociatedTokenAccountInstruction(owner.publicKey, ownerNftAta, owner.publicKey, ownerNftMint)
+    );
+    if (!vaultOwner.equals(carbonCreditsPda)) {
+      tx.add(createAssociatedTokenAccountInstruction(owner.publicKey, vault, vaultOwner, ownerTokenMint));
+    }
+    await provider.sendAndConfirm(tx, [owner]);
+    const [project] = PublicKey.findProgramAddressSync(
+      [Buffer.from("project"), owner.publicKey.toBuffer(), ownerNftMint.toBuffer()],
+      program.programId
+    );
+    await program.methods
+      .initializeProject(
+        new BN(amount),
+        new BN(pricePerToken),
+        new BN(CARBON_PAY_FEE),
+        PROJECT_URI,
+        PROJECT_NAME,
+        PROJECT_SYMBOL
+      )
+      .accountsStrict({
+        projectOwner: owner.publicKey,
+        project,
+        nftMint: ownerNftMint,
+        tokenMint: ownerTokenMint,
+        projectOwnerNftAccount: ownerNftAta,
+        vault,
+        carbonCredits: carbonCreditsPda,
+        metadata: metadataPda(ownerNftMint),
+        masterEdition: masterEditionPda(ownerNftMint),
+        tokenProgram: TOKEN_PROGRAM_ID,
+        tokenMetadataProgram: METADATA_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        rent: SYSVAR_RENT_PUBKEY,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+      })
+      .signers([owner])
+      .rpc();
+    return { project, owner, tokenMint: ownerTokenMint, vault };
+  };
+
+  // Creates the purchase NFT mint and buyer ATAs for a new purchase
+  // and derives the purchase and metadata PDAs
+  const preparePurchase = async (purchaser: Keypair, proj: ProjectFixture = mainProject()) => {
+    const purchaseMint = await createMint(
+      connection,
+      purchaser,
+      purchaser.publicKey,
+      purchaser.publicKey,
+      0
+    );
+    const nftAta = await getAssociatedTokenAddress(purchaseMint, purchaser.publicKey);
+    const tokenAta = await getAssociatedTokenAddress(proj.tokenMint, purchaser.publicKey);
+    const tx = new Transaction().add(
+      createAssociatedTokenAccountInstruction(
+        purchaser.publicKey,
+        nftAta,
+        purchaser.publicKey,
+        purchaseMint
+      )
+    );
+    if (!(await connection.getAccountInfo(tokenAta))) {
+      tx.add(
+        createAssociatedTokenAccountInstruction(
+          purchaser.publicKey,
+          tokenAta,
+          purchaser.publicKey,
+          proj.tokenMint
+        )
+      );
+    }
+    await provider.sendAndConfirm(tx, [purchaser]);
+
+    const [purchase] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("purchase"),
+        purchaser.publicKey.toBuffer(),
+        proj.project.toBuffer(),
+        purchaseMint.toBuffer(),
+      ],
+      program.programId
+    );
+    const metadata = metadataPda(purchaseMint);
+    return { proj, purchaseMint, nftAta, tokenAta, purchase, metadata };
+  };
+
+  const buyerStatsPda = (purchaser: PublicKey, project: PublicKey = projectPda) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("buyer_stats"), purchaser.toBuffer(), project.toBuffer()],
+      program.programId
+    )[0];
+
+  const holdingsPda = (owner: PublicKey, project: PublicKey = projectPda) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("holdings"), owner.toBuffer(), project.toBuffer()],
+      program.programId
+    )[0];
+
+  const subscriptionPda = (subscriber: PublicKey, project: PublicKey = projectPda) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("subscription"), subscriber.toBuffer(), project.toBuffer()],
+      program.programId
+    )[0];
+
+  // Accounts for purchaseCarbonCredits from a `preparePurchase` result
+  const purchaseAccounts = (
+    purchaser: Keypair,
+    p: Awaited<ReturnType<typeof preparePurchase>>,
+    allowlistEntry: PublicKey | null = null
+  ) => ({
+    project: p.proj.project,
+    projectOwner: p.proj.owner.publicKey,
+    projectMint: p.proj.tokenMint,
+    carbonCredits: carbonCreditsPda,
+    projectTokenAccount: p.proj.vault,
+    purchaseNftMint: p.purchaseMint,
+    buyerNftAccount: p.nftAta,
+    buyerTokenAccount: p.tokenAta,
+    purchase: p.purchase,
+    purchaseMetadata: p.metadata,
+    buyerStats: buyerStatsPda(purchaser.publicKey, p.proj.project),
+    holdings: holdingsPda(purchaser.publicKey, p.proj.project),
+    buyer: purchaser.publicKey,
+    platformConfig: platformConfigPda,
+    allowlistEntry,
+    tokenProgram: TOKEN_PROGRAM_ID,
+    tokenMetadataProgram: METADATA_PROGRAM_ID,
+    systemProgram: SystemProgram.programId,
+    rent: SYSVAR_RENT_PUBKEY,
+  });
+
+  const fundedKeypair = async (sol = 10) => {
+    const kp = Keypair.generate();
+    await connection
+      .requestAirdrop(kp.publicKey, sol * anchor.web3.LAMPORTS_PER_SOL)
+      .then(sig => connection.confirmTransaction(sig));
+    return kp;
+  };
+
   before(async () => {
     [carbonCreditsPda, carbonCreditsBump] =
       await PublicKey.findProgramAddress(
         [Buffer.from("carbon_credits")],
         program.programId
       );
+    [platformConfigPda] = await PublicKey.findProgramAddress(
+      [Buffer.from("platform_config")],
+      program.programId
+    );
     
     // Setup project owner
     projectOwner = Keypair.generate();
@@ -88,6 +280,18 @@ describe("🌳 CarbonPay Program Test Suite", () => {
     assert.equal(cc.bump, carbonCreditsBump);
     assert.equal(cc.totalCredits.toNumber(), 0);
     assert.equal(cc.offsetCredits.toNumber(), 0);
+    assert.equal(cc.unitsVersion, 1);
+
+    // Platform config, single approver until test 5
+    await program.methods
+      .initializePlatformConfig([provider.wallet.publicKey], 1)
+      .accountsPartial({
+        authority: provider.wallet.publicKey,
+        carbonCredits: carbonCreditsPda,
+        platformConfig: platformConfigPda,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
   });
 
   // ──────────────────────────────────────────────────────────────────────────────
@@ -237,6 +441,7 @@ describe("🌳 CarbonPay Program Test Suite", () => {
       assert.equal(projAcc.amount.toNumber(), PROJECT_AMOUNT, "Incorrect amount");
       assert.equal(projAcc.remainingAmount.toNumber(), PROJECT_AMOUNT, "Incorrect remainingAmount");
       assert.ok(projAcc.isActive, "Project is not active");
+      assert.equal(projAcc.vault.toBase58(), vaultAta.toBase58(), "Incorrect vault");
       
       // Verify that NFT was minted to the project owner
       const ownerNftBal = await connection.getTokenAccountBalance(projectOwnerNftAccount);
@@ -412,7 +617,11 @@ describe("🌳 CarbonPay Program Test Suite", () => {
           buyerTokenAccount: buyerTokenAta,
           purchase: purchasePda,
           purchaseMetadata: purchaseMetadataPda,
+          buyerStats: buyerStatsPda(buyer.publicKey),
+          holdings: holdingsPda(buyer.publicKey),
           buyer: buyer.publicKey,
+          platformConfig: platformConfigPda,
+          allowlistEntry: null,
           tokenProgram: TOKEN_PROGRAM_ID,
           tokenMetadataProgram: METADATA_PROGRAM_ID,
           systemProgram: SystemProgram.programId,
@@ -457,12 +666,14 @@ describe("🌳 CarbonPay Program Test Suite", () => {
   // ──────────────────────────────────────────────────────────────────────────────
   // 4) RequestOffset
   // ──────────────────────────────────────────────────────────────────────────────
+  let offsetReqPda: PublicKey;
+
   it("4. Request Offset (burn NFT, partial mint and register)", async () => {
     const offsetAmount = 5;
     const requestId = "REQ123";
 
     // a) Derive OffsetRequest PDA
-    const [offsetReqPda] = await PublicKey.findProgramAddress(
+    [offsetReqPda] = await PublicKey.findProgramAddress(
       [
         Buffer.from("offset_request"),
         buyer.publicKey.toBuffer(),
@@ -511,7 +722,10 @@ describe("🌳 CarbonPay Program Test Suite", () => {
       .accountsPartial({
         offsetRequester: buyer.publicKey,
         purchase: purchasePda,
+        offsetDelegate: null,
+        purchaseOwner: null,
         project: projectPda,
+        holdings: holdingsPda(buyer.publicKey),
         originalNftMint: purchaseNftMint,
         originalNftAccount: buyerNftAta,
         newNftMint,
@@ -539,7 +753,8 @@ describe("🌳 CarbonPay Program Test Suite", () => {
     const ccAfter = await program.account.carbonCredits.fetch(
       carbonCreditsPda
     );
-    assert.equal(ccAfter.offsetCredits.toNumber(), offsetAmount);
+    // Platform counters are in grams (6 decimals), project tokens here are whole tonnes
+    assert.equal(ccAfter.offsetCredits.toNumber(), offsetAmount * 1_000_000);
 
     const offsetAcc = await program.account.offsetRequest.fetch(
       offsetReqPda
@@ -563,4 +778,1320 @@ describe("🌳 CarbonPay Program Test Suite", () => {
     assert.equal(projectAfter.offsetAmount.toNumber(), offsetAmount, 
                  "Project offsetAmount should be updated");
   });
+
+  // ──────────────────────────────────────────────────────────────────────────────
+  // 5) Offset approval (M-of-N)
+  // ──────────────────────────────────────────────────────────────────────────────
+  let secondApprover: Keypair;
+
+  it("5. Approve offset request once the vote threshold is met", async () => {
+    secondApprover = Keypair.generate();
+    await connection
+      .requestAirdrop(secondApprover.publicKey, anchor.web3.LAMPORTS_PER_SOL)
+      .then(sig => connection.confirmTransaction(sig));
+
+    const [offsetApprovalPda] = await PublicKey.findProgramAddress(
+      [Buffer.from("offset_approval"), offsetReqPda.toBuffer()],
+      program.programId
+    );
+
+    // a) 2-of-2 approvers
+    await program.methods
+      .updateOffsetApprovers(
+        [provider.wallet.publicKey, secondApprover.publicKey],
+        2
+      )
+      .accountsPartial({
+        authority: provider.wallet.publicKey,
+        platformConfig: platformConfigPda,
+      })
+      .rpc();
+
+    const voteAccounts = (approver: PublicKey) => ({
+      approver,
+      platformConfig: platformConfigPda,
+      offsetRequest: offsetReqPda,
+      offsetApproval: offsetApprovalPda,
+      systemProgram: SystemProgram.programId,
+    });
+
+    // b) first vote keeps the request pending
+    await program.methods
+      .approveOffsetVote()
+      .accountsPartial(voteAccounts(provider.wallet.publicKey))
+      .rpc();
+    let offsetAcc = await program.account.offsetRequest.fetch(offsetReqPda);
+    assert.ok(offsetAcc.status.pending !== undefined);
+
+    // c) duplicate vote is rejected
+    try {
+      await program.methods
+        .approveOffsetVote()
+        .accountsPartial(voteAccounts(provider.wallet.publicKey))
+        .rpc();
+      assert.fail("Duplicate vote should fail");
+    } catch (error) {
+      assert.ok(String(error).includes("DuplicateVote"));
+    }
+
+    // d) non-approver is rejected
+    try {
+      await program.methods
+        .approveOffsetVote()
+        .accountsPartial(voteAccounts(buyer.publicKey))
+        .signers([buyer])
+        .rpc();
+      assert.fail("Non-approver vote should fail");
+    } catch (error) {
+      assert.ok(String(error).includes("NotAnApprover"));
+    }
+
+    // e) second vote reaches the threshold
+    await program.methods
+      .approveOffsetVote()
+      .accountsPartial(voteAccounts(secondApprover.publicKey))
+      .signers([secondApprover])
+      .rpc();
+    offsetAcc = await program.account.offsetRequest.fetch(offsetReqPda);
+    assert.ok(offsetAcc.status.approved !== undefined);
+    assert.equal(
+      offsetAcc.processor?.toBase58(),
+      secondApprover.publicKey.toBase58()
+    );
+  });
+
+  // ──────────────────────────────────────────────────────────────────────────────
+  // 6) Buyer allowlist (KYC)
+  // ──────────────────────────────────────────────────────────────────────────────
+  it("6. Purchase requires an allowlist entry when the project enforces it", async () => {
+    const kycBuyer = await fundedKeypair();
+    const [entryPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("allowlist"), projectPda.toBuffer(), kycBuyer.publicKey.toBuffer()],
+      program.programId
+    );
+
+    await program.methods
+      .setAllowlistRequired(true)
+      .accountsPartial({
+        authority: provider.wallet.publicKey,
+        platformConfig: platformConfigPda,
+        project: projectPda,
+      })
+      .rpc();
+
+    // a) not allowlisted yet
+    const first = await preparePurchase(kycBuyer);
+    try {
+      await program.methods
+        .purchaseCarbonCredits(new BN(1))
+        .accountsPartial(purchaseAccounts(kycBuyer, first))
+        .signers([kycBuyer])
+        .rpc();
+      assert.fail("Purchase without allowlist entry should fail");
+    } catch (error) {
+      assert.ok(String(error).includes("BuyerNotAllowlisted"));
+    }
+
+    // b) allowlisted, no expiry
+    await program.methods
+      .addAllowlistEntry(projectPda, kycBuyer.publicKey, new BN(0))
+      .accountsPartial({
+        authority: provider.wallet.publicKey,
+        platformConfig: platformConfigPda,
+        project: projectPda,
+        allowlistEntry: entryPda,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+    await program.methods
+      .purchaseCarbonCredits(new BN(1))
+      .accountsPartial(purchaseAccounts(kycBuyer, first, entryPda))
+      .signers([kycBuyer])
+      .rpc();
+    const purchaseAcc = await program.account.purchase.fetch(first.purchase);
+    assert.equal(purchaseAcc.amount.toNumber(), 1);
+
+    // c) expired entry is rejected
+    await program.methods
+      .setAllowlistExpiry(new BN(1))
+      .accountsPartial({
+        authority: provider.wallet.publicKey,
+        platformConfig: platformConfigPda,
+        allowlistEntry: entryPda,
+      })
+      .rpc();
+    const second = await preparePurchase(kycBuyer);
+    try {
+      await program.methods
+        .purchaseCarbonCredits(new BN(1))
+        .accountsPartial(purchaseAccounts(kycBuyer, second, entryPda))
+        .signers([kycBuyer])
+        .rpc();
+      assert.fail("Purchase with expired entry should fail");
+    } catch (error) {
+      assert.ok(String(error).includes("AllowlistEntryExpired"));
+    }
+
+    // d) cleanup
+    await program.methods
+      .removeAllowlistEntry()
+      .accountsPartial({
+        authority: provider.wallet.publicKey,
+        platformConfig: platformConfigPda,
+        allowlistEntry: entryPda,
+      })
+      .rpc();
+    await program.methods
+      .setAllowlistRequired(false)
+      .accountsPartial({
+        authority: provider.wallet.publicKey,
+        platformConfig: platformConfigPda,
+        project: projectPda,
+      })
+      .rpc();
+  });
+
+  // ──────────────────────────────────────────────────────────────────────────────
+  // 7) Purchase limits
+  // ──────────────────────────────────────────────────────────────────────────────
+  it("7. Enforce per-transaction and per-buyer purchase limits", async () => {
+    const limitedBuyer = await fundedKeypair();
+    const setLimits = (min: number, max: number, perBuyer: number) =>
+      program.methods
+        .setPurchaseLimits(new BN(min), new BN(max), new BN(perBuyer))
+        .accountsPartial({ projectOwner: projectOwner.publicKey, project: projectPda })
+        .signers([projectOwner])
+        .rpc();
+    const buy = async (amount: number) => {
+      const p = await preparePurchase(limitedBuyer);
+      await program.methods
+        .purchaseCarbonCredits(new BN(amount))
+        .accountsPartial(purchaseAccounts(limitedBuyer, p))
+        .signers([limitedBuyer])
+        .rpc();
+    };
+    const expectError = async (amount: number, code: string) => {
+      try {
+        await buy(amount);
+        assert.fail(`Purchase of ${amount} should fail with ${code}`);
+      } catch (error) {
+        assert.ok(String(error).includes(code), String(error));
+      }
+    };
+
+    await setLimits(2, 3, 4);
+    await expectError(1, "PurchaseBelowMinimum");
+    await expectError(4, "PurchaseAboveMaximum");
+    await buy(3);
+    await expectError(2, "BuyerCapExceeded");
+
+    const stats = await program.account.buyerStats.fetch(buyerStatsPda(limitedBuyer.publicKey));
+    assert.equal(stats.totalPurchased.toNumber(), 3);
+
+    await setLimits(0, 0, 0);
+  });
+
+  // ──────────────────────────────────────────────────────────────────────────────
+  // 8) Sale windows
+  // ──────────────────────────────────────────────────────────────────────────────
+  it("8. Reject purchases outside the sale window", async () => {
+    const windowBuyer = await fundedKeypair();
+    const now = Math.floor(Date.now() / 1000);
+    const setWindow = (start: number, end: number, earlyAccess: number) =>
+      program.methods
+        .setSaleWindow(new BN(start), new BN(end), new BN(earlyAccess))
+        .accountsPartial({ projectOwner: projectOwner.publicKey, project: projectPda })
+        .signers([projectOwner])
+        .rpc();
+    const expectError = async (code: string) => {
+      const p = await preparePurchase(windowBuyer);
+      try {
+        await program.methods
+          .purchaseCarbonCredits(new BN(1))
+          .accountsPartial(purchaseAccounts(windowBuyer, p))
+          .signers([windowBuyer])
+          .rpc();
+        assert.fail(`Purchase should fail with ${code}`);
+      } catch (error) {
+        assert.ok(String(error).includes(code), String(error));
+      }
+    };
+
+    await setWindow(now + 3600, now + 7200, 0);
+    await expectError("SaleNotStarted");
+
+    // early access phase is open only to allowlisted buyers
+    await setWindow(now + 3600, now + 7200, now - 3600);
+    await expectError("BuyerNotAllowlisted");
+
+    await setWindow(1, 2, 0);
+    await expectError("SaleEnded");
+
+    await setWindow(0, 0, 0);
+  });
+
+  // ──────────────────────────────────────────────────────────────────────────────
+  // 9) Tiered pricing
+  // ──────────────────────────────────────────────────────────────────────────────
+  it("9. Charge volume tiers across a single purchase", async () => {
+    const tierBuyer = await fundedKeypair();
+    const discounted = PRICE_PER_TOKEN / 2;
+    await program.methods
+      .setPriceTiers([{ minAmount: new BN(2), pricePerToken: new BN(discounted) }])
+      .accountsPartial({ projectOwner: projectOwner.publicKey, project: projectPda })
+      .signers([projectOwner])
+      .rpc();
+
+    // 2 tokens at the base price, 1 at the tier price
+    const total = 2 * PRICE_PER_TOKEN + discounted;
+    const fee = Math.floor((total * CARBON_PAY_FEE) / 10_000);
+    const ownerBefore = await connection.getBalance(projectOwner.publicKey);
+
+    const p = await preparePurchase(tierBuyer);
+    await program.methods
+      .purchaseCarbonCredits(new BN(3))
+      .accountsPartial(purchaseAccounts(tierBuyer, p))
+      .signers([tierBuyer])
+      .rpc();
+
+    const ownerAfter = await connection.getBalance(projectOwner.publicKey);
+    assert.equal(ownerAfter - ownerBefore, total - fee);
+
+    await program.methods
+      .setPriceTiers([])
+      .accountsPartial({ projectOwner: projectOwner.publicKey, project: projectPda })
+      .signers([projectOwner])
+      .rpc();
+  });
+
+  // ──────────────────────────────────────────────────────────────────────────────
+  // 10) Secondary marketplace
+  // ──────────────────────────────────────────────────────────────────────────────
+  it("10. List purchased credits and buy the listing", async () => {
+    const listAmount = 2;
+    const listPrice = PRICE_PER_TOKEN * 2;
+    const [listingPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("listing"), purchasePda.toBuffer()],
+      program.programId
+    );
+    const escrowAta = await getAssociatedTokenAddress(tokenMint, listingPda, true);
+    const before = await program.account.purchase.fetch(purchasePda);
+
+    await program.methods
+      .listCredits(new BN(listAmount), new BN(listPrice))
+      .accountsPartial({
+        seller: buyer.publicKey,
+        purchase: purchasePda,
+        project: projectPda,
+        tokenMint,
+        sellerTokenAccount: buyerTokenAta,
+        holdings: holdingsPda(buyer.publicKey),
+        subscription: subscriptionPda(buyer.publicKey),
+        offsetDelegate: PublicKey.findProgramAddressSync(
+          [Buffer.from("offset_delegate"), purchasePda.toBuffer()],
+          program.programId
+        )[0],
+        sellerNftAccount: await getAssociatedTokenAddress(before.certificateMint, buyer.publicKey),
+        listing: listingPda,
+        escrow: escrowAta,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([buyer])
+      .rpc();
+
+    const listed = await program.account.purchase.fetch(purchasePda);
+    assert.equal(
+      listed.remainingAmount.toNumber(),
+      before.remainingAmount.toNumber() - listAmount
+    );
+
+    const secondaryBuyer = await fundedKeypair();
+    const p = await preparePurchase(secondaryBuyer);
+    await program.methods
+      .buyListing()
+      .accountsPartial({
+        buyer: secondaryBuyer.publicKey,
+        seller: buyer.publicKey,
+        listing: listingPda,
+        project: projectPda,
+        tokenMint,
+        carbonCredits: carbonCreditsPda,
+        platformConfig: platformConfigPda,
+        allowlistEntry: null,
+        escrow: escrowAta,
+        buyerTokenAccount: p.tokenAta,
+        purchaseNftMint: p.purchaseMint,
+        buyerNftAccount: p.nftAta,
+        purchase: p.purchase,
+        holdings: holdingsPda(secondaryBuyer.publicKey),
+        purchaseMetadata: p.metadata,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        tokenMetadataProgram: METADATA_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        rent: SYSVAR_RENT_PUBKEY,
+      })
+      .signers([secondaryBuyer])
+      .rpc();
+
+    const resold = await program.account.purchase.fetch(p.purchase);
+    assert.equal(resold.amount.toNumber(), listAmount);
+    assert.equal(resold.buyer.toBase58(), secondaryBuyer.publicKey.toBase58());
+    const tokenBal = await connection.getTokenAccountBalance(p.tokenAta);
+    assert.equal(tokenBal.value.uiAmount, listAmount);
+    assert.equal(await connection.getAccountInfo(listingPda), null);
+  });
+
+  // ──────────────────────────────────────────────────────────────────────────────
+  // 11) Dutch auction
+  // ──────────────────────────────────────────────────────────────────────────────
+  it("11. Sell a launch by Dutch auction and refund down to the clearing price", async () => {
+    const auctionOwner = await fundedKeypair();
+    const auctionProject = await createProject(auctionOwner, 10);
+    const now = Math.floor(Date.now() / 1000);
+    const startPrice = PRICE_PER_TOKEN * 2;
+
+    await program.methods
+      .startDutchAuction(
+        new BN(startPrice),
+        new BN(PRICE_PER_TOKEN),
+        new BN(now - 5),
+        new BN(3600),
+        new BN(1)
+      )
+      .accountsPartial({ projectOwner: auctionOwner.publicKey, project: auctionProject.project })
+      .signers([auctionOwner])
+      .rpc();
+
+    // a) early bidder, then a second buyer sells the auction out
+    const early = await fundedKeypair();
+    const late = await fundedKeypair();
+    for (const [bidder, amount] of [[early, 4], [late, 6]] as [Keypair, number][]) {
+      const p = await preparePurchase(bidder, auctionProject);
+      await program.methods
+        .purchaseCarbonCredits(new BN(amount))
+        .accountsPartial(purchaseAccounts(bidder, p))
+        .signers([bidder])
+        .rpc();
+    }
+
+    // b) settle at the clearing price
+    await program.methods
+      .settleDutchAuction()
+      .accountsPartial({
+        project: auctionProject.project,
+        projectOwner: auctionOwner.publicKey,
+        carbonCredits: carbonCreditsPda,
+        platformConfig: platformConfigPda,
+      })
+      .rpc();
+    const settled = await program.account.project.fetch(auctionProject.project);
+    assert.ok(settled.auction?.settled);
+    assert.equal(settled.auction?.sold.toNumber(), 10);
+    const clearing = settled.auction!.lastPrice.toNumber();
+
+    // c) the early bidder gets back what they paid above the clearing price
+    const stats = await program.account.buyerStats.fetch(
+      buyerStatsPda(early.publicKey, auctionProject.project)
+    );
+    const refund = stats.auctionPaid.toNumber() - 4 * clearing;
+    const claim = program.methods
+      .claimAuctionRefund()
+      .accountsPartial({
+        buyer: early.publicKey,
+        project: auctionProject.project,
+        buyerStats: buyerStatsPda(early.publicKey, auctionProject.project),
+      })
+      .signers([early]);
+    if (refund > 0) {
+      const before = await connection.getBalance(early.publicKey);
+      await claim.rpc();
+      const after = await connection.getBalance(early.publicKey);
+      assert.ok(after > before);
+    } else {
+      try {
+        await claim.rpc();
+        assert.fail("Claim without refund should fail");
+      } catch (error) {
+        assert.ok(String(error).includes("NothingToRefund"));
+      }
+    }
+  });
+
+  // ──────────────────────────────────────────────────────────────────────────────
+  // 12) OTC offers
+  // ──────────────────────────────────────────────────────────────────────────────
+  it("12. Settle an OTC offer and cancel an unaccepted one", async () => {
+    const otcBuyer = await fundedKeypair();
+    const expiresAt = new BN(Math.floor(Date.now() / 1000) + 3600);
+    const offerPda = (offerId: number) =>
+      PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("otc_offer"),
+          projectPda.toBuffer(),
+          otcBuyer.publicKey.toBuffer(),
+          new BN(offerId).toArrayLike(Buffer, "le", 8),
+        ],
+        program.programId
+      )[0];
+    const createOffer = (offerId: number) =>
+      program.methods
+        .createOtcOffer(otcBuyer.publicKey, new BN(offerId), new BN(3), new BN(PRICE_PER_TOKEN / 2), expiresAt)
+        .accountsPartial({
+          projectOwner: projectOwner.publicKey,
+          project: projectPda,
+          otcOffer: offerPda(offerId),
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([projectOwner])
+        .rpc();
+
+    // a) accept
+    const before = await program.account.project.fetch(projectPda);
+    await createOffer(1);
+    const p = await preparePurchase(otcBuyer);
+    await program.methods
+      .acceptOtcOffer()
+      .accountsPartial({
+        buyer: otcBuyer.publicKey,
+        otcOffer: offerPda(1),
+        project: projectPda,
+        projectOwner: projectOwner.publicKey,
+        projectMint: tokenMint,
+        carbonCredits: carbonCreditsPda,
+        platformConfig: platformConfigPda,
+        allowlistEntry: null,
+        buyerStats: buyerStatsPda(otcBuyer.publicKey),
+        holdings: holdingsPda(otcBuyer.publicKey),
+        projectTokenAccount: vaultAta,
+        purchaseNftMint: p.purchaseMint,
+        buyerNftAccount: p.nftAta,
+        buyerTokenAccount: p.tokenAta,
+        purchase: p.purchase,
+        purchaseMetadata: p.metadata,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        tokenMetadataProgram: METADATA_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        rent: SYSVAR_RENT_PUBKEY,
+      })
+      .signers([otcBuyer])
+      .rpc();
+    const otcPurchase = await program.account.purchase.fetch(p.purchase);
+    assert.equal(otcPurchase.amount.toNumber(), 3);
+    assert.equal(await connection.getAccountInfo(offerPda(1)), null);
+    let after = await program.account.project.fetch(projectPda);
+    assert.equal(after.remainingAmount.toNumber(), before.remainingAmount.toNumber() - 3);
+
+    // b) cancel releases the reserved tokens
+    await createOffer(2);
+    await program.methods
+      .cancelOtcOffer()
+      .accountsPartial({
+        canceller: projectOwner.publicKey,
+        projectOwner: projectOwner.publicKey,
+        project: projectPda,
+        otcOffer: offerPda(2),
+      })
+      .signers([projectOwner])
+      .rpc();
+    after = await program.account.project.fetch(projectPda);
+    assert.equal(after.remainingAmount.toNumber(), before.remainingAmount.toNumber() - 3);
+  });
+
+  // ──────────────────────────────────────────────────────────────────────────────
+  // 13) Subscriptions
+  // ──────────────────────────────────────────────────────────────────────────────
+  it("13. Execute a prefunded subscription once per period and refund on cancel", async () => {
+    const subscriber = await fundedKeypair();
+    const subscription = subscriptionPda(subscriber.publicKey);
+    const p = await preparePurchase(subscriber);
+
+    await program.methods
+      .createSubscription(new BN(2), new BN(PRICE_PER_TOKEN), new BN(5 * PRICE_PER_TOKEN))
+      .accountsPartial({
+        buyer: subscriber.publicKey,
+        project: projectPda,
+        subscription,
+        purchase: p.purchase,
+        buyerStats: buyerStatsPda(subscriber.publicKey),
+        holdings: holdingsPda(subscriber.publicKey),
+        purchaseNftMint: p.purchaseMint,
+        buyerNftAccount: p.nftAta,
+        purchaseMetadata: p.metadata,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        tokenMetadataProgram: METADATA_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        rent: SYSVAR_RENT_PUBKEY,
+      })
+      .signers([subscriber])
+      .rpc();
+
+    const execute = () =>
+      program.methods
+        .executeSubscription()
+        .accountsPartial({
+          subscription,
+          purchase: p.purchase,
+          project: projectPda,
+          projectOwner: projectOwner.publicKey,
+          projectMint: tokenMint,
+          carbonCredits: carbonCreditsPda,
+          platformConfig: platformConfigPda,
+          allowlistEntry: null,
+          buyerStats: buyerStatsPda(subscriber.publicKey),
+          holdings: holdingsPda(subscriber.publicKey),
+          projectTokenAccount: vaultAta,
+          buyerTokenAccount: p.tokenAta,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+
+    // a) first period executes immediately, anyone can crank
+    await execute();
+    const position = await program.account.purchase.fetch(p.purchase);
+    assert.equal(position.remainingAmount.toNumber(), 2);
+
+    // b) second call in the same period is rejected
+    try {
+      await execute();
+      assert.fail("Second execution in the same period should fail");
+    } catch (error) {
+      assert.ok(String(error).includes("SubscriptionNotDue"));
+    }
+
+    // c) cancel refunds the rest of the escrow
+    const before = await connection.getBalance(subscriber.publicKey);
+    await program.methods
+      .cancelSubscription()
+      .accountsPartial({ buyer: subscriber.publicKey, subscription })
+      .signers([subscriber])
+      .rpc();
+    const after = await connection.getBalance(subscriber.publicKey);
+    assert.ok(after - before > 2 * PRICE_PER_TOKEN);
+    assert.equal(await connection.getAccountInfo(subscription), null);
+  });
+
+  // ──────────────────────────────────────────────────────────────────────────────
+  // 14) Basket index token
+  // ──────────────────────────────────────────────────────────────────────────────
+  it("14. Pool credits in a basket, then redeem and retire basket tokens", async () => {
+    const basketId = new BN(1);
+    const [basketPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("basket"), basketId.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+    const [basketMint] = PublicKey.findProgramAddressSync(
+      [Buffer.from("basket_mint"), basketPda.toBuffer()],
+      program.programId
+    );
+    const [reservePda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("basket_reserve"), basketPda.toBuffer(), projectPda.toBuffer()],
+      program.programId
+    );
+    const basketVault = await getAssociatedTokenAddress(tokenMint, basketPda, true);
+    const NATURE_BASED = 1 << 1;
+
+    await program.methods
+      .createBasket(basketId, NATURE_BASED)
+      .accountsPartial({
+        authority: provider.wallet.publicKey,
+        platformConfig: platformConfigPda,
+        basket: basketPda,
+        basketMint,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    const depositor = await fundedKeypair();
+    const p = await preparePurchase(depositor);
+    await program.methods
+      .purchaseCarbonCredits(new BN(4))
+      .accountsPartial(purchaseAccounts(depositor, p))
+      .signers([depositor])
+      .rpc();
+
+    const depositorBasketAta = await getAssociatedTokenAddress(basketMint, depositor.publicKey);
+    // the deposit burns the certificate and re-mints one for what stays in the position
+    const replacementMint = await createMint(connection, depositor, depositor.publicKey, depositor.publicKey, 0);
+    const replacementAta = await getAssociatedTokenAddress(replacementMint, depositor.publicKey);
+    await provider.sendAndConfirm(
+      new Transaction().add(
+        createAssociatedTokenAccountInstruction(
+          depositor.publicKey,
+          depositorBasketAta,
+          depositor.publicKey,
+          basketMint
+        ),
+        createAssociatedTokenAccountInstruction(
+          depositor.publicKey,
+          replacementAta,
+          depositor.publicKey,
+          replacementMint
+        )
+      ),
+      [depositor]
+    );
+
+    const deposit = () =>
+      program.methods
+        .depositToBasket(new BN(4))
+        .accountsPartial({
+          depositor: depositor.publicKey,
+          basket: basketPda,
+          basketMint,
+          project: projectPda,
+          tokenMint,
+          purchase: p.purchase,
+          holdings: holdingsPda(depositor.publicKey),
+          certificateMint: p.purchaseMint,
+          nftAccount: p.nftAta,
+          replacementNftMint: replacementMint,
+          replacementNftAccount: replacementAta,
+          replacementNftMetadata: metadataPda(replacementMint),
+          depositorTokenAccount: p.tokenAta,
+          depositorBasketAccount: depositorBasketAta,
+          basketReserve: reservePda,
+          basketVault,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          tokenMetadataProgram: METADATA_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          rent: SYSVAR_RENT_PUBKEY,
+        })
+        .signers([depositor])
+        .rpc();
+
+    // a) uncategorised projects are not eligible
+    try {
+      await deposit();
+      assert.fail("Deposit from an ineligible project should fail");
+    } catch (error) {
+      assert.ok(String(error).includes("ProjectNotEligible"));
+    }
+
+    await program.methods
+      .setProjectCategory({ natureBased: {} })
+      .accountsPartial({
+        authority: provider.wallet.publicKey,
+        platformConfig: platformConfigPda,
+        project: projectPda,
+      })
+      .rpc();
+    await deposit();
+
+    let reserve = await program.account.basketReserve.fetch(reservePda);
+    assert.equal(reserve.amount.toNumber(), 4);
+    let basketTokens = await connection.getTokenAccountBalance(depositorBasketAta);
+    assert.equal(basketTokens.value.amount, "4");
+
+    // b) redeem one basket token for the underlying project token
+    await program.methods
+      .redeemFromBasket(new BN(1))
+      .accountsPartial({
+        redeemer: depositor.publicKey,
+        basket: basketPda,
+        basketMint,
+        redeemerBasketAccount: depositorBasketAta,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .remainingAccounts([
+        { pubkey: reservePda, isSigner: false, isWritable: true },
+        { pubkey: basketVault, isSigner: false, isWritable: true },
+        { pubkey: p.tokenAta, isSigner: false, isWritable: true },
+      ])
+      .signers([depositor])
+      .rpc();
+
+    // c) retire two basket tokens against the project
+    const projectBefore = await program.account.project.fetch(projectPda);
+    await program.methods
+      .retireFromBasket(new BN(2))
+      .accountsPartial({
+        retiree: depositor.publicKey,
+        basket: basketPda,
+        basketMint,
+        retireeBasketAccount: depositorBasketAta,
+        carbonCredits: carbonCreditsPda,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .remainingAccounts([
+        { pubkey: reservePda, isSigner: false, isWritable: true },
+        { pubkey: basketVault, isSigner: false, isWritable: true },
+        { pubkey: tokenMint, isSigner: false, isWritable: true },
+        { pubkey: projectPda, isSigner: false, isWritable: true },
+      ])
+      .signers([depositor])
+      .rpc();
+
+    reserve = await program.account.basketReserve.fetch(reservePda);
+    assert.equal(reserve.amount.toNumber(), 1);
+    basketTokens = await connection.getTokenAccountBalance(depositorBasketAta);
+    assert.equal(basketTokens.value.amount, "1");
+    const projectAfter = await program.account.project.fetch(projectPda);
+    assert.equal(
+      projectAfter.offsetAmount.toNumber(),
+      projectBefore.offsetAmount.toNumber() + 2
+    );
+  });
+
+  // ──────────────────────────────────────────────────────────────────────────────
+  // 15) Purchase and retire in one step
+  // ──────────────────────────────────────────────────────────────────────────────
+  it("15. Purchase and retire credits without a purchase NFT", async () => {
+    const retiree = await fundedKeypair();
+    const requestId = "retire-now";
+    const [retirementPda] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("retirement"),
+        retiree.publicKey.toBuffer(),
+        projectPda.toBuffer(),
+        Buffer.from(requestId),
+      ],
+      program.programId
+    );
+    const projectBefore = await program.account.project.fetch(projectPda);
+    const supplyBefore = (await getMint(connection, tokenMint)).supply;
+
+    await program.methods
+      .purchaseAndRetire(new BN(2), requestId)
+      .accountsPartial({
+        project: projectPda,
+        projectOwner: projectOwner.publicKey,
+        projectMint: tokenMint,
+        carbonCredits: carbonCreditsPda,
+        projectTokenAccount: vaultAta,
+        offsetRequest: retirementPda,
+        buyerStats: buyerStatsPda(retiree.publicKey),
+        buyer: retiree.publicKey,
+        platformConfig: platformConfigPda,
+        allowlistEntry: null,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([retiree])
+      .rpc();
+
+    const retirement = await program.account.offsetRequest.fetch(retirementPda);
+    assert.equal(retirement.amount.toNumber(), 2);
+    assert.ok(retirement.offsetRequester.equals(retiree.publicKey));
+    assert.ok(retirement.purchase.equals(PublicKey.default));
+
+    const projectAfter = await program.account.project.fetch(projectPda);
+    assert.equal(projectAfter.remainingAmount.toNumber(), projectBefore.remainingAmount.toNumber() - 2);
+    assert.equal(projectAfter.offsetAmount.toNumber(), projectBefore.offsetAmount.toNumber() + 2);
+    const supplyAfter = (await getMint(connection, tokenMint)).supply;
+    assert.equal(supplyBefore - supplyAfter, BigInt(2));
+  });
+
+  // ──────────────────────────────────────────────────────────────────────────────
+  // 16) Delegated offsets
+  // ──────────────────────────────────────────────────────────────────────────────
+  it("16. Let an approved custodian request an offset for the purchase owner", async () => {
+    const owner = await fundedKeypair();
+    const custodian = await fundedKeypair();
+    const p = await preparePurchase(owner);
+    await program.methods
+      .purchaseCarbonCredits(new BN(4))
+      .accountsPartial(purchaseAccounts(owner, p))
+      .signers([owner])
+      .rpc();
+
+    const [delegatePda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("offset_delegate"), p.purchase.toBuffer()],
+      program.programId
+    );
+    const requestId = "CUSTODY1";
+    const [requestPda] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("offset_request"),
+        owner.publicKey.toBuffer(),
+        p.purchase.toBuffer(),
+        Buffer.from(requestId),
+      ],
+      program.programId
+    );
+
+    // the delegate PDA escrows the certificate and the allowance, the residual NFT is minted into its escrow
+    const escrowNftAta = await getAssociatedTokenAddress(p.purchaseMint, delegatePda, true);
+    const escrowTokenAta = await getAssociatedTokenAddress(tokenMint, delegatePda, true);
+    const newNftMint = await createMint(connection, custodian, custodian.publicKey, custodian.publicKey, 0);
+    const newNftAta = await getAssociatedTokenAddress(newNftMint, delegatePda, true);
+    await provider.sendAndConfirm(
+      new Transaction().add(
+        createAssociatedTokenAccountInstruction(custodian.publicKey, newNftAta, delegatePda, newNftMint)
+      ),
+      [custodian]
+    );
+
+    const requestOffset = (offsetDelegate: PublicKey | null, nftAccount: PublicKey, tokenAccount: PublicKey) =>
+      program.methods
+        .requestOffset(new BN(2), requestId)
+        .accountsPartial({
+          offsetRequester: custodian.publicKey,
+          purchase: p.purchase,
+          offsetDelegate,
+          purchaseOwner: offsetDelegate ? owner.publicKey : null,
+          project: projectPda,
+          holdings: holdingsPda(owner.publicKey),
+          originalNftMint: p.purchaseMint,
+          originalNftAccount: nftAccount,
+          newNftMint,
+          newNftAccount: newNftAta,
+          newNftMetadata: metadataPda(newNftMint),
+          tokenMint,
+          buyerTokenAccount: tokenAccount,
+          carbonCredits: carbonCreditsPda,
+          offsetRequest: requestPda,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          tokenMetadataProgram: METADATA_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          rent: SYSVAR_RENT_PUBKEY,
+        })
+        .signers([custodian])
+        .rpc();
+
+    // a) without an approval the custodian is rejected
+    try {
+      await requestOffset(null, p.nftAta, p.tokenAta);
+      assert.fail("Offset by a non-owner without approval should fail");
+    } catch (error) {
+      assert.ok(String(error).includes("NotPurchaseOwner"));
+    }
+
+    // b) the owner approves the custodian for up to 2 tokens
+    await program.methods
+      .approveOffsetDelegate(custodian.publicKey, new BN(2), new BN(0))
+      .accountsPartial({
+        owner: owner.publicKey,
+        purchase: p.purchase,
+        project: projectPda,
+        holdings: holdingsPda(owner.publicKey),
+        offsetDelegate: delegatePda,
+        certificateMint: p.purchaseMint,
+        nftAccount: p.nftAta,
+        delegateNftAccount: escrowNftAta,
+        tokenMint,
+        ownerTokenAccount: p.tokenAta,
+        delegateTokenAccount: escrowTokenAta,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([owner])
+      .rpc();
+    // only the allowance leaves the owner's token account
+    assert.equal((await connection.getTokenAccountBalance(p.tokenAta)).value.amount, "2");
+
+    await requestOffset(delegatePda, escrowNftAta, escrowTokenAta);
+
+    const request = await program.account.offsetRequest.fetch(requestPda);
+    assert.ok(request.offsetRequester.equals(owner.publicKey));
+    assert.equal(request.amount.toNumber(), 2);
+    const delegate = await program.account.offsetDelegate.fetch(delegatePda);
+    assert.equal(delegate.remainingAmount.toNumber(), 0);
+    const position = await program.account.purchase.fetch(p.purchase);
+    assert.equal(position.remainingAmount.toNumber(), 2);
+    assert.ok(position.certificateMint.equals(newNftMint));
+    assert.equal((await connection.getTokenAccountBalance(newNftAta)).value.amount, "1");
+    // the used-up allowance escrow is closed
+    assert.equal(await connection.getAccountInfo(escrowTokenAta), null);
+  });
+
+  // ──────────────────────────────────────────────────────────────────────────────
+  // 17) Batch offsets
+  // ──────────────────────────────────────────────────────────────────────────────
+  it("17. Offset several purchases in one aggregated request", async () => {
+    const holder = await fundedKeypair();
+    const positions = [];
+    for (const amount of [2, 3]) {
+      const p = await preparePurchase(holder);
+      await program.methods
+        .purchaseCarbonCredits(new BN(amount))
+        .accountsPartial(purchaseAccounts(holder, p))
+        .signers([holder])
+        .rpc();
+      positions.push(p);
+    }
+
+    const requestId = "BATCH1";
+    const [requestPda] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("offset_request"),
+        holder.publicKey.toBuffer(),
+        projectPda.toBuffer(),
+        Buffer.from(requestId),
+      ],
+      program.programId
+    );
+    const [batchPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("offset_batch"), requestPda.toBuffer()],
+      program.programId
+    );
+
+    // certificates stay with the holder, each purchase records what they no longer reflect
+    await program.methods
+      .requestBatchOffset([new BN(1), new BN(3)], requestId)
+      .accountsPartial({
+        offsetRequester: holder.publicKey,
+        project: projectPda,
+        tokenMint,
+        buyerTokenAccount: positions[0].tokenAta,
+        holdings: holdingsPda(holder.publicKey),
+        carbonCredits: carbonCreditsPda,
+        offsetRequest: requestPda,
+        offsetBatch: batchPda,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .remainingAccounts(
+        positions.flatMap((p) => [
+          { pubkey: p.purchase, isSigner: false, isWritable: true },
+          { pubkey: p.nftAta, isSigner: false, isWritable: false },
+        ])
+      )
+      .signers([holder])
+      .rpc();
+
+    const request = await program.account.offsetRequest.fetch(requestPda);
+    assert.equal(request.amount.toNumber(), 4);
+    const batch = await program.account.offsetBatch.fetch(batchPda);
+    assert.deepEqual(
+      batch.purchases.map((k) => k.toBase58()),
+      positions.map((p) => p.purchase.toBase58())
+    );
+    const first = await program.account.purchase.fetch(positions[0].purchase);
+    const second = await program.account.purchase.fetch(positions[1].purchase);
+    assert.equal(first.remainingAmount.toNumber(), 1);
+    assert.equal(second.remainingAmount.toNumber(), 0);
+    assert.equal(first.uncertifiedOffset.toNumber(), 1);
+    assert.equal(second.uncertifiedOffset.toNumber(), 3);
+    assert.ok(first.certificateMint.equals(positions[0].purchaseMint));
+    assert.equal((await connection.getTokenAccountBalance(positions[0].nftAta)).value.amount, "1");
+    assert.equal((await connection.getTokenAccountBalance(positions[1].nftAta)).value.amount, "1");
+    const balance = await connection.getTokenAccountBalance(positions[0].tokenAta);
+    assert.equal(balance.value.amount, "1");
+  });
+
+  // ──────────────────────────────────────────────────────────────────────────────
+  // 18) Split and merge positions
+  // ──────────────────────────────────────────────────────────────────────────────
+  it("18. Split a purchase into a new position and merge it back", async () => {
+    const holder = await fundedKeypair();
+    const original = await preparePurchase(holder);
+    await program.methods
+      .purchaseCarbonCredits(new BN(4))
+      .accountsPartial(purchaseAccounts(holder, original))
+      .signers([holder])
+      .rpc();
+
+    // a) split 1 token into a new position with its own certificate, re-minting the source's
+    const carved = await preparePurchase(holder);
+    const remint = await preparePurchase(holder);
+    await program.methods
+      .splitPurchase(new BN(1))
+      .accountsPartial({
+        buyer: holder.publicKey,
+        project: projectPda,
+        purchase: original.purchase,
+        certificateMint: original.purchaseMint,
+        nftAccount: original.nftAta,
+        replacementNftMint: remint.purchaseMint,
+        replacementNftAccount: remint.nftAta,
+        replacementNftMetadata: remint.metadata,
+        newNftMint: carved.purchaseMint,
+        newNftAccount: carved.nftAta,
+        newNftMetadata: carved.metadata,
+        subscription: subscriptionPda(holder.publicKey),
+        newPurchase: carved.purchase,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        tokenMetadataProgram: METADATA_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        rent: SYSVAR_RENT_PUBKEY,
+      })
+      .signers([holder])
+      .rpc();
+
+    let kept = await program.account.purchase.fetch(original.purchase);
+    const split = await program.account.purchase.fetch(carved.purchase);
+    assert.equal(kept.remainingAmount.toNumber(), 3);
+    assert.equal(split.remainingAmount.toNumber(), 1);
+    assert.ok(kept.certificateMint.equals(remint.purchaseMint));
+    assert.equal((await connection.getTokenAccountBalance(original.nftAta)).value.amount, "0");
+
+    // b) merge it back, burning the carved-out certificate and re-minting the target's;
+    //    the source must not be listed or delegated
+    const remerged = await preparePurchase(holder);
+    const [carvedListing] = PublicKey.findProgramAddressSync(
+      [Buffer.from("listing"), carved.purchase.toBuffer()],
+      program.programId
+    );
+    const [carvedDelegate] = PublicKey.findProgramAddressSync(
+      [Buffer.from("offset_delegate"), carved.purchase.toBuffer()],
+      program.programId
+    );
+    await program.methods
+      .mergePurchases()
+      .accountsPartial({
+        buyer: holder.publicKey,
+        purchase: original.purchase,
+        subscription: subscriptionPda(holder.publicKey),
+        certificateMint: remint.purchaseMint,
+        nftAccount: remint.nftAta,
+        replacementNftMint: remerged.purchaseMint,
+        replacementNftAccount: remerged.nftAta,
+        replacementNftMetadata: remerged.metadata,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        tokenMetadataProgram: METADATA_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        rent: SYSVAR_RENT_PUBKEY,
+      })
+      .remainingAccounts([
+        { pubkey: carved.purchase, isSigner: false, isWritable: true },
+        { pubkey: carved.purchaseMint, isSigner: false, isWritable: true },
+        { pubkey: carved.nftAta, isSigner: false, isWritable: true },
+        { pubkey: carvedListing, isSigner: false, isWritable: false },
+        { pubkey: carvedDelegate, isSigner: false, isWritable: false },
+      ])
+      .signers([holder])
+      .rpc();
+
+    kept = await program.account.purchase.fetch(original.purchase);
+    assert.equal(kept.remainingAmount.toNumber(), 4);
+    assert.ok(kept.certificateMint.equals(remerged.purchaseMint));
+    assert.equal((await connection.getTokenAccountBalance(remint.nftAta)).value.amount, "0");
+    assert.equal(await connection.getAccountInfo(carved.purchase), null);
+    const carvedNft = await connection.getTokenAccountBalance(carved.nftAta);
+    assert.equal(carvedNft.value.amount, "0");
+  });
+
+  // ──────────────────────────────────────────────────────────────────────────────
+  // 19) Purchase transfers
+  // ──────────────────────────────────────────────────────────────────────────────
+  it("19. Transfer a purchase with its NFT and re-key ownership", async () => {
+    const seller = await fundedKeypair();
+    const recipient = Keypair.generate();
+    const p = await preparePurchase(seller);
+    await program.methods
+      .purchaseCarbonCredits(new BN(3))
+      .accountsPartial(purchaseAccounts(seller, p))
+      .signers([seller])
+      .rpc();
+
+    const [newPurchase] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("purchase"),
+        recipient.publicKey.toBuffer(),
+        projectPda.toBuffer(),
+        p.purchaseMint.toBuffer(),
+      ],
+      program.programId
+    );
+    const [listingPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("listing"), p.purchase.toBuffer()],
+      program.programId
+    );
+    const recipientNftAta = await getAssociatedTokenAddress(p.purchaseMint, recipient.publicKey);
+    const recipientTokenAta = await getAssociatedTokenAddress(tokenMint, recipient.publicKey);
+
+    await program.methods
+      .transferPurchase()
+      .accountsPartial({
+        owner: seller.publicKey,
+        recipient: recipient.publicKey,
+        project: projectPda,
+        purchase: p.purchase,
+        listing: listingPda,
+        subscription: subscriptionPda(seller.publicKey),
+        offsetDelegate: PublicKey.findProgramAddressSync(
+          [Buffer.from("offset_delegate"), p.purchase.toBuffer()],
+          program.programId
+        )[0],
+        newPurchase,
+        holdings: holdingsPda(seller.publicKey),
+        recipientHoldings: holdingsPda(recipient.publicKey),
+        nftMint: p.purchaseMint,
+        ownerNftAccount: p.nftAta,
+        recipientNftAccount: recipientNftAta,
+        tokenMint,
+        ownerTokenAccount: p.tokenAta,
+        recipientTokenAccount: recipientTokenAta,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([seller])
+      .rpc();
+
+    assert.equal(await connection.getAccountInfo(p.purchase), null);
+    const moved = await program.account.purchase.fetch(newPurchase);
+    assert.ok(moved.buyer.equals(recipient.publicKey));
+    assert.equal(moved.remainingAmount.toNumber(), 3);
+    const nft = await connection.getTokenAccountBalance(recipientNftAta);
+    assert.equal(nft.value.amount, "1");
+    const tokens = await connection.getTokenAccountBalance(recipientTokenAta);
+    assert.equal(tokens.value.amount, "3");
+  });
+
+  // ──────────────────────────────────────────────────────────────────────────────
+  // 20) Canonical project vault
+  // ──────────────────────────────────────────────────────────────────────────────
+  it("20. Project creation creates the canonical vault and rejects any other", async () => {
+    const owner = await fundedKeypair();
+    const proj = await createProject(owner);
+    const projAcc = await program.account.project.fetch(proj.project);
+    assert.equal(
+      projAcc.vault.toBase58(),
+      (await getAssociatedTokenAddress(proj.tokenMint, carbonCreditsPda, true)).toBase58()
+    );
+    const vaultBal = await connection.getTokenAccountBalance(proj.vault);
+    assert.equal(vaultBal.value.amount, PROJECT_AMOUNT.toString());
+
+    // Minting the supply into the owner's own token account must fail
+    const cheater = await fundedKeypair();
+    try {
+      await createProject(cheater, PROJECT_AMOUNT, cheater.publicKey);
+      assert.fail("Project with a non-canonical vault should fail");
+    } catch (error) {
+      assert.ok(String(error).includes("ConstraintTokenOwner") || String(error).includes("ConstraintAssociated"));
+    }
+  });
+
+  // ──────────────────────────────────────────────────────────────────────────────
+  // 21) Metaplex account validation
+  // ──────────────────────────────────────────────────────────────────────────────
+  it("21. Reject spoofed metadata accounts before the Metaplex CPI", async () => {
+    const holder = await fundedKeypair();
+    const original = await preparePurchase(holder);
+
+    // a) purchase NFT metadata pointing at the project NFT's existing metadata
+    try {
+      await program.methods
+        .purchaseCarbonCredits(new BN(2))
+        .accountsPartial({ ...purchaseAccounts(holder, original), purchaseMetadata: metadataPda(nftMint) })
+        .signers([holder])
+        .rpc();
+      assert.fail("Purchase with spoofed metadata should fail");
+    } catch (error) {
+      assert.ok(String(error).includes("ConstraintSeeds"));
+    }
+    await program.methods
+      .purchaseCarbonCredits(new BN(2))
+      .accountsPartial(purchaseAccounts(holder, original))
+      .signers([holder])
+      .rpc();
+
+    // b) split certificate metadata derived under the wrong program
+    const carved = await preparePurchase(holder);
+    const [foreignMetadata] = PublicKey.findProgramAddressSync(
+      [Buffer.from("metadata"), METADATA_PROGRAM_ID.toBuffer(), carved.purchaseMint.toBuffer()],
+      program.programId
+    );
+    try {
+      await program.methods
+        .splitPurchase(new BN(1))
+        .accountsPartial({
+          buyer: holder.publicKey,
+          project: projectPda,
+          purchase: original.purchase,
+          newNftMint: carved.purchaseMint,
+          newNftAccount: carved.nftAta,
+          newNftMetadata: foreignMetadata,
+          subscription: subscriptionPda(holder.publicKey),
+          newPurchase: carved.purchase,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          tokenMetadataProgram: METADATA_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          rent: SYSVAR_RENT_PUBKEY,
+        })
+        .signers([holder])
+        .rpc();
+      assert.fail("Split with spoofed metadata should fail");
+    } catch (error) {
+      assert.ok(String(error).includes("ConstraintSeeds"));
+    }
+  });
+
+  // ──────────────────────────────────────────────────────────────────────────────
+  // 22) Fractional (kilogram) project tokens
+  // ──────────────────────────────────────────────────────────────────────────────
+  it("22. Price kilogram tokens per tonne and count them in grams", async () => {
+    const owner = await fundedKeypair();
+    const ccBefore = await program.account.carbonCredits.fetch(carbonCreditsPda);
+    // 5 tonnes in kilograms
+    const proj = await createProject(owner, 5_000, carbonCreditsPda, 3);
+    const projAcc = await program.account.project.fetch(proj.project);
+    assert.equal(projAcc.decimals, 3);
+    const ccAfter = await program.account.carbonCredits.fetch(carbonCreditsPda);
+    assert.equal(ccAfter.totalCredits.sub(ccBefore.totalCredits).toNumber(), 5_000_000);
+
+    // 1.001 tonnes
+    const buyer = await fundedKeypair();
+    const p = await preparePurchase(buyer, proj);
+    const ownerBefore = await connection.getBalance(owner.publicKey);
+    await program.methods
+      .purchaseCarbonCredits(new BN(1_001))
+      .accountsPartial(purchaseAccounts(buyer, p))
+      .signers([buyer])
+      .rpc();
+
+    const total = Math.ceil((1_001 * PRICE_PER_TOKEN) / 1_000);
+    const fee = Math.floor((total * CARBON_PAY_FEE) / 10_000);
+    const ownerAfter = await connection.getBalance(owner.publicKey);
+    assert.equal(ownerAfter - ownerBefore, total - fee);
+    const tokenBal = await connection.getTokenAccountBalance(p.tokenAta);
+    assert.equal(tokenBal.value.amount, "1001");
+    assert.equal(tokenBal.value.decimals, 3);
+  });
+
+  // ──────────────────────────────────────────────────────────────────────────────
+  // 23) Fee rounding policy
+  // ──────────────────────────────────────────────────────────────────────────────
+  it("23. Round fees by the platform policy and report the remainder", async () => {
+    const setFeePolicy = (feeRounding: object, minFee: number) =>
+      program.methods
+        .setFeePolicy(feeRounding as any, new BN(minFee))
+        .accountsPartial({
+          authority: provider.wallet.publicKey,
+          platformConfig: platformConfigPda,
+        })
+        .rpc();
+    const parser = new anchor.EventParser(program.programId, new anchor.BorshCoder(program.idl));
+    const buy = async (proj: ProjectFixture, amount: number) => {
+      const buyer = await fundedKeypair();
+      const p = await preparePurchase(buyer, proj);
+      const sig = await program.methods
+        .purchaseCarbonCredits(new BN(amount))
+        .accountsPartial(purchaseAccounts(buyer, p))
+        .signers([buyer])
+        .rpc({ commitment: "confirmed" });
+      const tx = await connection.getTransaction(sig, {
+        commitment: "confirmed",
+        maxSupportedTransactionVersion: 0,
+      });
+      const event = [...parser.parseLogs(tx.meta.logMessages)].find(
+        e => e.name === "carbonCreditsPurchased"
+      );
+      return event.data;
+    };
+
+    // 3 tokens at 1_001 lamports: 5% of 3_003 is 150.15 lamports
+    const proj = await createProject(await fundedKeypair(), PROJECT_AMOUNT, carbonCreditsPda, 0, 1_001);
+    await setFeePolicy({ ceil: {} }, 0);
+    let event = await buy(proj, 3);
+    assert.equal(event.totalPrice.toNumber(), 3_003);
+    assert.equal(event.fee.toNumber(), 151);
+    assert.equal(event.feeRemainder.toNumber(), 1_500);
+
+    await setFeePolicy({ floor: {} }, 1_000);
+    event = await buy(proj, 3);
+    assert.equal(event.fee.toNumber(), 1_000);
+    assert.equal(event.feeRemainder.toNumber(), 1_500);
+
+    const config = await program.account.platformConfig.fetch(platformConfigPda);
+    assert.deepEqual(config.feeRounding, { floor: {} });
+    assert.equal(config.minFee.toNumber(), 1_000);
+    await setFeePolicy({ floor: {} }, 0);
+  });
 });